@@ -0,0 +1,117 @@
+use std::collections::{
+    HashMap,
+};
+use std::fs;
+use std::io;
+use std::path::{
+    Component,
+    Path,
+    PathBuf,
+};
+
+
+/// A source of the files that a model file refers to, such as the material 
+/// libraries of a Wavefront OBJ file and the textures they use.
+/// 
+/// Paths are passed to the resolver as they are written in the referring 
+/// file, joined onto the directory of the referring file.
+pub trait FileResolver {
+    /// Read the entire contents of the file at `path`.
+    fn resolve(&self, path: &Path) -> io::Result<Vec<u8>>;
+}
+
+/// A file resolver that reads files relative to a directory on the file system.
+#[derive(Clone, Debug)]
+pub struct DirectoryFileResolver {
+    root: PathBuf,
+}
+
+impl DirectoryFileResolver {
+    pub fn new<P>(root: P) -> Self 
+    where
+        P: AsRef<Path>
+    {
+        Self { 
+            root: root.as_ref().to_path_buf(), 
+        }
+    }
+
+    #[inline]
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+impl FileResolver for DirectoryFileResolver {
+    fn resolve(&self, path: &Path) -> io::Result<Vec<u8>> {
+        fs::read(self.root.join(path))
+    }
+}
+
+/// A file resolver that serves files from memory, for loading models whose 
+/// files are embedded in a program, or built up in tests.
+#[derive(Clone, Debug, Default)]
+pub struct MemoryFileResolver {
+    files: HashMap<PathBuf, Vec<u8>>,
+}
+
+impl MemoryFileResolver {
+    pub fn new() -> Self {
+        Self { 
+            files: HashMap::new(), 
+        }
+    }
+
+    /// Add a file with the contents `data` at `path`, replacing any file 
+    /// already at that path.
+    pub fn with_file<P, D>(mut self, path: P, data: D) -> Self 
+    where
+        P: AsRef<Path>,
+        D: Into<Vec<u8>>,
+    {
+        self.files.insert(normalize_path(path.as_ref()), data.into());
+
+        self
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+}
+
+impl FileResolver for MemoryFileResolver {
+    fn resolve(&self, path: &Path) -> io::Result<Vec<u8>> {
+        self.files.get(&normalize_path(path)).cloned().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, format!("No file at path {}", path.display()))
+        })
+    }
+}
+
+/// Remove the `.` components from a path and apply its `..` components, 
+/// so that equivalent relative paths compare equal.
+fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                match normalized.components().next_back() {
+                    Some(Component::Normal(_)) => {
+                        normalized.pop();
+                    }
+                    _ => normalized.push(component),
+                }
+            }
+            _ => normalized.push(component),
+        }
+    }
+
+    normalized
+}
+
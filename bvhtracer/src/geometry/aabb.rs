@@ -62,6 +62,9 @@ where
         (self.bounds_min + self.bounds_max) * one_half
     }
 
+    /// Find the distance along the line of the ray at which the line enters the 
+    /// box, or `None` if the ray misses the box between distances `ray.t_min` and 
+    /// `ray.t` along the ray.
     pub fn intersect(&self, ray: &Ray<S>) -> Option<S> {
         let t_x1 = (self.bounds_min.x - ray.origin.x) * ray.recip_direction.x;
         let t_x2 = (self.bounds_max.x - ray.origin.x) * ray.recip_direction.x;
@@ -75,12 +78,52 @@ where
         let t_z2 = (self.bounds_max.z - ray.origin.z) * ray.recip_direction.z;
         let t_min = S::max(t_min, S::min(t_z1, t_z2)); 
         let t_max = S::min(t_max, S::max(t_z1, t_z2));
+        // Rounding in the slab distances can make a ray that grazes an edge of a 
+        // flat box miss it, e.g. the box of a triangle that the ray hits on a 
+        // shared edge. Padding the exit distance by the bound on the rounding 
+        // error keeps the test conservative.
+        let t_max = t_max * Self::slab_rounding_scale();
         
-        if (t_max >= t_min) && (t_min < ray.t) && (t_max > S::zero()) {
+        if (t_max >= t_min) && (t_min < ray.t) && (t_max > ray.t_min) {
             Some(t_min)
         } else {
             None
         }
     }
-}
 
+    /// The factor `1 + 2 * gamma(3)` bounding the relative rounding error of the 
+    /// distances to the slabs of a box.
+    #[inline]
+    fn slab_rounding_scale() -> S {
+        let one = S::one();
+        let two = one + one;
+        let three = two + one;
+        let unit_roundoff = S::epsilon() / two;
+        let gamma_3 = (three * unit_roundoff) / (one - three * unit_roundoff);
+
+        one + two * gamma_3
+    }
+
+    /// The distances along the line of the ray at which the line enters and 
+    /// leaves the box, or `None` if the line misses the box.
+    pub(crate) fn intersection_distances(&self, ray: &Ray<S>) -> Option<(S, S)> {
+        let t_x1 = (self.bounds_min.x - ray.origin.x) * ray.recip_direction.x;
+        let t_x2 = (self.bounds_max.x - ray.origin.x) * ray.recip_direction.x;
+        let t_min = S::min(t_x1, t_x2);
+        let t_max = S::max(t_x1, t_x2);
+        let t_y1 = (self.bounds_min.y - ray.origin.y) * ray.recip_direction.y; 
+        let t_y2 = (self.bounds_max.y - ray.origin.y) * ray.recip_direction.y;
+        let t_min = S::max(t_min, S::min(t_y1, t_y2)); 
+        let t_max = S::min(t_max, S::max(t_y1, t_y2));
+        let t_z1 = (self.bounds_min.z - ray.origin.z) * ray.recip_direction.z;
+        let t_z2 = (self.bounds_max.z - ray.origin.z) * ray.recip_direction.z;
+        let t_min = S::max(t_min, S::min(t_z1, t_z2)); 
+        let t_max = S::min(t_max, S::max(t_z1, t_z2));
+
+        if t_max >= t_min {
+            Some((t_min, t_max))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use crate::geometry::aabb::*;
+use crate::geometry::disk::*;
+use crate::geometry::shape::*;
+use crate::query::*;
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+    SimdScalar,
+    SimdScalarFloat,
+};
+
+
+/// An analytic cone closed by a disk at its base.
+///
+/// The cone runs from the center of its base to its apex. A ray hitting a cone
+/// reports the texture coordinates of the hit point on the cone in the `u` and
+/// `v` fields of its surface interaction.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct Cone<S>
+where
+    S: SimdScalar
+{
+    pub base: Vector3<S>,
+    pub apex: Vector3<S>,
+    pub radius: S,
+}
+
+impl<S> Cone<S>
+where
+    S: SimdScalar
+{
+    pub const fn new(base: Vector3<S>, apex: Vector3<S>, radius: S) -> Self {
+        Self { base, apex, radius, }
+    }
+}
+
+impl<S> Cone<S>
+where
+    S: SimdScalarFloat
+{
+    pub fn centroid(&self) -> Vector3<S> {
+        self.bounds().centroid()
+    }
+
+    pub fn bounds(&self) -> Aabb<S> {
+        let extent = disk_extent(&self.axis().normalize(), S::abs(self.radius));
+        let mut aabb = Aabb::new(self.base - extent, self.base + extent);
+        aabb.grow(&self.apex);
+
+        aabb
+    }
+
+    /// The vector from the center of the base of the cone to its apex.
+    pub fn axis(&self) -> Vector3<S> {
+        self.apex - self.base
+    }
+
+    pub fn height(&self) -> S {
+        self.axis().magnitude()
+    }
+
+    /// The distance from the rim of the base of the cone to its apex.
+    pub fn slant_height(&self) -> S {
+        let height = self.height();
+
+        S::sqrt(height * height + self.radius * self.radius)
+    }
+
+    /// The unit vectors across and along the axis of the cone.
+    #[inline]
+    fn frame(&self) -> (Vector3<S>, Vector3<S>, Vector3<S>) {
+        let axis = self.axis().normalize();
+        let (tangent, bitangent) = orthonormal_basis(&axis);
+
+        (tangent, bitangent, axis)
+    }
+
+    /// The coordinates of the vector `vector` in the frame of the cone.
+    #[inline]
+    fn local_coordinates(&self, vector: &Vector3<S>) -> Vector3<S> {
+        let (tangent, bitangent, axis) = self.frame();
+
+        Vector3::new(vector.dot(&tangent), vector.dot(&bitangent), vector.dot(&axis))
+    }
+
+    /// The part of the surface of the cone nearest the point with local
+    /// coordinates `local`.
+    #[inline]
+    fn part(&self, local: &Vector3<S>) -> Part {
+        let radius = S::abs(self.radius);
+        let height = self.height();
+        // The distance to the side is the radial distance to it scaled by the
+        // cosine of the angle between the side and the base.
+        let side_radius = radius * (height - local.z) / height;
+        let radial = S::sqrt(local.x * local.x + local.y * local.y);
+        let side_distance = S::abs(radial - side_radius) * height / self.slant_height();
+        let bottom_distance = S::abs(local.z);
+        if side_distance <= bottom_distance {
+            Part::Side
+        } else {
+            Part::Bottom
+        }
+    }
+
+    #[inline]
+    fn nearest_distance(&self, ray: &Ray<S>) -> Option<S> {
+        let two = S::one() + S::one();
+        let radius = S::abs(self.radius);
+        let height = self.height();
+        let origin = self.local_coordinates(&(ray.origin - self.base));
+        let direction = self.local_coordinates(&ray.direction);
+        let mut nearest: Option<S> = None;
+        let mut consider = |t: S| {
+            if ray.in_interval(t) {
+                nearest = Some(nearest.map_or(t, |nearest| S::min(nearest, t)));
+            }
+        };
+        // The side of the cone, where `x^2 + y^2 == (k * (height - z))^2` for the
+        // slope `k == radius / height`.
+        let k = radius / height;
+        let k_squared = k * k;
+        let s = height - origin.z;
+        let a = direction.x * direction.x + direction.y * direction.y - k_squared * direction.z * direction.z;
+        let b = two * (origin.x * direction.x + origin.y * direction.y + k_squared * s * direction.z);
+        let c = origin.x * origin.x + origin.y * origin.y - k_squared * s * s;
+        if let Some((t0, t1)) = solve_quadratic(a, b, c) {
+            for t in [t0, t1] {
+                // The equation also holds on the mirror image of the cone beyond
+                // its apex, which the height range excludes.
+                let z = origin.z + direction.z * t;
+                if z >= S::zero() && z <= height {
+                    consider(t);
+                }
+            }
+        }
+        // The base of the cone.
+        if direction.z != S::zero() {
+            let t = -origin.z / direction.z;
+            let x = origin.x + direction.x * t;
+            let y = origin.y + direction.y * t;
+            if x * x + y * y <= radius * radius {
+                consider(t);
+            }
+        }
+
+        nearest
+    }
+
+    #[inline]
+    pub fn intersect(&self, ray: &Ray<S>) -> Option<SurfaceInteraction<S>> {
+        let t = self.nearest_distance(ray)?;
+        let uv = self.uv(&ray.interpolate(t));
+
+        Some(SurfaceInteraction::new(t, uv.x, uv.y))
+    }
+
+    /// Determine whether the ray hits the cone before reaching distance `ray.t`
+    /// along the ray.
+    #[inline]
+    pub fn occluded(&self, ray: &Ray<S>) -> bool {
+        self.nearest_distance(ray).is_some()
+    }
+
+    /// The outward unit normal of the cone at the point `point` on its surface.
+    pub fn normal(&self, point: &Vector3<S>) -> Vector3<S> {
+        let (tangent, bitangent, axis) = self.frame();
+        let local = self.local_coordinates(&(point - self.base));
+        match self.part(&local) {
+            Part::Side => {
+                let radial = S::sqrt(local.x * local.x + local.y * local.y);
+                if radial == S::zero() {
+                    // The normal is undefined at the apex.
+                    return axis;
+                }
+                let outward = (tangent * local.x + bitangent * local.y) / radial;
+
+                (outward * self.height() + axis * S::abs(self.radius)) / self.slant_height()
+            }
+            _ => -axis,
+        }
+    }
+
+    /// The texture coordinates of the point `point` on the surface of the cone.
+    ///
+    /// The `u` coordinate runs once around the axis of the cone. On the side of
+    /// the cone, the `v` coordinate runs from the base at `v == 0` to the apex at
+    /// `v == 1`. The base is parameterized like a disk, with the `v` coordinate
+    /// running from its center at `v == 0` to its rim at `v == 1`.
+    pub fn uv(&self, point: &Vector3<S>) -> Vector2<S> {
+        let local = self.local_coordinates(&(point - self.base));
+        let two_pi = pi::<S>() + pi::<S>();
+        let u = azimuth(local.x, local.y) / two_pi;
+        let v = match self.part(&local) {
+            Part::Side => local.z / self.height(),
+            _ => S::sqrt(local.x * local.x + local.y * local.y) / S::abs(self.radius),
+        };
+
+        Vector2::new(u, v)
+    }
+
+    /// The rates of change of the position on the surface of the cone with
+    /// respect to the `u` and `v` texture coordinates at the point `point`.
+    pub fn position_derivatives(&self, point: &Vector3<S>) -> (Vector3<S>, Vector3<S>) {
+        let (tangent, bitangent, _) = self.frame();
+        let local = self.local_coordinates(&(point - self.base));
+        let two_pi = pi::<S>() + pi::<S>();
+        let (sin_phi, cos_phi) = S::sin_cos(azimuth(local.x, local.y));
+        let outward = tangent * cos_phi + bitangent * sin_phi;
+        let dpdu = (bitangent * local.x - tangent * local.y) * two_pi;
+        let dpdv = match self.part(&local) {
+            Part::Side => self.axis() - outward * S::abs(self.radius),
+            _ => outward * S::abs(self.radius),
+        };
+
+        (dpdu, dpdv)
+    }
+
+    /// The surface area of the cone, including its base.
+    pub fn area(&self) -> S {
+        let radius = S::abs(self.radius);
+
+        pi::<S>() * radius * (self.slant_height() + radius)
+    }
+}
@@ -0,0 +1,208 @@
+use crate::geometry::aabb::*;
+use crate::geometry::disk::*;
+use crate::geometry::shape::*;
+use crate::query::*;
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+    SimdScalar,
+    SimdScalarFloat,
+};
+
+
+/// An analytic cylinder closed by a disk at each end.
+///
+/// The cylinder runs along `axis` from the center of its base to the center of
+/// its top. A ray hitting a cylinder reports the texture coordinates of the hit
+/// point on the cylinder in the `u` and `v` fields of its surface interaction.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct Cylinder<S>
+where
+    S: SimdScalar
+{
+    pub base: Vector3<S>,
+    pub axis: Vector3<S>,
+    pub radius: S,
+}
+
+impl<S> Cylinder<S>
+where
+    S: SimdScalar
+{
+    pub const fn new(base: Vector3<S>, axis: Vector3<S>, radius: S) -> Self {
+        Self { base, axis, radius, }
+    }
+}
+
+impl<S> Cylinder<S>
+where
+    S: SimdScalarFloat
+{
+    pub fn centroid(&self) -> Vector3<S> {
+        let half: S = num_traits::cast(0.5_f64).unwrap();
+
+        self.base + self.axis * half
+    }
+
+    pub fn bounds(&self) -> Aabb<S> {
+        let extent = disk_extent(&self.axis.normalize(), S::abs(self.radius));
+        let mut aabb = Aabb::new(self.base - extent, self.base + extent);
+        aabb.grow(&(self.base + self.axis - extent));
+        aabb.grow(&(self.base + self.axis + extent));
+
+        aabb
+    }
+
+    pub fn height(&self) -> S {
+        self.axis.magnitude()
+    }
+
+    /// The unit vectors across and along the axis of the cylinder.
+    #[inline]
+    fn frame(&self) -> (Vector3<S>, Vector3<S>, Vector3<S>) {
+        let axis = self.axis.normalize();
+        let (tangent, bitangent) = orthonormal_basis(&axis);
+
+        (tangent, bitangent, axis)
+    }
+
+    /// The coordinates of the vector `vector` in the frame of the cylinder.
+    #[inline]
+    fn local_coordinates(&self, vector: &Vector3<S>) -> Vector3<S> {
+        let (tangent, bitangent, axis) = self.frame();
+
+        Vector3::new(vector.dot(&tangent), vector.dot(&bitangent), vector.dot(&axis))
+    }
+
+    /// The part of the surface of the cylinder nearest the point with local
+    /// coordinates `local`.
+    #[inline]
+    fn part(&self, local: &Vector3<S>) -> Part {
+        let radius = S::abs(self.radius);
+        let side_distance = S::abs(S::sqrt(local.x * local.x + local.y * local.y) - radius);
+        let bottom_distance = S::abs(local.z);
+        let top_distance = S::abs(local.z - self.height());
+        if side_distance <= bottom_distance && side_distance <= top_distance {
+            Part::Side
+        } else if bottom_distance <= top_distance {
+            Part::Bottom
+        } else {
+            Part::Top
+        }
+    }
+
+    #[inline]
+    fn nearest_distance(&self, ray: &Ray<S>) -> Option<S> {
+        let two = S::one() + S::one();
+        let radius = S::abs(self.radius);
+        let height = self.height();
+        let origin = self.local_coordinates(&(ray.origin - self.base));
+        let direction = self.local_coordinates(&ray.direction);
+        let mut nearest: Option<S> = None;
+        let mut consider = |t: S| {
+            if ray.in_interval(t) {
+                nearest = Some(nearest.map_or(t, |nearest| S::min(nearest, t)));
+            }
+        };
+        // The side of the cylinder.
+        let a = direction.x * direction.x + direction.y * direction.y;
+        let b = two * (origin.x * direction.x + origin.y * direction.y);
+        let c = origin.x * origin.x + origin.y * origin.y - radius * radius;
+        if a > S::zero() {
+            if let Some((t0, t1)) = solve_quadratic(a, b, c) {
+                for t in [t0, t1] {
+                    let z = origin.z + direction.z * t;
+                    if z >= S::zero() && z <= height {
+                        consider(t);
+                    }
+                }
+            }
+        }
+        // The caps of the cylinder.
+        if direction.z != S::zero() {
+            for plane in [S::zero(), height] {
+                let t = (plane - origin.z) / direction.z;
+                let x = origin.x + direction.x * t;
+                let y = origin.y + direction.y * t;
+                if x * x + y * y <= radius * radius {
+                    consider(t);
+                }
+            }
+        }
+
+        nearest
+    }
+
+    #[inline]
+    pub fn intersect(&self, ray: &Ray<S>) -> Option<SurfaceInteraction<S>> {
+        let t = self.nearest_distance(ray)?;
+        let uv = self.uv(&ray.interpolate(t));
+
+        Some(SurfaceInteraction::new(t, uv.x, uv.y))
+    }
+
+    /// Determine whether the ray hits the cylinder before reaching distance
+    /// `ray.t` along the ray.
+    #[inline]
+    pub fn occluded(&self, ray: &Ray<S>) -> bool {
+        self.nearest_distance(ray).is_some()
+    }
+
+    /// The outward unit normal of the cylinder at the point `point` on its surface.
+    pub fn normal(&self, point: &Vector3<S>) -> Vector3<S> {
+        let (tangent, bitangent, axis) = self.frame();
+        let local = self.local_coordinates(&(point - self.base));
+        match self.part(&local) {
+            Part::Side => (tangent * local.x + bitangent * local.y).normalize(),
+            Part::Bottom => -axis,
+            Part::Top => axis,
+        }
+    }
+
+    /// The texture coordinates of the point `point` on the surface of the cylinder.
+    ///
+    /// The `u` coordinate runs once around the axis of the cylinder. On the side
+    /// of the cylinder, the `v` coordinate runs from the base at `v == 0` to the
+    /// top at `v == 1`. The caps are parameterized like disks, with the `v`
+    /// coordinate running from the center of a cap at `v == 0` to its rim at
+    /// `v == 1`.
+    pub fn uv(&self, point: &Vector3<S>) -> Vector2<S> {
+        let local = self.local_coordinates(&(point - self.base));
+        let two_pi = pi::<S>() + pi::<S>();
+        let u = azimuth(local.x, local.y) / two_pi;
+        let v = match self.part(&local) {
+            Part::Side => local.z / self.height(),
+            Part::Bottom | Part::Top => S::sqrt(local.x * local.x + local.y * local.y) / S::abs(self.radius),
+        };
+
+        Vector2::new(u, v)
+    }
+
+    /// The rates of change of the position on the surface of the cylinder with
+    /// respect to the `u` and `v` texture coordinates at the point `point`.
+    pub fn position_derivatives(&self, point: &Vector3<S>) -> (Vector3<S>, Vector3<S>) {
+        let (tangent, bitangent, _) = self.frame();
+        let local = self.local_coordinates(&(point - self.base));
+        let two_pi = pi::<S>() + pi::<S>();
+        let dpdu = (bitangent * local.x - tangent * local.y) * two_pi;
+        let dpdv = match self.part(&local) {
+            Part::Side => self.axis,
+            Part::Bottom | Part::Top => {
+                let (sin_phi, cos_phi) = S::sin_cos(azimuth(local.x, local.y));
+                (tangent * cos_phi + bitangent * sin_phi) * S::abs(self.radius)
+            }
+        };
+
+        (dpdu, dpdv)
+    }
+
+    /// The surface area of the cylinder, including its caps.
+    pub fn area(&self) -> S {
+        let two_pi = pi::<S>() + pi::<S>();
+        let radius = S::abs(self.radius);
+
+        two_pi * radius * (self.height() + radius)
+    }
+}
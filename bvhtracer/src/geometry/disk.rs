@@ -0,0 +1,145 @@
+use crate::geometry::aabb::*;
+use crate::geometry::shape::*;
+use crate::query::*;
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+    SimdScalar,
+    SimdScalarFloat,
+};
+
+
+/// An analytic disk.
+///
+/// The front face of a disk is the face that its normal points out of. A ray
+/// hitting a disk reports the texture coordinates of the hit point on the disk
+/// in the `u` and `v` fields of its surface interaction.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct Disk<S>
+where
+    S: SimdScalar
+{
+    pub center: Vector3<S>,
+    pub normal: Vector3<S>,
+    pub radius: S,
+}
+
+impl<S> Disk<S>
+where
+    S: SimdScalar
+{
+    pub const fn new(center: Vector3<S>, normal: Vector3<S>, radius: S) -> Self {
+        Self { center, normal, radius, }
+    }
+}
+
+impl<S> Disk<S>
+where
+    S: SimdScalarFloat
+{
+    pub fn centroid(&self) -> Vector3<S> {
+        self.center
+    }
+
+    pub fn bounds(&self) -> Aabb<S> {
+        let extent = disk_extent(&self.normal(), S::abs(self.radius));
+
+        Aabb::new(self.center - extent, self.center + extent)
+    }
+
+    #[inline]
+    fn nearest_distance(&self, ray: &Ray<S>) -> Option<S> {
+        let normal = self.normal();
+        let denominator = normal.dot(&ray.direction);
+        if denominator == S::zero() {
+            return None;
+        }
+        let t = normal.dot(&(self.center - ray.origin)) / denominator;
+        if !ray.in_interval(t) {
+            return None;
+        }
+        let offset = ray.interpolate(t) - self.center;
+        if offset.magnitude_squared() > self.radius * self.radius {
+            return None;
+        }
+
+        Some(t)
+    }
+
+    #[inline]
+    pub fn intersect(&self, ray: &Ray<S>) -> Option<SurfaceInteraction<S>> {
+        let t = self.nearest_distance(ray)?;
+        let uv = self.uv(&ray.interpolate(t));
+
+        Some(SurfaceInteraction::new(t, uv.x, uv.y))
+    }
+
+    /// Determine whether the ray hits the disk before reaching distance `ray.t`
+    /// along the ray.
+    #[inline]
+    pub fn occluded(&self, ray: &Ray<S>) -> bool {
+        self.nearest_distance(ray).is_some()
+    }
+
+    /// The unit normal of the front face of the disk.
+    pub fn normal(&self) -> Vector3<S> {
+        self.normal.normalize()
+    }
+
+    /// The texture coordinates of the point `point` on the disk.
+    ///
+    /// The `u` coordinate runs once around the center of the disk, and the `v`
+    /// coordinate runs from the center at `v == 0` to the rim at `v == 1`.
+    pub fn uv(&self, point: &Vector3<S>) -> Vector2<S> {
+        let (tangent, bitangent) = orthonormal_basis(&self.normal());
+        let offset = point - self.center;
+        let x = offset.dot(&tangent);
+        let y = offset.dot(&bitangent);
+        let two_pi = pi::<S>() + pi::<S>();
+
+        Vector2::new(azimuth(x, y) / two_pi, S::sqrt(x * x + y * y) / S::abs(self.radius))
+    }
+
+    /// The rates of change of the position on the disk with respect to the `u`
+    /// and `v` texture coordinates at the point `point`.
+    pub fn position_derivatives(&self, point: &Vector3<S>) -> (Vector3<S>, Vector3<S>) {
+        let (tangent, bitangent) = orthonormal_basis(&self.normal());
+        let offset = point - self.center;
+        let uv = self.uv(point);
+        let two_pi = pi::<S>() + pi::<S>();
+        let (sin_phi, cos_phi) = S::sin_cos(uv.x * two_pi);
+        let radial = tangent * cos_phi + bitangent * sin_phi;
+        let around = bitangent * cos_phi - tangent * sin_phi;
+        let dpdu = around * (two_pi * offset.magnitude());
+        let dpdv = radial * S::abs(self.radius);
+
+        (dpdu, dpdv)
+    }
+
+    /// The point on the disk with texture coordinates `uv`.
+    pub fn point_at_uv(&self, uv: &Vector2<S>) -> Vector3<S> {
+        let (tangent, bitangent) = orthonormal_basis(&self.normal());
+        let two_pi = pi::<S>() + pi::<S>();
+        let (sin_phi, cos_phi) = S::sin_cos(uv.x * two_pi);
+
+        self.center + (tangent * cos_phi + bitangent * sin_phi) * (uv.y * S::abs(self.radius))
+    }
+
+    pub fn area(&self) -> S {
+        pi::<S>() * self.radius * self.radius
+    }
+}
+
+/// The extent from its center along each axis of a circle of radius `radius`
+/// in the plane with unit normal `normal`.
+#[inline]
+pub(crate) fn disk_extent<S>(normal: &Vector3<S>, radius: S) -> Vector3<S>
+where
+    S: SimdScalarFloat
+{
+    let extent = |component: S| radius * S::sqrt(S::max(S::zero(), S::one() - component * component));
+
+    Vector3::new(extent(normal.x), extent(normal.y), extent(normal.z))
+}
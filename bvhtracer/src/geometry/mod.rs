@@ -1,7 +1,25 @@
 mod aabb;
+mod cone;
+mod cylinder;
+mod disk;
+mod primitive;
+mod quad;
+mod sdf;
+mod shape;
+mod sphere;
 mod triangle;
+mod voxel_grid;
 
 
 pub use aabb::*;
+pub use cone::*;
+pub use cylinder::*;
+pub use disk::*;
+pub use primitive::*;
+pub use quad::*;
+pub use sdf::*;
+pub use shape::*;
+pub use sphere::*;
 pub use triangle::*;
+pub use voxel_grid::*;
 
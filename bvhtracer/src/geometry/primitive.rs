@@ -0,0 +1,123 @@
+use crate::geometry::aabb::*;
+use crate::geometry::sphere::*;
+use crate::geometry::triangle::*;
+use crate::query::*;
+use cglinalg::{
+    Vector3,
+    SimdScalarFloat,
+};
+
+
+/// A bounded piece of geometry that a boundary volume hierarchy can be built over.
+///
+/// A boundary volume hierarchy only needs the bounds and centroid of each
+/// primitive to build it, and a ray query against each primitive to traverse it.
+/// Implement this trait to trace rays against user defined shapes with the same
+/// builder and traversal as the built-in primitives.
+pub trait BoundedPrimitive<S>
+where
+    S: SimdScalarFloat
+{
+    /// The axis aligned bounding box enclosing the primitive.
+    fn bounds(&self) -> Aabb<S>;
+
+    /// The point used to sort the primitive into the nodes of a boundary
+    /// volume hierarchy.
+    fn centroid(&self) -> Vector3<S>;
+
+    /// Find the nearest intersection of the ray with the primitive between
+    /// distances `ray.t_min` and `ray.t` along the ray.
+    fn intersect(&self, ray: &Ray<S>) -> Option<SurfaceInteraction<S>>;
+
+    /// Determine whether the ray hits the primitive between distances
+    /// `ray.t_min` and `ray.t` along the ray.
+    ///
+    /// Shadow rays only need to know whether there is any hit at all, so
+    /// primitives with a cheaper any hit test than their closest hit test
+    /// should override this.
+    fn occluded(&self, ray: &Ray<S>) -> bool {
+        self.intersect(ray).is_some()
+    }
+}
+
+impl<S> BoundedPrimitive<S> for Triangle<S>
+where
+    S: SimdScalarFloat
+{
+    #[inline]
+    fn bounds(&self) -> Aabb<S> {
+        let mut aabb = Aabb::new(self.vertices[0], self.vertices[0]);
+        aabb.grow(&self.vertices[1]);
+        aabb.grow(&self.vertices[2]);
+
+        aabb
+    }
+
+    #[inline]
+    fn centroid(&self) -> Vector3<S> {
+        Triangle::centroid(self)
+    }
+
+    #[inline]
+    fn intersect(&self, ray: &Ray<S>) -> Option<SurfaceInteraction<S>> {
+        Triangle::intersect(self, ray)
+    }
+
+    #[inline]
+    fn occluded(&self, ray: &Ray<S>) -> bool {
+        Triangle::occluded(self, ray)
+    }
+}
+
+impl<S> BoundedPrimitive<S> for Sphere<S>
+where
+    S: SimdScalarFloat
+{
+    #[inline]
+    fn bounds(&self) -> Aabb<S> {
+        Sphere::bounds(self)
+    }
+
+    #[inline]
+    fn centroid(&self) -> Vector3<S> {
+        Sphere::centroid(self)
+    }
+
+    #[inline]
+    fn intersect(&self, ray: &Ray<S>) -> Option<SurfaceInteraction<S>> {
+        Sphere::intersect(self, ray)
+    }
+
+    #[inline]
+    fn occluded(&self, ray: &Ray<S>) -> bool {
+        Sphere::occluded(self, ray)
+    }
+}
+
+/// A solid box. The surface interaction of a hit reports no surface
+/// coordinates, so `u` and `v` are zero.
+impl<S> BoundedPrimitive<S> for Aabb<S>
+where
+    S: SimdScalarFloat
+{
+    #[inline]
+    fn bounds(&self) -> Aabb<S> {
+        *self
+    }
+
+    #[inline]
+    fn centroid(&self) -> Vector3<S> {
+        Aabb::centroid(self)
+    }
+
+    #[inline]
+    fn intersect(&self, ray: &Ray<S>) -> Option<SurfaceInteraction<S>> {
+        let (t_near, t_far) = self.intersection_distances(ray)?;
+        let t = if t_near > ray.t_min { t_near } else { t_far };
+        if ray.in_interval(t) {
+            Some(SurfaceInteraction::new(t, S::zero(), S::zero()))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use crate::geometry::aabb::*;
+use crate::query::*;
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+    SimdScalar,
+    SimdScalarFloat,
+};
+
+
+/// An analytic parallelogram spanned by two edges from one of its corners.
+///
+/// The front face of a quad is the face that its normal `edge_u × edge_v`
+/// points out of. A ray hitting a quad reports the position of the hit along
+/// each edge in the `u` and `v` fields of its surface interaction.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct Quad<S>
+where
+    S: SimdScalar
+{
+    pub origin: Vector3<S>,
+    pub edge_u: Vector3<S>,
+    pub edge_v: Vector3<S>,
+}
+
+impl<S> Quad<S>
+where
+    S: SimdScalar
+{
+    pub const fn new(origin: Vector3<S>, edge_u: Vector3<S>, edge_v: Vector3<S>) -> Self {
+        Self { origin, edge_u, edge_v, }
+    }
+}
+
+impl<S> Quad<S>
+where
+    S: SimdScalarFloat
+{
+    pub fn centroid(&self) -> Vector3<S> {
+        let half: S = num_traits::cast(0.5_f64).unwrap();
+
+        self.origin + (self.edge_u + self.edge_v) * half
+    }
+
+    pub fn bounds(&self) -> Aabb<S> {
+        let mut aabb = Aabb::new(self.origin, self.origin);
+        aabb.grow(&(self.origin + self.edge_u));
+        aabb.grow(&(self.origin + self.edge_v));
+        aabb.grow(&(self.origin + self.edge_u + self.edge_v));
+
+        aabb
+    }
+
+    /// The position of the point `point` in the plane of the quad along each
+    /// of its edges.
+    #[inline]
+    fn local_coordinates(&self, point: &Vector3<S>) -> Vector2<S> {
+        let normal = self.edge_u.cross(&self.edge_v);
+        let w = normal / normal.dot(&normal);
+        let planar = point - self.origin;
+
+        Vector2::new(w.dot(&planar.cross(&self.edge_v)), w.dot(&self.edge_u.cross(&planar)))
+    }
+
+    #[inline]
+    fn nearest_distance(&self, ray: &Ray<S>) -> Option<(S, Vector2<S>)> {
+        let normal = self.edge_u.cross(&self.edge_v);
+        let denominator = normal.dot(&ray.direction);
+        if denominator == S::zero() {
+            return None;
+        }
+        let t = normal.dot(&(self.origin - ray.origin)) / denominator;
+        if !ray.in_interval(t) {
+            return None;
+        }
+        let uv = self.local_coordinates(&ray.interpolate(t));
+        if uv.x < S::zero() || uv.x > S::one() || uv.y < S::zero() || uv.y > S::one() {
+            return None;
+        }
+
+        Some((t, uv))
+    }
+
+    #[inline]
+    pub fn intersect(&self, ray: &Ray<S>) -> Option<SurfaceInteraction<S>> {
+        self.nearest_distance(ray).map(|(t, uv)| SurfaceInteraction::new(t, uv.x, uv.y))
+    }
+
+    /// Determine whether the ray hits the quad before reaching distance `ray.t`
+    /// along the ray.
+    #[inline]
+    pub fn occluded(&self, ray: &Ray<S>) -> bool {
+        self.nearest_distance(ray).is_some()
+    }
+
+    /// The unit normal of the front face of the quad.
+    pub fn normal(&self) -> Vector3<S> {
+        self.edge_u.cross(&self.edge_v).normalize()
+    }
+
+    /// The texture coordinates of the point `point` on the quad, which run from
+    /// zero to one along each edge.
+    pub fn uv(&self, point: &Vector3<S>) -> Vector2<S> {
+        self.local_coordinates(point)
+    }
+
+    /// The rates of change of the position on the quad with respect to the `u`
+    /// and `v` texture coordinates.
+    pub fn position_derivatives(&self) -> (Vector3<S>, Vector3<S>) {
+        (self.edge_u, self.edge_v)
+    }
+
+    /// The point on the quad with texture coordinates `uv`.
+    pub fn point_at_uv(&self, uv: &Vector2<S>) -> Vector3<S> {
+        self.origin + self.edge_u * uv.x + self.edge_v * uv.y
+    }
+
+    pub fn area(&self) -> S {
+        self.edge_u.cross(&self.edge_v).magnitude()
+    }
+}
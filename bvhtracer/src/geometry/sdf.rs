@@ -0,0 +1,368 @@
+use crate::geometry::aabb::*;
+use crate::geometry::primitive::*;
+use crate::query::*;
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+    SimdScalar,
+    SimdScalarFloat,
+};
+use std::fmt;
+use std::sync::{
+    Arc,
+};
+
+
+/// A function computing the signed distance at a point.
+pub type SdfFunction<S> = Arc<dyn Fn(&Vector3<S>) -> S + Send + Sync>;
+
+
+/// A signed distance field.
+///
+/// A signed distance field gives the distance from a point to the nearest point
+/// of a surface, which is negative inside the surface and positive outside it.
+/// Signed distance fields are built up from the shapes below with the union,
+/// smooth union, subtraction, and repetition operators, or are given by an
+/// arbitrary function. Sphere tracing only needs the distance to never
+/// overestimate the distance to the surface, so a function that is a lower bound
+/// on the distance works too, at the cost of taking more steps.
+#[derive(Clone)]
+pub enum Sdf<S>
+where
+    S: SimdScalar
+{
+    /// A sphere.
+    Sphere { center: Vector3<S>, radius: S, },
+    /// An axis aligned box extending `half_extents` from its center along each axis.
+    Cuboid { center: Vector3<S>, half_extents: Vector3<S>, },
+    /// A torus around the **y-axis** through its center. The tube of radius
+    /// `minor_radius` sweeps a circle of radius `major_radius` in the **xz-plane**.
+    Torus { center: Vector3<S>, major_radius: S, minor_radius: S, },
+    /// The union of two signed distance fields.
+    Union(Box<Sdf<S>>, Box<Sdf<S>>),
+    /// The union of two signed distance fields, blended together over a
+    /// distance of about `smoothness` where they meet.
+    SmoothUnion { left: Box<Sdf<S>>, right: Box<Sdf<S>>, smoothness: S, },
+    /// The first signed distance field with the second one cut out of it.
+    Subtraction(Box<Sdf<S>>, Box<Sdf<S>>),
+    /// Infinitely many copies of a signed distance field, repeated every
+    /// `period` along each axis. An axis with a period of zero is not repeated.
+    /// The repeated field should fit inside one period around the origin.
+    Repetition { sdf: Box<Sdf<S>>, period: Vector3<S>, },
+    /// A signed distance field given by a function.
+    Function(SdfFunction<S>),
+}
+
+impl<S> Sdf<S>
+where
+    S: SimdScalarFloat
+{
+    pub fn sphere(center: Vector3<S>, radius: S) -> Self {
+        Self::Sphere { center, radius, }
+    }
+
+    pub fn cuboid(center: Vector3<S>, half_extents: Vector3<S>) -> Self {
+        Self::Cuboid { center, half_extents, }
+    }
+
+    pub fn torus(center: Vector3<S>, major_radius: S, minor_radius: S) -> Self {
+        Self::Torus { center, major_radius, minor_radius, }
+    }
+
+    /// Construct a signed distance field from a function computing the signed
+    /// distance at a point.
+    pub fn from_fn<F>(function: F) -> Self
+    where
+        F: Fn(&Vector3<S>) -> S + Send + Sync + 'static
+    {
+        Self::Function(Arc::new(function))
+    }
+
+    pub fn union(self, other: Self) -> Self {
+        Self::Union(Box::new(self), Box::new(other))
+    }
+
+    pub fn smooth_union(self, other: Self, smoothness: S) -> Self {
+        Self::SmoothUnion { left: Box::new(self), right: Box::new(other), smoothness, }
+    }
+
+    /// Cut `other` out of this signed distance field.
+    pub fn subtract(self, other: Self) -> Self {
+        Self::Subtraction(Box::new(self), Box::new(other))
+    }
+
+    pub fn repeat(self, period: Vector3<S>) -> Self {
+        Self::Repetition { sdf: Box::new(self), period, }
+    }
+
+    /// The signed distance from the point `point` to the surface.
+    pub fn distance(&self, point: &Vector3<S>) -> S {
+        match self {
+            Self::Sphere { center, radius, } => {
+                (point - center).magnitude() - *radius
+            }
+            Self::Cuboid { center, half_extents, } => {
+                let offset = point - center;
+                let q = Vector3::new(
+                    S::abs(offset.x) - half_extents.x,
+                    S::abs(offset.y) - half_extents.y,
+                    S::abs(offset.z) - half_extents.z,
+                );
+                let outside = Vector3::new(S::max(q.x, S::zero()), S::max(q.y, S::zero()), S::max(q.z, S::zero()));
+                let inside = S::min(S::max(q.x, S::max(q.y, q.z)), S::zero());
+
+                outside.magnitude() + inside
+            }
+            Self::Torus { center, major_radius, minor_radius, } => {
+                let offset = point - center;
+                let radial = S::sqrt(offset.x * offset.x + offset.z * offset.z) - *major_radius;
+
+                Vector2::new(radial, offset.y).magnitude() - *minor_radius
+            }
+            Self::Union(left, right) => {
+                S::min(left.distance(point), right.distance(point))
+            }
+            Self::SmoothUnion { left, right, smoothness, } => {
+                let left = left.distance(point);
+                let right = right.distance(point);
+                if *smoothness <= S::zero() {
+                    return S::min(left, right);
+                }
+                // The polynomial smooth minimum of Inigo Quilez.
+                let one = S::one();
+                let half: S = num_traits::cast(0.5_f64).unwrap();
+                let h = S::max(S::zero(), S::min(one, half + half * (right - left) / *smoothness));
+
+                right + (left - right) * h - *smoothness * h * (one - h)
+            }
+            Self::Subtraction(left, right) => {
+                S::max(left.distance(point), -right.distance(point))
+            }
+            Self::Repetition { sdf, period, } => {
+                let mut local = *point;
+                for axis in 0..3 {
+                    if period[axis] > S::zero() {
+                        local[axis] = point[axis] - period[axis] * S::round(point[axis] / period[axis]);
+                    }
+                }
+
+                sdf.distance(&local)
+            }
+            Self::Function(function) => function(point),
+        }
+    }
+}
+
+impl<S> fmt::Debug for Sdf<S>
+where
+    S: SimdScalar + fmt::Debug
+{
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Sphere { center, radius, } => formatter
+                .debug_struct("Sphere")
+                .field("center", center)
+                .field("radius", radius)
+                .finish(),
+            Self::Cuboid { center, half_extents, } => formatter
+                .debug_struct("Cuboid")
+                .field("center", center)
+                .field("half_extents", half_extents)
+                .finish(),
+            Self::Torus { center, major_radius, minor_radius, } => formatter
+                .debug_struct("Torus")
+                .field("center", center)
+                .field("major_radius", major_radius)
+                .field("minor_radius", minor_radius)
+                .finish(),
+            Self::Union(left, right) => formatter
+                .debug_tuple("Union")
+                .field(left)
+                .field(right)
+                .finish(),
+            Self::SmoothUnion { left, right, smoothness, } => formatter
+                .debug_struct("SmoothUnion")
+                .field("left", left)
+                .field("right", right)
+                .field("smoothness", smoothness)
+                .finish(),
+            Self::Subtraction(left, right) => formatter
+                .debug_tuple("Subtraction")
+                .field(left)
+                .field(right)
+                .finish(),
+            Self::Repetition { sdf, period, } => formatter
+                .debug_struct("Repetition")
+                .field("sdf", sdf)
+                .field("period", period)
+                .finish(),
+            Self::Function(_) => formatter.write_str("Function"),
+        }
+    }
+}
+
+
+/// An implicit surface given by the zero set of a signed distance field inside
+/// a bounding box.
+///
+/// Rays are intersected with the surface by sphere tracing: starting where the
+/// ray enters the bounding box, the ray repeatedly steps forward by the distance
+/// to the surface, which can never cross it, until it comes within the tolerance
+/// of the surface or leaves the bounding box. A ray whose origin lies on the
+/// surface first steps off of it, so that a ray leaving the surface does not hit
+/// the point it left.
+///
+/// The surface has no texture coordinates, so the `u` and `v` fields of the
+/// surface interaction of a hit are zero.
+#[derive(Clone, Debug)]
+pub struct ImplicitSurface<S>
+where
+    S: SimdScalar
+{
+    sdf: Sdf<S>,
+    bounds: Aabb<S>,
+    max_steps: usize,
+    tolerance: S,
+}
+
+impl<S> ImplicitSurface<S>
+where
+    S: SimdScalarFloat
+{
+    /// Construct an implicit surface from the part of the zero set of `sdf`
+    /// inside `bounds`.
+    pub fn new(sdf: Sdf<S>, bounds: Aabb<S>) -> Self {
+        Self {
+            sdf,
+            bounds,
+            max_steps: 256,
+            tolerance: num_traits::cast(0.0001_f64).unwrap(),
+        }
+    }
+
+    /// Set the maximum number of sphere tracing steps along a ray. A ray that
+    /// has not reached the surface after this many steps misses it.
+    pub fn with_max_steps(mut self, max_steps: usize) -> Self {
+        self.max_steps = max_steps;
+        self
+    }
+
+    /// Set the distance from the surface within which a ray hits the surface.
+    pub fn with_tolerance(mut self, tolerance: S) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn sdf(&self) -> &Sdf<S> {
+        &self.sdf
+    }
+
+    pub fn bounds(&self) -> Aabb<S> {
+        self.bounds
+    }
+
+    pub fn centroid(&self) -> Vector3<S> {
+        self.bounds.centroid()
+    }
+
+    /// The signed distance from the point `point` to the surface.
+    pub fn distance(&self, point: &Vector3<S>) -> S {
+        self.sdf.distance(point)
+    }
+
+    /// The outward unit normal of the surface at the point `point`, estimated
+    /// from the gradient of the signed distance field by central differences.
+    pub fn normal(&self, point: &Vector3<S>) -> Vector3<S> {
+        // Differences of nearby distances lose most of their precision, so the
+        // differences are taken further apart than the hit tolerance.
+        let ten: S = num_traits::cast(10_f64).unwrap();
+        let h = self.tolerance * ten;
+        let dx = Vector3::new(h, S::zero(), S::zero());
+        let dy = Vector3::new(S::zero(), h, S::zero());
+        let dz = Vector3::new(S::zero(), S::zero(), h);
+        let gradient = Vector3::new(
+            self.distance(&(point + dx)) - self.distance(&(point - dx)),
+            self.distance(&(point + dy)) - self.distance(&(point - dy)),
+            self.distance(&(point + dz)) - self.distance(&(point - dz)),
+        );
+        let magnitude = gradient.magnitude();
+        if magnitude > S::zero() {
+            gradient / magnitude
+        } else {
+            Vector3::zero()
+        }
+    }
+
+    /// Find the nearest distance along the ray past distance `ray.t_min` at which
+    /// the ray hits the surface, before distance `ray.t` along the ray.
+    fn nearest_distance(&self, ray: &Ray<S>) -> Option<S> {
+        let (t_near, t_far) = self.bounds.intersection_distances(ray)?;
+        let t_end = S::min(t_far, ray.t);
+        // The distance field measures distances in space, so steps along a ray
+        // whose direction is not a unit vector are scaled by its length.
+        let speed = ray.direction.magnitude();
+        if speed == S::zero() {
+            return None;
+        }
+        let mut t = S::max(t_near, S::zero());
+        let mut leaving_surface = t_near <= S::zero() && S::abs(self.distance(&ray.origin)) < self.tolerance;
+        for _ in 0..self.max_steps {
+            if t > t_end {
+                return None;
+            }
+            // Marching by the absolute distance finds the surface from inside it too.
+            let distance = S::abs(self.distance(&ray.interpolate(t)));
+            if leaving_surface {
+                if distance < self.tolerance {
+                    t += self.tolerance / speed;
+                    continue;
+                }
+                leaving_surface = false;
+            }
+            if distance < self.tolerance && t > ray.t_min {
+                return Some(t);
+            }
+            t += S::max(distance, self.tolerance) / speed;
+        }
+
+        None
+    }
+
+    pub fn intersect(&self, ray: &Ray<S>) -> Option<SurfaceInteraction<S>> {
+        let t = self.nearest_distance(ray)?;
+
+        Some(SurfaceInteraction::new(t, S::zero(), S::zero()))
+    }
+
+    /// Determine whether the ray hits the surface before reaching distance
+    /// `ray.t` along the ray.
+    pub fn occluded(&self, ray: &Ray<S>) -> bool {
+        self.nearest_distance(ray).is_some()
+    }
+}
+
+impl<S> BoundedPrimitive<S> for ImplicitSurface<S>
+where
+    S: SimdScalarFloat
+{
+    #[inline]
+    fn bounds(&self) -> Aabb<S> {
+        ImplicitSurface::bounds(self)
+    }
+
+    #[inline]
+    fn centroid(&self) -> Vector3<S> {
+        ImplicitSurface::centroid(self)
+    }
+
+    #[inline]
+    fn intersect(&self, ray: &Ray<S>) -> Option<SurfaceInteraction<S>> {
+        ImplicitSurface::intersect(self, ray)
+    }
+
+    #[inline]
+    fn occluded(&self, ray: &Ray<S>) -> bool {
+        ImplicitSurface::occluded(self, ray)
+    }
+}
@@ -0,0 +1,261 @@
+use crate::geometry::aabb::*;
+use crate::geometry::cone::*;
+use crate::geometry::cylinder::*;
+use crate::geometry::disk::*;
+use crate::geometry::primitive::*;
+use crate::geometry::quad::*;
+use crate::query::*;
+use cglinalg::{
+    Vector2,
+    Vector3,
+    SimdScalar,
+    SimdScalarFloat,
+};
+
+
+/// An analytic shape, so that quads, disks, cylinders, and cones can share one
+/// boundary volume hierarchy.
+///
+/// A ray hitting a shape reports the texture coordinates of the hit point on
+/// the shape in the `u` and `v` fields of its surface interaction.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Shape<S>
+where
+    S: SimdScalar
+{
+    Quad(Quad<S>),
+    Disk(Disk<S>),
+    Cylinder(Cylinder<S>),
+    Cone(Cone<S>),
+}
+
+impl<S> Shape<S>
+where
+    S: SimdScalarFloat
+{
+    pub fn centroid(&self) -> Vector3<S> {
+        match self {
+            Self::Quad(quad) => quad.centroid(),
+            Self::Disk(disk) => disk.centroid(),
+            Self::Cylinder(cylinder) => cylinder.centroid(),
+            Self::Cone(cone) => cone.centroid(),
+        }
+    }
+
+    pub fn bounds(&self) -> Aabb<S> {
+        match self {
+            Self::Quad(quad) => quad.bounds(),
+            Self::Disk(disk) => disk.bounds(),
+            Self::Cylinder(cylinder) => cylinder.bounds(),
+            Self::Cone(cone) => cone.bounds(),
+        }
+    }
+
+    pub fn intersect(&self, ray: &Ray<S>) -> Option<SurfaceInteraction<S>> {
+        match self {
+            Self::Quad(quad) => quad.intersect(ray),
+            Self::Disk(disk) => disk.intersect(ray),
+            Self::Cylinder(cylinder) => cylinder.intersect(ray),
+            Self::Cone(cone) => cone.intersect(ray),
+        }
+    }
+
+    /// Determine whether the ray hits the shape before reaching distance `ray.t`
+    /// along the ray.
+    pub fn occluded(&self, ray: &Ray<S>) -> bool {
+        match self {
+            Self::Quad(quad) => quad.occluded(ray),
+            Self::Disk(disk) => disk.occluded(ray),
+            Self::Cylinder(cylinder) => cylinder.occluded(ray),
+            Self::Cone(cone) => cone.occluded(ray),
+        }
+    }
+
+    /// The outward unit normal of the shape at the point `point` on its surface.
+    /// For a quad or a disk, this is the normal of its front face.
+    pub fn normal(&self, point: &Vector3<S>) -> Vector3<S> {
+        match self {
+            Self::Quad(quad) => quad.normal(),
+            Self::Disk(disk) => disk.normal(),
+            Self::Cylinder(cylinder) => cylinder.normal(point),
+            Self::Cone(cone) => cone.normal(point),
+        }
+    }
+
+    /// The texture coordinates of the point `point` on the surface of the shape.
+    pub fn uv(&self, point: &Vector3<S>) -> Vector2<S> {
+        match self {
+            Self::Quad(quad) => quad.uv(point),
+            Self::Disk(disk) => disk.uv(point),
+            Self::Cylinder(cylinder) => cylinder.uv(point),
+            Self::Cone(cone) => cone.uv(point),
+        }
+    }
+
+    /// The rates of change of the position on the surface of the shape with
+    /// respect to the `u` and `v` texture coordinates at the point `point`.
+    pub fn position_derivatives(&self, point: &Vector3<S>) -> (Vector3<S>, Vector3<S>) {
+        match self {
+            Self::Quad(quad) => quad.position_derivatives(),
+            Self::Disk(disk) => disk.position_derivatives(point),
+            Self::Cylinder(cylinder) => cylinder.position_derivatives(point),
+            Self::Cone(cone) => cone.position_derivatives(point),
+        }
+    }
+
+    /// The surface area of the shape.
+    pub fn area(&self) -> S {
+        match self {
+            Self::Quad(quad) => quad.area(),
+            Self::Disk(disk) => disk.area(),
+            Self::Cylinder(cylinder) => cylinder.area(),
+            Self::Cone(cone) => cone.area(),
+        }
+    }
+}
+
+impl<S> From<Quad<S>> for Shape<S>
+where
+    S: SimdScalar
+{
+    fn from(quad: Quad<S>) -> Self {
+        Self::Quad(quad)
+    }
+}
+
+impl<S> From<Disk<S>> for Shape<S>
+where
+    S: SimdScalar
+{
+    fn from(disk: Disk<S>) -> Self {
+        Self::Disk(disk)
+    }
+}
+
+impl<S> From<Cylinder<S>> for Shape<S>
+where
+    S: SimdScalar
+{
+    fn from(cylinder: Cylinder<S>) -> Self {
+        Self::Cylinder(cylinder)
+    }
+}
+
+impl<S> From<Cone<S>> for Shape<S>
+where
+    S: SimdScalar
+{
+    fn from(cone: Cone<S>) -> Self {
+        Self::Cone(cone)
+    }
+}
+
+impl<S> BoundedPrimitive<S> for Shape<S>
+where
+    S: SimdScalarFloat
+{
+    #[inline]
+    fn bounds(&self) -> Aabb<S> {
+        Shape::bounds(self)
+    }
+
+    #[inline]
+    fn centroid(&self) -> Vector3<S> {
+        Shape::centroid(self)
+    }
+
+    #[inline]
+    fn intersect(&self, ray: &Ray<S>) -> Option<SurfaceInteraction<S>> {
+        Shape::intersect(self, ray)
+    }
+
+    #[inline]
+    fn occluded(&self, ray: &Ray<S>) -> bool {
+        Shape::occluded(self, ray)
+    }
+}
+
+/// A part of the surface of a capped cylinder or cone.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Part {
+    Side,
+    Bottom,
+    Top,
+}
+
+#[inline]
+pub(crate) fn pi<S>() -> S
+where
+    S: SimdScalarFloat
+{
+    num_traits::cast(std::f64::consts::PI).unwrap()
+}
+
+/// The angle of the point `(x, y)` around the origin, counterclockwise from the
+/// positive **x-axis**, in `[0, 2 * pi)`.
+#[inline]
+pub(crate) fn azimuth<S>(x: S, y: S) -> S
+where
+    S: SimdScalarFloat
+{
+    let phi = S::atan2(y, x);
+    if phi < S::zero() {
+        phi + pi::<S>() + pi::<S>()
+    } else {
+        phi
+    }
+}
+
+/// Construct two unit vectors that complete the unit vector `normal` to a right
+/// handed orthonormal basis, i.e. `tangent × bitangent == normal`.
+///
+/// This is the branchless construction of Duff et al., which is continuous
+/// everywhere except across the plane `normal.z == 0`.
+#[inline]
+pub(crate) fn orthonormal_basis<S>(normal: &Vector3<S>) -> (Vector3<S>, Vector3<S>)
+where
+    S: SimdScalarFloat
+{
+    let one = S::one();
+    let sign = if normal.z >= S::zero() { one } else { -one };
+    let a = -one / (sign + normal.z);
+    let b = normal.x * normal.y * a;
+    let tangent = Vector3::new(one + sign * normal.x * normal.x * a, sign * b, -sign * normal.x);
+    let bitangent = Vector3::new(b, sign + normal.y * normal.y * a, -normal.y);
+
+    (tangent, bitangent)
+}
+
+/// Solve `a * t^2 + b * t + c == 0` for `t`, returning the real roots in
+/// increasing order, or the single root twice when the equation is linear.
+#[inline]
+pub(crate) fn solve_quadratic<S>(a: S, b: S, c: S) -> Option<(S, S)>
+where
+    S: SimdScalarFloat
+{
+    if a == S::zero() {
+        if b == S::zero() {
+            return None;
+        }
+        let t = -c / b;
+
+        return Some((t, t));
+    }
+    let two = S::one() + S::one();
+    let four = two + two;
+    let discriminant = b * b - four * a * c;
+    if discriminant < S::zero() {
+        return None;
+    }
+    // Avoid subtracting nearly equal numbers when computing the smaller root.
+    let sqrt_discriminant = S::sqrt(discriminant);
+    let q = if b < S::zero() {
+        -(b - sqrt_discriminant) / two
+    } else {
+        -(b + sqrt_discriminant) / two
+    };
+    let t0 = q / a;
+    let t1 = if q == S::zero() { t0 } else { c / q };
+
+    Some((S::min(t0, t1), S::max(t0, t1)))
+}
@@ -0,0 +1,186 @@
+use crate::geometry::aabb::*;
+use crate::query::*;
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+    SimdScalar,
+    SimdScalarFloat,
+};
+
+
+/// An analytic sphere.
+///
+/// A ray hitting a sphere reports the texture coordinates of the hit point on
+/// the sphere in the `u` and `v` fields of its surface interaction, in place of
+/// the barycentric coordinates that a triangle reports.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct Sphere<S>
+where
+    S: SimdScalar
+{
+    pub center: Vector3<S>,
+    pub radius: S,
+}
+
+impl<S> Sphere<S>
+where
+    S: SimdScalar
+{
+    pub const fn new(center: Vector3<S>, radius: S) -> Self {
+        Self { center, radius, }
+    }
+}
+
+impl<S> Sphere<S>
+where
+    S: SimdScalarFloat
+{
+    pub fn centroid(&self) -> Vector3<S> {
+        self.center
+    }
+
+    pub fn bounds(&self) -> Aabb<S> {
+        let extent = Vector3::from_fill(S::abs(self.radius));
+
+        Aabb::new(self.center - extent, self.center + extent)
+    }
+
+    /// The distances along the ray at which the ray enters and leaves the
+    /// sphere, nearest first.
+    #[inline]
+    fn intersection_distances(&self, ray: &Ray<S>) -> Option<(S, S)> {
+        // Solve |origin + t * direction - center|^2 = radius^2 for t. The
+        // discriminant is computed from the distance of the sphere center to
+        // the line of the ray instead of from `b^2 - 4ac`, which loses most of
+        // its precision when the sphere is small compared to its distance.
+        let offset = ray.origin - self.center;
+        let a = ray.direction.dot(&ray.direction);
+        let half_b = offset.dot(&ray.direction);
+        let perpendicular = offset - ray.direction * (half_b / a);
+        let discriminant = a * (self.radius * self.radius - perpendicular.dot(&perpendicular));
+        if discriminant < S::zero() || a == S::zero() {
+            return None;
+        }
+        let sqrt_discriminant = S::sqrt(discriminant);
+        let q = if half_b > S::zero() {
+            -half_b - sqrt_discriminant
+        } else {
+            -half_b + sqrt_discriminant
+        };
+        let t0 = q / a;
+        let t1 = if q == S::zero() { t0 } else { (offset.dot(&offset) - self.radius * self.radius) / q };
+
+        Some((S::min(t0, t1), S::max(t0, t1)))
+    }
+
+    /// Find the nearest distance along the ray past distance `ray.t_min` at which
+    /// the ray hits the sphere.
+    #[inline]
+    fn nearest_distance(&self, ray: &Ray<S>) -> Option<S> {
+        let (t_near, t_far) = self.intersection_distances(ray)?;
+        if t_near > ray.t_min {
+            Some(t_near)
+        } else if t_far > ray.t_min {
+            // The ray starts inside the sphere.
+            Some(t_far)
+        } else {
+            None
+        }
+    }
+
+    #[inline]
+    pub fn intersect(&self, ray: &Ray<S>) -> Option<SurfaceInteraction<S>> {
+        let t = self.nearest_distance(ray)?;
+        if t >= ray.t {
+            return None;
+        }
+        let uv = self.uv(&ray.interpolate(t));
+
+        Some(SurfaceInteraction::new(t, uv.x, uv.y))
+    }
+
+    /// Determine whether the ray hits the sphere before reaching distance `ray.t`
+    /// along the ray.
+    #[inline]
+    pub fn occluded(&self, ray: &Ray<S>) -> bool {
+        match self.nearest_distance(ray) {
+            Some(t) => t < ray.t,
+            None => false,
+        }
+    }
+
+    /// The outward unit normal of the sphere at the point `point` on its surface.
+    pub fn normal(&self, point: &Vector3<S>) -> Vector3<S> {
+        (point - self.center) / S::abs(self.radius)
+    }
+
+    /// The texture coordinates of the point `point` on the surface of the sphere.
+    ///
+    /// The `u` coordinate runs once around the **y-axis**, starting and ending on
+    /// the negative **x-axis**, and the `v` coordinate runs from the bottom of
+    /// the sphere at `v == 0` to the top of the sphere at `v == 1`.
+    pub fn uv(&self, point: &Vector3<S>) -> Vector2<S> {
+        let one = S::one();
+        let two = one + one;
+        let normal = self.normal(point);
+        let theta = S::acos(S::max(-one, S::min(one, -normal.y)));
+        let phi = S::atan2(-normal.z, normal.x) + pi::<S>();
+
+        Vector2::new(phi / (two * pi::<S>()), theta / pi::<S>())
+    }
+
+    /// The outward unit normal of the sphere at the point with texture
+    /// coordinates `uv`. This is the inverse of [`Sphere::uv`].
+    pub fn normal_at_uv(&self, uv: &Vector2<S>) -> Vector3<S> {
+        let one = S::one();
+        let two = one + one;
+        let theta = uv.y * pi::<S>();
+        let phi = uv.x * two * pi::<S>();
+        let (sin_theta, cos_theta) = S::sin_cos(theta);
+        let (sin_phi, cos_phi) = S::sin_cos(phi);
+
+        Vector3::new(-sin_theta * cos_phi, -cos_theta, sin_theta * sin_phi)
+    }
+
+    /// The rates of change of the position on the surface of the sphere with
+    /// respect to the `u` and `v` texture coordinates at the point `point`.
+    pub fn position_derivatives(&self, point: &Vector3<S>) -> (Vector3<S>, Vector3<S>) {
+        let one = S::one();
+        let two = one + one;
+        let normal = self.normal(point);
+        let radius = S::abs(self.radius);
+        let radial = S::sqrt(normal.x * normal.x + normal.z * normal.z);
+        let dpdu = Vector3::new(-normal.z, S::zero(), normal.x) * (-two * pi::<S>() * radius);
+        let dpdv = if radial > S::zero() {
+            let cos_theta = -normal.y;
+            Vector3::new(normal.x * cos_theta / radial, radial, normal.z * cos_theta / radial) * (pi::<S>() * radius)
+        } else {
+            // The longitude is undefined at the poles.
+            Vector3::unit_x() * (pi::<S>() * radius)
+        };
+
+        (dpdu, dpdv)
+    }
+
+    /// The surface area of the sphere.
+    pub fn area(&self) -> S {
+        let four = S::one() + S::one() + S::one() + S::one();
+
+        four * pi::<S>() * self.radius * self.radius
+    }
+
+    /// Determine whether the point `point` lies inside the sphere.
+    pub fn contains(&self, point: &Vector3<S>) -> bool {
+        (point - self.center).magnitude() <= S::abs(self.radius)
+    }
+}
+
+#[inline]
+fn pi<S>() -> S
+where
+    S: SimdScalarFloat
+{
+    num_traits::cast(std::f64::consts::PI).unwrap()
+}
@@ -4,7 +4,52 @@ use cglinalg::{
     SimdScalar,
     SimdScalarFloat,
 };
+use std::sync::atomic::{
+    AtomicU8,
+    Ordering,
+};
+
+
+/// The test used to intersect rays with triangles.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TriangleIntersection {
+    /// The Moeller-Trumbore test. It is fast, but rounding errors can let a ray 
+    /// that passes exactly through an edge or a vertex shared by several 
+    /// triangles slip through the cracks between them.
+    MollerTrumbore,
+    /// The watertight test of Woop, Benthin, and Wald. A ray that passes through 
+    /// an edge or a vertex shared by several triangles hits at least one of them.
+    Watertight,
+}
+
+static GLOBAL_TRIANGLE_INTERSECTION: AtomicU8 = AtomicU8::new(TriangleIntersection::MollerTrumbore as u8);
+
+impl TriangleIntersection {
+    /// The test used by every triangle intersection that does not choose one 
+    /// itself, i.e. by [`Triangle::intersect`], [`Triangle::occluded`], and 
+    /// boundary volume hierarchies built without a triangle intersection test.
+    /// This is [`TriangleIntersection::MollerTrumbore`] unless changed with 
+    /// [`TriangleIntersection::set_global_default`].
+    pub fn global_default() -> Self {
+        if GLOBAL_TRIANGLE_INTERSECTION.load(Ordering::Relaxed) == Self::Watertight as u8 {
+            Self::Watertight
+        } else {
+            Self::MollerTrumbore
+        }
+    }
 
+    /// Set the test used by every triangle intersection that does not choose 
+    /// one itself. 
+    /// 
+    /// The default is shared by the whole process, and changing it changes the 
+    /// behavior of every such intersection on every thread at once. Set it once 
+    /// at startup before any rays are traced, and choose the test per hierarchy 
+    /// with `BvhBuilder::with_triangle_intersection` where different parts of a 
+    /// program need different tests.
+    pub fn set_global_default(intersection: Self) {
+        GLOBAL_TRIANGLE_INTERSECTION.store(intersection as u8, Ordering::Relaxed);
+    }
+}
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, PartialEq, Default)]
@@ -38,15 +83,71 @@ where
         (self.vertices[0] + self.vertices[1] + self.vertices[2]) * one_third
     }
 
+    /// Find the intersection of the ray with the triangle using the global 
+    /// default triangle intersection test.
     #[inline]
     pub fn intersect(&self, ray: &Ray<S>) -> Option<SurfaceInteraction<S>> {
+        self.intersect_with(ray, TriangleIntersection::global_default())
+    }
+
+    /// Find the intersection of the ray with the triangle using the triangle 
+    /// intersection test `intersection`. Only hits inside the interval 
+    /// `(ray.t_min, ray.t)` along the ray are reported.
+    #[inline]
+    pub fn intersect_with(&self, ray: &Ray<S>, intersection: TriangleIntersection) -> Option<SurfaceInteraction<S>> {
+        let (t, u, v) = match intersection {
+            TriangleIntersection::MollerTrumbore => self.intersect_moller_trumbore(ray)?,
+            TriangleIntersection::Watertight => self.intersect_watertight(ray)?,
+        };
+        if ray.in_interval(t) {
+            Some(SurfaceInteraction::new(t, u, v))
+        } else {
+            None
+        }
+    }
+
+    /// Determine whether the ray hits the triangle before reaching distance `ray.t`
+    /// along the ray, using the global default triangle intersection test.
+    #[inline]
+    pub fn occluded(&self, ray: &Ray<S>) -> bool {
+        self.occluded_with(ray, TriangleIntersection::global_default())
+    }
+
+    /// Determine whether the ray hits the triangle between distances `ray.t_min` 
+    /// and `ray.t` along the ray, using the triangle intersection test `intersection`.
+    #[inline]
+    pub fn occluded_with(&self, ray: &Ray<S>, intersection: TriangleIntersection) -> bool {
+        let hit = match intersection {
+            TriangleIntersection::MollerTrumbore => self.intersect_moller_trumbore(ray),
+            TriangleIntersection::Watertight => self.intersect_watertight(ray),
+        };
+
+        matches!(hit, Some((t, _, _)) if t > ray.t_min && t < ray.t)
+    }
+
+    #[inline]
+    pub fn intersect_mut(&self, intersection: &mut Intersection<S>) -> bool {
+        if let Some(interaction) = self.intersect(&intersection.ray) {
+            intersection.ray.t = interaction.t;
+            intersection.interaction = interaction;
+
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Find the distance to the plane of the triangle along the line of the ray, 
+    /// and the barycentric coordinates of the second and third vertices at the 
+    /// crossing, when the line crosses the triangle.
+    #[inline]
+    fn intersect_moller_trumbore(&self, ray: &Ray<S>) -> Option<(S, S, S)> {
         // Moeller-Trumbore ray/triangle intersection algorithm.
         let edge1 = self.vertices[1] - self.vertices[0];
         let edge2 = self.vertices[2] - self.vertices[0];
         let normal = ray.direction.cross(&edge2);
         let area = edge1.dot(&normal);
-        let threshold: S = num_traits::cast(0.0001_f64).unwrap();
-        if S::abs(area) < threshold {
+        if area == S::zero() {
             // The ray is parallel to the triangle.
             return None;
         }
@@ -62,49 +163,76 @@ where
             return None;
         }
         let t = f * edge2.dot(&q);
-        if t > threshold {
-            let new_t = S::min(ray.t, t);
 
-            Some(SurfaceInteraction::new(new_t, u, v))
-        } else {
-            None
-        }
+        Some((t, u, v))
     }
 
+    /// Find the distance to the plane of the triangle along the line of the ray, 
+    /// and the barycentric coordinates of the second and third vertices at the 
+    /// crossing, when the line crosses the triangle, without letting the line 
+    /// slip through shared edges and vertices.
     #[inline]
-    pub fn intersect_mut(&self, intersection: &mut Intersection<S>) -> bool {
-        // Moeller-Trumbore ray/triangle intersection algorithm
-        let edge1 = self.vertices[1] - self.vertices[0];
-        let edge2 = self.vertices[2] - self.vertices[0];
-        let normal = intersection.ray.direction.cross(&edge2);
-        let area = edge1.dot(&normal);
-        let threshold: S = num_traits::cast(0.0001_f64).unwrap();
-        if S::abs(area) < threshold {
-            // The ray is parallel to the triangle.
-            return false;
+    fn intersect_watertight(&self, ray: &Ray<S>) -> Option<(S, S, S)> {
+        // Woop-Benthin-Wald watertight ray/triangle intersection algorithm. The 
+        // triangle is moved into a frame where the ray starts at the origin and 
+        // runs along the z-axis, so that whether the ray crosses an edge only 
+        // depends on the sign of a 2D edge function, which is computed the same
+        // way for both of the triangles sharing the edge.
+        let direction_abs = Vector3::new(S::abs(ray.direction.x), S::abs(ray.direction.y), S::abs(ray.direction.z));
+        let k_z = if direction_abs.x > direction_abs.y {
+            if direction_abs.x > direction_abs.z { 0 } else { 2 }
+        } else if direction_abs.y > direction_abs.z {
+            1
+        } else {
+            2
+        };
+        let (k_x, k_y) = if ray.direction[k_z] < S::zero() {
+            ((k_z + 2) % 3, (k_z + 1) % 3)
+        } else {
+            ((k_z + 1) % 3, (k_z + 2) % 3)
+        };
+        let shear_x = ray.direction[k_x] / ray.direction[k_z];
+        let shear_y = ray.direction[k_y] / ray.direction[k_z];
+        let shear_z = S::one() / ray.direction[k_z];
+        let a = self.vertices[0] - ray.origin;
+        let b = self.vertices[1] - ray.origin;
+        let c = self.vertices[2] - ray.origin;
+        let a_x = a[k_x] - shear_x * a[k_z];
+        let a_y = a[k_y] - shear_y * a[k_z];
+        let b_x = b[k_x] - shear_x * b[k_z];
+        let b_y = b[k_y] - shear_y * b[k_z];
+        let c_x = c[k_x] - shear_x * c[k_z];
+        let c_y = c[k_y] - shear_y * c[k_z];
+        let mut edge_a = c_x * b_y - c_y * b_x;
+        let mut edge_b = a_x * c_y - a_y * c_x;
+        let mut edge_c = b_x * a_y - b_y * a_x;
+        if edge_a == S::zero() || edge_b == S::zero() || edge_c == S::zero() {
+            // The ray passes through an edge or a vertex up to rounding, so the 
+            // edge functions are recomputed in double precision to settle which 
+            // side of the edge it is on.
+            let to_f64 = |value: S| -> f64 { num_traits::cast(value).unwrap() };
+            let from_f64 = |value: f64| -> S { num_traits::cast(value).unwrap() };
+            edge_a = from_f64(to_f64(c_x) * to_f64(b_y) - to_f64(c_y) * to_f64(b_x));
+            edge_b = from_f64(to_f64(a_x) * to_f64(c_y) - to_f64(a_y) * to_f64(c_x));
+            edge_c = from_f64(to_f64(b_x) * to_f64(a_y) - to_f64(b_y) * to_f64(a_x));
         }
-        let f = S::one() / area;
-        let s = intersection.ray.origin - self.vertices[0];
-        let u = f * s.dot(&normal);
-        if u < S::zero() || u > S::one() {
-            return false;
+        let zero = S::zero();
+        if (edge_a < zero || edge_b < zero || edge_c < zero) && (edge_a > zero || edge_b > zero || edge_c > zero) {
+            return None;
         }
-        let q = s.cross(&edge1);
-        let v = f * intersection.ray.direction.dot(&q);
-        if v < S::zero() || u + v > S::one() {
-            return false;
+        let determinant = edge_a + edge_b + edge_c;
+        if determinant == zero {
+            return None;
         }
-        let t = f * edge2.dot(&q);
-        if t > threshold {
-            intersection.ray.t = S::min(intersection.ray.t, t);
-            intersection.interaction.t = intersection.ray.t;
-            intersection.interaction.u = u;
-            intersection.interaction.v = v;
+        let a_z = shear_z * a[k_z];
+        let b_z = shear_z * b[k_z];
+        let c_z = shear_z * c[k_z];
+        let scaled_t = edge_a * a_z + edge_b * b_z + edge_c * c_z;
+        let recip_determinant = S::one() / determinant;
+        let t = scaled_t * recip_determinant;
+        let u = edge_b * recip_determinant;
+        let v = edge_c * recip_determinant;
 
-            return true;
-        } else {
-            return false;
-        }
+        Some((t, u, v))
     }
 }
-
@@ -0,0 +1,300 @@
+use crate::geometry::aabb::*;
+use crate::geometry::primitive::*;
+use crate::query::*;
+use cglinalg::{
+    Vector3,
+    SimdScalar,
+    SimdScalarFloat,
+};
+
+
+/// The voxel that a ray hits first in a voxel grid.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct VoxelHit<S>
+where
+    S: SimdScalar
+{
+    /// The distance along the ray to the hit.
+    pub t: S,
+    /// The index of the voxel along each axis of the grid.
+    pub cell: [usize; 3],
+    /// The unit normal of the face of the voxel that the ray entered through,
+    /// which faces the ray.
+    pub normal: Vector3<S>,
+    /// The material id of the voxel.
+    pub material_id: u32,
+}
+
+
+/// A dense grid of cubical voxels filling an axis aligned box.
+///
+/// Each voxel is either empty or solid with a material id, which indexes the
+/// material table of the model holding the grid. Rays are traced through the
+/// grid one voxel at a time with the 3D-DDA algorithm of Amanatides and Woo,
+/// so the cost of a ray depends on the number of voxels it passes rather than
+/// on the number of solid voxels in the grid.
+///
+/// A ray hitting the grid reports the position of the hit on the face of the
+/// voxel it entered in the `u` and `v` fields of its surface interaction.
+#[derive(Clone, Debug, PartialEq)]
+pub struct VoxelGrid<S>
+where
+    S: SimdScalar
+{
+    bounds_min: Vector3<S>,
+    voxel_size: S,
+    dimensions: [usize; 3],
+    voxels: Vec<u32>,
+}
+
+impl<S> VoxelGrid<S>
+where
+    S: SimdScalar
+{
+    /// The value of an empty voxel.
+    const EMPTY: u32 = u32::MAX;
+
+    /// The number of voxels along each axis of the grid.
+    pub const fn dimensions(&self) -> [usize; 3] {
+        self.dimensions
+    }
+
+    /// The length of each edge of a voxel.
+    pub const fn voxel_size(&self) -> S {
+        self.voxel_size
+    }
+
+    /// The number of voxels in the grid, solid or empty.
+    pub fn len(&self) -> usize {
+        self.voxels.len()
+    }
+
+    /// Determine whether the grid has no voxels at all.
+    pub fn is_empty(&self) -> bool {
+        self.voxels.is_empty()
+    }
+
+    /// The number of solid voxels in the grid.
+    pub fn len_solid(&self) -> usize {
+        self.voxels.iter().filter(|&&voxel| voxel != Self::EMPTY).count()
+    }
+
+    #[inline]
+    fn linear_index(&self, cell: [usize; 3]) -> usize {
+        (cell[2] * self.dimensions[1] + cell[1]) * self.dimensions[0] + cell[0]
+    }
+
+    /// The material id of the voxel at `cell`, or `None` if the voxel is empty.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `cell` is outside the grid.
+    pub fn get(&self, cell: [usize; 3]) -> Option<u32> {
+        self.assert_in_grid(cell);
+        match self.voxels[self.linear_index(cell)] {
+            Self::EMPTY => None,
+            material_id => Some(material_id),
+        }
+    }
+
+    /// Fill the voxel at `cell` with the material with id `material_id`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `cell` is outside the grid, or if `material_id`
+    /// is `u32::MAX`, which is reserved for empty voxels.
+    pub fn set(&mut self, cell: [usize; 3], material_id: u32) {
+        self.assert_in_grid(cell);
+        assert_ne!(material_id, Self::EMPTY, "The material id {} is reserved for empty voxels.", Self::EMPTY);
+        let index = self.linear_index(cell);
+        self.voxels[index] = material_id;
+    }
+
+    /// Empty the voxel at `cell`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `cell` is outside the grid.
+    pub fn clear(&mut self, cell: [usize; 3]) {
+        self.assert_in_grid(cell);
+        let index = self.linear_index(cell);
+        self.voxels[index] = Self::EMPTY;
+    }
+
+    /// The material ids of the solid voxels in the grid.
+    pub fn material_ids(&self) -> impl Iterator<Item = u32> + '_ {
+        self.voxels.iter().copied().filter(|&voxel| voxel != Self::EMPTY)
+    }
+
+    fn assert_in_grid(&self, cell: [usize; 3]) {
+        assert!(
+            cell.iter().zip(self.dimensions.iter()).all(|(&index, &dimension)| index < dimension),
+            "The voxel {:?} is outside of a grid of dimensions {:?}.",
+            cell, self.dimensions
+        );
+    }
+}
+
+impl<S> VoxelGrid<S>
+where
+    S: SimdScalarFloat
+{
+    /// Construct an empty grid of `dimensions` voxels with edges of length
+    /// `voxel_size`, whose lowest corner is `bounds_min`.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if the voxel size is not positive.
+    pub fn new(bounds_min: Vector3<S>, voxel_size: S, dimensions: [usize; 3]) -> Self {
+        assert!(voxel_size > S::zero(), "The voxel size must be positive.");
+        let voxels = vec![Self::EMPTY; dimensions[0] * dimensions[1] * dimensions[2]];
+
+        Self { bounds_min, voxel_size, dimensions, voxels, }
+    }
+
+    /// The bounding box of the whole grid.
+    pub fn bounds(&self) -> Aabb<S> {
+        let extent = Vector3::new(
+            self.voxel_size * num_traits::cast(self.dimensions[0]).unwrap(),
+            self.voxel_size * num_traits::cast(self.dimensions[1]).unwrap(),
+            self.voxel_size * num_traits::cast(self.dimensions[2]).unwrap(),
+        );
+
+        Aabb::new(self.bounds_min, self.bounds_min + extent)
+    }
+
+    /// The voxel containing the point `point`, or `None` if the point is
+    /// outside the grid.
+    pub fn cell_at(&self, point: &Vector3<S>) -> Option<[usize; 3]> {
+        let mut cell = [0; 3];
+        for axis in 0..3 {
+            let position = (point[axis] - self.bounds_min[axis]) / self.voxel_size;
+            if position.is_nan() || position < S::zero() {
+                return None;
+            }
+            let index: usize = num_traits::cast(S::floor(position))?;
+            if index >= self.dimensions[axis] {
+                return None;
+            }
+            cell[axis] = index;
+        }
+
+        Some(cell)
+    }
+
+    /// Find the first solid voxel that the ray enters past distance `ray.t_min`
+    /// and before distance `ray.t` along the ray.
+    ///
+    /// The voxel that a ray starts in is never hit, so a ray leaving the surface
+    /// of a voxel does not hit the voxel it left.
+    pub fn intersect_voxel(&self, ray: &Ray<S>) -> Option<VoxelHit<S>> {
+        let bounds = self.bounds();
+        let (t_enter, t_exit) = bounds.intersection_distances(ray)?;
+        let t_start = S::max(t_enter, S::zero());
+        let t_end = S::min(t_exit, ray.t);
+        if t_start > t_end || self.is_empty() {
+            return None;
+        }
+
+        // The axis whose faces the ray crosses to enter the current voxel. This is
+        // the axis of the face of the grid that the ray enters through, if it
+        // starts outside of the grid.
+        let mut entry_axis = {
+            let mut entry_axis = 0;
+            let mut entry_t = -S::max_value();
+            for axis in 0..3 {
+                if ray.direction[axis] != S::zero() {
+                    let plane = if ray.direction[axis] > S::zero() { bounds.bounds_min[axis] } else { bounds.bounds_max[axis] };
+                    let t = (plane - ray.origin[axis]) / ray.direction[axis];
+                    if t > entry_t {
+                        entry_t = t;
+                        entry_axis = axis;
+                    }
+                }
+            }
+            entry_axis
+        };
+        let start = ray.interpolate(t_start);
+        let mut cell = [0_isize; 3];
+        let mut step = [0_isize; 3];
+        let mut t_max = [S::max_value(); 3];
+        let mut t_delta = [S::max_value(); 3];
+        for axis in 0..3 {
+            let position = (start[axis] - self.bounds_min[axis]) / self.voxel_size;
+            let index: isize = num_traits::cast(S::floor(position)).unwrap_or(0);
+            cell[axis] = isize::max(0, isize::min(index, self.dimensions[axis] as isize - 1));
+            if ray.direction[axis] > S::zero() {
+                step[axis] = 1;
+                let boundary = self.bounds_min[axis] + self.voxel_size * num_traits::cast(cell[axis] + 1).unwrap();
+                t_max[axis] = (boundary - ray.origin[axis]) / ray.direction[axis];
+                t_delta[axis] = self.voxel_size / ray.direction[axis];
+            } else if ray.direction[axis] < S::zero() {
+                step[axis] = -1;
+                let boundary = self.bounds_min[axis] + self.voxel_size * num_traits::cast(cell[axis]).unwrap();
+                t_max[axis] = (boundary - ray.origin[axis]) / ray.direction[axis];
+                t_delta[axis] = -self.voxel_size / ray.direction[axis];
+            }
+        }
+
+        let mut t_cell = t_start;
+        loop {
+            let current_cell = [cell[0] as usize, cell[1] as usize, cell[2] as usize];
+            let voxel = self.voxels[self.linear_index(current_cell)];
+            if voxel != Self::EMPTY && t_cell > ray.t_min {
+                let mut normal = Vector3::zero();
+                normal[entry_axis] = if step[entry_axis] > 0 { -S::one() } else { S::one() };
+
+                return Some(VoxelHit { t: t_cell, cell: current_cell, normal, material_id: voxel, });
+            }
+
+            let axis = if t_max[0] < t_max[1] {
+                if t_max[0] < t_max[2] { 0 } else { 2 }
+            } else if t_max[1] < t_max[2] {
+                1
+            } else {
+                2
+            };
+            t_cell = t_max[axis];
+            if t_cell > t_end {
+                return None;
+            }
+            cell[axis] += step[axis];
+            if cell[axis] < 0 || cell[axis] >= self.dimensions[axis] as isize {
+                return None;
+            }
+            t_max[axis] += t_delta[axis];
+            entry_axis = axis;
+        }
+    }
+}
+
+impl<S> BoundedPrimitive<S> for VoxelGrid<S>
+where
+    S: SimdScalarFloat
+{
+    fn bounds(&self) -> Aabb<S> {
+        VoxelGrid::bounds(self)
+    }
+
+    fn centroid(&self) -> Vector3<S> {
+        VoxelGrid::bounds(self).centroid()
+    }
+
+    fn intersect(&self, ray: &Ray<S>) -> Option<SurfaceInteraction<S>> {
+        let hit = self.intersect_voxel(ray)?;
+        // The position of the hit on the face of the voxel, along the two axes
+        // that span the face.
+        let entry_axis = (0..3).find(|&axis| hit.normal[axis] != S::zero()).unwrap();
+        let axis_u = (entry_axis + 1) % 3;
+        let axis_v = (entry_axis + 2) % 3;
+        let position = ray.interpolate(hit.t);
+        let face_coordinate = |axis: usize| {
+            let cell_min = self.bounds_min[axis] + self.voxel_size * num_traits::cast(hit.cell[axis]).unwrap();
+            let coordinate = (position[axis] - cell_min) / self.voxel_size;
+
+            S::max(S::zero(), S::min(S::one(), coordinate))
+        };
+
+        Some(SurfaceInteraction::new(hit.t, face_coordinate(axis_u), face_coordinate(axis_v)))
+    }
+}
@@ -6,11 +6,13 @@ mod model;
 mod query;
 mod scene;
 mod materials;
+mod lights;
 mod camera;
 mod renderer;
 mod physics;
 mod transform;
 mod transform_component;
+mod file_resolver;
 
 
 pub use geometry::*;
@@ -20,9 +22,11 @@ pub use model::*;
 pub use query::*;
 pub use scene::*;
 pub use materials::*;
+pub use lights::*;
 pub use camera::*;
 pub use renderer::*;
 pub use physics::*;
 pub use transform::*;
 pub use transform_component::*;
+pub use file_resolver::*;
 
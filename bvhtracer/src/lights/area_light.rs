@@ -0,0 +1,227 @@
+use crate::geometry::*;
+use crate::mesh::*;
+use crate::model::*;
+use crate::query::*;
+use crate::scene::*;
+use crate::transform::*;
+use super::light::*;
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+};
+
+
+/// A light that emits from the surface of a triangle mesh, such as a light panel 
+/// or a glowing object. 
+/// 
+/// Every triangle in the mesh emits the same radiance from its front face, i.e.
+/// the face that its vertices wind counterclockwise around. Positions on the
+/// light are sampled uniformly by area.
+#[derive(Clone, Debug)]
+pub struct AreaLight {
+    /// The primitives of the light in world space.
+    primitives: Vec<Triangle<f32>>,
+    /// The acceleration structure for finding where rays hit the light.
+    bvh: Bvh,
+    /// The running sums of the primitive areas, used to sample a primitive 
+    /// in proportion to its area.
+    cumulative_areas: Vec<f32>,
+    radiance: Vector3<f32>,
+    two_sided: bool,
+}
+
+impl AreaLight {
+    /// Construct a new area light from the primitives of a mesh, placed in the 
+    /// world by `transform`.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if the mesh has no primitives.
+    pub fn new(mesh: &Mesh<f32>, transform: &Transform3<f32>, radiance: Vector3<f32>) -> Self {
+        Self::from_primitives(mesh.primitives(), transform, radiance)
+    }
+
+    /// Construct a new area light from the mesh and the world transform of an 
+    /// object in a scene, so that the object glows.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if the object's mesh has no primitives.
+    pub fn from_scene_object(object: &SceneObject, radiance: Vector3<f32>) -> Self {
+        let model = object.model().model();
+        let borrow = model.borrow();
+
+        Self::from_primitives(borrow.primitives(), object.get_transform(), radiance)
+    }
+
+    fn from_primitives(primitives: &[Triangle<f32>], transform: &Transform3<f32>, radiance: Vector3<f32>) -> Self {
+        assert!(!primitives.is_empty(), "An area light needs at least one primitive.");
+        let mut primitives_world_space = primitives.iter()
+            .map(|primitive| Triangle::new(
+                transform.transform_point(&primitive.vertices[0]),
+                transform.transform_point(&primitive.vertices[1]),
+                transform.transform_point(&primitive.vertices[2]),
+            ))
+            .collect::<Vec<_>>();
+        let bvh = BvhBuilder::new().build_for(&mut primitives_world_space);
+        // Build the cumulative areas after the BVH build reorders the primitives.
+        let cumulative_areas = primitives_world_space.iter()
+            .scan(0_f32, |total_area, primitive| {
+                *total_area += Self::primitive_area(primitive);
+                Some(*total_area)
+            })
+            .collect::<Vec<_>>();
+
+        Self { 
+            primitives: primitives_world_space, 
+            bvh, 
+            cumulative_areas, 
+            radiance, 
+            two_sided: false, 
+        }
+    }
+
+    /// Set whether the light emits from both faces of each primitive instead of 
+    /// only the front face.
+    pub fn with_two_sided(mut self, two_sided: bool) -> Self {
+        self.two_sided = two_sided;
+
+        self
+    }
+
+    #[inline]
+    pub const fn radiance(&self) -> Vector3<f32> {
+        self.radiance
+    }
+
+    #[inline]
+    pub const fn is_two_sided(&self) -> bool {
+        self.two_sided
+    }
+
+    /// The world space primitives of the light.
+    #[inline]
+    pub fn primitives(&self) -> &[Triangle<f32>] {
+        &self.primitives
+    }
+
+    /// The total surface area of the light.
+    #[inline]
+    pub fn area(&self) -> f32 {
+        self.cumulative_areas[self.cumulative_areas.len() - 1]
+    }
+
+    fn primitive_area(primitive: &Triangle<f32>) -> f32 {
+        let edge1 = primitive.vertices[1] - primitive.vertices[0];
+        let edge2 = primitive.vertices[2] - primitive.vertices[0];
+
+        0.5_f32 * edge1.cross(&edge2).magnitude()
+    }
+
+    fn primitive_normal(primitive: &Triangle<f32>) -> Vector3<f32> {
+        let edge1 = primitive.vertices[1] - primitive.vertices[0];
+        let edge2 = primitive.vertices[2] - primitive.vertices[0];
+
+        edge1.cross(&edge2).normalize()
+    }
+
+    /// The cosine of the angle between the primitive normal and the direction 
+    /// `direction` leaving the light, or `None` if the light does not emit in 
+    /// that direction.
+    fn emitting_cosine(&self, primitive: &Triangle<f32>, direction: &Vector3<f32>) -> Option<f32> {
+        let cos_theta = Self::primitive_normal(primitive).dot(direction);
+        if cos_theta > 0_f32 || (self.two_sided && cos_theta < 0_f32) {
+            Some(f32::abs(cos_theta))
+        } else {
+            None
+        }
+    }
+
+    /// Find the closest position on the light along a ray, returning the primitive
+    /// hit and the distance to it.
+    fn intersect(&self, point: &Vector3<f32>, direction: &Vector3<f32>) -> Option<(&Triangle<f32>, f32)> {
+        let ray = Ray::from_origin_dir(*point, *direction);
+        self.bvh.intersect(&self.primitives, &ray).map(|intersection| {
+            let primitive_index = intersection.instance_primitive.primitive_index() as usize;
+            (&self.primitives[primitive_index], intersection.interaction.t)
+        })
+    }
+}
+
+impl Light for AreaLight {
+    fn sample(&self, point: &Vector3<f32>, u: &Vector2<f32>) -> Option<LightSample> {
+        let total_area = self.area();
+        if total_area <= 0_f32 {
+            return None;
+        }
+
+        // Select a primitive in proportion to its area, and reuse the position of
+        // the random number inside the primitive's share of the total area to
+        // sample a point on the primitive.
+        let target_area = u.x * total_area;
+        let primitive_index = usize::min(
+            self.cumulative_areas.partition_point(|&cumulative_area| cumulative_area <= target_area),
+            self.primitives.len() - 1
+        );
+        let lower_area = if primitive_index == 0 { 0_f32 } else { self.cumulative_areas[primitive_index - 1] };
+        let primitive_area = self.cumulative_areas[primitive_index] - lower_area;
+        let u0 = f32::min((target_area - lower_area) / primitive_area, 1_f32);
+        let primitive = &self.primitives[primitive_index];
+        let light_point = {
+            // Uniformly sample barycentric coordinates.
+            let s = f32::sqrt(u0);
+            let b0 = 1_f32 - s;
+            let b1 = u.y * s;
+            let b2 = 1_f32 - b0 - b1;
+            primitive.vertices[0] * b0 + primitive.vertices[1] * b1 + primitive.vertices[2] * b2
+        };
+        let displacement = light_point - point;
+        let distance_squared = displacement.magnitude_squared();
+        if distance_squared == 0_f32 {
+            return None;
+        }
+        let distance = f32::sqrt(distance_squared);
+        let direction = displacement / distance;
+        let cos_theta = self.emitting_cosine(primitive, &(-direction))?;
+        if cos_theta == 0_f32 {
+            return None;
+        }
+        // Convert the density from area measure to solid angle measure.
+        let pdf = distance_squared / (cos_theta * total_area);
+
+        Some(LightSample::new(self.radiance, direction, distance, pdf))
+    }
+
+    fn pdf(&self, point: &Vector3<f32>, direction: &Vector3<f32>) -> f32 {
+        if let Some((primitive, distance)) = self.intersect(point, direction) {
+            match self.emitting_cosine(primitive, &(-direction)) {
+                Some(cos_theta) if cos_theta > 0_f32 => {
+                    (distance * distance) / (cos_theta * self.area())
+                }
+                _ => 0_f32,
+            }
+        } else {
+            0_f32
+        }
+    }
+
+    fn eval(&self, point: &Vector3<f32>, direction: &Vector3<f32>) -> Vector3<f32> {
+        if let Some((primitive, _)) = self.intersect(point, direction) {
+            if self.emitting_cosine(primitive, &(-direction)).is_some() {
+                return self.radiance;
+            }
+        }
+
+        Vector3::zero()
+    }
+
+    fn distance(&self, point: &Vector3<f32>, direction: &Vector3<f32>) -> Option<f32> {
+        self.intersect(point, direction).map(|(_, distance)| distance)
+    }
+
+    fn is_delta(&self) -> bool {
+        false
+    }
+}
+
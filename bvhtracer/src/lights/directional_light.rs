@@ -0,0 +1,60 @@
+use super::light::*;
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+};
+
+
+/// A light infinitely far away whose light arrives from a single direction,
+/// such as the sun.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct DirectionalLight {
+    direction: Vector3<f32>,
+    radiance: Vector3<f32>,
+}
+
+impl DirectionalLight {
+    /// Construct a new directional light whose light travels in the direction 
+    /// `direction`.
+    pub fn new(direction: Vector3<f32>, radiance: Vector3<f32>) -> Self {
+        Self { 
+            direction: direction.normalize(), 
+            radiance, 
+        }
+    }
+
+    /// The unit direction the light travels in.
+    #[inline]
+    pub const fn direction(&self) -> Vector3<f32> {
+        self.direction
+    }
+
+    #[inline]
+    pub const fn radiance(&self) -> Vector3<f32> {
+        self.radiance
+    }
+}
+
+impl Light for DirectionalLight {
+    fn sample(&self, _point: &Vector3<f32>, _u: &Vector2<f32>) -> Option<LightSample> {
+        Some(LightSample::new(self.radiance, -self.direction, f32::MAX, 1_f32))
+    }
+
+    fn pdf(&self, _point: &Vector3<f32>, _direction: &Vector3<f32>) -> f32 {
+        0_f32
+    }
+
+    fn eval(&self, _point: &Vector3<f32>, _direction: &Vector3<f32>) -> Vector3<f32> {
+        Vector3::zero()
+    }
+
+    fn distance(&self, _point: &Vector3<f32>, _direction: &Vector3<f32>) -> Option<f32> {
+        None
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+}
+
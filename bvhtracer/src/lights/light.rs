@@ -0,0 +1,82 @@
+use crate::query::*;
+use cglinalg::{
+    Vector2,
+    Vector3,
+};
+use std::fmt;
+
+
+/// The fraction of the distance to a sampled light position that a shadow ray 
+/// covers. Shadow rays stop just short of the light so that they do not hit 
+/// the geometry the light is attached to.
+pub(crate) const SHADOW_RAY_LENGTH_SCALE: f32 = 1_f32 - 1e-3;
+
+
+/// A sample of the illumination that a light casts on a point in a scene.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct LightSample {
+    /// The radiance arriving at the point from the sampled position on the light.
+    pub radiance: Vector3<f32>,
+    /// The unit direction from the point toward the sampled position on the light.
+    pub direction: Vector3<f32>,
+    /// The distance from the point to the sampled position on the light. Lights 
+    /// that are infinitely far away use `f32::MAX`.
+    pub distance: f32,
+    /// The probability density of sampling `direction`, with respect to solid angle.
+    /// Lights described by a delta distribution, such as point lights, use one.
+    pub pdf: f32,
+}
+
+impl LightSample {
+    pub fn new(radiance: Vector3<f32>, direction: Vector3<f32>, distance: f32, pdf: f32) -> Self {
+        Self { radiance, direction, distance, pdf, }
+    }
+
+    /// Construct a ray from `origin` toward the sampled position on the light, 
+    /// for testing whether the light is visible from `origin`.
+    /// 
+    /// The ray counts every hit past its origin, so an origin on a surface should 
+    /// first be offset away from it, e.g. with [`Intersection::spawn_ray`].
+    pub fn shadow_ray(&self, origin: &Vector3<f32>) -> Ray<f32> {
+        let t = if self.distance < f32::MAX {
+            self.distance * SHADOW_RAY_LENGTH_SCALE
+        } else {
+            f32::MAX
+        };
+
+        Ray::from_interval(*origin, self.direction, 0_f32, t)
+    }
+}
+
+/// A source of light in a scene.
+pub trait Light: fmt::Debug + Send + Sync {
+    /// Sample the illumination arriving at `point` from the light, using the 
+    /// uniformly distributed random numbers `u` in `[0, 1)^2`. 
+    /// 
+    /// Returns `None` when the light does not illuminate `point`.
+    fn sample(&self, point: &Vector3<f32>, u: &Vector2<f32>) -> Option<LightSample>;
+
+    /// The probability density, with respect to solid angle, that [`Light::sample`] 
+    /// samples `direction` from `point`. 
+    /// 
+    /// This is zero for lights described by a delta distribution, because no 
+    /// ray sampled some other way can ever hit them.
+    fn pdf(&self, point: &Vector3<f32>, direction: &Vector3<f32>) -> f32;
+
+    /// The radiance arriving at `point` from the light along the unit direction 
+    /// `direction`.
+    /// 
+    /// This is zero for lights described by a delta distribution.
+    fn eval(&self, point: &Vector3<f32>, direction: &Vector3<f32>) -> Vector3<f32>;
+
+    /// The distance from `point` to the light along the unit direction `direction`,
+    /// or `None` if a ray from `point` in that direction misses the light.
+    /// 
+    /// This is `None` for lights described by a delta distribution.
+    fn distance(&self, point: &Vector3<f32>, direction: &Vector3<f32>) -> Option<f32>;
+
+    /// Determine whether the light is described by a delta distribution, i.e. 
+    /// whether it can only be reached by sampling it directly.
+    fn is_delta(&self) -> bool;
+}
+
@@ -0,0 +1,15 @@
+mod light;
+mod point_light;
+mod directional_light;
+mod spot_light;
+mod area_light;
+mod shape_light;
+
+
+pub use light::*;
+pub use point_light::*;
+pub use directional_light::*;
+pub use spot_light::*;
+pub use area_light::*;
+pub use shape_light::*;
+
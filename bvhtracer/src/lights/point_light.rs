@@ -0,0 +1,63 @@
+use super::light::*;
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+};
+
+
+/// A light that emits equally in every direction from a single point.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct PointLight {
+    position: Vector3<f32>,
+    intensity: Vector3<f32>,
+}
+
+impl PointLight {
+    /// Construct a new point light with radiant intensity `intensity` at `position`.
+    pub fn new(position: Vector3<f32>, intensity: Vector3<f32>) -> Self {
+        Self { position, intensity, }
+    }
+
+    #[inline]
+    pub const fn position(&self) -> Vector3<f32> {
+        self.position
+    }
+
+    #[inline]
+    pub const fn intensity(&self) -> Vector3<f32> {
+        self.intensity
+    }
+}
+
+impl Light for PointLight {
+    fn sample(&self, point: &Vector3<f32>, _u: &Vector2<f32>) -> Option<LightSample> {
+        let displacement = self.position - point;
+        let distance_squared = displacement.magnitude_squared();
+        if distance_squared == 0_f32 {
+            return None;
+        }
+        let distance = f32::sqrt(distance_squared);
+        let direction = displacement / distance;
+        let radiance = self.intensity / distance_squared;
+
+        Some(LightSample::new(radiance, direction, distance, 1_f32))
+    }
+
+    fn pdf(&self, _point: &Vector3<f32>, _direction: &Vector3<f32>) -> f32 {
+        0_f32
+    }
+
+    fn eval(&self, _point: &Vector3<f32>, _direction: &Vector3<f32>) -> Vector3<f32> {
+        Vector3::zero()
+    }
+
+    fn distance(&self, _point: &Vector3<f32>, _direction: &Vector3<f32>) -> Option<f32> {
+        None
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+}
+
@@ -0,0 +1,156 @@
+use crate::geometry::*;
+use crate::query::*;
+use super::light::*;
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+};
+
+
+/// A light that emits from the surface of an analytic quad or disk, such as a
+/// light panel or a round ceiling lamp.
+///
+/// The light emits the same radiance from the front face of the shape, i.e. the
+/// face that its normal points out of. Positions on the light are sampled
+/// uniformly by area, directly from the parameterization of the shape instead of
+/// from a triangulation of it.
+#[derive(Clone, Debug)]
+pub struct ShapeLight {
+    /// The shape of the light in world space.
+    shape: Shape<f32>,
+    radiance: Vector3<f32>,
+    two_sided: bool,
+}
+
+impl ShapeLight {
+    /// Construct a new light emitting from the world space quad `quad`.
+    pub fn from_quad(quad: Quad<f32>, radiance: Vector3<f32>) -> Self {
+        Self { shape: Shape::Quad(quad), radiance, two_sided: false, }
+    }
+
+    /// Construct a new light emitting from the world space disk `disk`.
+    pub fn from_disk(disk: Disk<f32>, radiance: Vector3<f32>) -> Self {
+        Self { shape: Shape::Disk(disk), radiance, two_sided: false, }
+    }
+
+    /// Set whether the light emits from both faces of its shape instead of only
+    /// the front face.
+    pub fn with_two_sided(mut self, two_sided: bool) -> Self {
+        self.two_sided = two_sided;
+
+        self
+    }
+
+    #[inline]
+    pub const fn radiance(&self) -> Vector3<f32> {
+        self.radiance
+    }
+
+    #[inline]
+    pub const fn is_two_sided(&self) -> bool {
+        self.two_sided
+    }
+
+    /// The world space shape of the light.
+    #[inline]
+    pub const fn shape(&self) -> &Shape<f32> {
+        &self.shape
+    }
+
+    /// The surface area of the light.
+    #[inline]
+    pub fn area(&self) -> f32 {
+        self.shape.area()
+    }
+
+    /// Sample a position on the light uniformly by area, using the uniformly
+    /// distributed random numbers `u` in `[0, 1)^2`.
+    fn sample_point(&self, u: &Vector2<f32>) -> Vector3<f32> {
+        match &self.shape {
+            Shape::Quad(quad) => quad.point_at_uv(u),
+            // The area inside a radius grows with its square, so a uniform radius
+            // would crowd the samples toward the center.
+            Shape::Disk(disk) => disk.point_at_uv(&Vector2::new(u.x, f32::sqrt(u.y))),
+            _ => unreachable!("A shape light is either a quad or a disk."),
+        }
+    }
+
+    /// The cosine of the angle between the normal of the light and the direction
+    /// `direction` leaving the light, or `None` if the light does not emit in
+    /// that direction.
+    fn emitting_cosine(&self, direction: &Vector3<f32>) -> Option<f32> {
+        let normal = match &self.shape {
+            Shape::Quad(quad) => quad.normal(),
+            Shape::Disk(disk) => disk.normal(),
+            _ => unreachable!("A shape light is either a quad or a disk."),
+        };
+        let cos_theta = normal.dot(direction);
+        if cos_theta > 0_f32 || (self.two_sided && cos_theta < 0_f32) {
+            Some(f32::abs(cos_theta))
+        } else {
+            None
+        }
+    }
+
+    /// Find the distance to the light along a ray.
+    fn intersect(&self, point: &Vector3<f32>, direction: &Vector3<f32>) -> Option<f32> {
+        let ray = Ray::from_origin_dir(*point, *direction);
+
+        self.shape.intersect(&ray).map(|interaction| interaction.t)
+    }
+}
+
+impl Light for ShapeLight {
+    fn sample(&self, point: &Vector3<f32>, u: &Vector2<f32>) -> Option<LightSample> {
+        let area = self.area();
+        if area <= 0_f32 {
+            return None;
+        }
+        let light_point = self.sample_point(u);
+        let displacement = light_point - point;
+        let distance_squared = displacement.magnitude_squared();
+        if distance_squared == 0_f32 {
+            return None;
+        }
+        let distance = f32::sqrt(distance_squared);
+        let direction = displacement / distance;
+        let cos_theta = self.emitting_cosine(&(-direction))?;
+        if cos_theta == 0_f32 {
+            return None;
+        }
+        // Convert the density from area measure to solid angle measure.
+        let pdf = distance_squared / (cos_theta * area);
+
+        Some(LightSample::new(self.radiance, direction, distance, pdf))
+    }
+
+    fn pdf(&self, point: &Vector3<f32>, direction: &Vector3<f32>) -> f32 {
+        if let Some(distance) = self.intersect(point, direction) {
+            match self.emitting_cosine(&(-direction)) {
+                Some(cos_theta) if cos_theta > 0_f32 => {
+                    (distance * distance) / (cos_theta * self.area())
+                }
+                _ => 0_f32,
+            }
+        } else {
+            0_f32
+        }
+    }
+
+    fn eval(&self, point: &Vector3<f32>, direction: &Vector3<f32>) -> Vector3<f32> {
+        if self.intersect(point, direction).is_some() && self.emitting_cosine(&(-direction)).is_some() {
+            return self.radiance;
+        }
+
+        Vector3::zero()
+    }
+
+    fn distance(&self, point: &Vector3<f32>, direction: &Vector3<f32>) -> Option<f32> {
+        self.intersect(point, direction)
+    }
+
+    fn is_delta(&self) -> bool {
+        false
+    }
+}
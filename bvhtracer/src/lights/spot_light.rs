@@ -0,0 +1,115 @@
+use super::light::*;
+use cglinalg::{
+    Magnitude,
+    Radians,
+    Vector2,
+    Vector3,
+};
+
+
+/// A point light that only emits inside of a cone. 
+/// 
+/// The intensity is constant inside the inner cone of half angle `falloff_start`, 
+/// and falls off smoothly to zero at the outer cone of half angle `total_width`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SpotLight {
+    position: Vector3<f32>,
+    direction: Vector3<f32>,
+    intensity: Vector3<f32>,
+    cos_total_width: f32,
+    cos_falloff_start: f32,
+}
+
+impl SpotLight {
+    /// Construct a new spot light at `position` that points in the direction
+    /// `direction`.
+    pub fn new<A, B>(
+        position: Vector3<f32>, 
+        direction: Vector3<f32>, 
+        intensity: Vector3<f32>, 
+        total_width: A, 
+        falloff_start: B
+    ) -> Self 
+    where
+        A: Into<Radians<f32>>,
+        B: Into<Radians<f32>>,
+    {
+        let cos_total_width = f32::cos(total_width.into().0);
+        let cos_falloff_start = f32::max(cos_total_width, f32::cos(falloff_start.into().0));
+
+        Self { 
+            position, 
+            direction: direction.normalize(), 
+            intensity, 
+            cos_total_width, 
+            cos_falloff_start, 
+        }
+    }
+
+    #[inline]
+    pub const fn position(&self) -> Vector3<f32> {
+        self.position
+    }
+
+    /// The unit direction the spot light points in.
+    #[inline]
+    pub const fn direction(&self) -> Vector3<f32> {
+        self.direction
+    }
+
+    #[inline]
+    pub const fn intensity(&self) -> Vector3<f32> {
+        self.intensity
+    }
+
+    /// The fraction of the intensity emitted in the unit direction `direction`
+    /// from the light.
+    fn falloff(&self, direction: &Vector3<f32>) -> f32 {
+        let cos_theta = direction.dot(&self.direction);
+        if cos_theta < self.cos_total_width {
+            return 0_f32;
+        }
+        if cos_theta >= self.cos_falloff_start {
+            return 1_f32;
+        }
+        let delta = (cos_theta - self.cos_total_width) / (self.cos_falloff_start - self.cos_total_width);
+
+        (delta * delta) * (delta * delta)
+    }
+}
+
+impl Light for SpotLight {
+    fn sample(&self, point: &Vector3<f32>, _u: &Vector2<f32>) -> Option<LightSample> {
+        let displacement = self.position - point;
+        let distance_squared = displacement.magnitude_squared();
+        if distance_squared == 0_f32 {
+            return None;
+        }
+        let distance = f32::sqrt(distance_squared);
+        let direction = displacement / distance;
+        let falloff = self.falloff(&(-direction));
+        if falloff == 0_f32 {
+            return None;
+        }
+        let radiance = self.intensity * (falloff / distance_squared);
+
+        Some(LightSample::new(radiance, direction, distance, 1_f32))
+    }
+
+    fn pdf(&self, _point: &Vector3<f32>, _direction: &Vector3<f32>) -> f32 {
+        0_f32
+    }
+
+    fn eval(&self, _point: &Vector3<f32>, _direction: &Vector3<f32>) -> Vector3<f32> {
+        Vector3::zero()
+    }
+
+    fn distance(&self, _point: &Vector3<f32>, _direction: &Vector3<f32>) -> Option<f32> {
+        None
+    }
+
+    fn is_delta(&self) -> bool {
+        true
+    }
+}
+
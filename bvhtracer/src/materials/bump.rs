@@ -0,0 +1,149 @@
+use super::material::*;
+use super::parameter::*;
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+};
+use std::sync::{
+    Arc,
+};
+
+
+/// A height field over the texture coordinates of a surface that perturbs 
+/// its shading normal, giving the appearance of fine surface detail without 
+/// changing the geometry.
+#[derive(Clone, Debug)]
+pub struct BumpMap {
+    height: MaterialParameter<f32>,
+    scale: f32,
+}
+
+impl BumpMap {
+    /// Construct a new bump map from the height field `height`, which is 
+    /// usually a grayscale texture.
+    pub fn new<A>(height: A) -> Self 
+    where
+        A: Into<MaterialParameter<f32>>
+    {
+        Self { 
+            height: height.into(), 
+            scale: 1_f32,
+        }
+    }
+
+    /// Set the factor that scales the heights of the height field.
+    pub fn with_scale(mut self, scale: f32) -> Self {
+        self.scale = scale;
+
+        self
+    }
+
+    #[inline]
+    pub const fn height(&self) -> &MaterialParameter<f32> {
+        &self.height
+    }
+
+    #[inline]
+    pub const fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// The step in texture coordinates used to difference the height field. 
+    /// Textures are differenced across one texel.
+    fn delta(&self) -> Vector2<f32> {
+        match &self.height {
+            MaterialParameter::Texture(texture) if !texture.is_empty() => {
+                Vector2::new(1_f32 / texture.width() as f32, 1_f32 / texture.height() as f32)
+            }
+            _ => Vector2::new(1_f32, 1_f32),
+        }
+    }
+
+    /// Perturb the unit shading normal `normal` at the texture coordinates `uv`, 
+    /// where `dpdu` and `dpdv` are the partial derivatives of the surface 
+    /// position with respect to the texture coordinates. 
+    /// 
+    /// The perturbed normal lies in the same hemisphere as `normal`. If the 
+    /// derivatives do not span a plane, `normal` is returned unchanged.
+    pub fn perturb_normal(
+        &self, 
+        normal: &Vector3<f32>, 
+        dpdu: &Vector3<f32>, 
+        dpdv: &Vector3<f32>, 
+        uv: &Vector2<f32>
+    ) -> Vector3<f32> 
+    {
+        let delta = self.delta();
+        let height = self.height.evaluate(uv);
+        let height_u = self.height.evaluate(&Vector2::new(uv.x + delta.x, uv.y));
+        let height_v = self.height.evaluate(&Vector2::new(uv.x, uv.y + delta.y));
+        let dhdu = self.scale * (height_u - height) / delta.x;
+        let dhdv = self.scale * (height_v - height) / delta.y;
+        let displaced_dpdu = dpdu + normal * dhdu;
+        let displaced_dpdv = dpdv + normal * dhdv;
+        let perturbed = displaced_dpdu.cross(&displaced_dpdv);
+        let perturbed_magnitude = perturbed.magnitude();
+        if !(perturbed_magnitude > 0_f32) || !perturbed_magnitude.is_finite() {
+            return *normal;
+        }
+        let perturbed = perturbed / perturbed_magnitude;
+
+        if perturbed.dot(normal) < 0_f32 {
+            -perturbed
+        } else {
+            perturbed
+        }
+    }
+}
+
+
+/// A material whose shading normal is perturbed by a bump map. Scattering 
+/// is delegated to the underlying material.
+#[derive(Clone, Debug)]
+pub struct BumpMappedMaterial {
+    material: Arc<dyn Material>,
+    bump_map: BumpMap,
+}
+
+impl BumpMappedMaterial {
+    pub fn new(material: Arc<dyn Material>, bump_map: BumpMap) -> Self {
+        Self { material, bump_map, }
+    }
+
+    #[inline]
+    pub fn material(&self) -> &Arc<dyn Material> {
+        &self.material
+    }
+}
+
+impl Material for BumpMappedMaterial {
+    fn sample(&self, uv: &Vector2<f32>, wo: &Vector3<f32>, u: &Vector2<f32>) -> Option<BsdfSample> {
+        self.material.sample(uv, wo, u)
+    }
+
+    fn eval(&self, uv: &Vector2<f32>, wo: &Vector3<f32>, wi: &Vector3<f32>) -> Vector3<f32> {
+        self.material.eval(uv, wo, wi)
+    }
+
+    fn pdf(&self, uv: &Vector2<f32>, wo: &Vector3<f32>, wi: &Vector3<f32>) -> f32 {
+        self.material.pdf(uv, wo, wi)
+    }
+
+    fn albedo(&self, uv: &Vector2<f32>) -> Vector3<f32> {
+        self.material.albedo(uv)
+    }
+
+    fn is_specular(&self) -> bool {
+        self.material.is_specular()
+    }
+
+    fn is_transmissive(&self) -> bool {
+        self.material.is_transmissive()
+    }
+
+    fn bump_map(&self) -> Option<&BumpMap> {
+        Some(&self.bump_map)
+    }
+}
+
@@ -0,0 +1,108 @@
+use super::material::*;
+use super::parameter::*;
+use super::fresnel::*;
+use super::microfacet::*;
+use super::sampling::*;
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+};
+
+
+/// A rough metal, modeled by a Trowbridge-Reitz (GGX) microfacet distribution. 
+/// 
+/// The Fresnel reflectance of the metal is described by its reflectance at normal 
+/// incidence, which gives the metal its color, using Schlick's approximation.
+#[derive(Clone, Debug)]
+pub struct ConductorMaterial {
+    reflectance: MaterialParameter<Vector3<f32>>,
+    roughness: MaterialParameter<f32>,
+}
+
+impl ConductorMaterial {
+    /// Construct a new conductor with reflectance at normal incidence `reflectance`,
+    /// and perceptual roughness `roughness` in `[0, 1]`. Either parameter may be
+    /// a texture.
+    pub fn new<A, B>(reflectance: A, roughness: B) -> Self 
+    where
+        A: Into<MaterialParameter<Vector3<f32>>>,
+        B: Into<MaterialParameter<f32>>,
+    {
+        Self { 
+            reflectance: reflectance.into(), 
+            roughness: roughness.into(), 
+        }
+    }
+
+    #[inline]
+    pub const fn reflectance(&self) -> &MaterialParameter<Vector3<f32>> {
+        &self.reflectance
+    }
+
+    #[inline]
+    pub const fn roughness(&self) -> &MaterialParameter<f32> {
+        &self.roughness
+    }
+}
+
+impl Material for ConductorMaterial {
+    fn sample(&self, uv: &Vector2<f32>, wo: &Vector3<f32>, u: &Vector2<f32>) -> Option<BsdfSample> {
+        if wo.z <= 0_f32 {
+            return None;
+        }
+        let distribution = TrowbridgeReitz::from_roughness(self.roughness.evaluate(uv));
+        let h = distribution.sample_h(wo, u);
+        let wi = reflect(wo, &h);
+        if wi.z <= 0_f32 {
+            return None;
+        }
+        let pdf = self.pdf(uv, wo, &wi);
+        if pdf <= 0_f32 {
+            return None;
+        }
+        let value = self.eval(uv, wo, &wi);
+
+        Some(BsdfSample::new(value, wi, pdf, false))
+    }
+
+    fn eval(&self, uv: &Vector2<f32>, wo: &Vector3<f32>, wi: &Vector3<f32>) -> Vector3<f32> {
+        if wo.z <= 0_f32 || wi.z <= 0_f32 {
+            return Vector3::zero();
+        }
+        let h = wo + wi;
+        if h.magnitude_squared() == 0_f32 {
+            return Vector3::zero();
+        }
+        let h = h.normalize();
+        let distribution = TrowbridgeReitz::from_roughness(self.roughness.evaluate(uv));
+        let fresnel = fresnel_schlick(wi.dot(&h), &self.reflectance.evaluate(uv));
+        let d = distribution.d(&h);
+        let g = distribution.g(wo, wi);
+
+        fresnel * (d * g / (4_f32 * wo.z * wi.z))
+    }
+
+    fn pdf(&self, uv: &Vector2<f32>, wo: &Vector3<f32>, wi: &Vector3<f32>) -> f32 {
+        if wo.z <= 0_f32 || wi.z <= 0_f32 {
+            return 0_f32;
+        }
+        let h = wo + wi;
+        if h.magnitude_squared() == 0_f32 {
+            return 0_f32;
+        }
+        let h = h.normalize();
+        let distribution = TrowbridgeReitz::from_roughness(self.roughness.evaluate(uv));
+
+        distribution.pdf_h(wo, &h) / (4_f32 * wo.dot(&h))
+    }
+
+    fn albedo(&self, uv: &Vector2<f32>) -> Vector3<f32> {
+        self.reflectance.evaluate(uv)
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+}
+
@@ -59,7 +59,14 @@ pub enum TextureBufferError {
 
 impl fmt::Display for TextureBufferError {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        unimplemented!()
+        match self {
+            TextureBufferError::Decoding(err) => {
+                write!(formatter, "{}", err)
+            }
+            TextureBufferError::ColorSpaceMismatch() => {
+                write!(formatter, "The color space of the image does not match the pixel type of the texture")
+            }
+        }
     }
 }
 
@@ -259,3 +266,35 @@ where
     }
 }
 
+/// Decode a PNG or JPEG image into an RGB texture. The alpha channel of images 
+/// with one is dropped.
+pub(crate) fn decode_rgb_texture(data: &[u8], is_jpeg: bool) -> TextureBufferResult<TextureBuffer2D<Rgb<u8>, Vec<u8>>> {
+    if is_jpeg {
+        let rgb_decoder: JpegTextureBufferDecoder<Rgb<u8>, _> = JpegTextureBufferDecoder::new(io::Cursor::new(data));
+        match rgb_decoder.read_texture() {
+            Err(TextureBufferError::ColorSpaceMismatch()) => {
+                let rgba_decoder: JpegTextureBufferDecoder<Rgba<u8>, _> = JpegTextureBufferDecoder::new(io::Cursor::new(data));
+                rgba_decoder.read_texture().map(|texture| rgba_to_rgb(&texture))
+            }
+            result => result,
+        }
+    } else {
+        let rgb_decoder: PngTextureBufferDecoder<Rgb<u8>, _> = PngTextureBufferDecoder::new(io::Cursor::new(data));
+        match rgb_decoder.read_texture() {
+            Err(TextureBufferError::ColorSpaceMismatch()) => {
+                let rgba_decoder: PngTextureBufferDecoder<Rgba<u8>, _> = PngTextureBufferDecoder::new(io::Cursor::new(data));
+                rgba_decoder.read_texture().map(|texture| rgba_to_rgb(&texture))
+            }
+            result => result,
+        }
+    }
+}
+
+fn rgba_to_rgb(texture: &TextureBuffer2D<Rgba<u8>, Vec<u8>>) -> TextureBuffer2D<Rgb<u8>, Vec<u8>> {
+    TextureBuffer2D::from_fn(texture.width(), texture.height(), |x, y| {
+        let texel = texture[(x, y)];
+
+        Rgb::new(texel[0], texel[1], texel[2])
+    })
+}
+
@@ -0,0 +1,95 @@
+use super::material::*;
+use super::parameter::*;
+use super::fresnel::*;
+use cglinalg::{
+    Vector2,
+    Vector3,
+};
+
+
+/// A smooth boundary of a transparent material such as glass or water, which 
+/// reflects and refracts light according to the Fresnel equations. 
+/// 
+/// The shading normal points to the outside of the material.
+#[derive(Clone, Debug)]
+pub struct DielectricMaterial {
+    eta: f32,
+    transmittance: MaterialParameter<Vector3<f32>>,
+}
+
+impl DielectricMaterial {
+    /// Construct a new colorless dielectric with index of refraction `eta` 
+    /// relative to the medium outside of it.
+    pub fn new(eta: f32) -> Self {
+        Self { 
+            eta, 
+            transmittance: MaterialParameter::Constant(Vector3::from_fill(1_f32)), 
+        }
+    }
+
+    /// Set the fraction of light that is transmitted through the boundary 
+    /// instead of being absorbed, which tints the material.
+    pub fn with_transmittance<A>(mut self, transmittance: A) -> Self 
+    where
+        A: Into<MaterialParameter<Vector3<f32>>>
+    {
+        self.transmittance = transmittance.into();
+
+        self
+    }
+
+    #[inline]
+    pub const fn eta(&self) -> f32 {
+        self.eta
+    }
+
+    #[inline]
+    pub const fn transmittance(&self) -> &MaterialParameter<Vector3<f32>> {
+        &self.transmittance
+    }
+}
+
+impl Material for DielectricMaterial {
+    fn sample(&self, uv: &Vector2<f32>, wo: &Vector3<f32>, u: &Vector2<f32>) -> Option<BsdfSample> {
+        if wo.z == 0_f32 {
+            return None;
+        }
+        let reflectance = fresnel_dielectric(wo.z, self.eta);
+        if u.x < reflectance {
+            let wi = Vector3::new(-wo.x, -wo.y, wo.z);
+            let value = Vector3::from_fill(reflectance / f32::abs(wi.z));
+
+            Some(BsdfSample::new(value, wi, reflectance, true))
+        } else {
+            let transmission = 1_f32 - reflectance;
+            let (wi, eta) = refract(wo, &Vector3::unit_z(), self.eta)?;
+            // Radiance is compressed into a smaller solid angle when it enters a 
+            // denser medium, which scales it by the square of the relative index 
+            // of refraction.
+            let value = self.transmittance.evaluate(uv) * (transmission / (f32::abs(wi.z) * eta * eta));
+
+            Some(BsdfSample::new(value, wi, transmission, true))
+        }
+    }
+
+    fn eval(&self, _uv: &Vector2<f32>, _wo: &Vector3<f32>, _wi: &Vector3<f32>) -> Vector3<f32> {
+        Vector3::zero()
+    }
+
+    fn pdf(&self, _uv: &Vector2<f32>, _wo: &Vector3<f32>, _wi: &Vector3<f32>) -> f32 {
+        0_f32
+    }
+
+    fn albedo(&self, uv: &Vector2<f32>) -> Vector3<f32> {
+        self.transmittance.evaluate(uv)
+    }
+
+    fn is_specular(&self) -> bool {
+        true
+    }
+
+    fn is_transmissive(&self) -> bool {
+        true
+    }
+}
+
@@ -0,0 +1,68 @@
+use cglinalg::{
+    Vector3,
+};
+
+
+/// The fraction of light reflected at a smooth boundary between two dielectrics.
+/// 
+/// Here `eta` is the ratio of the index of refraction on the side opposite the 
+/// normal to the index of refraction on the side of the normal, and `cos_theta_i` 
+/// is the cosine of the angle between the incident direction and the normal. A 
+/// negative cosine means the incident direction lies on the side opposite the normal.
+pub(crate) fn fresnel_dielectric(cos_theta_i: f32, eta: f32) -> f32 {
+    let (cos_theta_i, eta) = {
+        let cos_theta_i = f32::clamp(cos_theta_i, -1_f32, 1_f32);
+        if cos_theta_i < 0_f32 {
+            (-cos_theta_i, 1_f32 / eta)
+        } else {
+            (cos_theta_i, eta)
+        }
+    };
+    let sin2_theta_i = 1_f32 - cos_theta_i * cos_theta_i;
+    let sin2_theta_t = sin2_theta_i / (eta * eta);
+    if sin2_theta_t >= 1_f32 {
+        // Total internal reflection.
+        return 1_f32;
+    }
+    let cos_theta_t = f32::sqrt(f32::max(0_f32, 1_f32 - sin2_theta_t));
+    let r_parallel = (eta * cos_theta_i - cos_theta_t) / (eta * cos_theta_i + cos_theta_t);
+    let r_perpendicular = (cos_theta_i - eta * cos_theta_t) / (cos_theta_i + eta * cos_theta_t);
+
+    0.5_f32 * (r_parallel * r_parallel + r_perpendicular * r_perpendicular)
+}
+
+/// Schlick's approximation of the reflectance of a conductor whose reflectance at 
+/// normal incidence is `reflectance`.
+pub(crate) fn fresnel_schlick(cos_theta_i: f32, reflectance: &Vector3<f32>) -> Vector3<f32> {
+    let m = f32::clamp(1_f32 - f32::abs(cos_theta_i), 0_f32, 1_f32);
+    let m5 = (m * m) * (m * m) * m;
+
+    reflectance + (Vector3::from_fill(1_f32) - reflectance) * m5
+}
+
+/// Refract the direction `wi` through a smooth boundary with unit normal `normal`. 
+/// 
+/// Here `eta` is the ratio of the index of refraction on the side opposite 
+/// the normal to the index of refraction on the side of the normal. Returns the 
+/// refracted direction and the relative index of refraction along the path, or 
+/// `None` in case of total internal reflection.
+pub(crate) fn refract(wi: &Vector3<f32>, normal: &Vector3<f32>, eta: f32) -> Option<(Vector3<f32>, f32)> {
+    let (cos_theta_i, eta, normal) = {
+        let cos_theta_i = wi.dot(normal);
+        if cos_theta_i < 0_f32 {
+            (-cos_theta_i, 1_f32 / eta, -normal)
+        } else {
+            (cos_theta_i, eta, *normal)
+        }
+    };
+    let sin2_theta_i = f32::max(0_f32, 1_f32 - cos_theta_i * cos_theta_i);
+    let sin2_theta_t = sin2_theta_i / (eta * eta);
+    if sin2_theta_t >= 1_f32 {
+        return None;
+    }
+    let cos_theta_t = f32::sqrt(1_f32 - sin2_theta_t);
+    let wt = -wi / eta + normal * (cos_theta_i / eta - cos_theta_t);
+
+    Some((wt, eta))
+}
+
@@ -0,0 +1,75 @@
+use super::material::*;
+use super::parameter::*;
+use super::sampling::*;
+use cglinalg::{
+    Vector2,
+    Vector3,
+};
+use std::f32;
+
+
+/// A perfectly diffuse material that scatters light equally in all directions 
+/// above the surface.
+#[derive(Clone, Debug)]
+pub struct LambertianMaterial {
+    albedo: MaterialParameter<Vector3<f32>>,
+}
+
+impl LambertianMaterial {
+    /// Construct a new diffuse material with the fraction of light `albedo` it
+    /// reflects, which may be a texture.
+    pub fn new<A>(albedo: A) -> Self 
+    where
+        A: Into<MaterialParameter<Vector3<f32>>>
+    {
+        Self { 
+            albedo: albedo.into(), 
+        }
+    }
+
+    #[inline]
+    pub const fn albedo(&self) -> &MaterialParameter<Vector3<f32>> {
+        &self.albedo
+    }
+}
+
+impl Material for LambertianMaterial {
+    fn sample(&self, uv: &Vector2<f32>, wo: &Vector3<f32>, u: &Vector2<f32>) -> Option<BsdfSample> {
+        if wo.z <= 0_f32 {
+            return None;
+        }
+        let wi = sample_cosine_hemisphere(u);
+        let pdf = cosine_hemisphere_pdf(wi.z);
+        if pdf <= 0_f32 {
+            return None;
+        }
+        let value = self.eval(uv, wo, &wi);
+
+        Some(BsdfSample::new(value, wi, pdf, false))
+    }
+
+    fn eval(&self, uv: &Vector2<f32>, wo: &Vector3<f32>, wi: &Vector3<f32>) -> Vector3<f32> {
+        if wo.z <= 0_f32 || wi.z <= 0_f32 {
+            return Vector3::zero();
+        }
+
+        self.albedo.evaluate(uv) * f32::consts::FRAC_1_PI
+    }
+
+    fn pdf(&self, _uv: &Vector2<f32>, wo: &Vector3<f32>, wi: &Vector3<f32>) -> f32 {
+        if wo.z <= 0_f32 {
+            return 0_f32;
+        }
+
+        cosine_hemisphere_pdf(wi.z)
+    }
+
+    fn albedo(&self, uv: &Vector2<f32>) -> Vector3<f32> {
+        self.albedo.evaluate(uv)
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+}
+
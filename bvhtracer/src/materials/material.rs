@@ -1,15 +1,97 @@
 use crate::texture_buffer::*;
+use super::bump::*;
 use cglinalg::{
     Vector2,
+    Vector3,
 };
+use std::fmt;
 
 
-pub trait Material: Clone {
-    type P: Pixel;
+/// A sample of an incident direction from a material's BSDF.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct BsdfSample {
+    /// The value of the BSDF for the outgoing direction and the sampled incident 
+    /// direction. For specular scattering, this is the value with the delta 
+    /// distribution factored out.
+    pub value: Vector3<f32>,
+    /// The sampled incident direction in the shading frame.
+    pub direction: Vector3<f32>,
+    /// The probability density of sampling `direction`, with respect to solid angle. 
+    /// For specular scattering, this is the probability of choosing the sampled 
+    /// specular direction.
+    pub pdf: f32,
+    /// Whether the sample came from a delta distribution, such as a perfect mirror.
+    pub is_specular: bool,
+}
+
+impl BsdfSample {
+    pub fn new(value: Vector3<f32>, direction: Vector3<f32>, pdf: f32, is_specular: bool) -> Self {
+        Self { value, direction, pdf, is_specular, }
+    }
 
-    fn evaluate(&self, uv: Vector2<f32>) -> Self::P;
+    /// The factor that scales the throughput of a path extended along the 
+    /// sampled direction, i.e. the BSDF value times the cosine of the sampled 
+    /// direction over the sampling density.
+    pub fn weight(&self) -> Vector3<f32> {
+        self.value * (f32::abs(self.direction.z) / self.pdf)
+    }
 }
 
+/// A model of how light scatters at a surface, described by a bidirectional 
+/// scattering distribution function (BSDF).
+/// 
+/// Directions are expressed in the surface's [`ShadingFrame`], in which the shading 
+/// normal is the positive z-axis. The outgoing direction `wo` and the incident 
+/// direction `wi` both point away from the surface. The texture coordinates `uv` 
+/// of the shading point are used to look up textured material parameters.
+pub trait Material: fmt::Debug + Send + Sync {
+    /// Sample an incident direction for the outgoing direction `wo`, using the 
+    /// uniformly distributed random numbers `u` in `[0, 1)^2`. 
+    /// 
+    /// Returns `None` when the material does not scatter light arriving from `wo`.
+    fn sample(&self, uv: &Vector2<f32>, wo: &Vector3<f32>, u: &Vector2<f32>) -> Option<BsdfSample>;
+
+    /// Evaluate the BSDF for the pair of directions `wo` and `wi`. 
+    /// 
+    /// This is zero for specular materials, since no pair of directions 
+    /// chosen independently lies on a delta distribution.
+    fn eval(&self, uv: &Vector2<f32>, wo: &Vector3<f32>, wi: &Vector3<f32>) -> Vector3<f32>;
+
+    /// The probability density, with respect to solid angle, that [`Material::sample`] 
+    /// samples `wi` for the outgoing direction `wo`.
+    /// 
+    /// This is zero for specular materials.
+    fn pdf(&self, uv: &Vector2<f32>, wo: &Vector3<f32>, wi: &Vector3<f32>) -> f32;
+
+    /// The overall color of the material at the texture coordinates `uv`, for 
+    /// previews that do not simulate light transport.
+    fn albedo(&self, uv: &Vector2<f32>) -> Vector3<f32>;
+
+    /// Determine whether the material only scatters light in discrete directions, 
+    /// in which case it cannot be sampled by light sampling.
+    fn is_specular(&self) -> bool;
+
+    /// Determine whether light can pass through the material. 
+    /// 
+    /// Opaque materials are evaluated with the shading normal facing the outgoing 
+    /// direction, so they scatter light from both sides of a surface. Transmissive 
+    /// materials distinguish the inside of a surface from the outside, so they 
+    /// are evaluated with the shading normal facing outward.
+    fn is_transmissive(&self) -> bool {
+        false
+    }
+
+    /// The bump map that perturbs the shading normal of the material, if any.
+    fn bump_map(&self) -> Option<&BumpMap> {
+        None
+    }
+}
+
+
+/// A texture that can be looked up by texture coordinates. 
+/// 
+/// A texture material is one input to a material, such as the albedo of a 
+/// diffuse surface. See [`MaterialParameter`].
 #[derive(Clone, Debug)]
 pub struct TextureMaterial<P> {
     texture: TextureBuffer2D<P, Vec<u8>>,
@@ -24,31 +106,56 @@ where
     }
 }
 
-impl<P> Default for TextureMaterial<P>
+impl<P> TextureMaterial<P>
 where
-    P: Pixel
+    P: Pixel<Subpixel = u8>
 {
-    fn default() -> Self {
-        Self { 
-            texture: TextureBuffer2D::default(),
-        }
+    /// Determine whether the material has no texels to sample from.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.texture.width() == 0 || self.texture.height() == 0
     }
-}
 
-impl<P> Material for TextureMaterial<P>
-where
-    P: Pixel<Subpixel = u8>
-{
-    type P = P;
+    /// The width of the texture in texels.
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.texture.width()
+    }
 
-    fn evaluate(&self, uv: Vector2<f32>) -> Self::P {
+    /// The height of the texture in texels.
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.texture.height()
+    }
+
+    /// Look up the texel at the texture coordinates `uv`, wrapping around the 
+    /// edges of the texture. Coordinates outside of `[0, 1)` wrap in both 
+    /// directions, so the texture repeats across the entire plane.
+    /// 
+    /// # Panics
+    /// 
+    /// Panics if the texture is empty.
+    pub fn evaluate(&self, uv: Vector2<f32>) -> P {
+        assert!(!self.is_empty(), "Cannot look up a texel in an empty texture.");
         let width_f32 = self.texture.width() as f32;
         let height_f32 = self.texture.height() as f32;
-        let iu = ((uv.x * width_f32) as usize) % self.texture.width();
-        let iv = ((uv.y * height_f32) as usize) % self.texture.height();
-        let texel = self.texture[(iu, iv)];
+        // Wrapping a tiny negative coordinate can round up to one, so the texel 
+        // indices are wrapped again.
+        let iu = ((uv.x.rem_euclid(1_f32) * width_f32) as usize) % self.texture.width();
+        let iv = ((uv.y.rem_euclid(1_f32) * height_f32) as usize) % self.texture.height();
+
+        self.texture[(iu, iv)]
+    }
+}
 
-        texel
+impl<P> Default for TextureMaterial<P>
+where
+    P: Pixel
+{
+    fn default() -> Self {
+        Self { 
+            texture: TextureBuffer2D::default(),
+        }
     }
 }
 
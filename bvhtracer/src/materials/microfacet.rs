@@ -0,0 +1,128 @@
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+};
+use std::f32;
+
+
+/// The smallest microfacet roughness, which keeps nearly smooth surfaces 
+/// numerically stable.
+const MIN_ALPHA: f32 = 1e-3;
+
+
+/// The Trowbridge-Reitz (GGX) distribution of microfacet normals on a rough 
+/// surface, expressed in the shading frame.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub(crate) struct TrowbridgeReitz {
+    alpha: f32,
+}
+
+impl TrowbridgeReitz {
+    /// Construct a microfacet distribution from a perceptual roughness in `[0, 1]`. 
+    /// The width of the distribution is the square of the roughness.
+    pub(crate) fn from_roughness(roughness: f32) -> Self {
+        let roughness = f32::clamp(roughness, 0_f32, 1_f32);
+
+        Self {
+            alpha: f32::max(roughness * roughness, MIN_ALPHA),
+        }
+    }
+
+    /// The density of microfacets with normal `h`.
+    pub(crate) fn d(&self, h: &Vector3<f32>) -> f32 {
+        let cos2_theta = h.z * h.z;
+        if cos2_theta <= 0_f32 {
+            return 0_f32;
+        }
+        let tan2_theta = (1_f32 - cos2_theta) / cos2_theta;
+        let alpha2 = self.alpha * self.alpha;
+        let e = 1_f32 + tan2_theta / alpha2;
+
+        1_f32 / (f32::consts::PI * alpha2 * cos2_theta * cos2_theta * e * e)
+    }
+
+    fn lambda(&self, w: &Vector3<f32>) -> f32 {
+        let cos2_theta = w.z * w.z;
+        if cos2_theta <= 0_f32 {
+            return f32::INFINITY;
+        }
+        let tan2_theta = (1_f32 - cos2_theta) / cos2_theta;
+        let alpha2 = self.alpha * self.alpha;
+
+        0.5_f32 * (f32::sqrt(1_f32 + alpha2 * tan2_theta) - 1_f32)
+    }
+
+    /// The fraction of microfacets visible from the direction `w`.
+    pub(crate) fn g1(&self, w: &Vector3<f32>) -> f32 {
+        1_f32 / (1_f32 + self.lambda(w))
+    }
+
+    /// The fraction of microfacets visible from both of the directions `wo` and `wi`.
+    pub(crate) fn g(&self, wo: &Vector3<f32>, wi: &Vector3<f32>) -> f32 {
+        1_f32 / (1_f32 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// Sample a microfacet normal visible from the direction `wo` in the upper 
+    /// hemisphere, using the uniformly distributed random numbers `u` in `[0, 1)^2`.
+    pub(crate) fn sample_h(&self, wo: &Vector3<f32>, u: &Vector2<f32>) -> Vector3<f32> {
+        // Heitz, Sampling the GGX Distribution of Visible Normals.
+        let vh = Vector3::new(self.alpha * wo.x, self.alpha * wo.y, wo.z).normalize();
+        let length_squared = vh.x * vh.x + vh.y * vh.y;
+        let t1 = if length_squared > 0_f32 {
+            Vector3::new(-vh.y, vh.x, 0_f32) / f32::sqrt(length_squared)
+        } else {
+            Vector3::unit_x()
+        };
+        let t2 = vh.cross(&t1);
+        let radius = f32::sqrt(u.x);
+        let phi = 2_f32 * f32::consts::PI * u.y;
+        let p1 = radius * f32::cos(phi);
+        let p2 = {
+            let p2 = radius * f32::sin(phi);
+            let s = 0.5_f32 * (1_f32 + vh.z);
+            (1_f32 - s) * f32::sqrt(f32::max(0_f32, 1_f32 - p1 * p1)) + s * p2
+        };
+        let nh = t1 * p1 + t2 * p2 + vh * f32::sqrt(f32::max(0_f32, 1_f32 - p1 * p1 - p2 * p2));
+
+        Vector3::new(self.alpha * nh.x, self.alpha * nh.y, f32::max(1e-6, nh.z)).normalize()
+    }
+
+    /// The probability density that [`TrowbridgeReitz::sample_h`] samples the 
+    /// microfacet normal `h` for the direction `wo`.
+    pub(crate) fn pdf_h(&self, wo: &Vector3<f32>, h: &Vector3<f32>) -> f32 {
+        if wo.z <= 0_f32 {
+            return 0_f32;
+        }
+
+        self.g1(wo) * f32::max(0_f32, wo.dot(h)) * self.d(h) / wo.z
+    }
+}
+
+
+#[cfg(test)]
+mod trowbridge_reitz_tests {
+    use super::*;
+
+
+    /// The projected area of the microfacets should equal the area of the 
+    /// macrosurface.
+    #[test]
+    fn test_trowbridge_reitz_normalized() {
+        for roughness in [0.3_f32, 0.5_f32, 1_f32] {
+            let distribution = TrowbridgeReitz::from_roughness(roughness);
+            let theta_steps = 1024;
+            let d_theta = 0.5_f32 * f32::consts::PI / (theta_steps as f32);
+            let mut projected_area = 0_f32;
+            for i in 0..theta_steps {
+                let theta = (i as f32 + 0.5_f32) * d_theta;
+                let h = Vector3::new(f32::sin(theta), 0_f32, f32::cos(theta));
+                projected_area += distribution.d(&h) * h.z * f32::sin(theta) * d_theta;
+            }
+            projected_area *= 2_f32 * f32::consts::PI;
+
+            assert!(f32::abs(projected_area - 1_f32) < 1e-2, "roughness = {}, area = {}", roughness, projected_area);
+        }
+    }
+}
+
@@ -0,0 +1,60 @@
+use super::material::*;
+use super::parameter::*;
+use cglinalg::{
+    Vector2,
+    Vector3,
+};
+
+
+/// A perfectly smooth mirror that reflects light only in the mirror direction.
+#[derive(Clone, Debug)]
+pub struct MirrorMaterial {
+    reflectance: MaterialParameter<Vector3<f32>>,
+}
+
+impl MirrorMaterial {
+    /// Construct a new mirror that reflects the fraction of light `reflectance`, 
+    /// which may be a texture.
+    pub fn new<A>(reflectance: A) -> Self 
+    where
+        A: Into<MaterialParameter<Vector3<f32>>>
+    {
+        Self { 
+            reflectance: reflectance.into(), 
+        }
+    }
+
+    #[inline]
+    pub const fn reflectance(&self) -> &MaterialParameter<Vector3<f32>> {
+        &self.reflectance
+    }
+}
+
+impl Material for MirrorMaterial {
+    fn sample(&self, uv: &Vector2<f32>, wo: &Vector3<f32>, _u: &Vector2<f32>) -> Option<BsdfSample> {
+        if wo.z <= 0_f32 {
+            return None;
+        }
+        let wi = Vector3::new(-wo.x, -wo.y, wo.z);
+        let value = self.reflectance.evaluate(uv) / wi.z;
+
+        Some(BsdfSample::new(value, wi, 1_f32, true))
+    }
+
+    fn eval(&self, _uv: &Vector2<f32>, _wo: &Vector3<f32>, _wi: &Vector3<f32>) -> Vector3<f32> {
+        Vector3::zero()
+    }
+
+    fn pdf(&self, _uv: &Vector2<f32>, _wo: &Vector3<f32>, _wi: &Vector3<f32>) -> f32 {
+        0_f32
+    }
+
+    fn albedo(&self, uv: &Vector2<f32>) -> Vector3<f32> {
+        self.reflectance.evaluate(uv)
+    }
+
+    fn is_specular(&self) -> bool {
+        true
+    }
+}
+
@@ -1,9 +1,30 @@
 mod texture;
 mod decoders;
 mod material;
+mod parameter;
+mod shading_frame;
+mod sampling;
+mod fresnel;
+mod microfacet;
+mod lambertian;
+mod mirror;
+mod conductor;
+mod dielectric;
+mod plastic;
+mod bump;
+mod mtl;
 
 
 pub use texture::*;
 pub use decoders::*;
 pub use material::*;
+pub use parameter::*;
+pub use shading_frame::*;
+pub use lambertian::*;
+pub use mirror::*;
+pub use conductor::*;
+pub use dielectric::*;
+pub use plastic::*;
+pub use bump::*;
+pub use mtl::*;
 
@@ -0,0 +1,371 @@
+use crate::texture_buffer::*;
+use crate::file_resolver::*;
+use super::material::*;
+use super::decoders::*;
+use super::lambertian::*;
+use super::mirror::*;
+use super::dielectric::*;
+use super::plastic::*;
+use super::bump::*;
+use cglinalg::{
+    Vector3,
+};
+use std::error;
+use std::fmt;
+use std::io;
+use std::io::{
+    Read,
+};
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::sync::{
+    Arc,
+};
+
+
+pub type MtlResult<T> = Result<T, MtlError>;
+
+/// The diffuse color of a material that does not set one.
+const DEFAULT_DIFFUSE: f32 = 0.8;
+
+
+#[derive(Debug)]
+pub enum MtlError {
+    /// A statement in a material library could not be parsed.
+    Parse { line_number: usize, message: String },
+    /// A texture could not be read from its file resolver.
+    Resolve { path: PathBuf, error: io::Error },
+    /// A texture could not be decoded.
+    Texture { path: PathBuf, error: TextureBufferError },
+    IoError(io::Error),
+}
+
+impl fmt::Display for MtlError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MtlError::Parse { line_number, message } => {
+                write!(formatter, "Could not parse material library at line {}: {}", line_number, message)
+            }
+            MtlError::Resolve { path, error } => {
+                write!(formatter, "Could not read texture {}: {}", path.display(), error)
+            }
+            MtlError::Texture { path, error } => {
+                write!(formatter, "Could not decode texture {}: {}", path.display(), error)
+            }
+            MtlError::IoError(err) => {
+                write!(formatter, "An error occurred in reading from the reader: {}", err)
+            }
+        }
+    }
+}
+
+impl error::Error for MtlError {}
+
+
+/// A material read from a Wavefront MTL material library.
+///
+/// The fields hold the statements of the material as they appear in the
+/// library. Use [`MtlMaterial::to_material`] to construct the crate material
+/// that best matches them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MtlMaterial {
+    /// The name of the material given by `newmtl`.
+    pub name: String,
+    /// The diffuse color `Kd`.
+    pub diffuse: Vector3<f32>,
+    /// The specular color `Ks`.
+    pub specular: Vector3<f32>,
+    /// The Phong specular exponent `Ns`.
+    pub specular_exponent: f32,
+    /// The opacity `d`, or one minus the transparency `Tr`.
+    pub dissolve: f32,
+    /// The index of refraction `Ni`.
+    pub optical_density: f32,
+    /// The illumination model `illum`.
+    pub illumination_model: u32,
+    /// The path of the diffuse color texture `map_Kd`.
+    pub diffuse_map: Option<String>,
+    /// The path of the height field `map_Bump`.
+    pub bump_map: Option<String>,
+    /// The bump multiplier given by the `-bm` option of `map_Bump`.
+    pub bump_multiplier: f32,
+}
+
+impl MtlMaterial {
+    /// Construct a new material with the default values of each statement.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: String::from(name),
+            diffuse: Vector3::from_fill(DEFAULT_DIFFUSE),
+            specular: Vector3::zero(),
+            specular_exponent: 0_f32,
+            dissolve: 1_f32,
+            optical_density: 1_f32,
+            illumination_model: 2,
+            diffuse_map: None,
+            bump_map: None,
+            bump_multiplier: 1_f32,
+        }
+    }
+
+    /// Determine whether the illumination model or opacity of the material
+    /// call for refraction.
+    fn is_transparent(&self) -> bool {
+        matches!(self.illumination_model, 4 | 6 | 7 | 9) || self.dissolve < 1_f32
+    }
+
+    /// Determine whether the material is a ray traced reflector with no diffuse
+    /// component.
+    fn is_mirror(&self) -> bool {
+        matches!(self.illumination_model, 3 | 5 | 8)
+            && self.diffuse_map.is_none()
+            && is_black(&self.diffuse)
+            && !is_black(&self.specular)
+    }
+
+    /// The perceptual roughness of a microfacet distribution that approximates
+    /// the Phong lobe with exponent `Ns`.
+    fn roughness(&self) -> f32 {
+        let exponent = f32::max(self.specular_exponent, 0_f32);
+        let alpha = f32::sqrt(2_f32 / (exponent + 2_f32));
+
+        f32::sqrt(alpha)
+    }
+
+    /// Construct the crate material that best matches the material.
+    ///
+    /// * Transparent materials, i.e. illumination models 4, 6, 7 and 9, or
+    ///   materials with an opacity less than one, become a [`DielectricMaterial`]
+    ///   with index of refraction `Ni`.
+    /// * Ray traced reflectors with a black diffuse color become a [`MirrorMaterial`]
+    ///   with reflectance `Ks`.
+    /// * Materials with a specular color become a [`PlasticMaterial`], whose
+    ///   roughness is derived from `Ns`.
+    /// * All other materials become a [`LambertianMaterial`].
+    ///
+    /// The diffuse texture replaces the diffuse color when both are given. A bump
+    /// map wraps the material in a [`BumpMappedMaterial`]. Texture paths are read
+    /// from `resolver`, relative to `directory`, the directory of the material
+    /// library.
+    pub fn to_material<F>(&self, resolver: &F, directory: &Path) -> MtlResult<Arc<dyn Material>>
+    where
+        F: FileResolver + ?Sized
+    {
+        let diffuse_map = match &self.diffuse_map {
+            Some(path) => Some(read_texture(resolver, &directory.join(path))?),
+            None => None,
+        };
+        let material: Arc<dyn Material> = if self.is_transparent() {
+            Arc::new(DielectricMaterial::new(self.optical_density))
+        } else if self.is_mirror() {
+            Arc::new(MirrorMaterial::new(self.specular))
+        } else if !is_black(&self.specular) {
+            match diffuse_map {
+                Some(texture) => Arc::new(PlasticMaterial::new(texture, self.roughness())),
+                None => Arc::new(PlasticMaterial::new(self.diffuse, self.roughness())),
+            }
+        } else {
+            match diffuse_map {
+                Some(texture) => Arc::new(LambertianMaterial::new(texture)),
+                None => Arc::new(LambertianMaterial::new(self.diffuse)),
+            }
+        };
+        let material: Arc<dyn Material> = match &self.bump_map {
+            Some(path) => {
+                let height = read_texture(resolver, &directory.join(path))?;
+                let bump_map = BumpMap::new(height).with_scale(self.bump_multiplier);
+
+                Arc::new(BumpMappedMaterial::new(material, bump_map))
+            }
+            None => material,
+        };
+
+        Ok(material)
+    }
+}
+
+fn is_black(color: &Vector3<f32>) -> bool {
+    color.x <= 0_f32 && color.y <= 0_f32 && color.z <= 0_f32
+}
+
+/// Read a PNG or JPEG texture through a file resolver, choosing the format by
+/// the extension of `path`.
+fn read_texture<F>(resolver: &F, path: &Path) -> MtlResult<TextureMaterial<Rgb<u8>>>
+where
+    F: FileResolver + ?Sized
+{
+    let data = resolver.resolve(path).map_err(|error| {
+        MtlError::Resolve { path: path.to_path_buf(), error }
+    })?;
+    let is_jpeg = path.extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.eq_ignore_ascii_case("jpg") || extension.eq_ignore_ascii_case("jpeg"))
+        .unwrap_or(false);
+    let texture = decode_rgb_texture(&data, is_jpeg);
+
+    texture
+        .map(TextureMaterial::new)
+        .map_err(|error| MtlError::Texture { path: path.to_path_buf(), error })
+}
+
+/// A decoder for Wavefront MTL material libraries.
+///
+/// The statements `Kd`, `Ks`, `Ns`, `d`, `Tr`, `Ni`, `illum`, `map_Kd` and
+/// `map_Bump` are read. All other statements are ignored.
+pub struct MtlDecoder<R> {
+    reader: R,
+}
+
+impl<R> MtlDecoder<R>
+where
+    R: Read,
+{
+    pub fn new(reader: R) -> Self {
+        Self { reader, }
+    }
+
+    pub fn read_materials(mut self) -> MtlResult<Vec<MtlMaterial>> {
+        let mut buffer = String::new();
+        self.reader.read_to_string(&mut buffer).map_err(|err| {
+            MtlError::IoError(err)
+        })?;
+
+        parse_mtl(&buffer)
+    }
+}
+
+fn parse_mtl(buffer: &str) -> MtlResult<Vec<MtlMaterial>> {
+    let mut materials: Vec<MtlMaterial> = vec![];
+    for (line_index, line) in buffer.lines().enumerate() {
+        let line_number = line_index + 1;
+        let error = |message: &str| MtlError::Parse { line_number, message: String::from(message) };
+        let statement = match line.find('#') {
+            Some(comment_start) => &line[..comment_start],
+            None => line,
+        };
+        let mut tokens = statement.split_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let arguments = tokens.collect::<Vec<_>>();
+        if keyword == "newmtl" {
+            if arguments.is_empty() {
+                return Err(error("expected a material name"));
+            }
+            materials.push(MtlMaterial::new(&arguments.join(" ")));
+            continue;
+        }
+        let is_known_keyword = matches!(
+            keyword,
+            "Kd" | "Ks" | "Ns" | "d" | "Tr" | "Ni" | "illum" | "map_Kd" | "map_Bump" | "map_bump" | "bump"
+        );
+        if !is_known_keyword {
+            continue;
+        }
+        let material = materials.last_mut().ok_or_else(|| error("expected newmtl before material statements"))?;
+        match keyword {
+            "Kd" => material.diffuse = parse_color(&arguments).map_err(error)?,
+            "Ks" => material.specular = parse_color(&arguments).map_err(error)?,
+            "Ns" => material.specular_exponent = parse_scalar(&arguments).map_err(error)?,
+            "d" => {
+                let arguments = match arguments.first() {
+                    Some(&"-halo") => &arguments[1..],
+                    _ => &arguments[..],
+                };
+                material.dissolve = parse_scalar(arguments).map_err(error)?;
+            }
+            "Tr" => material.dissolve = 1_f32 - parse_scalar(&arguments).map_err(error)?,
+            "Ni" => material.optical_density = parse_scalar(&arguments).map_err(error)?,
+            "illum" => {
+                material.illumination_model = match arguments[..] {
+                    [value] => value.parse::<u32>().map_err(|_| error("expected an illumination model"))?,
+                    _ => return Err(error("expected an illumination model")),
+                };
+            }
+            "map_Kd" => {
+                let texture_map = parse_texture_map(&arguments).map_err(error)?;
+                material.diffuse_map = Some(texture_map.path);
+            }
+            _ => {
+                let texture_map = parse_texture_map(&arguments).map_err(error)?;
+                material.bump_map = Some(texture_map.path);
+                material.bump_multiplier = texture_map.bump_multiplier;
+            }
+        }
+    }
+
+    Ok(materials)
+}
+
+fn parse_scalar(arguments: &[&str]) -> Result<f32, &'static str> {
+    match arguments {
+        [value] => value.parse::<f32>().map_err(|_| "expected a number"),
+        _ => Err("expected one number"),
+    }
+}
+
+/// Parse a color given as either one or three numbers. A single number sets
+/// every channel. Colors may be prefixed with `xyz`, whose values are read
+/// as they are.
+fn parse_color(arguments: &[&str]) -> Result<Vector3<f32>, &'static str> {
+    let arguments = match arguments.first() {
+        Some(&"spectral") => return Err("spectral colors are not supported"),
+        Some(&"xyz") => &arguments[1..],
+        _ => arguments,
+    };
+    let parse = |value: &str| value.parse::<f32>().map_err(|_| "expected a number");
+    match arguments {
+        [value] => Ok(Vector3::from_fill(parse(value)?)),
+        [r, g, b] => Ok(Vector3::new(parse(r)?, parse(g)?, parse(b)?)),
+        _ => Err("expected a color of one or three numbers"),
+    }
+}
+
+struct TextureMap {
+    path: String,
+    bump_multiplier: f32,
+}
+
+/// Parse the options and path of a texture map statement. The bump multiplier
+/// option is kept, and every other option is skipped.
+fn parse_texture_map(arguments: &[&str]) -> Result<TextureMap, &'static str> {
+    let mut bump_multiplier = 1_f32;
+    let mut index = 0;
+    while index < arguments.len() {
+        let max_option_arguments = match arguments[index] {
+            "-blendu" | "-blendv" | "-boost" | "-cc" | "-clamp" | "-imfchan" | "-texres" | "-type" | "-bm" => 1,
+            "-mm" => 2,
+            "-o" | "-s" | "-t" => 3,
+            _ => break,
+        };
+        let option = arguments[index];
+        index += 1;
+        if index >= arguments.len() {
+            return Err("expected an option value");
+        }
+        if option == "-bm" {
+            bump_multiplier = arguments[index].parse::<f32>().map_err(|_| "expected a bump multiplier")?;
+        }
+        // Vector options take between one and three numbers.
+        let mut option_arguments = 0;
+        while option_arguments < max_option_arguments
+            && index < arguments.len()
+            && (option_arguments == 0 || arguments[index].parse::<f32>().is_ok())
+        {
+            index += 1;
+            option_arguments += 1;
+        }
+    }
+    if index >= arguments.len() {
+        return Err("expected a texture path");
+    }
+
+    Ok(TextureMap {
+        path: arguments[index..].join(" "),
+        bump_multiplier,
+    })
+}
+
@@ -0,0 +1,75 @@
+use crate::texture_buffer::*;
+use super::material::*;
+use cglinalg::{
+    Vector2,
+    Vector3,
+};
+
+
+/// A material parameter that is either constant over a surface, or looked up 
+/// from a texture at the texture coordinates of the shading point.
+#[derive(Clone, Debug)]
+pub enum MaterialParameter<T> {
+    Constant(T),
+    Texture(TextureMaterial<Rgb<u8>>),
+}
+
+impl MaterialParameter<Vector3<f32>> {
+    /// Evaluate the parameter at the texture coordinates `uv`. Texels are mapped 
+    /// from `[0, 255]` to `[0, 1]` per channel. An empty texture evaluates to zero.
+    pub fn evaluate(&self, uv: &Vector2<f32>) -> Vector3<f32> {
+        match self {
+            MaterialParameter::Constant(value) => *value,
+            MaterialParameter::Texture(texture) if texture.is_empty() => Vector3::zero(),
+            MaterialParameter::Texture(texture) => {
+                let texel = texture.evaluate(*uv);
+                let s = 1_f32 / 255_f32;
+
+                Vector3::new(texel.r() as f32 * s, texel.g() as f32 * s, texel.b() as f32 * s)
+            }
+        }
+    }
+}
+
+impl MaterialParameter<f32> {
+    /// Evaluate the parameter at the texture coordinates `uv`. Textured scalar 
+    /// parameters read the red channel of the texture mapped from `[0, 255]` to 
+    /// `[0, 1]`, so grayscale maps can be used directly. An empty texture 
+    /// evaluates to zero.
+    pub fn evaluate(&self, uv: &Vector2<f32>) -> f32 {
+        match self {
+            MaterialParameter::Constant(value) => *value,
+            MaterialParameter::Texture(texture) if texture.is_empty() => 0_f32,
+            MaterialParameter::Texture(texture) => {
+                let texel = texture.evaluate(*uv);
+
+                texel.r() as f32 / 255_f32
+            }
+        }
+    }
+}
+
+impl From<Vector3<f32>> for MaterialParameter<Vector3<f32>> {
+    fn from(value: Vector3<f32>) -> Self {
+        MaterialParameter::Constant(value)
+    }
+}
+
+impl From<f32> for MaterialParameter<f32> {
+    fn from(value: f32) -> Self {
+        MaterialParameter::Constant(value)
+    }
+}
+
+impl From<TextureMaterial<Rgb<u8>>> for MaterialParameter<Vector3<f32>> {
+    fn from(texture: TextureMaterial<Rgb<u8>>) -> Self {
+        MaterialParameter::Texture(texture)
+    }
+}
+
+impl From<TextureMaterial<Rgb<u8>>> for MaterialParameter<f32> {
+    fn from(texture: TextureMaterial<Rgb<u8>>) -> Self {
+        MaterialParameter::Texture(texture)
+    }
+}
+
@@ -0,0 +1,173 @@
+use super::material::*;
+use super::parameter::*;
+use super::fresnel::*;
+use super::microfacet::*;
+use super::sampling::*;
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+};
+use std::f32;
+
+
+/// The index of refraction of a typical plastic coating.
+const DEFAULT_PLASTIC_ETA: f32 = 1.5;
+
+
+/// A diffuse base under a rough dielectric coating, such as plastic or 
+/// varnished wood. 
+/// 
+/// Light either reflects specularly off of the coating, modeled by a 
+/// Trowbridge-Reitz (GGX) microfacet distribution, or is transmitted through 
+/// the coating into the base, where it scatters diffusely and leaves through 
+/// the coating again.
+#[derive(Clone, Debug)]
+pub struct PlasticMaterial {
+    diffuse: MaterialParameter<Vector3<f32>>,
+    roughness: MaterialParameter<f32>,
+    eta: f32,
+}
+
+impl PlasticMaterial {
+    /// Construct a new plastic with the albedo of its base `diffuse`, and the 
+    /// perceptual roughness of its coating `roughness` in `[0, 1]`. Either 
+    /// parameter may be a texture.
+    pub fn new<A, B>(diffuse: A, roughness: B) -> Self 
+    where
+        A: Into<MaterialParameter<Vector3<f32>>>,
+        B: Into<MaterialParameter<f32>>,
+    {
+        Self { 
+            diffuse: diffuse.into(), 
+            roughness: roughness.into(), 
+            eta: DEFAULT_PLASTIC_ETA,
+        }
+    }
+
+    /// Set the index of refraction of the coating.
+    pub fn with_eta(mut self, eta: f32) -> Self {
+        self.eta = eta;
+
+        self
+    }
+
+    #[inline]
+    pub const fn diffuse(&self) -> &MaterialParameter<Vector3<f32>> {
+        &self.diffuse
+    }
+
+    #[inline]
+    pub const fn roughness(&self) -> &MaterialParameter<f32> {
+        &self.roughness
+    }
+
+    #[inline]
+    pub const fn eta(&self) -> f32 {
+        self.eta
+    }
+
+    /// The probability of sampling the coating instead of the base, in proportion 
+    /// to an estimate of how much light each layer reflects.
+    fn specular_probability(&self, diffuse: &Vector3<f32>, wo: &Vector3<f32>) -> f32 {
+        let specular_weight = fresnel_dielectric(wo.z, self.eta);
+        let diffuse_weight = (1_f32 - specular_weight) * (diffuse.x + diffuse.y + diffuse.z) / 3_f32;
+        let total_weight = specular_weight + diffuse_weight;
+        if total_weight <= 0_f32 {
+            return 1_f32;
+        }
+
+        specular_weight / total_weight
+    }
+}
+
+impl Material for PlasticMaterial {
+    fn sample(&self, uv: &Vector2<f32>, wo: &Vector3<f32>, u: &Vector2<f32>) -> Option<BsdfSample> {
+        if wo.z <= 0_f32 {
+            return None;
+        }
+        let diffuse = self.diffuse.evaluate(uv);
+        let specular_probability = self.specular_probability(&diffuse, wo);
+        // Reuse the position of the first random number inside the chosen layer's 
+        // share of the unit interval to sample the layer.
+        let wi = if u.x < specular_probability {
+            let u_specular = Vector2::new(u.x / specular_probability, u.y);
+            let distribution = TrowbridgeReitz::from_roughness(self.roughness.evaluate(uv));
+            let h = distribution.sample_h(wo, &u_specular);
+            reflect(wo, &h)
+        } else {
+            let u_diffuse = Vector2::new(
+                f32::min((u.x - specular_probability) / (1_f32 - specular_probability), 1_f32 - f32::EPSILON), 
+                u.y
+            );
+            sample_cosine_hemisphere(&u_diffuse)
+        };
+        if wi.z <= 0_f32 {
+            return None;
+        }
+        let pdf = self.pdf(uv, wo, &wi);
+        if pdf <= 0_f32 {
+            return None;
+        }
+        let value = self.eval(uv, wo, &wi);
+
+        Some(BsdfSample::new(value, wi, pdf, false))
+    }
+
+    fn eval(&self, uv: &Vector2<f32>, wo: &Vector3<f32>, wi: &Vector3<f32>) -> Vector3<f32> {
+        if wo.z <= 0_f32 || wi.z <= 0_f32 {
+            return Vector3::zero();
+        }
+        let diffuse = {
+            // The light passes through the coating on the way in and on the way out.
+            let transmission_in = 1_f32 - fresnel_dielectric(wi.z, self.eta);
+            let transmission_out = 1_f32 - fresnel_dielectric(wo.z, self.eta);
+            self.diffuse.evaluate(uv) * (transmission_in * transmission_out * f32::consts::FRAC_1_PI)
+        };
+        let specular = {
+            let h = wo + wi;
+            if h.magnitude_squared() == 0_f32 {
+                0_f32
+            } else {
+                let h = h.normalize();
+                let distribution = TrowbridgeReitz::from_roughness(self.roughness.evaluate(uv));
+                let fresnel = fresnel_dielectric(wi.dot(&h), self.eta);
+                let d = distribution.d(&h);
+                let g = distribution.g(wo, wi);
+                fresnel * d * g / (4_f32 * wo.z * wi.z)
+            }
+        };
+
+        diffuse + Vector3::from_fill(specular)
+    }
+
+    fn pdf(&self, uv: &Vector2<f32>, wo: &Vector3<f32>, wi: &Vector3<f32>) -> f32 {
+        if wo.z <= 0_f32 || wi.z <= 0_f32 {
+            return 0_f32;
+        }
+        let diffuse = self.diffuse.evaluate(uv);
+        let specular_probability = self.specular_probability(&diffuse, wo);
+        let specular_pdf = {
+            let h = wo + wi;
+            if h.magnitude_squared() == 0_f32 {
+                0_f32
+            } else {
+                let h = h.normalize();
+                let distribution = TrowbridgeReitz::from_roughness(self.roughness.evaluate(uv));
+                distribution.pdf_h(wo, &h) / (4_f32 * wo.dot(&h))
+            }
+        };
+        let diffuse_pdf = cosine_hemisphere_pdf(wi.z);
+
+        specular_probability * specular_pdf + (1_f32 - specular_probability) * diffuse_pdf
+    }
+
+    fn albedo(&self, uv: &Vector2<f32>) -> Vector3<f32> {
+        self.diffuse.evaluate(uv)
+    }
+
+    fn is_specular(&self) -> bool {
+        false
+    }
+}
+
@@ -0,0 +1,33 @@
+use cglinalg::{
+    Vector2,
+    Vector3,
+};
+use std::f32;
+
+
+/// Sample a direction from the cosine-weighted hemisphere around the z-axis 
+/// using the uniformly distributed random numbers `u` in `[0, 1)^2`.
+pub(crate) fn sample_cosine_hemisphere(u: &Vector2<f32>) -> Vector3<f32> {
+    let radius = f32::sqrt(u.x);
+    let phi = 2_f32 * f32::consts::PI * u.y;
+    let x = radius * f32::cos(phi);
+    let y = radius * f32::sin(phi);
+    let z = f32::sqrt(f32::max(0_f32, 1_f32 - u.x));
+
+    Vector3::new(x, y, z)
+}
+
+/// The probability density of sampling a direction with z-component `cos_theta` 
+/// from the cosine-weighted hemisphere.
+#[inline]
+pub(crate) fn cosine_hemisphere_pdf(cos_theta: f32) -> f32 {
+    f32::max(0_f32, cos_theta) * f32::consts::FRAC_1_PI
+}
+
+/// Reflect the direction `wo` about the unit normal `normal`. Both directions 
+/// point away from the surface.
+#[inline]
+pub(crate) fn reflect(wo: &Vector3<f32>, normal: &Vector3<f32>) -> Vector3<f32> {
+    -wo + normal * (2_f32 * wo.dot(normal))
+}
+
@@ -0,0 +1,67 @@
+use cglinalg::{
+    Vector3,
+};
+
+
+/// An orthonormal basis around a shading normal, in which materials are 
+/// evaluated. 
+/// 
+/// The shading normal is the z-axis of the shading frame, so the cosine of the 
+/// angle between a unit direction in the shading frame and the shading normal 
+/// is the direction's z-component.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct ShadingFrame {
+    tangent: Vector3<f32>,
+    bitangent: Vector3<f32>,
+    normal: Vector3<f32>,
+}
+
+impl ShadingFrame {
+    /// Construct a shading frame from three orthonormal vectors.
+    pub fn new(tangent: Vector3<f32>, bitangent: Vector3<f32>, normal: Vector3<f32>) -> Self {
+        Self { tangent, bitangent, normal, }
+    }
+
+    /// Construct a shading frame around a unit normal vector, with an arbitrary 
+    /// choice of tangent.
+    pub fn from_normal(normal: &Vector3<f32>) -> Self {
+        // Duff et al., Building an Orthonormal Basis, Revisited.
+        let sign = f32::copysign(1_f32, normal.z);
+        let a = -1_f32 / (sign + normal.z);
+        let b = normal.x * normal.y * a;
+        let tangent = Vector3::new(1_f32 + sign * normal.x * normal.x * a, sign * b, -sign * normal.x);
+        let bitangent = Vector3::new(b, sign + normal.y * normal.y * a, -normal.y);
+
+        Self::new(tangent, bitangent, *normal)
+    }
+
+    #[inline]
+    pub const fn tangent(&self) -> Vector3<f32> {
+        self.tangent
+    }
+
+    #[inline]
+    pub const fn bitangent(&self) -> Vector3<f32> {
+        self.bitangent
+    }
+
+    #[inline]
+    pub const fn normal(&self) -> Vector3<f32> {
+        self.normal
+    }
+
+    /// Convert a vector from world space to the shading frame.
+    pub fn to_local(&self, vector: &Vector3<f32>) -> Vector3<f32> {
+        Vector3::new(
+            vector.dot(&self.tangent), 
+            vector.dot(&self.bitangent), 
+            vector.dot(&self.normal)
+        )
+    }
+
+    /// Convert a vector from the shading frame to world space.
+    pub fn to_world(&self, vector: &Vector3<f32>) -> Vector3<f32> {
+        self.tangent * vector.x + self.bitangent * vector.y + self.normal * vector.z
+    }
+}
+
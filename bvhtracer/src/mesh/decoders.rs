@@ -2,7 +2,7 @@ use crate::mesh::*;
 use crate::geometry::*;
 
 use cglinalg::{
-    Magnitude,
+    Degrees,
     Vector2,
     Vector3,
 };
@@ -17,6 +17,9 @@ use std::io::{
 use std::path::{
     Path,
 };
+use std::borrow::{
+    Cow,
+};
 use std::io;
 use std::error;
 use std::fmt;
@@ -24,6 +27,14 @@ use std::fmt;
 
 pub type MeshResult<T> = Result<T, MeshError>;
 
+/// The material name given to faces that come before the first material 
+/// statement in a mesh file that names its materials.
+pub const DEFAULT_MATERIAL_NAME: &str = "default";
+
+/// The crease angle with which decoders calculate smooth normals for the 
+/// vertices of a mesh file that does not have them.
+pub const DEFAULT_CREASE_ANGLE: Degrees<f32> = Degrees(60_f32);
+
 
 #[derive(Debug)]
 pub struct DecodingError {
@@ -31,7 +42,7 @@ pub struct DecodingError {
 }
 
 impl DecodingError {
-    fn new(underlying: impl Into<Box<dyn error::Error + Send + Sync>>) -> Self {
+    pub(crate) fn new(underlying: impl Into<Box<dyn error::Error + Send + Sync>>) -> Self {
         Self { 
             underlying: Some(underlying.into()),
         }
@@ -118,22 +129,54 @@ where
             let primitive = Triangle::new(vertex0, vertex1, vertex2);
 
             let tex_coords = TextureCoordinates::default();
-
-            let v0v2 = (primitive.vertices[2] - primitive.vertices[0]).normalize();
-            let v0v1 = (primitive.vertices[1] - primitive.vertices[0]).normalize();
-            let normal = v0v2.cross(&v0v1).normalize();
-            let normals = Normals::from([normal, normal, normal]);
+            let normals = Normals::default();
             
             builder = builder.with_primitive(primitive, tex_coords, normals);
         }
     
-        let mesh = builder.build();
+        let mut mesh = builder.build();
+        mesh.compute_smooth_normals(DEFAULT_CREASE_ANGLE);
+        // The front faces of a tri file wind clockwise.
+        mesh.flip_normals();
 
         Ok(mesh)
     }
 }
 
 
+/// How the faces of a Wavefront OBJ file are split into separate meshes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ObjMeshSplit {
+    /// One mesh per object, named by the `o` statements of the file.
+    Objects,
+    /// One mesh per group, named by the `g` statements of the file. Groups 
+    /// with the same name in different objects share a mesh, and a face 
+    /// that belongs to several groups appears in the mesh of each group.
+    Groups,
+}
+
+/// A mesh together with the name of the object or group it was read from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NamedMesh {
+    pub name: String,
+    pub mesh: Mesh<f32>,
+}
+
+impl NamedMesh {
+    pub fn new(name: &str, mesh: Mesh<f32>) -> Self {
+        Self { 
+            name: String::from(name), 
+            mesh,
+        }
+    }
+}
+
+
+/// A decoder for Wavefront OBJ files. 
+/// 
+/// Polygon faces with more than three vertices are triangulated. Points and 
+/// lines are skipped. Vertices without normals get smooth normals calculated 
+/// with the [`DEFAULT_CREASE_ANGLE`].
 pub struct ObjMeshDecoder<R> {
     reader: R,
 }
@@ -145,6 +188,57 @@ where
     pub fn new(reader: R) -> Self {
         Self { reader, }
     }
+
+    /// Read every object and group of the file into its own mesh, in the order 
+    /// in which they first appear in the file. Objects and groups without any 
+    /// faces are skipped.
+    pub fn read_meshes(mut self, split: ObjMeshSplit) -> MeshResult<Vec<NamedMesh>> {
+        let mut buffer = String::new();
+        self.reader.read_to_string(&mut buffer).map_err(|err| {
+            MeshError::IoError(err)
+        })?;
+        let obj_set = parse_obj(&buffer)?;
+        let has_materials = has_material_names(&obj_set);
+        let mut meshes = vec![];
+        match split {
+            ObjMeshSplit::Objects => {
+                for object in obj_set.objects.iter() {
+                    let builder = with_object_faces(MeshBuilder::new(), object, has_materials, |_| true)?;
+                    let mut mesh = builder.build();
+                    mesh.compute_missing_normals(DEFAULT_CREASE_ANGLE);
+                    if mesh.len_primitives() > 0 {
+                        meshes.push(NamedMesh::new(&object.name, mesh));
+                    }
+                }
+            }
+            ObjMeshSplit::Groups => {
+                let mut group_names: Vec<&str> = vec![];
+                for object in obj_set.objects.iter() {
+                    for group in object.group_set.iter() {
+                        if !group_names.contains(&group.0.as_str()) {
+                            group_names.push(&group.0);
+                        }
+                    }
+                }
+                for group_name in group_names.iter() {
+                    let mut builder = MeshBuilder::new();
+                    for object in obj_set.objects.iter() {
+                        let element_groups = element_group_names(object);
+                        builder = with_object_faces(builder, object, has_materials, |element_index| {
+                            element_groups[element_index].contains(group_name)
+                        })?;
+                    }
+                    let mut mesh = builder.build();
+                    mesh.compute_missing_normals(DEFAULT_CREASE_ANGLE);
+                    if mesh.len_primitives() > 0 {
+                        meshes.push(NamedMesh::new(group_name, mesh));
+                    }
+                }
+            }
+        }
+
+        Ok(meshes)
+    }
 }
 
 impl<'a, R> MeshDecoder<'a> for ObjMeshDecoder<R> 
@@ -153,65 +247,193 @@ where
 {
     type Reader = R;
 
-    // TODO: Calculate normals from vertex data in the case that they're missing?
+    /// Read the faces of every object in the file into one mesh.
     fn read_mesh(mut self) -> MeshResult<Mesh<f32>> {
         let mut buffer = String::new();
         self.reader.read_to_string(&mut buffer).map_err(|err| {
             MeshError::IoError(err)
         })?;
-        let obj_set = obj::parse(&buffer).unwrap();
-        let object = &obj_set.objects[0];
-        let mut builder = MeshBuilder::new();
-        for element in object.element_set.iter() {
-            match element {
-                obj::Element::Face(vtn1, vtn2, vtn3) => {
-                    let triples = [
-                        object.get_vtn_triple(*vtn1).unwrap(),
-                        object.get_vtn_triple(*vtn2).unwrap(),
-                        object.get_vtn_triple(*vtn3).unwrap(),
-                    ];
+        let (mesh, _) = decode_obj(&buffer)?;
+
+        Ok(mesh)
+    }
+}
+
+/// Decode the faces of every object in a Wavefront OBJ file into one mesh, 
+/// along with the paths of the material libraries that the file refers to.
+pub(crate) fn decode_obj(buffer: &str) -> MeshResult<(Mesh<f32>, Vec<String>)> {
+    let obj_set = parse_obj(buffer)?;
+    let has_materials = has_material_names(&obj_set);
+    let mut builder = MeshBuilder::new();
+    for object in obj_set.objects.iter() {
+        builder = with_object_faces(builder, object, has_materials, |_| true)?;
+    }
+    let mut mesh = builder.build();
+    mesh.compute_missing_normals(DEFAULT_CREASE_ANGLE);
     
-                    let mut vertices = Triangle::default();
-                    let mut tex_coords = TextureCoordinates::default();
-                    let mut normals = Normals::default();
-
-                    debug_assert_eq!(triples.len(), tex_coords.len());
-                    debug_assert_eq!(triples.len(), normals.len());
-
-                    for i in 0..triples.len() {
-                        match triples[i] {
-                            obj::VTNTriple::V(vp) => {
-                                vertices.vertices[i] = Vector3::new(vp.x as f32, vp.y as f32, vp.z as f32);
-                                tex_coords[i] = Vector2::zero();
-                                normals[i] = Vector3::zero();
-                            }
-                            obj::VTNTriple::VT(vp, vt) => {
-                                vertices.vertices[i] = Vector3::new(vp.x as f32, vp.y as f32, vp.z as f32);
-                                tex_coords[i] = Vector2::new(vt.u as f32, vt.v as f32);
-                                normals[i] = Vector3::zero();
-                            }
-                            obj::VTNTriple::VN(vp, vn) => {
-                                vertices.vertices[i] = Vector3::new(vp.x as f32, vp.y as f32, vp.z as f32);
-                                tex_coords[i] = Vector2::zero();
-                                normals[i] = Vector3::new(vn.x as f32, vn.y as f32, vn.z as f32);
-                            }
-                            obj::VTNTriple::VTN(vp, vt, vn) => {
-                                vertices.vertices[i] = Vector3::new(vp.x as f32, vp.y as f32, vp.z as f32);
-                                tex_coords[i] = Vector2::new(vt.u as f32, vt.v as f32);
-                                normals[i] = Vector3::new(vn.x as f32, vn.y as f32, vn.z as f32);
-                            }
-                        }   
+    Ok((mesh, obj_set.material_libraries))
+}
+
+fn parse_obj(buffer: &str) -> MeshResult<obj::ObjectSet> {
+    let buffer = triangulate_obj_polygons(buffer);
+
+    obj::parse(&*buffer).map_err(|err| {
+        MeshError::Decoding(DecodingError::new(err))
+    })
+}
+
+/// Determine whether any face in the file follows a material statement. If so, 
+/// every face is given a material name.
+fn has_material_names(obj_set: &obj::ObjectSet) -> bool {
+    obj_set.objects.iter().any(|object| {
+        object.geometry_set.iter().any(|geometry| geometry.material_name.is_some() && !geometry.shapes.is_empty())
+    })
+}
+
+/// The names of the groups that each element of an object belongs to.
+fn element_group_names(object: &obj::Object) -> Vec<Vec<&str>> {
+    let mut element_groups = vec![vec![]; object.element_set.len()];
+    for shape in object.shape_set.iter() {
+        for &group_index in shape.groups.iter() {
+            element_groups[shape.element].push(object.group_set[group_index].0.as_str());
+        }
+    }
+
+    element_groups
+}
+
+/// Add the faces of an object whose element indices pass `filter` to a mesh builder.
+fn with_object_faces<F>(
+    mut builder: MeshBuilder<f32>, 
+    object: &obj::Object, 
+    has_materials: bool, 
+    filter: F
+) -> MeshResult<MeshBuilder<f32>> 
+where
+    F: Fn(usize) -> bool
+{
+    let element_material_names = {
+        // Every face belongs to the geometry of the material that was 
+        // active when the face was read.
+        let mut element_material_names = vec![None; object.element_set.len()];
+        for geometry in object.geometry_set.iter() {
+            for &shape_index in geometry.shapes.iter() {
+                let element_index = object.shape_set[shape_index].element;
+                element_material_names[element_index] = geometry.material_name.as_deref();
+            }
+        }
+        element_material_names
+    };
+    for (element_index, element) in object.element_set.iter().enumerate() {
+        if !filter(element_index) {
+            continue;
+        }
+        if let obj::Element::Face(vtn1, vtn2, vtn3) = element {
+            if has_materials {
+                let material_name = element_material_names[element_index].unwrap_or(DEFAULT_MATERIAL_NAME);
+                builder = builder.with_material_name(material_name);
+            }
+            let triples = [
+                object.get_vtn_triple(*vtn1),
+                object.get_vtn_triple(*vtn2),
+                object.get_vtn_triple(*vtn3),
+            ];
+
+            let mut vertices = Triangle::default();
+            let mut tex_coords = TextureCoordinates::default();
+            let mut normals = Normals::default();
+
+            debug_assert_eq!(triples.len(), tex_coords.len());
+            debug_assert_eq!(triples.len(), normals.len());
+
+            for i in 0..triples.len() {
+                let triple = triples[i].ok_or_else(|| {
+                    MeshError::Decoding(DecodingError::new("A face refers to a vertex that does not exist"))
+                })?;
+                match triple {
+                    obj::VTNTriple::V(vp) => {
+                        vertices.vertices[i] = Vector3::new(vp.x as f32, vp.y as f32, vp.z as f32);
+                        tex_coords[i] = Vector2::zero();
+                        normals[i] = Vector3::zero();
                     }
-    
-                    builder = builder.with_primitive(vertices, tex_coords, normals);
-                }
-                _ => {}
+                    obj::VTNTriple::VT(vp, vt) => {
+                        vertices.vertices[i] = Vector3::new(vp.x as f32, vp.y as f32, vp.z as f32);
+                        tex_coords[i] = Vector2::new(vt.u as f32, vt.v as f32);
+                        normals[i] = Vector3::zero();
+                    }
+                    obj::VTNTriple::VN(vp, vn) => {
+                        vertices.vertices[i] = Vector3::new(vp.x as f32, vp.y as f32, vp.z as f32);
+                        tex_coords[i] = Vector2::zero();
+                        normals[i] = Vector3::new(vn.x as f32, vn.y as f32, vn.z as f32);
+                    }
+                    obj::VTNTriple::VTN(vp, vt, vn) => {
+                        vertices.vertices[i] = Vector3::new(vp.x as f32, vp.y as f32, vp.z as f32);
+                        tex_coords[i] = Vector2::new(vt.u as f32, vt.v as f32);
+                        normals[i] = Vector3::new(vn.x as f32, vn.y as f32, vn.z as f32);
+                    }
+                }   
             }
+
+            builder = builder.with_primitive(vertices, tex_coords, normals);
         }
-    
-        let mesh = builder.build();
-        
-        Ok(mesh)
     }
+
+    Ok(builder)
 }
 
+/// Rewrite every face statement of a Wavefront OBJ file with more than three 
+/// vertices as a sequence of triangle face statements, so that concave polygons 
+/// are split correctly. Faces whose vertices cannot be resolved are left for 
+/// the parser to report, as are faces continued across lines.
+fn triangulate_obj_polygons(buffer: &str) -> Cow<'_, str> {
+    let is_polygon = |line: &str| {
+        let mut tokens = line.split_whitespace();
+        tokens.next() == Some("f") && tokens.count() > 3 && !line.trim_end().ends_with('\\')
+    };
+    if !buffer.lines().any(is_polygon) {
+        return Cow::Borrowed(buffer);
+    }
+
+    let mut positions: Vec<Vector3<f32>> = vec![];
+    let mut triangulated = String::with_capacity(buffer.len());
+    for line in buffer.lines() {
+        let mut tokens = line.split_whitespace();
+        match tokens.next() {
+            Some("v") => {
+                let mut coordinates = tokens.map(|token| token.parse::<f32>().unwrap_or(0_f32));
+                let x = coordinates.next().unwrap_or(0_f32);
+                let y = coordinates.next().unwrap_or(0_f32);
+                let z = coordinates.next().unwrap_or(0_f32);
+                positions.push(Vector3::new(x, y, z));
+            }
+            Some("f") if is_polygon(line) => {
+                let vertex_tokens = tokens.collect::<Vec<_>>();
+                let polygon = vertex_tokens.iter()
+                    .map(|token| {
+                        let index = token.split('/').next()?.parse::<isize>().ok()?;
+                        let position_index = if index < 0 {
+                            positions.len() as isize + index
+                        } else {
+                            index - 1
+                        };
+
+                        positions.get(usize::try_from(position_index).ok()?).copied()
+                    })
+                    .collect::<Option<Vec<_>>>();
+                if let Some(polygon) = polygon {
+                    for [i0, i1, i2] in triangulate_polygon(&polygon) {
+                        triangulated.push_str(&format!(
+                            "f {} {} {}\n", vertex_tokens[i0], vertex_tokens[i1], vertex_tokens[i2]
+                        ));
+                    }
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        triangulated.push_str(line);
+        triangulated.push('\n');
+    }
+
+    Cow::Owned(triangulated)
+}
@@ -0,0 +1,306 @@
+use crate::mesh::*;
+use crate::transform::*;
+
+use cglinalg::{
+    Vector2,
+    Vector3,
+};
+
+use std::borrow::{
+    Cow,
+};
+use std::collections::{
+    HashMap,
+};
+use std::io::{
+    BufWriter,
+    Write,
+};
+
+
+pub trait MeshEncoder<'a>: Sized {
+    type Writer: Write + 'a;
+
+    fn write_mesh(self, mesh: &Mesh<f32>) -> MeshResult<()>;
+}
+
+/// Apply the transform of an encoder to a mesh before it is written, copying
+/// the mesh only when there is a transform to apply.
+fn baked_mesh<'m>(mesh: &'m Mesh<f32>, transform: Option<&Transform3<f32>>) -> Cow<'m, Mesh<f32>> {
+    match transform {
+        Some(transform) => Cow::Owned(mesh.transformed(transform)),
+        None => Cow::Borrowed(mesh),
+    }
+}
+
+/// Assigns an index to each distinct value, in the order in which the values are
+/// first seen. Values are compared by their bit patterns.
+struct IndexTable<K, V> {
+    indices: HashMap<K, usize>,
+    values: Vec<V>,
+}
+
+impl<K, V> IndexTable<K, V>
+where
+    K: std::hash::Hash + Eq,
+{
+    fn new() -> Self {
+        Self {
+            indices: HashMap::new(),
+            values: vec![],
+        }
+    }
+
+    fn insert(&mut self, key: K, value: V) -> usize {
+        let values = &mut self.values;
+        *self.indices.entry(key).or_insert_with(|| {
+            values.push(value);
+            values.len() - 1
+        })
+    }
+}
+
+fn vector3_bits(vector: &Vector3<f32>) -> [u32; 3] {
+    [vector.x.to_bits(), vector.y.to_bits(), vector.z.to_bits()]
+}
+
+fn vector2_bits(vector: &Vector2<f32>) -> [u32; 2] {
+    [vector.x.to_bits(), vector.y.to_bits()]
+}
+
+
+/// An encoder for Wavefront OBJ files.
+///
+/// The positions, texture coordinates, and normals of the mesh are each written
+/// once per distinct value, and each primitive is written as a face referring to
+/// them. When the materials of the mesh are named, a `usemtl` statement is
+/// written wherever the material changes.
+pub struct ObjMeshEncoder<W> {
+    writer: W,
+    transform: Option<Transform3<f32>>,
+}
+
+impl<W> ObjMeshEncoder<W>
+where
+    W: Write,
+{
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            transform: None,
+        }
+    }
+
+    /// Bake `transform` into the vertices and normals that are written, e.g. the
+    /// world transform of a scene object, to write the mesh in world space.
+    pub fn with_transform(mut self, transform: &Transform3<f32>) -> Self {
+        self.transform = Some(*transform);
+
+        self
+    }
+}
+
+impl<'a, W> MeshEncoder<'a> for ObjMeshEncoder<W>
+where
+    W: Write + 'a,
+{
+    type Writer = W;
+
+    fn write_mesh(self, mesh: &Mesh<f32>) -> MeshResult<()> {
+        let mesh = baked_mesh(mesh, self.transform.as_ref());
+        let mut positions = IndexTable::new();
+        let mut tex_coords = IndexTable::new();
+        let mut normals = IndexTable::new();
+        let mut faces = Vec::with_capacity(mesh.len_primitives());
+        for primitive_index in 0..mesh.len_primitives() {
+            let primitive = &mesh.primitives()[primitive_index];
+            let primitive_tex_coords = &mesh.tex_coords()[primitive_index];
+            let primitive_normals = &mesh.normals()[primitive_index];
+            let mut face = [[0; 3]; 3];
+            for i in 0..3 {
+                face[i] = [
+                    positions.insert(vector3_bits(&primitive.vertices[i]), primitive.vertices[i]) + 1,
+                    tex_coords.insert(vector2_bits(&primitive_tex_coords[i]), primitive_tex_coords[i]) + 1,
+                    normals.insert(vector3_bits(&primitive_normals[i]), primitive_normals[i]) + 1,
+                ];
+            }
+            faces.push(face);
+        }
+
+        let mut writer = BufWriter::new(self.writer);
+        let mut write = || -> std::io::Result<()> {
+            for position in positions.values.iter() {
+                writeln!(writer, "v {} {} {}", position.x, position.y, position.z)?;
+            }
+            for tex_coord in tex_coords.values.iter() {
+                writeln!(writer, "vt {} {}", tex_coord.x, tex_coord.y)?;
+            }
+            for normal in normals.values.iter() {
+                writeln!(writer, "vn {} {} {}", normal.x, normal.y, normal.z)?;
+            }
+            let mut current_material_id = None;
+            for (face, &material_id) in faces.iter().zip(mesh.material_ids().iter()) {
+                if current_material_id != Some(material_id) {
+                    if let Some(material_name) = mesh.material_names().get(material_id as usize) {
+                        writeln!(writer, "usemtl {}", material_name)?;
+                    }
+                    current_material_id = Some(material_id);
+                }
+                writeln!(
+                    writer, "f {}/{}/{} {}/{}/{} {}/{}/{}",
+                    face[0][0], face[0][1], face[0][2],
+                    face[1][0], face[1][1], face[1][2],
+                    face[2][0], face[2][1], face[2][2]
+                )?;
+            }
+
+            writer.flush()
+        };
+
+        write().map_err(MeshError::IoError)
+    }
+}
+
+
+/// An encoder for Stanford PLY files in the binary little endian format.
+///
+/// Each distinct combination of position, normal, and texture coordinates is
+/// written as one vertex with the properties `x`, `y`, `z`, `nx`, `ny`, `nz`,
+/// `u`, and `v`, and each primitive is written as a face with three vertex
+/// indices.
+pub struct PlyMeshEncoder<W> {
+    writer: W,
+    transform: Option<Transform3<f32>>,
+}
+
+impl<W> PlyMeshEncoder<W>
+where
+    W: Write,
+{
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            transform: None,
+        }
+    }
+
+    /// Bake `transform` into the vertices and normals that are written, e.g. the
+    /// world transform of a scene object, to write the mesh in world space.
+    pub fn with_transform(mut self, transform: &Transform3<f32>) -> Self {
+        self.transform = Some(*transform);
+
+        self
+    }
+}
+
+impl<'a, W> MeshEncoder<'a> for PlyMeshEncoder<W>
+where
+    W: Write + 'a,
+{
+    type Writer = W;
+
+    fn write_mesh(self, mesh: &Mesh<f32>) -> MeshResult<()> {
+        let mesh = baked_mesh(mesh, self.transform.as_ref());
+        let mut vertices = IndexTable::new();
+        let mut faces = Vec::with_capacity(mesh.len_primitives());
+        for primitive_index in 0..mesh.len_primitives() {
+            let primitive = &mesh.primitives()[primitive_index];
+            let primitive_tex_coords = &mesh.tex_coords()[primitive_index];
+            let primitive_normals = &mesh.normals()[primitive_index];
+            let mut face = [0_u32; 3];
+            for i in 0..3 {
+                let vertex = [
+                    primitive.vertices[i].x, primitive.vertices[i].y, primitive.vertices[i].z,
+                    primitive_normals[i].x, primitive_normals[i].y, primitive_normals[i].z,
+                    primitive_tex_coords[i].x, primitive_tex_coords[i].y,
+                ];
+                face[i] = vertices.insert(vertex.map(f32::to_bits), vertex) as u32;
+            }
+            faces.push(face);
+        }
+
+        let mut writer = BufWriter::new(self.writer);
+        let mut write = || -> std::io::Result<()> {
+            writeln!(writer, "ply")?;
+            writeln!(writer, "format binary_little_endian 1.0")?;
+            writeln!(writer, "element vertex {}", vertices.values.len())?;
+            for property in ["x", "y", "z", "nx", "ny", "nz", "u", "v"].iter() {
+                writeln!(writer, "property float {}", property)?;
+            }
+            writeln!(writer, "element face {}", faces.len())?;
+            writeln!(writer, "property list uchar uint vertex_indices")?;
+            writeln!(writer, "end_header")?;
+            for vertex in vertices.values.iter() {
+                for component in vertex.iter() {
+                    writer.write_all(&component.to_le_bytes())?;
+                }
+            }
+            for face in faces.iter() {
+                writer.write_all(&[3_u8])?;
+                for index in face.iter() {
+                    writer.write_all(&index.to_le_bytes())?;
+                }
+            }
+
+            writer.flush()
+        };
+
+        write().map_err(MeshError::IoError)
+    }
+}
+
+
+/// An encoder for `.tri` files, which store the three vertices of each
+/// primitive on one line. Texture coordinates and normals are not written.
+pub struct TriMeshEncoder<W> {
+    writer: W,
+    transform: Option<Transform3<f32>>,
+}
+
+impl<W> TriMeshEncoder<W>
+where
+    W: Write,
+{
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer,
+            transform: None,
+        }
+    }
+
+    /// Bake `transform` into the vertices that are written, e.g. the world
+    /// transform of a scene object, to write the mesh in world space.
+    pub fn with_transform(mut self, transform: &Transform3<f32>) -> Self {
+        self.transform = Some(*transform);
+
+        self
+    }
+}
+
+impl<'a, W> MeshEncoder<'a> for TriMeshEncoder<W>
+where
+    W: Write + 'a,
+{
+    type Writer = W;
+
+    fn write_mesh(self, mesh: &Mesh<f32>) -> MeshResult<()> {
+        let mesh = baked_mesh(mesh, self.transform.as_ref());
+        let mut writer = BufWriter::new(self.writer);
+        let mut write = || -> std::io::Result<()> {
+            for primitive in mesh.primitives().iter() {
+                let [vertex0, vertex1, vertex2] = primitive.vertices;
+                writeln!(
+                    writer, "{} {} {} {} {} {} {} {} {}",
+                    vertex0.x, vertex0.y, vertex0.z,
+                    vertex1.x, vertex1.y, vertex1.z,
+                    vertex2.x, vertex2.y, vertex2.z
+                )?;
+            }
+
+            writer.flush()
+        };
+
+        write().map_err(MeshError::IoError)
+    }
+}
+
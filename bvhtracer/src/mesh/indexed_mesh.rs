@@ -0,0 +1,310 @@
+use crate::geometry::*;
+use crate::mesh::*;
+use cglinalg::{
+    Vector2,
+    Vector3,
+    SimdScalar,
+};
+use std::collections::{
+    HashMap,
+};
+
+
+/// A triangle mesh whose vertices are stored once in a vertex buffer and shared
+/// between triangles through an index buffer.
+///
+/// Each vertex is a position together with its texture coordinates and normal.
+/// Closed meshes share each vertex among several triangles, so an indexed mesh
+/// uses a fraction of the memory of the equivalent [`Mesh`], which stores three
+/// separate vertices for every primitive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexedMesh<S>
+where
+    S: SimdScalar,
+{
+    vertices: Vec<Vector3<S>>,
+    tex_coords: Vec<Vector2<S>>,
+    normals: Vec<Vector3<S>>,
+    indices: Vec<[u32; 3]>,
+    /// The index into a model's material table of the material for each primitive.
+    material_ids: Vec<u32>,
+    /// The names of the materials referred to by the material ids, indexed by
+    /// material id, for meshes decoded from formats that name their materials.
+    material_names: Vec<String>,
+}
+
+impl<S> IndexedMesh<S>
+where
+    S: SimdScalar,
+{
+    /// The number of vertices in the vertex buffer.
+    pub fn len_vertices(&self) -> usize {
+        self.vertices.len()
+    }
+
+    pub fn len_primitives(&self) -> usize {
+        self.indices.len()
+    }
+
+    /// The positions of the vertices in the vertex buffer.
+    pub fn vertices(&self) -> &[Vector3<S>] {
+        &self.vertices
+    }
+
+    /// The positions of the vertices in the vertex buffer, which can be moved
+    /// without changing the topology of the mesh.
+    pub fn vertices_mut(&mut self) -> &mut [Vector3<S>] {
+        &mut self.vertices
+    }
+
+    /// The texture coordinates of the vertices in the vertex buffer.
+    pub fn tex_coords(&self) -> &[Vector2<S>] {
+        &self.tex_coords
+    }
+
+    /// The normals of the vertices in the vertex buffer.
+    pub fn normals(&self) -> &[Vector3<S>] {
+        &self.normals
+    }
+
+    /// The index buffer, with three consecutive vertex indices per primitive.
+    pub fn indices(&self) -> &[u32] {
+        self.indices.as_flattened()
+    }
+
+    /// The three vertex indices of each primitive.
+    pub fn triangle_indices(&self) -> &[[u32; 3]] {
+        &self.indices
+    }
+
+    /// Assemble the primitive with index `index` from the vertex buffer.
+    pub fn primitive(&self, index: usize) -> Triangle<S> {
+        let [index0, index1, index2] = self.indices[index];
+
+        Triangle::new(
+            self.vertices[index0 as usize],
+            self.vertices[index1 as usize],
+            self.vertices[index2 as usize]
+        )
+    }
+
+    /// Assemble the texture coordinates of the primitive with index `index`.
+    pub fn primitive_tex_coords(&self, index: usize) -> TextureCoordinates<S, 3> {
+        let [index0, index1, index2] = self.indices[index];
+
+        TextureCoordinates::from([
+            self.tex_coords[index0 as usize],
+            self.tex_coords[index1 as usize],
+            self.tex_coords[index2 as usize],
+        ])
+    }
+
+    /// Assemble the normals of the primitive with index `index`.
+    pub fn primitive_normals(&self, index: usize) -> Normals<S, 3> {
+        let [index0, index1, index2] = self.indices[index];
+
+        Normals::from([
+            self.normals[index0 as usize],
+            self.normals[index1 as usize],
+            self.normals[index2 as usize],
+        ])
+    }
+
+    /// The material id of each primitive in the mesh.
+    pub fn material_ids(&self) -> &[u32] {
+        &self.material_ids
+    }
+
+    /// The names of the materials in the mesh, indexed by material id. This is
+    /// empty for meshes whose materials are not named.
+    pub fn material_names(&self) -> &[String] {
+        &self.material_names
+    }
+
+    /// Convert the mesh to a triangle soup, with three separate vertices for
+    /// every primitive.
+    pub fn to_mesh(&self) -> Mesh<S> {
+        let mut builder = MeshBuilder::new();
+        for material_name in self.material_names.iter() {
+            builder = builder.with_material_name(material_name);
+        }
+        for primitive_index in 0..self.len_primitives() {
+            builder = builder
+                .with_material_id(self.material_ids[primitive_index])
+                .with_primitive(
+                    self.primitive(primitive_index),
+                    self.primitive_tex_coords(primitive_index),
+                    self.primitive_normals(primitive_index)
+                );
+        }
+
+        builder.build()
+    }
+
+    pub(crate) fn vertices_and_triangle_indices_mut(&mut self) -> (&[Vector3<S>], &mut [[u32; 3]]) {
+        (&self.vertices, &mut self.indices)
+    }
+
+    /// Reorder the material ids of the mesh to follow primitives that were
+    /// reordered in place, where `order[i]` is the original index of the
+    /// primitive now at index `i`.
+    pub(crate) fn reorder_material_ids(&mut self, order: &[u32]) {
+        debug_assert_eq!(order.len(), self.material_ids.len());
+        let old_material_ids = self.material_ids.clone();
+        for (new_index, &old_index) in order.iter().enumerate() {
+            self.material_ids[new_index] = old_material_ids[old_index as usize];
+        }
+    }
+}
+
+impl IndexedMesh<f32> {
+    /// Convert a triangle soup to an indexed mesh, merging the vertices that have
+    /// exactly the same position, texture coordinates, and normal.
+    pub fn from_mesh(mesh: &Mesh<f32>) -> Self {
+        let mut vertex_indices = HashMap::new();
+        let mut builder = IndexedMeshBuilder::new();
+        for material_name in mesh.material_names().iter() {
+            builder = builder.with_material_name(material_name);
+        }
+        for primitive_index in 0..mesh.len_primitives() {
+            let primitive = &mesh.primitives()[primitive_index];
+            let tex_coords = &mesh.tex_coords()[primitive_index];
+            let normals = &mesh.normals()[primitive_index];
+            let mut triangle = [0_u32; 3];
+            for i in 0..3 {
+                let key = [
+                    primitive.vertices[i].x, primitive.vertices[i].y, primitive.vertices[i].z,
+                    tex_coords[i].x, tex_coords[i].y,
+                    normals[i].x, normals[i].y, normals[i].z,
+                ].map(f32::to_bits);
+                triangle[i] = match vertex_indices.get(&key) {
+                    Some(&vertex_index) => vertex_index,
+                    None => {
+                        let vertex_index = builder.vertices.len() as u32;
+                        builder = builder.with_vertex(primitive.vertices[i], tex_coords[i], normals[i]);
+                        vertex_indices.insert(key, vertex_index);
+                        vertex_index
+                    }
+                };
+            }
+            builder = builder
+                .with_material_id(mesh.material_ids()[primitive_index])
+                .with_triangle(triangle);
+        }
+
+        builder.build()
+    }
+}
+
+impl From<&Mesh<f32>> for IndexedMesh<f32> {
+    fn from(mesh: &Mesh<f32>) -> Self {
+        Self::from_mesh(mesh)
+    }
+}
+
+impl<S> From<&IndexedMesh<S>> for Mesh<S>
+where
+    S: SimdScalar,
+{
+    fn from(mesh: &IndexedMesh<S>) -> Self {
+        mesh.to_mesh()
+    }
+}
+
+
+pub struct IndexedMeshBuilder<S>
+where
+    S: SimdScalar,
+{
+    vertices: Vec<Vector3<S>>,
+    tex_coords: Vec<Vector2<S>>,
+    normals: Vec<Vector3<S>>,
+    indices: Vec<[u32; 3]>,
+    material_ids: Vec<u32>,
+    material_names: Vec<String>,
+    current_material_id: u32,
+}
+
+impl<S> IndexedMeshBuilder<S>
+where
+    S: SimdScalar,
+{
+    pub fn new() -> Self {
+        Self {
+            vertices: vec![],
+            tex_coords: vec![],
+            normals: vec![],
+            indices: vec![],
+            material_ids: vec![],
+            material_names: vec![],
+            current_material_id: 0,
+        }
+    }
+
+    /// Add a vertex to the vertex buffer. Vertices are numbered from zero in the
+    /// order in which they are added.
+    pub fn with_vertex(mut self, position: Vector3<S>, tex_coords: Vector2<S>, normal: Vector3<S>) -> Self {
+        self.vertices.push(position);
+        self.tex_coords.push(tex_coords);
+        self.normals.push(normal);
+
+        self
+    }
+
+    /// Assign the primitives added after this call to the material with id
+    /// `material_id`. Primitives use material id zero by default.
+    pub fn with_material_id(mut self, material_id: u32) -> Self {
+        self.current_material_id = material_id;
+
+        self
+    }
+
+    /// Assign the primitives added after this call to the material named `name`.
+    /// Each new name gets the next unused material id, starting from zero.
+    pub fn with_material_name(mut self, name: &str) -> Self {
+        let material_id = match self.material_names.iter().position(|material_name| material_name == name) {
+            Some(material_id) => material_id,
+            None => {
+                self.material_names.push(String::from(name));
+                self.material_names.len() - 1
+            }
+        };
+        self.current_material_id = material_id as u32;
+
+        self
+    }
+
+    /// Add a primitive with the vertices at the indices `triangle` in the vertex
+    /// buffer.
+    pub fn with_triangle(mut self, triangle: [u32; 3]) -> Self {
+        self.indices.push(triangle);
+        self.material_ids.push(self.current_material_id);
+
+        self
+    }
+
+    /// Construct the indexed mesh.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if a primitive refers to a vertex that is not in
+    /// the vertex buffer.
+    pub fn build(self) -> IndexedMesh<S> {
+        let vertex_count = self.vertices.len();
+        assert!(
+            self.indices.iter().flatten().all(|&index| (index as usize) < vertex_count),
+            "A primitive refers to a vertex outside of the vertex buffer of {} vertices.",
+            vertex_count
+        );
+
+        IndexedMesh {
+            vertices: self.vertices,
+            tex_coords: self.tex_coords,
+            normals: self.normals,
+            indices: self.indices,
+            material_ids: self.material_ids,
+            material_names: self.material_names,
+        }
+    }
+}
+
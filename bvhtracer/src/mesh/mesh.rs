@@ -1,8 +1,13 @@
 use crate::geometry::*;
+use crate::mesh::normals::*;
+use crate::transform::*;
 use cglinalg::{
+    Magnitude,
+    Radians,
     Vector2,
     Vector3,
     SimdScalar,
+    SimdScalarFloat,
 };
 use std::ops;
 use std::slice;
@@ -96,7 +101,64 @@ impl<S, const N: usize> ops::IndexMut<usize> for Normals<S, N> {
 }
 
 
-// TODO: Add tangent and bitangent vectors.
+/// The tangents of the vertices of a primitive, which point in the direction of
+/// increasing `u` texture coordinates.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Tangents<S, const N: usize> {
+    data: [Vector3<S>; N],
+}
+
+impl<S, const N: usize> Tangents<S, N> {
+    pub fn len(&self) -> usize {
+        N
+    }
+}
+
+impl<S, const N: usize> From<[Vector3<S>; N]> for Tangents<S, N> {
+    fn from(data: [Vector3<S>; N]) -> Self {
+        Self { data, }
+    }
+}
+
+impl<S, const N: usize> ops::Index<usize> for Tangents<S, N> {
+    type Output = Vector3<S>;
+
+    fn index(&self, _index: usize) -> &Self::Output {
+        &self.data[_index]
+    }
+}
+
+
+/// The bitangents of the vertices of a primitive, which point in the direction 
+/// of increasing `v` texture coordinates.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Bitangents<S, const N: usize> {
+    data: [Vector3<S>; N],
+}
+
+impl<S, const N: usize> Bitangents<S, N> {
+    pub fn len(&self) -> usize {
+        N
+    }
+}
+
+impl<S, const N: usize> From<[Vector3<S>; N]> for Bitangents<S, N> {
+    fn from(data: [Vector3<S>; N]) -> Self {
+        Self { data, }
+    }
+}
+
+impl<S, const N: usize> ops::Index<usize> for Bitangents<S, N> {
+    type Output = Vector3<S>;
+
+    fn index(&self, _index: usize) -> &Self::Output {
+        &self.data[_index]
+    }
+}
+
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Mesh<S> 
 where
@@ -105,14 +167,39 @@ where
     vertices: Vec<Vector3<S>>,
     tex_coords: Vec<Vector2<S>>,
     normals: Vec<Vector3<S>>,
+    /// The tangents of the vertices, which are empty until they are computed.
+    tangents: Vec<Vector3<S>>,
+    /// The bitangents of the vertices, which are empty until they are computed.
+    bitangents: Vec<Vector3<S>>,
+    /// The index into a model's material table of the material for each primitive.
+    material_ids: Vec<u32>,
+    /// The names of the materials referred to by the material ids, indexed by 
+    /// material id, for meshes decoded from formats that name their materials.
+    material_names: Vec<String>,
 }
 
 impl<S> Mesh<S> 
 where
     S: SimdScalar,
 {
-    pub(crate) fn from_parts(vertices: Vec<Vector3<S>>, tex_coords: Vec<Vector2<S>>, normals: Vec<Vector3<S>>) -> Self {
-        Self { vertices, tex_coords, normals, }
+    pub(crate) fn from_parts(
+        vertices: Vec<Vector3<S>>, 
+        tex_coords: Vec<Vector2<S>>, 
+        normals: Vec<Vector3<S>>, 
+        material_ids: Vec<u32>,
+        material_names: Vec<String>) -> Self 
+    {
+        debug_assert_eq!(vertices.len(), 3 * material_ids.len());
+
+        Self { 
+            vertices, 
+            tex_coords, 
+            normals, 
+            tangents: vec![], 
+            bitangents: vec![], 
+            material_ids, 
+            material_names, 
+        }
     }
 
     pub fn len(&self) -> usize {
@@ -162,6 +249,253 @@ where
             slice::from_raw_parts(p, len)
         }
     }
+
+    /// Determine whether the tangents and bitangents of the mesh have been 
+    /// computed.
+    pub fn has_tangents(&self) -> bool {
+        !self.tangents.is_empty()
+    }
+
+    /// The tangents of each primitive, or an empty slice if they have not been 
+    /// computed.
+    pub fn tangents(&self) -> &[Tangents<S, 3>] {
+        debug_assert_eq!(self.tangents.len() % 3, 0);
+        unsafe {
+            let p = self.tangents.as_ptr() as *const Tangents<S, 3>;
+            let len = self.tangents.len() / 3;
+
+            slice::from_raw_parts(p, len)
+        }
+    }
+
+    /// The bitangents of each primitive, or an empty slice if they have not been 
+    /// computed.
+    pub fn bitangents(&self) -> &[Bitangents<S, 3>] {
+        debug_assert_eq!(self.bitangents.len() % 3, 0);
+        unsafe {
+            let p = self.bitangents.as_ptr() as *const Bitangents<S, 3>;
+            let len = self.bitangents.len() / 3;
+
+            slice::from_raw_parts(p, len)
+        }
+    }
+
+    /// The material id of each primitive in the mesh.
+    pub fn material_ids(&self) -> &[u32] {
+        &self.material_ids
+    }
+
+    /// The names of the materials in the mesh, indexed by material id. This is 
+    /// empty for meshes whose materials are not named.
+    pub fn material_names(&self) -> &[String] {
+        &self.material_names
+    }
+
+    /// Find the material id for the material named `name`.
+    pub fn material_id(&self, name: &str) -> Option<u32> {
+        self.material_names
+            .iter()
+            .position(|material_name| material_name == name)
+            .map(|material_id| material_id as u32)
+    }
+
+    /// Reorder the per primitive attributes of the mesh to follow primitives that 
+    /// were reordered in place, where `order[i]` is the original index of the 
+    /// primitive now at index `i`.
+    pub(crate) fn reorder_attributes(&mut self, order: &[u32]) {
+        debug_assert_eq!(order.len(), self.material_ids.len());
+        let old_tex_coords = self.tex_coords.clone();
+        let old_normals = self.normals.clone();
+        let old_tangents = self.tangents.clone();
+        let old_bitangents = self.bitangents.clone();
+        let old_material_ids = self.material_ids.clone();
+        for (new_index, &old_index) in order.iter().enumerate() {
+            let old_index = old_index as usize;
+            for i in 0..3 {
+                self.tex_coords[3 * new_index + i] = old_tex_coords[3 * old_index + i];
+                self.normals[3 * new_index + i] = old_normals[3 * old_index + i];
+            }
+            if self.has_tangents() {
+                for i in 0..3 {
+                    self.tangents[3 * new_index + i] = old_tangents[3 * old_index + i];
+                    self.bitangents[3 * new_index + i] = old_bitangents[3 * old_index + i];
+                }
+            }
+            self.material_ids[new_index] = old_material_ids[old_index];
+        }
+    }
+
+    /// Reverse the winding order of the primitive with index `index`, along with 
+    /// the order of its per vertex attributes.
+    pub(crate) fn flip_primitive(&mut self, index: usize) {
+        self.vertices.swap(3 * index + 1, 3 * index + 2);
+        self.tex_coords.swap(3 * index + 1, 3 * index + 2);
+        self.normals.swap(3 * index + 1, 3 * index + 2);
+        if self.has_tangents() {
+            self.tangents.swap(3 * index + 1, 3 * index + 2);
+            self.bitangents.swap(3 * index + 1, 3 * index + 2);
+        }
+    }
+
+    /// Remove the primitives for which `keep` is false, along with their 
+    /// attributes, preserving the order of the remaining primitives.
+    pub(crate) fn retain_primitives(&mut self, keep: &[bool]) {
+        debug_assert_eq!(keep.len(), self.material_ids.len());
+        let has_tangents = self.has_tangents();
+        let mut new_len = 0;
+        for (old_index, _) in keep.iter().enumerate().filter(|(_, &keep)| keep) {
+            for i in 0..3 {
+                self.vertices[3 * new_len + i] = self.vertices[3 * old_index + i];
+                self.tex_coords[3 * new_len + i] = self.tex_coords[3 * old_index + i];
+                self.normals[3 * new_len + i] = self.normals[3 * old_index + i];
+                if has_tangents {
+                    self.tangents[3 * new_len + i] = self.tangents[3 * old_index + i];
+                    self.bitangents[3 * new_len + i] = self.bitangents[3 * old_index + i];
+                }
+            }
+            self.material_ids[new_len] = self.material_ids[old_index];
+            new_len += 1;
+        }
+        self.vertices.truncate(3 * new_len);
+        self.tex_coords.truncate(3 * new_len);
+        self.normals.truncate(3 * new_len);
+        if has_tangents {
+            self.tangents.truncate(3 * new_len);
+            self.bitangents.truncate(3 * new_len);
+        }
+        self.material_ids.truncate(new_len);
+    }
+}
+
+
+impl<S> Mesh<S>
+where
+    S: SimdScalarFloat,
+{
+    /// Construct a copy of the mesh with `transform` applied to its vertices 
+    /// and normals. 
+    /// 
+    /// Normals are transformed by the inverse transpose of the transform and 
+    /// renormalized, tangents and bitangents are transformed like any other 
+    /// direction and renormalized, and the winding order of every primitive is reversed when 
+    /// the transform is a reflection, so that front faces stay front faces.
+    pub fn transformed(&self, transform: &Transform3<S>) -> Self {
+        let normal_matrix = match transform.inverse() {
+            Some(transform_inv) => transform_inv.compute_matrix().transpose(),
+            None => transform.compute_matrix(),
+        };
+        let axis_x = transform.transform_vector(&Vector3::unit_x());
+        let axis_y = transform.transform_vector(&Vector3::unit_y());
+        let axis_z = transform.transform_vector(&Vector3::unit_z());
+        let is_reflection = axis_x.cross(&axis_y).dot(&axis_z) < S::zero();
+
+        let mut mesh = self.clone();
+        for vertex in mesh.vertices.iter_mut() {
+            *vertex = transform.transform_point(vertex);
+        }
+        for normal in mesh.normals.iter_mut() {
+            let new_normal = (normal_matrix * normal.extend(S::zero())).contract();
+            let magnitude = new_normal.magnitude();
+            *normal = if magnitude > S::zero() { new_normal / magnitude } else { new_normal };
+        }
+        for tangent in mesh.tangents.iter_mut().chain(mesh.bitangents.iter_mut()) {
+            let new_tangent = transform.transform_vector(tangent);
+            let magnitude = new_tangent.magnitude();
+            *tangent = if magnitude > S::zero() { new_tangent / magnitude } else { new_tangent };
+        }
+        if is_reflection {
+            for i in 0..mesh.material_ids.len() {
+                mesh.vertices.swap(3 * i + 1, 3 * i + 2);
+                mesh.tex_coords.swap(3 * i + 1, 3 * i + 2);
+                mesh.normals.swap(3 * i + 1, 3 * i + 2);
+                if mesh.has_tangents() {
+                    mesh.tangents.swap(3 * i + 1, 3 * i + 2);
+                    mesh.bitangents.swap(3 * i + 1, 3 * i + 2);
+                }
+            }
+        }
+
+        mesh
+    }
+
+    /// Replace the normals of the mesh with angle weighted smooth normals. 
+    /// 
+    /// Every vertex takes the average normal of the primitives that share its 
+    /// position, weighted by the angle each primitive makes at the vertex. 
+    /// Primitives whose normals differ by more than `crease_angle` are not 
+    /// averaged together, so that the edge between them stays sharp: a crease 
+    /// angle of zero gives flat normals, and a crease angle of 180 degrees 
+    /// smooths across every edge. Front faces wind counterclockwise. Any 
+    /// tangents are discarded, since they depend on the normals.
+    pub fn compute_smooth_normals<A>(&mut self, crease_angle: A) 
+    where
+        A: Into<Radians<S>>,
+    {
+        self.normals = smooth_normals(&self.vertices, crease_angle.into());
+        self.tangents.clear();
+        self.bitangents.clear();
+    }
+
+    /// Reverse the direction of every normal of the mesh, e.g. for a mesh whose 
+    /// front faces wind clockwise. Any tangents are discarded, since they depend 
+    /// on the normals.
+    pub fn flip_normals(&mut self) {
+        for normal in self.normals.iter_mut() {
+            *normal = -*normal;
+        }
+        self.tangents.clear();
+        self.bitangents.clear();
+    }
+
+    /// Determine whether any vertex of the mesh is missing its normal, i.e. has 
+    /// a normal that is zero or not finite.
+    pub fn has_missing_normals(&self) -> bool {
+        self.normals.iter().any(|normal| is_missing_normal(normal))
+    }
+
+    /// Replace the missing normals of the mesh with angle weighted smooth 
+    /// normals, as in [`Mesh::compute_smooth_normals`], keeping the normals 
+    /// that are already present.
+    pub fn compute_missing_normals<A>(&mut self, crease_angle: A) 
+    where
+        A: Into<Radians<S>>,
+    {
+        if !self.has_missing_normals() {
+            return;
+        }
+        let smooth_normals = smooth_normals(&self.vertices, crease_angle.into());
+        for (normal, smooth_normal) in self.normals.iter_mut().zip(smooth_normals) {
+            if is_missing_normal(normal) {
+                *normal = smooth_normal;
+            }
+        }
+        self.tangents.clear();
+        self.bitangents.clear();
+    }
+
+    /// Compute a tangent and a bitangent for every vertex of the mesh from its 
+    /// normals and texture coordinates, in the manner of MikkTSpace, for normal 
+    /// mapping.
+    /// 
+    /// The tangent of a vertex points along increasing `u` texture coordinates 
+    /// and is orthogonal to the normal. The bitangent is the cross product of 
+    /// the normal and the tangent, negated where the texture is mirrored, so 
+    /// that it points along increasing `v` texture coordinates. Vertices that 
+    /// share a position, texture coordinates, and normal share a tangent frame. 
+    /// Vertices whose texture coordinates are degenerate get an arbitrary frame 
+    /// about their normal.
+    pub fn compute_tangents(&mut self) {
+        let (tangents, bitangents) = tangent_frames(&self.vertices, &self.tex_coords, &self.normals);
+        self.tangents = tangents;
+        self.bitangents = bitangents;
+    }
+}
+
+fn is_missing_normal<S>(normal: &Vector3<S>) -> bool 
+where
+    S: SimdScalarFloat,
+{
+    normal.is_zero() || !normal.is_finite()
 }
 
 
@@ -172,6 +506,9 @@ where
     vertices: Vec<Vector3<S>>,
     tex_coords: Vec<Vector2<S>>,
     normals: Vec<Vector3<S>>,
+    material_ids: Vec<u32>,
+    material_names: Vec<String>,
+    current_material_id: u32,
 }
 
 impl<S> MeshBuilder<S>
@@ -182,10 +519,36 @@ where
         Self { 
             vertices: vec![], 
             tex_coords: vec![], 
-            normals: vec![] 
+            normals: vec![],
+            material_ids: vec![],
+            material_names: vec![],
+            current_material_id: 0,
         }
     }
 
+    /// Assign the primitives added after this call to the material with id 
+    /// `material_id`. Primitives use material id zero by default.
+    pub fn with_material_id(mut self, material_id: u32) -> Self {
+        self.current_material_id = material_id;
+
+        self
+    }
+
+    /// Assign the primitives added after this call to the material named `name`. 
+    /// Each new name gets the next unused material id, starting from zero.
+    pub fn with_material_name(mut self, name: &str) -> Self {
+        let material_id = match self.material_names.iter().position(|material_name| material_name == name) {
+            Some(material_id) => material_id,
+            None => {
+                self.material_names.push(String::from(name));
+                self.material_names.len() - 1
+            }
+        };
+        self.current_material_id = material_id as u32;
+
+        self
+    }
+
     pub fn with_primitive(mut self, primitive: Triangle<S>, tex_coords: TextureCoordinates<S, 3>, normals: Normals<S, 3>) -> Self {
         self.vertices.push(primitive.vertices[0]);
         self.vertices.push(primitive.vertices[1]);
@@ -196,6 +559,7 @@ where
         self.normals.push(normals[0]);
         self.normals.push(normals[1]);
         self.normals.push(normals[2]);
+        self.material_ids.push(self.current_material_id);
 
         debug_assert_eq!(self.vertices.len(), self.tex_coords.len());
         debug_assert_eq!(self.vertices.len(), self.normals.len());
@@ -204,7 +568,13 @@ where
     }
 
     pub fn build(self) -> Mesh<S> {
-        Mesh::from_parts(self.vertices, self.tex_coords, self.normals)
+        Mesh::from_parts(
+            self.vertices, 
+            self.tex_coords, 
+            self.normals, 
+            self.material_ids, 
+            self.material_names
+        )
     }
 }
 
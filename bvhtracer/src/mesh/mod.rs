@@ -1,7 +1,20 @@
 mod mesh;
 mod decoders;
+mod encoders;
+mod indexed_mesh;
+mod normals;
+mod ply_decoder;
+mod stl_decoder;
+mod triangulate;
+mod validation;
 
 
 pub use mesh::*;
 pub use decoders::*;
+pub use encoders::*;
+pub use indexed_mesh::*;
+pub use ply_decoder::*;
+pub use stl_decoder::*;
+pub use triangulate::*;
+pub use validation::*;
 
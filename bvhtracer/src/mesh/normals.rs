@@ -0,0 +1,271 @@
+use cglinalg::{
+    Magnitude,
+    Radians,
+    Vector2,
+    Vector3,
+    SimdScalarFloat,
+};
+use std::cmp::{
+    Ordering,
+};
+
+
+/// Calculate an angle weighted smooth normal for every vertex of a triangle soup,
+/// where each three consecutive vertices form a primitive.
+///
+/// The normal of a vertex is the sum of the normals of the primitives that share
+/// its position, each weighted by the angle that the primitive makes at that
+/// position. A primitive only contributes when its normal is within `crease_angle`
+/// of the normal of the primitive the vertex belongs to, so that sharp edges stay
+/// sharp. Front faces wind counterclockwise.
+pub(crate) fn smooth_normals<S>(vertices: &[Vector3<S>], crease_angle: Radians<S>) -> Vec<Vector3<S>>
+where
+    S: SimdScalarFloat,
+{
+    debug_assert_eq!(vertices.len() % 3, 0);
+    let face_normals = vertices.chunks_exact(3)
+        .map(face_normal)
+        .collect::<Vec<_>>();
+    let angles = corner_angles(vertices);
+    let cos_crease_angle = crease_angle.0.cos();
+    let keys = vertices.iter()
+        .map(|vertex| [vertex.x, vertex.y, vertex.z])
+        .collect::<Vec<_>>();
+    let mut normals = vec![Vector3::zero(); vertices.len()];
+    for group in weld_groups(&keys).iter() {
+        for &corner in group.iter() {
+            let face_normal = face_normals[corner / 3];
+            let mut normal = Vector3::zero();
+            let mut is_smoothed = false;
+            for &other_corner in group.iter() {
+                let other_face_normal = face_normals[other_corner / 3];
+                if other_corner / 3 == corner / 3 {
+                    normal += other_face_normal * angles[other_corner];
+                } else if face_normal.dot(&other_face_normal) >= cos_crease_angle {
+                    normal += other_face_normal * angles[other_corner];
+                    is_smoothed = true;
+                }
+            }
+            if !is_smoothed && !face_normal.is_zero() {
+                // A vertex on a single primitive takes the face normal as it is, 
+                // so that flat regions have exactly flat normals.
+                normals[corner] = face_normal;
+                continue;
+            }
+            if normal.is_zero() {
+                // A degenerate primitive has no normal of its own to compare
+                // against, so it takes the normal of everything around it.
+                for &other_corner in group.iter() {
+                    normal += face_normals[other_corner / 3] * angles[other_corner];
+                }
+            }
+            normals[corner] = normalize_or_zero(&normal);
+        }
+    }
+
+    normals
+}
+
+/// Calculate a tangent and a bitangent for every vertex of a triangle soup, in the
+/// manner of MikkTSpace.
+///
+/// The tangent of a primitive points along increasing `u` texture coordinates
+/// and its bitangent along increasing `v`. The tangent of each vertex is the
+/// angle weighted sum of the tangents of the primitives sharing the same
+/// position, texture coordinates, normal, and handedness, projected into the
+/// plane of the normal. The bitangent is the cross product of the normal and
+/// the tangent, flipped to match the handedness of the texture mapping, so each
+/// vertex gets an orthonormal frame. Vertices without usable texture coordinates
+/// get an arbitrary frame about their normal.
+pub(crate) fn tangent_frames<S>(
+    vertices: &[Vector3<S>],
+    tex_coords: &[Vector2<S>],
+    normals: &[Vector3<S>]) -> (Vec<Vector3<S>>, Vec<Vector3<S>>)
+where
+    S: SimdScalarFloat,
+{
+    debug_assert_eq!(vertices.len() % 3, 0);
+    debug_assert_eq!(vertices.len(), tex_coords.len());
+    debug_assert_eq!(vertices.len(), normals.len());
+    let angles = corner_angles(vertices);
+    let face_tangents = vertices.chunks_exact(3)
+        .zip(tex_coords.chunks_exact(3))
+        .map(|(vertices, tex_coords)| face_tangent(vertices, tex_coords))
+        .collect::<Vec<_>>();
+    let corner_normals = (0..vertices.len())
+        .map(|corner| {
+            let normal = normals[corner];
+            if normal.is_finite() && !normal.is_zero() {
+                normal.normalize()
+            } else {
+                face_normal(&vertices[(3 * (corner / 3))..(3 * (corner / 3) + 3)])
+            }
+        })
+        .collect::<Vec<_>>();
+    // The tangent of each corner in the plane of its normal, and the handedness
+    // of the texture mapping at that corner.
+    let corner_tangents = (0..vertices.len())
+        .map(|corner| match face_tangents[corner / 3] {
+            Some((tangent, bitangent)) => {
+                let normal = corner_normals[corner];
+                let tangent = normalize_or_zero(&(tangent - normal * normal.dot(&tangent)));
+                let sign = if normal.cross(&tangent).dot(&bitangent) < S::zero() { -S::one() } else { S::one() };
+
+                (tangent, sign)
+            }
+            None => (Vector3::zero(), S::one()),
+        })
+        .collect::<Vec<_>>();
+    let keys = (0..vertices.len())
+        .map(|corner| {
+            let vertex = vertices[corner];
+            let tex_coord = tex_coords[corner];
+            let normal = corner_normals[corner];
+            let sign = corner_tangents[corner].1;
+
+            [vertex.x, vertex.y, vertex.z, tex_coord.x, tex_coord.y, normal.x, normal.y, normal.z, sign]
+        })
+        .collect::<Vec<_>>();
+
+    let mut tangents = vec![Vector3::zero(); vertices.len()];
+    let mut bitangents = vec![Vector3::zero(); vertices.len()];
+    for group in weld_groups(&keys).iter() {
+        let normal = corner_normals[group[0]];
+        let sign = corner_tangents[group[0]].1;
+        let mut tangent = Vector3::zero();
+        for &corner in group.iter() {
+            tangent += corner_tangents[corner].0 * angles[corner];
+        }
+        let tangent = match normalize_or_zero(&(tangent - normal * normal.dot(&tangent))) {
+            tangent if tangent.is_zero() => any_tangent(&normal),
+            tangent => tangent,
+        };
+        let bitangent = normal.cross(&tangent) * sign;
+        for &corner in group.iter() {
+            tangents[corner] = tangent;
+            bitangents[corner] = bitangent;
+        }
+    }
+
+    (tangents, bitangents)
+}
+
+/// The unit normal of a primitive, or zero if the primitive is degenerate.
+fn face_normal<S>(vertices: &[Vector3<S>]) -> Vector3<S>
+where
+    S: SimdScalarFloat,
+{
+    // Normalizing the edges first keeps the cross product from overflowing or 
+    // underflowing for very large or very small primitives.
+    let edge1 = normalize_or_zero(&(vertices[1] - vertices[0]));
+    let edge2 = normalize_or_zero(&(vertices[2] - vertices[0]));
+
+    normalize_or_zero(&edge1.cross(&edge2))
+}
+
+/// The directions in which the `u` and `v` texture coordinates increase across
+/// a primitive, or `None` if the texture coordinates do not span the primitive.
+fn face_tangent<S>(vertices: &[Vector3<S>], tex_coords: &[Vector2<S>]) -> Option<(Vector3<S>, Vector3<S>)>
+where
+    S: SimdScalarFloat,
+{
+    let edge1 = vertices[1] - vertices[0];
+    let edge2 = vertices[2] - vertices[0];
+    let delta_uv1 = tex_coords[1] - tex_coords[0];
+    let delta_uv2 = tex_coords[2] - tex_coords[0];
+    let determinant = delta_uv1.x * delta_uv2.y - delta_uv2.x * delta_uv1.y;
+    if determinant == S::zero() || !determinant.is_finite() {
+        return None;
+    }
+    let tangent = (edge1 * delta_uv2.y - edge2 * delta_uv1.y) / determinant;
+    let bitangent = (edge2 * delta_uv1.x - edge1 * delta_uv2.x) / determinant;
+    if !tangent.is_finite() || !bitangent.is_finite() {
+        return None;
+    }
+
+    Some((tangent, bitangent))
+}
+
+/// The interior angle of each primitive at each of its vertices.
+fn corner_angles<S>(vertices: &[Vector3<S>]) -> Vec<S>
+where
+    S: SimdScalarFloat,
+{
+    let mut angles = Vec::with_capacity(vertices.len());
+    for primitive in vertices.chunks_exact(3) {
+        for i in 0..3 {
+            let edge1 = normalize_or_zero(&(primitive[(i + 1) % 3] - primitive[i]));
+            let edge2 = normalize_or_zero(&(primitive[(i + 2) % 3] - primitive[i]));
+            let angle = if edge1.is_zero() || edge2.is_zero() {
+                S::zero()
+            } else {
+                edge1.dot(&edge2).max(-S::one()).min(S::one()).acos()
+            };
+            angles.push(angle);
+        }
+    }
+
+    angles
+}
+
+/// A unit vector perpendicular to `normal`.
+fn any_tangent<S>(normal: &Vector3<S>) -> Vector3<S>
+where
+    S: SimdScalarFloat,
+{
+    let axis = if normal.x.abs() < S::from(0.9).unwrap() {
+        Vector3::unit_x()
+    } else {
+        Vector3::unit_y()
+    };
+
+    normalize_or_zero(&(axis - normal * normal.dot(&axis)))
+}
+
+fn normalize_or_zero<S>(vector: &Vector3<S>) -> Vector3<S>
+where
+    S: SimdScalarFloat,
+{
+    let magnitude = vector.magnitude();
+    if magnitude > S::zero() && magnitude.is_finite() {
+        vector / magnitude
+    } else {
+        Vector3::zero()
+    }
+}
+
+/// Partition the indices of `keys` into groups of exactly equal keys.
+fn weld_groups<S, const N: usize>(keys: &[[S; N]]) -> Vec<Vec<usize>>
+where
+    S: SimdScalarFloat,
+{
+    // NaN compares equal to itself and greater than every number, so that the
+    // order is total and vertices with invalid coordinates do not upset the sort.
+    let compare_scalars = |a: &S, b: &S| match (a.is_nan(), b.is_nan()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Greater,
+        (false, true) => Ordering::Less,
+        (false, false) => a.partial_cmp(b).unwrap(),
+    };
+    let compare_keys = |a: &[S; N], b: &[S; N]| {
+        a.iter()
+            .zip(b.iter())
+            .map(|(a, b)| compare_scalars(a, b))
+            .find(|&ordering| ordering != Ordering::Equal)
+            .unwrap_or(Ordering::Equal)
+    };
+    let mut order = (0..keys.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| compare_keys(&keys[a], &keys[b]));
+
+    let mut groups: Vec<Vec<usize>> = vec![];
+    for &index in order.iter() {
+        match groups.last_mut() {
+            Some(group) if compare_keys(&keys[group[0]], &keys[index]) == Ordering::Equal => {
+                group.push(index);
+            }
+            _ => groups.push(vec![index]),
+        }
+    }
+
+    groups
+}
@@ -0,0 +1,406 @@
+use crate::mesh::*;
+use crate::geometry::*;
+
+use cglinalg::{
+    Vector2,
+    Vector3,
+};
+
+use std::io::{
+    Read,
+};
+use std::str;
+
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum PlyScalarType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64,
+}
+
+impl PlyScalarType {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "char" | "int8" => Some(PlyScalarType::Int8),
+            "uchar" | "uint8" => Some(PlyScalarType::UInt8),
+            "short" | "int16" => Some(PlyScalarType::Int16),
+            "ushort" | "uint16" => Some(PlyScalarType::UInt16),
+            "int" | "int32" => Some(PlyScalarType::Int32),
+            "uint" | "uint32" => Some(PlyScalarType::UInt32),
+            "float" | "float32" => Some(PlyScalarType::Float32),
+            "double" | "float64" => Some(PlyScalarType::Float64),
+            _ => None,
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            PlyScalarType::Int8 | PlyScalarType::UInt8 => 1,
+            PlyScalarType::Int16 | PlyScalarType::UInt16 => 2,
+            PlyScalarType::Int32 | PlyScalarType::UInt32 | PlyScalarType::Float32 => 4,
+            PlyScalarType::Float64 => 8,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum PlyProperty {
+    Scalar { name: String, scalar_type: PlyScalarType },
+    List { name: String, count_type: PlyScalarType, item_type: PlyScalarType },
+}
+
+impl PlyProperty {
+    fn name(&self) -> &str {
+        match self {
+            PlyProperty::Scalar { name, .. } => name,
+            PlyProperty::List { name, .. } => name,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct PlyElement {
+    name: String,
+    count: usize,
+    properties: Vec<PlyProperty>,
+}
+
+#[derive(Clone, Debug)]
+struct PlyHeader {
+    format: PlyFormat,
+    elements: Vec<PlyElement>,
+}
+
+/// The values of one property of one element instance.
+#[derive(Clone, Debug)]
+enum PlyValue {
+    Scalar(f64),
+    List(Vec<f64>),
+}
+
+/// Reads the values of the body of a PLY file in any of its formats.
+enum PlyBodyReader<'a> {
+    Ascii {
+        tokens: str::SplitAsciiWhitespace<'a>,
+    },
+    Binary {
+        data: &'a [u8],
+        offset: usize,
+        big_endian: bool,
+    },
+}
+
+impl<'a> PlyBodyReader<'a> {
+    fn new(format: PlyFormat, body: &'a [u8]) -> MeshResult<Self> {
+        match format {
+            PlyFormat::Ascii => {
+                let text = str::from_utf8(body).map_err(|_| {
+                    decoding_error("ASCII PLY body is not valid text")
+                })?;
+
+                Ok(PlyBodyReader::Ascii { tokens: text.split_ascii_whitespace() })
+            }
+            PlyFormat::BinaryLittleEndian => {
+                Ok(PlyBodyReader::Binary { data: body, offset: 0, big_endian: false })
+            }
+            PlyFormat::BinaryBigEndian => {
+                Ok(PlyBodyReader::Binary { data: body, offset: 0, big_endian: true })
+            }
+        }
+    }
+
+    fn read_scalar(&mut self, scalar_type: PlyScalarType) -> MeshResult<f64> {
+        match self {
+            PlyBodyReader::Ascii { tokens } => {
+                let token = tokens.next().ok_or_else(|| {
+                    decoding_error("unexpected end of PLY body")
+                })?;
+                let value = token.parse::<f64>().map_err(|_| {
+                    decoding_error(&format!("invalid PLY value `{}`", token))
+                })?;
+
+                Ok(value)
+            }
+            PlyBodyReader::Binary { data, offset, big_endian } => {
+                let size = scalar_type.size();
+                let bytes = data.get(*offset..(*offset + size)).ok_or_else(|| {
+                    decoding_error("unexpected end of PLY body")
+                })?;
+                *offset += size;
+                let mut buffer = [0_u8; 8];
+                buffer[..size].copy_from_slice(bytes);
+                if *big_endian {
+                    buffer[..size].reverse();
+                }
+                let value = match scalar_type {
+                    PlyScalarType::Int8 => buffer[0] as i8 as f64,
+                    PlyScalarType::UInt8 => buffer[0] as f64,
+                    PlyScalarType::Int16 => i16::from_le_bytes([buffer[0], buffer[1]]) as f64,
+                    PlyScalarType::UInt16 => u16::from_le_bytes([buffer[0], buffer[1]]) as f64,
+                    PlyScalarType::Int32 => {
+                        i32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as f64
+                    }
+                    PlyScalarType::UInt32 => {
+                        u32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as f64
+                    }
+                    PlyScalarType::Float32 => {
+                        f32::from_le_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as f64
+                    }
+                    PlyScalarType::Float64 => f64::from_le_bytes(buffer),
+                };
+
+                Ok(value)
+            }
+        }
+    }
+
+    fn read_property(&mut self, property: &PlyProperty) -> MeshResult<PlyValue> {
+        match property {
+            PlyProperty::Scalar { scalar_type, .. } => {
+                self.read_scalar(*scalar_type).map(PlyValue::Scalar)
+            }
+            PlyProperty::List { count_type, item_type, .. } => {
+                let count = self.read_scalar(*count_type)?;
+                if count < 0_f64 {
+                    return Err(decoding_error("negative PLY list length"));
+                }
+                let items = (0..(count as usize))
+                    .map(|_| self.read_scalar(*item_type))
+                    .collect::<MeshResult<Vec<_>>>()?;
+
+                Ok(PlyValue::List(items))
+            }
+        }
+    }
+}
+
+
+/// A decoder for Stanford PLY files in the ASCII, binary little endian, and
+/// binary big endian formats.
+///
+/// The positions of the `vertex` element are read from its `x`, `y`, and `z`
+/// properties, along with its normals from `nx`, `ny`, and `nz`, and its texture
+/// coordinates from `u` and `v` (or `s` and `t`) when present. Missing texture
+/// coordinates are set to zero, and missing normals are calculated as smooth
+/// normals with the [`DEFAULT_CREASE_ANGLE`]. The faces of the `face` element
+/// are read from its `vertex_indices` list, and polygon faces are triangulated.
+/// Every other element is skipped.
+pub struct PlyMeshDecoder<R> {
+    reader: R,
+}
+
+impl<R> PlyMeshDecoder<R>
+where
+    R: Read,
+{
+    pub fn new(reader: R) -> Self {
+        Self { reader, }
+    }
+}
+
+impl<'a, R> MeshDecoder<'a> for PlyMeshDecoder<R>
+where
+    R: Read + 'a,
+{
+    type Reader = R;
+
+    fn read_mesh(mut self) -> MeshResult<Mesh<f32>> {
+        let mut buffer = vec![];
+        self.reader.read_to_end(&mut buffer).map_err(|err| {
+            MeshError::IoError(err)
+        })?;
+        let (header, body_offset) = parse_header(&buffer)?;
+        let mut body = PlyBodyReader::new(header.format, &buffer[body_offset..])?;
+        let mut positions = vec![];
+        let mut normals = None;
+        let mut tex_coords = None;
+        let mut faces = vec![];
+        for element in header.elements.iter() {
+            let values = (0..element.count)
+                .map(|_| {
+                    element.properties.iter()
+                        .map(|property| body.read_property(property))
+                        .collect::<MeshResult<Vec<_>>>()
+                })
+                .collect::<MeshResult<Vec<_>>>()?;
+            match element.name.as_str() {
+                "vertex" => {
+                    let position_properties = property_indices(element, &["x", "y", "z"])
+                        .ok_or_else(|| decoding_error("PLY vertex element is missing a position property"))?;
+                    positions = read_vector3s(&values, position_properties);
+                    normals = property_indices(element, &["nx", "ny", "nz"])
+                        .map(|normal_properties| read_vector3s(&values, normal_properties));
+                    tex_coords = property_indices(element, &["u", "v"])
+                        .or_else(|| property_indices(element, &["s", "t"]))
+                        .or_else(|| property_indices(element, &["texture_u", "texture_v"]))
+                        .map(|tex_coord_properties| read_vector2s(&values, tex_coord_properties));
+                }
+                "face" => {
+                    let face_property = element.properties.iter()
+                        .position(|property| {
+                            matches!(property, PlyProperty::List { .. })
+                                && (property.name() == "vertex_indices" || property.name() == "vertex_index")
+                        })
+                        .ok_or_else(|| decoding_error("PLY face element is missing a vertex_indices list"))?;
+                    faces = values.iter()
+                        .map(|instance| match &instance[face_property] {
+                            PlyValue::List(indices) => indices.iter().map(|&index| index as i64).collect::<Vec<_>>(),
+                            PlyValue::Scalar(_) => vec![],
+                        })
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+
+        let mut builder = MeshBuilder::new();
+        for face in faces.iter() {
+            if face.iter().any(|&index| index < 0 || index as usize >= positions.len()) {
+                return Err(decoding_error("PLY face refers to a vertex that does not exist"));
+            }
+            let face = face.iter().map(|&index| index as usize).collect::<Vec<_>>();
+            let face_positions = face.iter().map(|&index| positions[index]).collect::<Vec<_>>();
+            for triangle in triangulate_polygon(&face_positions) {
+                let [i0, i1, i2] = triangle.map(|i| face[i]);
+                let primitive = Triangle::new(positions[i0], positions[i1], positions[i2]);
+                let primitive_tex_coords = match &tex_coords {
+                    Some(tex_coords) => TextureCoordinates::from([tex_coords[i0], tex_coords[i1], tex_coords[i2]]),
+                    None => TextureCoordinates::default(),
+                };
+                let primitive_normals = match &normals {
+                    Some(normals) => Normals::from([normals[i0], normals[i1], normals[i2]]),
+                    None => Normals::default(),
+                };
+                builder = builder.with_primitive(primitive, primitive_tex_coords, primitive_normals);
+            }
+        }
+        let mut mesh = builder.build();
+        mesh.compute_missing_normals(DEFAULT_CREASE_ANGLE);
+
+        Ok(mesh)
+    }
+}
+
+fn decoding_error(message: &str) -> MeshError {
+    MeshError::Decoding(DecodingError::new(String::from(message)))
+}
+
+/// Parse the header of a PLY file, returning the header together with the
+/// offset of the first byte of the body.
+fn parse_header(buffer: &[u8]) -> MeshResult<(PlyHeader, usize)> {
+    let mut offset = 0;
+    let mut format = None;
+    let mut elements: Vec<PlyElement> = vec![];
+    let mut line_number = 0;
+    loop {
+        let line_end = buffer[offset..].iter()
+            .position(|&byte| byte == b'\n')
+            .map(|position| offset + position)
+            .ok_or_else(|| decoding_error("PLY header is missing end_header"))?;
+        let line = str::from_utf8(&buffer[offset..line_end]).map_err(|_| {
+            decoding_error("PLY header is not valid text")
+        })?;
+        offset = line_end + 1;
+        line_number += 1;
+        let mut tokens = line.split_ascii_whitespace();
+        let keyword = match tokens.next() {
+            Some(keyword) => keyword,
+            None => continue,
+        };
+        let arguments = tokens.collect::<Vec<_>>();
+        let invalid_line = || decoding_error(&format!("invalid PLY header line {}: `{}`", line_number, line.trim()));
+        if line_number == 1 {
+            if keyword != "ply" {
+                return Err(decoding_error("not a PLY file"));
+            }
+            continue;
+        }
+        match keyword {
+            "format" => {
+                format = match arguments.first().copied() {
+                    Some("ascii") => Some(PlyFormat::Ascii),
+                    Some("binary_little_endian") => Some(PlyFormat::BinaryLittleEndian),
+                    Some("binary_big_endian") => Some(PlyFormat::BinaryBigEndian),
+                    _ => return Err(invalid_line()),
+                };
+            }
+            "element" => {
+                let (name, count) = match arguments.as_slice() {
+                    [name, count] => (name, count.parse::<usize>().map_err(|_| invalid_line())?),
+                    _ => return Err(invalid_line()),
+                };
+                elements.push(PlyElement { name: String::from(*name), count, properties: vec![], });
+            }
+            "property" => {
+                let element = elements.last_mut().ok_or_else(invalid_line)?;
+                let property = match arguments.as_slice() {
+                    ["list", count_type, item_type, name] => PlyProperty::List {
+                        name: String::from(*name),
+                        count_type: PlyScalarType::parse(count_type).ok_or_else(invalid_line)?,
+                        item_type: PlyScalarType::parse(item_type).ok_or_else(invalid_line)?,
+                    },
+                    [scalar_type, name] => PlyProperty::Scalar {
+                        name: String::from(*name),
+                        scalar_type: PlyScalarType::parse(scalar_type).ok_or_else(invalid_line)?,
+                    },
+                    _ => return Err(invalid_line()),
+                };
+                element.properties.push(property);
+            }
+            "comment" | "obj_info" => {}
+            "end_header" => break,
+            _ => return Err(invalid_line()),
+        }
+    }
+    let format = format.ok_or_else(|| decoding_error("PLY header is missing its format"))?;
+
+    Ok((PlyHeader { format, elements, }, offset))
+}
+
+fn property_indices<const N: usize>(element: &PlyElement, names: &[&str; N]) -> Option<[usize; N]> {
+    let mut indices = [0; N];
+    for (i, name) in names.iter().enumerate() {
+        indices[i] = element.properties.iter().position(|property| {
+            matches!(property, PlyProperty::Scalar { .. }) && property.name() == *name
+        })?;
+    }
+
+    Some(indices)
+}
+
+fn scalar(value: &PlyValue) -> f32 {
+    match value {
+        PlyValue::Scalar(value) => *value as f32,
+        PlyValue::List(_) => 0_f32,
+    }
+}
+
+fn read_vector3s(values: &[Vec<PlyValue>], properties: [usize; 3]) -> Vec<Vector3<f32>> {
+    values.iter()
+        .map(|instance| Vector3::new(
+            scalar(&instance[properties[0]]),
+            scalar(&instance[properties[1]]),
+            scalar(&instance[properties[2]])
+        ))
+        .collect()
+}
+
+fn read_vector2s(values: &[Vec<PlyValue>], properties: [usize; 2]) -> Vec<Vector2<f32>> {
+    values.iter()
+        .map(|instance| Vector2::new(scalar(&instance[properties[0]]), scalar(&instance[properties[1]])))
+        .collect()
+}
+
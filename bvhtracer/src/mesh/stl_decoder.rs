@@ -0,0 +1,190 @@
+use crate::mesh::*;
+use crate::geometry::*;
+
+use cglinalg::{
+    Vector3,
+};
+
+use std::io::{
+    Read,
+};
+use std::str;
+
+
+/// The length of the header of a binary STL file.
+const STL_HEADER_SIZE: usize = 80;
+
+/// The length of one facet of a binary STL file: a normal, three vertices, and
+/// a two byte attribute count.
+const STL_FACET_SIZE: usize = 50;
+
+
+/// A decoder for STL files in the ASCII and binary formats.
+///
+/// The format is detected from the contents of the file. Since many binary STL
+/// files also begin with `solid`, a file is read as binary whenever its length
+/// matches the facet count in its binary header. The facet normal of each
+/// triangle is used as its vertex normals, and the texture coordinates are set
+/// to zero, since STL files do not have any. Many programs write zero facet
+/// normals, which are replaced with smooth normals calculated with the
+/// [`DEFAULT_CREASE_ANGLE`].
+pub struct StlMeshDecoder<R> {
+    reader: R,
+}
+
+impl<R> StlMeshDecoder<R>
+where
+    R: Read,
+{
+    pub fn new(reader: R) -> Self {
+        Self { reader, }
+    }
+}
+
+impl<'a, R> MeshDecoder<'a> for StlMeshDecoder<R>
+where
+    R: Read + 'a,
+{
+    type Reader = R;
+
+    fn read_mesh(mut self) -> MeshResult<Mesh<f32>> {
+        let mut buffer = vec![];
+        self.reader.read_to_end(&mut buffer).map_err(|err| {
+            MeshError::IoError(err)
+        })?;
+        let facets = if is_binary_stl(&buffer) || !buffer.trim_ascii_start().starts_with(b"solid") {
+            read_binary_facets(&buffer)?
+        } else {
+            read_ascii_facets(&buffer)?
+        };
+
+        let mut builder = MeshBuilder::new();
+        for (normal, vertices) in facets.iter() {
+            let primitive = Triangle::new(vertices[0], vertices[1], vertices[2]);
+            let tex_coords = TextureCoordinates::default();
+            let normals = Normals::from([*normal, *normal, *normal]);
+            builder = builder.with_primitive(primitive, tex_coords, normals);
+        }
+        let mut mesh = builder.build();
+        mesh.compute_missing_normals(DEFAULT_CREASE_ANGLE);
+
+        Ok(mesh)
+    }
+}
+
+type StlFacet = (Vector3<f32>, [Vector3<f32>; 3]);
+
+fn decoding_error(message: &str) -> MeshError {
+    MeshError::Decoding(DecodingError::new(String::from(message)))
+}
+
+fn binary_facet_count(buffer: &[u8]) -> Option<usize> {
+    let bytes = buffer.get(STL_HEADER_SIZE..(STL_HEADER_SIZE + 4))?;
+    let count = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+
+    Some(count as usize)
+}
+
+fn is_binary_stl(buffer: &[u8]) -> bool {
+    match binary_facet_count(buffer) {
+        Some(count) => buffer.len() == STL_HEADER_SIZE + 4 + count * STL_FACET_SIZE,
+        None => false,
+    }
+}
+
+fn read_binary_facets(buffer: &[u8]) -> MeshResult<Vec<StlFacet>> {
+    let count = binary_facet_count(buffer).ok_or_else(|| {
+        decoding_error("binary STL file is missing its header")
+    })?;
+    let body = &buffer[(STL_HEADER_SIZE + 4)..];
+    if body.len() < count * STL_FACET_SIZE {
+        return Err(decoding_error("binary STL file has fewer facets than its header states"));
+    }
+    let read_vector = |bytes: &[u8]| {
+        let component = |i: usize| f32::from_le_bytes([bytes[4 * i], bytes[4 * i + 1], bytes[4 * i + 2], bytes[4 * i + 3]]);
+        Vector3::new(component(0), component(1), component(2))
+    };
+    let facets = body.chunks_exact(STL_FACET_SIZE)
+        .take(count)
+        .map(|facet| {
+            let normal = read_vector(&facet[0..12]);
+            let vertices = [read_vector(&facet[12..24]), read_vector(&facet[24..36]), read_vector(&facet[36..48])];
+
+            (normal, vertices)
+        })
+        .collect();
+
+    Ok(facets)
+}
+
+fn read_ascii_facets(buffer: &[u8]) -> MeshResult<Vec<StlFacet>> {
+    let text = str::from_utf8(buffer).map_err(|_| {
+        decoding_error("ASCII STL file is not valid text")
+    })?;
+    let mut tokens = text.split_ascii_whitespace();
+    let mut facets = vec![];
+    let mut in_solid = false;
+    while let Some(token) = tokens.next() {
+        match token {
+            "solid" if !in_solid => {
+                in_solid = true;
+                // Skip the name of the solid, which cannot contain `facet` or `endsolid`.
+                let remainder = tokens.clone().take_while(|&token| token != "facet" && token != "endsolid").count();
+                for _ in 0..remainder {
+                    tokens.next();
+                }
+            }
+            "facet" if in_solid => {
+                expect_token(&mut tokens, "normal")?;
+                let normal = read_ascii_vector(&mut tokens)?;
+                expect_token(&mut tokens, "outer")?;
+                expect_token(&mut tokens, "loop")?;
+                let mut vertices = [Vector3::zero(); 3];
+                for vertex in vertices.iter_mut() {
+                    expect_token(&mut tokens, "vertex")?;
+                    *vertex = read_ascii_vector(&mut tokens)?;
+                }
+                expect_token(&mut tokens, "endloop")?;
+                expect_token(&mut tokens, "endfacet")?;
+                facets.push((normal, vertices));
+            }
+            "endsolid" if in_solid => {
+                in_solid = false;
+                // Skip the name of the solid, up to the start of the next solid.
+                let remainder = tokens.clone().take_while(|&token| token != "solid").count();
+                for _ in 0..remainder {
+                    tokens.next();
+                }
+            }
+            _ => {
+                return Err(decoding_error(&format!("unexpected `{}` in STL file", token)));
+            }
+        }
+    }
+    if in_solid {
+        return Err(decoding_error("ASCII STL file is missing endsolid"));
+    }
+
+    Ok(facets)
+}
+
+fn expect_token(tokens: &mut str::SplitAsciiWhitespace, expected: &str) -> MeshResult<()> {
+    match tokens.next() {
+        Some(token) if token == expected => Ok(()),
+        Some(token) => Err(decoding_error(&format!("expected `{}` in STL file but found `{}`", expected, token))),
+        None => Err(decoding_error(&format!("expected `{}` in STL file but found the end of the file", expected))),
+    }
+}
+
+fn read_ascii_vector(tokens: &mut str::SplitAsciiWhitespace) -> MeshResult<Vector3<f32>> {
+    let mut components = [0_f32; 3];
+    for component in components.iter_mut() {
+        let token = tokens.next().ok_or_else(|| decoding_error("unexpected end of STL file"))?;
+        *component = token.parse::<f32>().map_err(|_| {
+            decoding_error(&format!("invalid STL number `{}`", token))
+        })?;
+    }
+
+    Ok(Vector3::new(components[0], components[1], components[2]))
+}
+
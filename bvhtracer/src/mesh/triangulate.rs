@@ -0,0 +1,117 @@
+use cglinalg::{
+    Vector2,
+    Vector3,
+};
+
+
+/// Split a simple planar polygon into triangles by ear clipping.
+///
+/// The polygon may be convex or concave. Each triangle is a triple of indices
+/// into `vertices`, and has the same winding order as the polygon. Polygons
+/// that are degenerate or not simple are triangulated as well as possible,
+/// falling back to a triangle fan about the first vertex when the polygon
+/// has no area.
+pub fn triangulate_polygon(vertices: &[Vector3<f32>]) -> Vec<[usize; 3]> {
+    let vertex_count = vertices.len();
+    if vertex_count < 3 {
+        return vec![];
+    }
+    if vertex_count == 3 {
+        return vec![[0, 1, 2]];
+    }
+
+    let normal = newell_normal(vertices);
+    let abs_normal = Vector3::new(f32::abs(normal.x), f32::abs(normal.y), f32::abs(normal.z));
+    if !(abs_normal.x > 0_f32 || abs_normal.y > 0_f32 || abs_normal.z > 0_f32) {
+        return triangulate_fan(vertex_count);
+    }
+    // Project the polygon onto the coordinate plane most parallel to it, choosing
+    // the axes so that the projected polygon winds counterclockwise.
+    let dominant_axis = if abs_normal.x >= abs_normal.y && abs_normal.x >= abs_normal.z {
+        0
+    } else if abs_normal.y >= abs_normal.z {
+        1
+    } else {
+        2
+    };
+    let (axis_u, axis_v) = match (dominant_axis, normal[dominant_axis] > 0_f32) {
+        (0, true) => (1, 2),
+        (0, false) => (2, 1),
+        (1, true) => (2, 0),
+        (1, false) => (0, 2),
+        (_, true) => (0, 1),
+        (_, false) => (1, 0),
+    };
+    let points = vertices.iter()
+        .map(|vertex| Vector2::new(vertex[axis_u], vertex[axis_v]))
+        .collect::<Vec<_>>();
+
+    let mut remaining = (0..vertex_count).collect::<Vec<_>>();
+    let mut triangles = Vec::with_capacity(vertex_count - 2);
+    let mut current = 0;
+    let mut attempts = 0;
+    while remaining.len() > 3 {
+        let len = remaining.len();
+        let prev = remaining[(current + len - 1) % len];
+        let this = remaining[current];
+        let next = remaining[(current + 1) % len];
+        // A self intersecting polygon can run out of ears, in which case the
+        // current vertex is clipped anyway so that triangulation terminates.
+        if is_ear(&points, &remaining, prev, this, next) || attempts >= len {
+            triangles.push([prev, this, next]);
+            remaining.remove(current);
+            current %= remaining.len();
+            attempts = 0;
+        } else {
+            current = (current + 1) % len;
+            attempts += 1;
+        }
+    }
+    triangles.push([remaining[0], remaining[1], remaining[2]]);
+
+    triangles
+}
+
+fn triangulate_fan(vertex_count: usize) -> Vec<[usize; 3]> {
+    (1..(vertex_count - 1)).map(|i| [0, i, i + 1]).collect()
+}
+
+/// Compute the unnormalized normal of a polygon by Newell's method, which is
+/// robust for polygons that are concave or not quite planar.
+fn newell_normal(vertices: &[Vector3<f32>]) -> Vector3<f32> {
+    let mut normal = Vector3::zero();
+    for i in 0..vertices.len() {
+        let current = vertices[i];
+        let next = vertices[(i + 1) % vertices.len()];
+        normal.x += (current.y - next.y) * (current.z + next.z);
+        normal.y += (current.z - next.z) * (current.x + next.x);
+        normal.z += (current.x - next.x) * (current.y + next.y);
+    }
+
+    normal
+}
+
+fn cross_2d(origin: &Vector2<f32>, a: &Vector2<f32>, b: &Vector2<f32>) -> f32 {
+    (a.x - origin.x) * (b.y - origin.y) - (a.y - origin.y) * (b.x - origin.x)
+}
+
+fn is_ear(points: &[Vector2<f32>], remaining: &[usize], prev: usize, this: usize, next: usize) -> bool {
+    let a = &points[prev];
+    let b = &points[this];
+    let c = &points[next];
+    if cross_2d(a, b, c) <= 0_f32 {
+        return false;
+    }
+
+    remaining.iter()
+        .filter(|&&index| index != prev && index != this && index != next)
+        .all(|&index| {
+            let p = &points[index];
+            let inside = cross_2d(a, b, p) >= 0_f32
+                && cross_2d(b, c, p) >= 0_f32
+                && cross_2d(c, a, p) >= 0_f32;
+
+            !inside
+        })
+}
+
@@ -0,0 +1,438 @@
+use crate::geometry::*;
+use crate::mesh::*;
+use cglinalg::{
+    Magnitude,
+    Vector3,
+};
+use std::collections::{
+    HashMap,
+    VecDeque,
+};
+use std::fmt;
+
+
+/// The largest ratio of the area of a primitive to the square of its longest
+/// edge at which the primitive is considered degenerate. Such a primitive is
+/// a sliver thinner than a millionth of its length, which ray intersection
+/// tests cannot hit reliably.
+const DEGENERATE_AREA_RATIO: f32 = 1e-6;
+
+/// The distance within which vertices are welded together by default.
+pub const DEFAULT_WELD_TOLERANCE: f32 = 1e-5;
+
+
+/// A problem found by validating a mesh.
+#[derive(Clone, Debug, PartialEq)]
+pub enum MeshIssue {
+    /// A primitive whose area is zero, or so small relative to the length of
+    /// its edges that it cannot be hit reliably.
+    Degenerate { primitive: usize },
+    /// A primitive with a vertex, texture coordinate, or normal that is NaN or
+    /// infinite.
+    NonFinite { primitive: usize },
+    /// An edge shared by more than two primitives.
+    NonManifoldEdge { edge: [Vector3<f32>; 2], primitives: Vec<usize> },
+    /// Two primitives sharing an edge that wind in opposite directions, so that
+    /// one of them faces the wrong way.
+    InconsistentWinding { primitives: [usize; 2] },
+    /// A primitive whose vertex normals point against the normal given by its
+    /// winding order.
+    FlippedNormals { primitive: usize },
+}
+
+impl fmt::Display for MeshIssue {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MeshIssue::Degenerate { primitive } => {
+                write!(formatter, "Primitive {} is degenerate", primitive)
+            }
+            MeshIssue::NonFinite { primitive } => {
+                write!(formatter, "Primitive {} has an attribute that is not finite", primitive)
+            }
+            MeshIssue::NonManifoldEdge { edge, primitives } => {
+                write!(
+                    formatter,
+                    "The edge from {:?} to {:?} is shared by the {} primitives {:?}",
+                    edge[0], edge[1], primitives.len(), primitives
+                )
+            }
+            MeshIssue::InconsistentWinding { primitives } => {
+                write!(formatter, "Primitives {} and {} wind in opposite directions", primitives[0], primitives[1])
+            }
+            MeshIssue::FlippedNormals { primitive } => {
+                write!(formatter, "Primitive {} has normals that point against its winding order", primitive)
+            }
+        }
+    }
+}
+
+
+/// The fixes that [`Mesh::clean`] applies to a mesh.
+///
+/// By default every fix is applied, and vertices are welded together within
+/// the [`DEFAULT_WELD_TOLERANCE`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MeshCleanup {
+    weld_tolerance: Option<f32>,
+    remove_degenerates: bool,
+    unify_winding: bool,
+}
+
+impl MeshCleanup {
+    pub fn new() -> Self {
+        Self {
+            weld_tolerance: Some(DEFAULT_WELD_TOLERANCE),
+            remove_degenerates: true,
+            unify_winding: true,
+        }
+    }
+
+    /// Weld together the vertices that are within `tolerance` of each other.
+    pub fn with_weld_tolerance(mut self, tolerance: f32) -> Self {
+        self.weld_tolerance = Some(tolerance);
+
+        self
+    }
+
+    /// Leave the vertices of the mesh where they are.
+    pub fn without_welding(mut self) -> Self {
+        self.weld_tolerance = None;
+
+        self
+    }
+
+    /// Keep the degenerate and non-finite primitives of the mesh.
+    pub fn without_degenerate_removal(mut self) -> Self {
+        self.remove_degenerates = false;
+
+        self
+    }
+
+    /// Keep the winding order of every primitive of the mesh.
+    pub fn without_winding_unification(mut self) -> Self {
+        self.unify_winding = false;
+
+        self
+    }
+
+    #[inline]
+    pub const fn weld_tolerance(&self) -> Option<f32> {
+        self.weld_tolerance
+    }
+
+    #[inline]
+    pub const fn removes_degenerates(&self) -> bool {
+        self.remove_degenerates
+    }
+
+    #[inline]
+    pub const fn unifies_winding(&self) -> bool {
+        self.unify_winding
+    }
+}
+
+impl Default for MeshCleanup {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+
+impl Mesh<f32> {
+    /// Find the problems with the mesh that upset rendering: degenerate and
+    /// non-finite primitives, edges shared by more than two primitives,
+    /// neighboring primitives that wind in opposite directions, and primitives
+    /// whose normals disagree with their winding order.
+    ///
+    /// Primitives are connected through vertices with exactly the same position,
+    /// so a mesh should be welded first if its vertices have drifted apart.
+    /// The issues of each primitive are listed in primitive order, followed by
+    /// the issues of the edges.
+    pub fn validate(&self) -> Vec<MeshIssue> {
+        let mut issues = vec![];
+        for primitive_index in 0..self.len_primitives() {
+            if !is_finite_primitive(self, primitive_index) {
+                issues.push(MeshIssue::NonFinite { primitive: primitive_index });
+            } else if is_degenerate_primitive(&self.primitives()[primitive_index]) {
+                issues.push(MeshIssue::Degenerate { primitive: primitive_index });
+            } else if has_flipped_normals(self, primitive_index) {
+                issues.push(MeshIssue::FlippedNormals { primitive: primitive_index });
+            }
+        }
+
+        let mut edges = edge_map(self).into_iter().collect::<Vec<_>>();
+        edges.sort_by_key(|(_, sides)| sides.iter().map(|side| side.primitive).min());
+        for (_, sides) in edges.iter() {
+            match sides.as_slice() {
+                [side1, side2] if side1.forward == side2.forward => {
+                    issues.push(MeshIssue::InconsistentWinding {
+                        primitives: [side1.primitive, side2.primitive],
+                    });
+                }
+                [side, _, _, ..] => {
+                    let primitive = &self.primitives()[side.primitive];
+                    let edge = [primitive.vertices[side.start], primitive.vertices[(side.start + 1) % 3]];
+                    issues.push(MeshIssue::NonManifoldEdge {
+                        edge,
+                        primitives: sides.iter().map(|side| side.primitive).collect(),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        issues
+    }
+
+    /// Move the vertices that are within `tolerance` of each other onto the
+    /// same position, so that the primitives sharing them are connected.
+    /// Returns the number of vertices that moved.
+    ///
+    /// Each vertex moves onto a vertex earlier in the mesh that is within 
+    /// `tolerance` of it, if there is one. Vertices that are not finite are left 
+    /// in place.
+    pub fn weld_vertices(&mut self, tolerance: f32) -> usize {
+        let cell_size = if tolerance > 0_f32 { tolerance } else { 1_f32 };
+        let cell = |vertex: &Vector3<f32>| {
+            [
+                (vertex.x / cell_size).floor() as i64,
+                (vertex.y / cell_size).floor() as i64,
+                (vertex.z / cell_size).floor() as i64,
+            ]
+        };
+        let mut representatives: HashMap<[i64; 3], Vec<Vector3<f32>>> = HashMap::new();
+        let mut moved = 0;
+        for primitive in self.primitives_mut().iter_mut() {
+            for vertex in primitive.vertices.iter_mut() {
+                if !vertex.is_finite() {
+                    continue;
+                }
+                let [x, y, z] = cell(vertex);
+                let mut nearest = None;
+                'search: for dx in -1..=1 {
+                    for dy in -1..=1 {
+                        for dz in -1..=1 {
+                            let candidates = match representatives.get(&[x + dx, y + dy, z + dz]) {
+                                Some(candidates) => candidates,
+                                None => continue,
+                            };
+                            let found = candidates.iter().find(|candidate| {
+                                (*candidate - *vertex).magnitude() <= tolerance
+                            });
+                            if let Some(found) = found {
+                                nearest = Some(*found);
+                                break 'search;
+                            }
+                        }
+                    }
+                }
+                match nearest {
+                    Some(nearest) => {
+                        if nearest != *vertex {
+                            *vertex = nearest;
+                            moved += 1;
+                        }
+                    }
+                    None => {
+                        representatives.entry([x, y, z]).or_default().push(*vertex);
+                    }
+                }
+            }
+        }
+
+        moved
+    }
+
+    /// Remove the degenerate primitives of the mesh, along with the primitives
+    /// that have a vertex, texture coordinate, or normal that is not finite.
+    /// Returns the number of primitives removed.
+    pub fn remove_degenerate_primitives(&mut self) -> usize {
+        let keep = (0..self.len_primitives())
+            .map(|primitive_index| {
+                is_finite_primitive(self, primitive_index)
+                    && !is_degenerate_primitive(&self.primitives()[primitive_index])
+            })
+            .collect::<Vec<_>>();
+        let removed = keep.iter().filter(|&&keep| !keep).count();
+        if removed > 0 {
+            self.retain_primitives(&keep);
+        }
+
+        removed
+    }
+
+    /// Reverse the winding order of primitives so that neighboring primitives
+    /// wind in the same direction. Returns the number of primitives flipped.
+    ///
+    /// Each connected piece of the mesh is oriented to agree with the majority
+    /// of its vertex normals. When the normals are missing or undecided, a
+    /// closed piece is oriented so that its primitives face outward, and an
+    /// open piece keeps the orientation of its first primitive. Primitives
+    /// are only connected through edges shared by exactly two primitives.
+    pub fn unify_winding(&mut self) -> usize {
+        let primitive_count = self.len_primitives();
+        let mut neighbors = vec![vec![]; primitive_count];
+        let mut is_open = vec![false; primitive_count];
+        for (_, sides) in edge_map(self).iter() {
+            match sides.as_slice() {
+                [side1, side2] => {
+                    let is_consistent = side1.forward != side2.forward;
+                    neighbors[side1.primitive].push((side2.primitive, is_consistent));
+                    neighbors[side2.primitive].push((side1.primitive, is_consistent));
+                }
+                _ => {
+                    for side in sides.iter() {
+                        is_open[side.primitive] = true;
+                    }
+                }
+            }
+        }
+
+        let mut flip = vec![false; primitive_count];
+        let mut visited = vec![false; primitive_count];
+        let mut flipped = 0;
+        for seed in 0..primitive_count {
+            if visited[seed] {
+                continue;
+            }
+            // Orient the connected piece of the mesh containing the seed
+            // primitive relative to the seed.
+            let mut component = vec![];
+            let mut queue = VecDeque::from([seed]);
+            visited[seed] = true;
+            while let Some(primitive_index) = queue.pop_front() {
+                component.push(primitive_index);
+                for &(neighbor, is_consistent) in neighbors[primitive_index].iter() {
+                    if !visited[neighbor] {
+                        visited[neighbor] = true;
+                        flip[neighbor] = if is_consistent { flip[primitive_index] } else { !flip[primitive_index] };
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+
+            // Then orient the piece as a whole.
+            let mut normal_votes = 0_i64;
+            let mut signed_volume = 0_f32;
+            for &primitive_index in component.iter() {
+                let primitive = &self.primitives()[primitive_index];
+                let sign = if flip[primitive_index] { -1_f32 } else { 1_f32 };
+                let normal = face_normal(primitive) * sign;
+                let vertex_normals = &self.normals()[primitive_index];
+                let vertex_normal = vertex_normals[0] + vertex_normals[1] + vertex_normals[2];
+                let agreement = normal.dot(&vertex_normal);
+                if agreement > 0_f32 {
+                    normal_votes += 1;
+                } else if agreement < 0_f32 {
+                    normal_votes -= 1;
+                }
+                let [vertex0, vertex1, vertex2] = primitive.vertices;
+                signed_volume += sign * vertex0.dot(&vertex1.cross(&vertex2));
+            }
+            let is_closed = component.iter().all(|&primitive_index| !is_open[primitive_index]);
+            let flip_component = if normal_votes != 0 {
+                normal_votes < 0
+            } else {
+                is_closed && signed_volume < 0_f32
+            };
+            for &primitive_index in component.iter() {
+                if flip[primitive_index] != flip_component {
+                    self.flip_primitive(primitive_index);
+                    flipped += 1;
+                }
+            }
+        }
+
+        flipped
+    }
+
+    /// Apply the fixes of `cleanup` to the mesh: remove the degenerate and
+    /// non-finite primitives, weld the vertices, remove the primitives that
+    /// welding collapsed, and unify the winding order.
+    pub fn clean(&mut self, cleanup: &MeshCleanup) {
+        if cleanup.remove_degenerates {
+            self.remove_degenerate_primitives();
+        }
+        if let Some(tolerance) = cleanup.weld_tolerance {
+            if self.weld_vertices(tolerance) > 0 && cleanup.remove_degenerates {
+                self.remove_degenerate_primitives();
+            }
+        }
+        if cleanup.unify_winding {
+            self.unify_winding();
+        }
+    }
+}
+
+
+/// One side of an edge: the primitive it belongs to, the index of the first
+/// vertex of the edge in the primitive, and whether the primitive traverses
+/// the edge from its smaller endpoint to its larger one.
+#[derive(Copy, Clone, Debug)]
+struct EdgeSide {
+    primitive: usize,
+    start: usize,
+    forward: bool,
+}
+
+type PositionKey = [u32; 3];
+
+fn position_key(vertex: &Vector3<f32>) -> PositionKey {
+    // Adding zero turns negative zero into positive zero.
+    [(vertex.x + 0_f32).to_bits(), (vertex.y + 0_f32).to_bits(), (vertex.z + 0_f32).to_bits()]
+}
+
+/// Collect the sides of every edge of the finite, non-degenerate primitives of
+/// a mesh, where edges are identified by the exact positions of their endpoints.
+fn edge_map(mesh: &Mesh<f32>) -> HashMap<(PositionKey, PositionKey), Vec<EdgeSide>> {
+    let mut edges: HashMap<_, Vec<EdgeSide>> = HashMap::new();
+    for (primitive_index, primitive) in mesh.primitives().iter().enumerate() {
+        if !is_finite_primitive(mesh, primitive_index) || is_degenerate_primitive(primitive) {
+            continue;
+        }
+        for start in 0..3 {
+            let key0 = position_key(&primitive.vertices[start]);
+            let key1 = position_key(&primitive.vertices[(start + 1) % 3]);
+            let (key, forward) = if key0 < key1 { ((key0, key1), true) } else { ((key1, key0), false) };
+            edges.entry(key).or_default().push(EdgeSide { primitive: primitive_index, start, forward, });
+        }
+    }
+
+    edges
+}
+
+fn face_normal(primitive: &Triangle<f32>) -> Vector3<f32> {
+    let edge1 = primitive.vertices[1] - primitive.vertices[0];
+    let edge2 = primitive.vertices[2] - primitive.vertices[0];
+
+    edge1.cross(&edge2)
+}
+
+fn is_finite_primitive(mesh: &Mesh<f32>, primitive_index: usize) -> bool {
+    let primitive = &mesh.primitives()[primitive_index];
+    let tex_coords = &mesh.tex_coords()[primitive_index];
+    let normals = &mesh.normals()[primitive_index];
+
+    (0..3).all(|i| {
+        primitive.vertices[i].is_finite() && tex_coords[i].is_finite() && normals[i].is_finite()
+    })
+}
+
+fn is_degenerate_primitive(primitive: &Triangle<f32>) -> bool {
+    let [vertex0, vertex1, vertex2] = primitive.vertices;
+    let longest_edge_squared = (vertex1 - vertex0).magnitude_squared()
+        .max((vertex2 - vertex1).magnitude_squared())
+        .max((vertex0 - vertex2).magnitude_squared());
+    let area = face_normal(primitive).magnitude() / 2_f32;
+
+    area <= DEGENERATE_AREA_RATIO * longest_edge_squared
+}
+
+/// Determine whether the vertex normals of a primitive, taken together, point
+/// against its winding order. Primitives without normals have none to flip.
+fn has_flipped_normals(mesh: &Mesh<f32>, primitive_index: usize) -> bool {
+    let normals = &mesh.normals()[primitive_index];
+    let vertex_normal = normals[0] + normals[1] + normals[2];
+
+    face_normal(&mesh.primitives()[primitive_index]).dot(&vertex_normal) < 0_f32
+}
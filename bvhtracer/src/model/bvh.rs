@@ -1,5 +1,6 @@
 use crate::query::*;
 use crate::geometry::*;
+use crate::mesh::*;
 use cglinalg::{
     Vector3,
 };
@@ -50,18 +51,207 @@ impl Default for Bin {
     }
 }
 
-#[derive(Clone, Debug)]
-struct PrimitiveIter<'a> {
+/// The triangles of a mesh, which are intersected with the triangle intersection 
+/// test of the boundary volume hierarchy.
+trait TriangleSource {
+    fn len(&self) -> usize;
+
+    fn triangle(&self, index: u32) -> Triangle<f32>;
+}
+
+/// Triangles that can be reordered in place while building a boundary volume 
+/// hierarchy.
+trait TriangleSourceMut: TriangleSource {
+    fn swap(&mut self, index1: u32, index2: u32);
+}
+
+/// The triangles of a mesh.
+struct MeshTriangles<'a> {
     primitives: &'a [Triangle<f32>],
+}
+
+impl<'a> TriangleSource for MeshTriangles<'a> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.primitives.len()
+    }
+
+    #[inline]
+    fn triangle(&self, index: u32) -> Triangle<f32> {
+        self.primitives[index as usize]
+    }
+}
+
+/// The triangles of an indexed mesh, assembled from a vertex buffer and an 
+/// index buffer.
+struct IndexedTriangles<'a> {
+    vertices: &'a [Vector3<f32>],
+    indices: &'a [[u32; 3]],
+}
+
+impl<'a> TriangleSource for IndexedTriangles<'a> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    #[inline]
+    fn triangle(&self, index: u32) -> Triangle<f32> {
+        let [index0, index1, index2] = self.indices[index as usize];
+
+        Triangle::new(
+            self.vertices[index0 as usize], 
+            self.vertices[index1 as usize], 
+            self.vertices[index2 as usize]
+        )
+    }
+}
+
+struct IndexedTrianglesMut<'a> {
+    vertices: &'a [Vector3<f32>],
+    indices: &'a mut [[u32; 3]],
+}
+
+impl<'a> TriangleSource for IndexedTrianglesMut<'a> {
+    #[inline]
+    fn len(&self) -> usize {
+        self.indices.len()
+    }
+
+    #[inline]
+    fn triangle(&self, index: u32) -> Triangle<f32> {
+        let [index0, index1, index2] = self.indices[index as usize];
+
+        Triangle::new(
+            self.vertices[index0 as usize], 
+            self.vertices[index1 as usize], 
+            self.vertices[index2 as usize]
+        )
+    }
+}
+
+impl<'a> TriangleSourceMut for IndexedTrianglesMut<'a> {
+    #[inline]
+    fn swap(&mut self, index1: u32, index2: u32) {
+        self.indices.swap(index1 as usize, index2 as usize);
+    }
+}
+
+/// The primitives that a boundary volume hierarchy is built over, either a 
+/// slice of [`BoundedPrimitive`]s or the triangles of a mesh. Only the triangles 
+/// of a mesh use the triangle intersection test `intersection`.
+trait PrimitiveSource {
+    fn len(&self) -> usize;
+
+    fn bounds(&self, index: u32) -> Aabb<f32>;
+
+    fn centroid(&self, index: u32) -> Vector3<f32>;
+
+    fn intersect(&self, index: u32, ray: &Ray<f32>, intersection: TriangleIntersection) -> Option<SurfaceInteraction<f32>>;
+
+    fn occluded(&self, index: u32, ray: &Ray<f32>, intersection: TriangleIntersection) -> bool;
+}
+
+/// Primitives that can be reordered in place while building a boundary volume 
+/// hierarchy.
+trait PrimitiveSourceMut: PrimitiveSource {
+    fn swap(&mut self, index1: u32, index2: u32);
+}
+
+impl<M> PrimitiveSource for M 
+where
+    M: TriangleSource + ?Sized,
+{
+    #[inline]
+    fn len(&self) -> usize {
+        TriangleSource::len(self)
+    }
+
+    #[inline]
+    fn bounds(&self, index: u32) -> Aabb<f32> {
+        let triangle = self.triangle(index);
+        let bounds_min = __min(&__min(&triangle.vertices[0], &triangle.vertices[1]), &triangle.vertices[2]);
+        let bounds_max = __max(&__max(&triangle.vertices[0], &triangle.vertices[1]), &triangle.vertices[2]);
+
+        Aabb::new(bounds_min, bounds_max)
+    }
+
+    #[inline]
+    fn centroid(&self, index: u32) -> Vector3<f32> {
+        self.triangle(index).centroid()
+    }
+
+    #[inline]
+    fn intersect(&self, index: u32, ray: &Ray<f32>, intersection: TriangleIntersection) -> Option<SurfaceInteraction<f32>> {
+        self.triangle(index).intersect_with(ray, intersection)
+    }
+
+    #[inline]
+    fn occluded(&self, index: u32, ray: &Ray<f32>, intersection: TriangleIntersection) -> bool {
+        self.triangle(index).occluded_with(ray, intersection)
+    }
+}
+
+impl<M> PrimitiveSourceMut for M 
+where
+    M: TriangleSourceMut + ?Sized,
+{
+    #[inline]
+    fn swap(&mut self, index1: u32, index2: u32) {
+        TriangleSourceMut::swap(self, index1, index2);
+    }
+}
+
+impl<P> PrimitiveSource for [P] 
+where
+    P: BoundedPrimitive<f32>,
+{
+    #[inline]
+    fn len(&self) -> usize {
+        <[P]>::len(self)
+    }
+
+    #[inline]
+    fn bounds(&self, index: u32) -> Aabb<f32> {
+        self[index as usize].bounds()
+    }
+
+    #[inline]
+    fn centroid(&self, index: u32) -> Vector3<f32> {
+        self[index as usize].centroid()
+    }
+
+    #[inline]
+    fn intersect(&self, index: u32, ray: &Ray<f32>, _intersection: TriangleIntersection) -> Option<SurfaceInteraction<f32>> {
+        self[index as usize].intersect(ray)
+    }
+
+    #[inline]
+    fn occluded(&self, index: u32, ray: &Ray<f32>, _intersection: TriangleIntersection) -> bool {
+        self[index as usize].occluded(ray)
+    }
+}
+
+impl<P> PrimitiveSourceMut for [P] 
+where
+    P: BoundedPrimitive<f32>,
+{
+    #[inline]
+    fn swap(&mut self, index1: u32, index2: u32) {
+        <[P]>::swap(self, index1 as usize, index2 as usize);
+    }
+}
+
+/// An iterator over the indices of the primitives in a leaf node.
+struct PrimitiveIter {
     primitive_count: u32,
     base_primitive_index: u32,
     current_offset: u32,
 }
 
-impl<'a> PrimitiveIter<'a> {
-    fn new(primitives: &'a [Triangle<f32>], primitive_count: u32, base_primitive_index: u32) -> Self {
+impl PrimitiveIter {
+    fn new(primitive_count: u32, base_primitive_index: u32) -> Self {
         Self {
-            primitives,
             primitive_count,
             base_primitive_index,
             current_offset: 0,
@@ -69,16 +259,15 @@ impl<'a> PrimitiveIter<'a> {
     }
 }
 
-impl<'a> Iterator for PrimitiveIter<'a> {
-    type Item = (u32, &'a Triangle<f32>);
+impl Iterator for PrimitiveIter {
+    type Item = u32;
 
     fn next(&mut self) -> Option<Self::Item> {
         if self.current_offset < self.primitive_count {
             let current_primitive_index = self.base_primitive_index + self.current_offset;
-            let current_object = &self.primitives[current_primitive_index as usize];
             self.current_offset += 1;
             
-            return Some((current_primitive_index, current_object));
+            return Some(current_primitive_index);
         }
 
         None
@@ -230,25 +419,34 @@ pub struct Bvh {
     node_indices: Vec<u32>,
     root_node_index: u32,
     nodes_used: u32,
+    triangle_intersection: Option<TriangleIntersection>,
 }
 
 impl Bvh {
-    fn primitive_iter<'a>(&self, mesh: &'a [Triangle<f32>], node: &BvhNode) -> PrimitiveIter<'a> {
+    fn primitive_iter(&self, node: &BvhNode) -> PrimitiveIter {
         let base_primitive_index = self.node_indices[node.as_leaf().first_primitive_index as usize];
         
-        PrimitiveIter::new(mesh, node.primitive_count, base_primitive_index)
+        PrimitiveIter::new(node.primitive_count, base_primitive_index)
     }
 
-    fn intersect_subtree(&self, mesh: &[Triangle<f32>], ray: &Ray<f32>, node_index: u32) -> Option<Intersection<f32>> {
+    fn intersect_subtree<M>(&self, mesh: &M, ray: &Ray<f32>, node_index: u32) -> Option<Intersection<f32>> 
+    where
+        M: PrimitiveSource + ?Sized,
+    {
+        if self.node_indices.is_empty() {
+            return None;
+        }
+
         let mut current_node = &self.nodes[node_index];
         let mut stack = vec![];
         let mut closest_ray = *ray;
         let mut closest_interaction = None;
         let mut closest_primitive_index = 0;
+        let triangle_intersection = self.triangle_intersection();
         loop {
             if current_node.is_leaf() {
-                for (primitive_index, primitive) in self.primitive_iter(mesh, current_node) {
-                    if let Some(interaction) = primitive.intersect(&ray) {
+                for primitive_index in self.primitive_iter(current_node) {
+                    if let Some(interaction) = mesh.intersect(primitive_index, ray, triangle_intersection) {
                         if interaction.t < closest_ray.t {
                             closest_ray.t = interaction.t;
                             closest_interaction = Some(interaction);
@@ -304,8 +502,111 @@ impl Bvh {
         }
     }
 
-    pub fn intersect(&self, mesh: &[Triangle<f32>], ray: &Ray<f32>) -> Option<Intersection<f32>> {
-        self.intersect_subtree(mesh, ray, self.root_node_index)
+    /// Find the closest intersection of a ray with the primitives that the boundary 
+    /// volume hierarchy was built over, between distances `ray.t_min` and `ray.t` 
+    /// along the ray.
+    pub fn intersect<P>(&self, primitives: &[P], ray: &Ray<f32>) -> Option<Intersection<f32>> 
+    where
+        P: BoundedPrimitive<f32>,
+    {
+        self.intersect_subtree(primitives, ray, self.root_node_index)
+    }
+
+    /// Find the closest intersection of a ray with a mesh whose boundary volume 
+    /// hierarchy was built with [`BvhBuilder::build_for_mesh`], using the triangle 
+    /// intersection test of the hierarchy.
+    pub fn intersect_mesh(&self, mesh: &Mesh<f32>, ray: &Ray<f32>) -> Option<Intersection<f32>> {
+        let triangles = MeshTriangles { primitives: mesh.primitives(), };
+
+        self.intersect_subtree(&triangles, ray, self.root_node_index)
+    }
+
+    /// Find the closest intersection of a ray with an indexed mesh whose boundary 
+    /// volume hierarchy was built with [`BvhBuilder::build_for_indexed_mesh`]. The 
+    /// primitive index of the intersection indexes the triangles of the mesh.
+    pub fn intersect_indexed(&self, mesh: &IndexedMesh<f32>, ray: &Ray<f32>) -> Option<Intersection<f32>> {
+        let triangles = IndexedTriangles { vertices: mesh.vertices(), indices: mesh.triangle_indices(), };
+
+        self.intersect_subtree(&triangles, ray, self.root_node_index)
+    }
+
+    fn occluded_subtree<M>(&self, mesh: &M, ray: &Ray<f32>, node_index: u32) -> bool 
+    where
+        M: PrimitiveSource + ?Sized,
+    {
+        if self.node_indices.is_empty() {
+            return false;
+        }
+
+        let mut current_node = &self.nodes[node_index];
+        let mut stack = vec![];
+        let triangle_intersection = self.triangle_intersection();
+        loop {
+            if current_node.is_leaf() {
+                for primitive_index in self.primitive_iter(current_node) {
+                    if mesh.occluded(primitive_index, ray, triangle_intersection) {
+                        return true;
+                    }
+                }
+            } else {
+                // Any hit terminates the traversal, so the children do not need
+                // to be visited in front to back order.
+                let left_child = &self.nodes[current_node.as_branch().left_node()];
+                let right_child = &self.nodes[current_node.as_branch().right_node()];
+                if left_child.aabb.intersect(ray).is_some() {
+                    stack.push(left_child);
+                }
+                if right_child.aabb.intersect(ray).is_some() {
+                    stack.push(right_child);
+                }
+            }
+
+            if let Some(next_node) = stack.pop() {
+                current_node = next_node;
+            } else {
+                return false;
+            }
+        }
+    }
+
+    /// Determine whether the ray hits any of the primitives between distances 
+    /// `ray.t_min` and `ray.t` along the ray. 
+    /// 
+    /// This is cheaper than [`Bvh::intersect`] because the traversal stops at the 
+    /// first hit it finds instead of searching for the closest one, which is all
+    /// that shadow rays and ambient occlusion rays need.
+    pub fn occluded<P>(&self, primitives: &[P], ray: &Ray<f32>) -> bool 
+    where
+        P: BoundedPrimitive<f32>,
+    {
+        self.occluded_subtree(primitives, ray, self.root_node_index)
+    }
+
+    /// Determine whether the ray hits any triangle of a mesh between distances 
+    /// `ray.t_min` and `ray.t` along the ray, using the triangle intersection test 
+    /// of the hierarchy.
+    pub fn occluded_mesh(&self, mesh: &Mesh<f32>, ray: &Ray<f32>) -> bool {
+        let triangles = MeshTriangles { primitives: mesh.primitives(), };
+
+        self.occluded_subtree(&triangles, ray, self.root_node_index)
+    }
+
+    /// Determine whether the ray hits any triangle of an indexed mesh between 
+    /// distances `ray.t_min` and `ray.t` along the ray.
+    pub fn occluded_indexed(&self, mesh: &IndexedMesh<f32>, ray: &Ray<f32>) -> bool {
+        let triangles = IndexedTriangles { vertices: mesh.vertices(), indices: mesh.triangle_indices(), };
+
+        self.occluded_subtree(&triangles, ray, self.root_node_index)
+    }
+
+    /// The test used to intersect rays with the triangles of a mesh in 
+    /// [`Bvh::intersect_mesh`], [`Bvh::intersect_indexed`], and their occlusion 
+    /// counterparts. This is the global default triangle intersection test unless 
+    /// the hierarchy was built with [`BvhBuilder::with_triangle_intersection`]. 
+    /// [`Bvh::intersect`] leaves the choice to the primitives.
+    #[inline]
+    pub fn triangle_intersection(&self) -> TriangleIntersection {
+        self.triangle_intersection.unwrap_or_else(TriangleIntersection::global_default)
     }
 
     /// Returns the number of nodes in the boundary volume hierarchy.
@@ -314,15 +615,15 @@ impl Bvh {
         self.nodes_used as usize
     }
 
-    fn update_node_bounds(&mut self, mesh: &[Triangle<f32>], node_index: u32) {
+    fn update_node_bounds<M>(&mut self, mesh: &M, node_index: u32) 
+    where
+        M: PrimitiveSource + ?Sized,
+    {
         let mut new_aabb = Aabb::new(Vector3::from_fill(f32::MAX), Vector3::from_fill(-f32::MAX));
-        for (_, primitive) in self.primitive_iter(mesh, &self.nodes[node_index]) {
-            new_aabb.bounds_min = __min(&new_aabb.bounds_min, &primitive.vertices[0]);
-            new_aabb.bounds_min = __min(&new_aabb.bounds_min, &primitive.vertices[1]);
-            new_aabb.bounds_min = __min(&new_aabb.bounds_min, &primitive.vertices[2]);
-            new_aabb.bounds_max = __max(&new_aabb.bounds_max, &primitive.vertices[0]);
-            new_aabb.bounds_max = __max(&new_aabb.bounds_max, &primitive.vertices[1]);
-            new_aabb.bounds_max = __max(&new_aabb.bounds_max, &primitive.vertices[2]);
+        for primitive_index in self.primitive_iter(&self.nodes[node_index]) {
+            let primitive_aabb = mesh.bounds(primitive_index);
+            new_aabb.bounds_min = __min(&new_aabb.bounds_min, &primitive_aabb.bounds_min);
+            new_aabb.bounds_max = __max(&new_aabb.bounds_max, &primitive_aabb.bounds_max);
         }
 
         let node = &mut self.nodes[node_index];
@@ -330,7 +631,10 @@ impl Bvh {
     }
 
     // TODO: Optimize by finding the longest axis first?
-    fn find_best_split_plane(&self, mesh: &[Triangle<f32>], node: &BvhNode) -> (isize, f32, f32) {
+    fn find_best_split_plane<M>(&self, mesh: &M, node: &BvhNode) -> (isize, f32, f32) 
+    where
+        M: PrimitiveSource + ?Sized,
+    {
         const BIN_COUNT: usize = 8;
         let mut best_axis = -1;
         let mut best_position = 0_f32;
@@ -338,9 +642,10 @@ impl Bvh {
         for axis in 0..3 {
             let mut bounds_min = 1e30;
             let mut bounds_max = 1e-30;
-            for (_, primitive) in self.primitive_iter(mesh, node) {
-                bounds_min = f32::min(bounds_min, primitive.centroid()[axis]);
-                bounds_max = f32::max(bounds_max, primitive.centroid()[axis]);
+            for primitive_index in self.primitive_iter(node) {
+                let centroid = mesh.centroid(primitive_index);
+                bounds_min = f32::min(bounds_min, centroid[axis]);
+                bounds_max = f32::max(bounds_max, centroid[axis]);
             }
             if bounds_min == bounds_max {
                 continue;
@@ -348,13 +653,13 @@ impl Bvh {
 
             let mut bins = [Bin::default(); BIN_COUNT];
             let bin_scale = (BIN_COUNT as f32) / (bounds_max - bounds_min);
-            for (_, primitive) in self.primitive_iter(mesh, node) {
-                let possible_bin_index = ((primitive.centroid()[axis] - bounds_min) * bin_scale) as usize;
+            for primitive_index in self.primitive_iter(node) {
+                let possible_bin_index = ((mesh.centroid(primitive_index)[axis] - bounds_min) * bin_scale) as usize;
                 let bin_index = usize::min(BIN_COUNT - 1, possible_bin_index);
+                let primitive_aabb = mesh.bounds(primitive_index);
                 bins[bin_index].primitive_count += 1;
-                bins[bin_index].bounding_box.grow(&primitive.vertices[0]);
-                bins[bin_index].bounding_box.grow(&primitive.vertices[1]);
-                bins[bin_index].bounding_box.grow(&primitive.vertices[2]);
+                bins[bin_index].bounding_box.grow(&primitive_aabb.bounds_min);
+                bins[bin_index].bounding_box.grow(&primitive_aabb.bounds_max);
             }
 
             // Assemble the data for calculating the `BIN_COUNT - 1` planes between the `BIN_COUNT` bins.
@@ -393,7 +698,10 @@ impl Bvh {
         (best_axis, best_position, best_cost)
     }
 
-    fn subdivide(&mut self, mesh: &mut [Triangle<f32>], node_index: u32) {
+    fn subdivide<M>(&mut self, mesh: &mut M, order: &mut [u32], node_index: u32) 
+    where
+        M: PrimitiveSourceMut + ?Sized,
+    {
         #[inline]
         fn calculate_node_cost(node: &BvhNode) -> f32 {
             let parent_area = node.aabb.area();
@@ -420,10 +728,11 @@ impl Bvh {
             let mut i = node.as_leaf().first_primitive_index;
             let mut j = i + node.primitive_count - 1;
             while i <= j {
-                if mesh[i as usize].centroid()[axis] < split_position {
+                if mesh.centroid(i)[axis] < split_position {
                     i += 1;
                 } else {
-                    mesh.swap(i as usize, j as usize);
+                    mesh.swap(i, j);
+                    order.swap(i as usize, j as usize);
                     j -= 1;
                 }
             }
@@ -462,11 +771,29 @@ impl Bvh {
         self.update_node_bounds(mesh, left_child_index);
         self.update_node_bounds(mesh, right_child_index);
         // Recurse
-        self.subdivide(mesh, left_child_index);
-        self.subdivide(mesh, right_child_index);
+        self.subdivide(mesh, order, left_child_index);
+        self.subdivide(mesh, order, right_child_index);
+    }
+
+    /// Refit the boundary volume hierarchy after its primitives have moved or 
+    /// changed shape, without changing the structure of the tree.
+    pub fn refit<P>(&mut self, primitives: &[P]) 
+    where
+        P: BoundedPrimitive<f32>,
+    {
+        self.refit_primitives(primitives);
+    }
+
+    /// Refit the boundary volume hierarchy of an indexed mesh after its vertices 
+    /// have moved.
+    pub fn refit_indexed(&mut self, mesh: &IndexedMesh<f32>) {
+        self.refit_primitives(&IndexedTriangles { vertices: mesh.vertices(), indices: mesh.triangle_indices(), });
     }
 
-    pub fn refit(&mut self, mesh: &[Triangle<f32>]) {
+    fn refit_primitives<M>(&mut self, mesh: &M) 
+    where
+        M: PrimitiveSource + ?Sized,
+    {
         for node_index in (0..self.nodes_used).rev().filter(|i| *i != 1) {
             {
                 let node = &self.nodes[node_index];
@@ -515,14 +842,116 @@ impl BvhBuilder {
         // has two child nodes, and we want them to align nicely in the cache. In order
         // to do that, we insert a dummy node at index 1, using index 0 for the root.
         let nodes_used = 2;
+        let triangle_intersection = None;
 
-        let partial_bvh = Bvh { nodes, node_indices, root_node_index, nodes_used, };
+        let partial_bvh = Bvh { nodes, node_indices, root_node_index, nodes_used, triangle_intersection, };
 
         Self { partial_bvh, }
     }
 
-    pub fn build_for(mut self, mesh: &mut [Triangle<f32>]) -> Bvh {
-        // Populate the triangle index array.
+    /// Use the triangle intersection test `intersection` for the triangles of a 
+    /// mesh in the boundary volume hierarchy instead of the global default.
+    pub fn with_triangle_intersection(mut self, intersection: TriangleIntersection) -> Self {
+        self.partial_bvh.triangle_intersection = Some(intersection);
+
+        self
+    }
+
+    /// Build a boundary volume hierarchy for a collection of primitives, reordering 
+    /// the primitives in place. 
+    /// 
+    /// Any per primitive data stored alongside the primitives is not reordered 
+    /// with them. Use [`BvhBuilder::build_for_mesh`] to keep the attributes of a
+    /// [`Mesh`] aligned with its primitives, or [`BvhBuilder::build_for_with_order`]
+    /// to reorder other data.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if there are more primitives than an 
+    /// [`InstancePrimitiveIndex`] can address. Enable the `wide_indices` 
+    /// feature to raise the limit.
+    pub fn build_for<P>(self, primitives: &mut [P]) -> Bvh 
+    where
+        P: BoundedPrimitive<f32>,
+    {
+        self.build_with_order(primitives).0
+    }
+
+    /// Build a boundary volume hierarchy for a collection of primitives, reordering 
+    /// the primitives in place. Returns the original index of each primitive in its 
+    /// new position, so that any per primitive data can be reordered to match.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if there are more primitives than an 
+    /// [`InstancePrimitiveIndex`] can address. Enable the `wide_indices` 
+    /// feature to raise the limit.
+    pub fn build_for_with_order<P>(self, primitives: &mut [P]) -> (Bvh, Vec<u32>) 
+    where
+        P: BoundedPrimitive<f32>,
+    {
+        self.build_with_order(primitives)
+    }
+
+    /// Build a boundary volume hierarchy for a mesh, reordering the texture 
+    /// coordinates, normals, and material ids of the mesh along with its primitives.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if the mesh has more primitives than an 
+    /// [`InstancePrimitiveIndex`] can address. Enable the `wide_indices` 
+    /// feature to raise the limit.
+    pub fn build_for_mesh(self, mesh: &mut Mesh<f32>) -> Bvh {
+        let (bvh, order) = self.build_with_order(mesh.primitives_mut());
+        mesh.reorder_attributes(&order);
+
+        bvh
+    }
+
+    /// Build a boundary volume hierarchy for an indexed mesh, reordering the 
+    /// triangles of the mesh and their material ids in place. The vertex buffer 
+    /// of the mesh is left as it is.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if the mesh has more primitives than an 
+    /// [`InstancePrimitiveIndex`] can address. Enable the `wide_indices` 
+    /// feature to raise the limit.
+    pub fn build_for_indexed_mesh(self, mesh: &mut IndexedMesh<f32>) -> Bvh {
+        let (vertices, indices) = mesh.vertices_and_triangle_indices_mut();
+        let mut triangles = IndexedTrianglesMut { vertices, indices, };
+        let (bvh, order) = self.build_with_order(&mut triangles);
+        mesh.reorder_material_ids(&order);
+
+        bvh
+    }
+
+    /// Build a boundary volume hierarchy, returning the original index of each 
+    /// primitive in its new position.
+    fn build_with_order<M>(mut self, mesh: &mut M) -> (Bvh, Vec<u32>) 
+    where
+        M: PrimitiveSourceMut + ?Sized,
+    {
+        assert!(
+            mesh.len() <= (InstancePrimitiveIndex::MAX_PRIMITIVE_INDEX as usize) + 1,
+            "A BVH can hold at most {} primitives, but got {} primitives.",
+            (InstancePrimitiveIndex::MAX_PRIMITIVE_INDEX as usize) + 1, mesh.len()
+        );
+        assert!(
+            2 * mesh.len() <= (u32::MAX as usize),
+            "A BVH can hold at most {} nodes, but {} primitives need {} nodes.",
+            u32::MAX, mesh.len(), 2 * mesh.len()
+        );
+
+        if mesh.len() == 0 {
+            // An empty hierarchy keeps a root node so that it has bounds. The 
+            // traversals never descend into it.
+            self.partial_bvh.nodes = BvhNodeArray(vec![BvhNode::default(); 2]);
+
+            return (self.partial_bvh, vec![]);
+        }
+
+        // Populate the primitive index array.
         for i in 0..mesh.len() {
             self.partial_bvh.node_indices.push(i as u32);
         }
@@ -535,9 +964,10 @@ impl BvhBuilder {
         root_node.primitive_count = mesh.len() as u32;
 
         self.partial_bvh.update_node_bounds(mesh, self.partial_bvh.root_node_index);
-        self.partial_bvh.subdivide(mesh, self.partial_bvh.root_node_index);
+        let mut order = (0..(mesh.len() as u32)).collect::<Vec<_>>();
+        self.partial_bvh.subdivide(mesh, &mut order, self.partial_bvh.root_node_index);
 
-        self.partial_bvh
+        (self.partial_bvh, order)
     }
 }
 
@@ -641,6 +1071,16 @@ mod bvh_tests {
 
         assert_eq!(result, expected);
     }
+
+    #[test]
+    fn test_bvh_without_primitives() {
+        let mut mesh: Vec<Triangle<f32>> = vec![];
+        let bvh = BvhBuilder::new().build_for(&mut mesh);
+        let ray = Ray::from_origin_dir(Vector3::new(0_f32, 0_f32, 5_f32), -Vector3::unit_z());
+
+        assert!(bvh.intersect(&mesh, &ray).is_none());
+        assert!(!bvh.occluded(&mesh, &ray));
+    }
 }
 
 #[cfg(test)]
@@ -817,8 +1257,9 @@ mod bvh_tree_one_triangle_tests {
         let node_indices = vec![0];
         let root_node_index = 0;
         let nodes_used = 2;
+        let triangle_intersection = None;
 
-        Bvh { nodes, node_indices, root_node_index, nodes_used, }
+        Bvh { nodes, node_indices, root_node_index, nodes_used, triangle_intersection, }
     }
 
 
@@ -2,11 +2,17 @@ use crate::mesh::*;
 use crate::materials::*;
 use super::model::*;
 use crate::texture_buffer::*;
+use crate::file_resolver::*;
 use std::error;
 use std::fmt;
+use std::io;
 use std::io::{
     Read,
 };
+use std::path::{
+    Path,
+    PathBuf,
+};
 
 
 pub type ModelResult<T> = Result<T, ModelError>;
@@ -14,13 +20,32 @@ pub type ModelResult<T> = Result<T, ModelError>;
 
 #[derive(Debug)]
 pub enum ModelError {
-    Material(),
-    Mesh(),
+    /// A material or material library could not be loaded.
+    Material(MtlError),
+    /// The mesh could not be loaded.
+    Mesh(MeshError),
+    /// A texture could not be loaded.
+    Texture(TextureBufferError),
+    /// A file that the model refers to could not be read from its file resolver.
+    Resolve { path: PathBuf, error: io::Error },
 }
 
 impl fmt::Display for ModelError {
     fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-        unimplemented!()
+        match self {
+            ModelError::Material(err) => {
+                write!(formatter, "{}", err)
+            }
+            ModelError::Mesh(err) => {
+                write!(formatter, "{}", err)
+            }
+            ModelError::Texture(err) => {
+                write!(formatter, "{}", err)
+            }
+            ModelError::Resolve { path, error } => {
+                write!(formatter, "Could not read {}: {}", path.display(), error)
+            }
+        }
     }
 }
 
@@ -53,9 +78,9 @@ where
 {
     fn read_model(self) -> ModelResult<ModelInstance> {
         let obj_decoder = ObjMeshDecoder::new(self.mesh_reader);
-        let mesh = obj_decoder.read_mesh().unwrap();
+        let mesh = obj_decoder.read_mesh().map_err(ModelError::Mesh)?;
         let texture_decoder: PngTextureBufferDecoder<Rgb<u8>, _> = PngTextureBufferDecoder::new(self.material_reader);
-        let texture_buffer = texture_decoder.read_texture().unwrap();
+        let texture_buffer = texture_decoder.read_texture().map_err(ModelError::Texture)?;
         let texture = TextureMaterial::new(texture_buffer);
         let model = ModelBuilder::new()
             .with_mesh(mesh)
@@ -66,3 +91,81 @@ where
     }
 }
 
+
+/// A decoder for Wavefront OBJ models that loads the materials of a model from 
+/// the material libraries named by its `mtllib` statements. 
+/// 
+/// Material libraries and textures are read through a [`FileResolver`], with 
+/// the paths of material libraries relative to `directory`, the directory of 
+/// the OBJ file in the resolver. Faces are assigned the material named by the 
+/// `usemtl` statement they follow. Materials the model does not use are ignored, 
+/// and faces without a material use the default material of a [`ModelBuilder`].
+pub struct ObjModelDecoder<'b, R, F: ?Sized> {
+    reader: R,
+    resolver: &'b F,
+    directory: PathBuf,
+}
+
+impl<'a, 'b, R, F> ObjModelDecoder<'b, R, F> 
+where
+    R: Read + 'a,
+    F: FileResolver + ?Sized,
+{
+    pub fn new(reader: R, resolver: &'b F) -> Self {
+        Self { 
+            reader, 
+            resolver, 
+            directory: PathBuf::new(),
+        }
+    }
+
+    /// Set the directory of the OBJ file in the file resolver.
+    pub fn with_directory<P>(mut self, directory: P) -> Self 
+    where
+        P: AsRef<Path>
+    {
+        self.directory = directory.as_ref().to_path_buf();
+
+        self
+    }
+}
+
+impl<'a, 'b, R, F> ModelDecoder<'a> for ObjModelDecoder<'b, R, F> 
+where
+    R: Read + 'a,
+    F: FileResolver + ?Sized,
+{
+    fn read_model(mut self) -> ModelResult<ModelInstance> {
+        let mut buffer = String::new();
+        self.reader.read_to_string(&mut buffer).map_err(|err| {
+            ModelError::Mesh(MeshError::IoError(err))
+        })?;
+        let (mesh, material_libraries) = decode_obj(&buffer).map_err(ModelError::Mesh)?;
+        let mut builder = ModelBuilder::new();
+        for material_library in material_libraries.iter() {
+            let path = self.directory.join(material_library);
+            let data = self.resolver.resolve(&path).map_err(|error| {
+                ModelError::Resolve { path: path.clone(), error }
+            })?;
+            let materials = MtlDecoder::new(data.as_slice())
+                .read_materials()
+                .map_err(ModelError::Material)?;
+            let library_directory = path.parent().unwrap_or(Path::new(""));
+            for mtl_material in materials.iter() {
+                if mesh.material_id(&mtl_material.name).is_none() {
+                    continue;
+                }
+                let material = mtl_material
+                    .to_material(self.resolver, library_directory)
+                    .map_err(ModelError::Material)?;
+                builder = builder.with_named_material(&mtl_material.name, material);
+            }
+        }
+        let model = builder
+            .with_mesh(mesh)
+            .build();
+
+        Ok(model)
+    }
+}
+
@@ -4,23 +4,107 @@ use crate::model::bvh::*;
 use crate::materials::*;
 use crate::texture_buffer::*;
 use crate::mesh::*;
-use std::rc::{
-    Rc,
+use cglinalg::{
+    Vector2,
+    Vector3,
 };
-use std::cell::{
-    RefCell,
+use std::slice;
+use std::sync::{
+    Arc,
+    RwLock,
+    RwLockReadGuard,
+    RwLockWriteGuard,
 };
 
 
+/// The albedo of the diffuse material used for primitives that are not assigned 
+/// a material.
+const DEFAULT_ALBEDO: f32 = 0.8;
+
+
+/// A thread safe shared handle to a model. 
+/// 
+/// Any number of threads can read from a model at once, e.g. to traverse its 
+/// BVH while rendering, while mutating a model requires exclusive access.
+#[derive(Clone, Debug)]
+pub struct ModelHandle {
+    inner: Arc<RwLock<Model>>,
+}
+
+impl ModelHandle {
+    fn new(model: Model) -> Self {
+        Self {
+            inner: Arc::new(RwLock::new(model)),
+        }
+    }
+
+    /// Acquire shared read access to the model.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if a thread panicked while mutating the model.
+    pub fn borrow(&self) -> RwLockReadGuard<'_, Model> {
+        self.inner.read().unwrap()
+    }
+
+    /// Acquire exclusive write access to the model.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if a thread panicked while mutating the model.
+    pub fn borrow_mut(&self) -> RwLockWriteGuard<'_, Model> {
+        self.inner.write().unwrap()
+    }
+}
+
+
 #[derive(Clone, Debug)]
 pub struct ModelInstance {
-    handle: Rc<RefCell<Model>>,
+    handle: ModelHandle,
 }
 
 impl ModelInstance {
-    pub fn new(mesh: Mesh<f32>, bvh: Bvh, texture: TextureMaterial<Rgb<u8>>) -> Self {
+    pub fn new(mesh: Mesh<f32>, bvh: Bvh, materials: Vec<Arc<dyn Material>>) -> Self {
+        Self { 
+            handle: ModelHandle::new(Model::new(mesh, bvh, materials)),
+        }
+    }
+
+    /// Construct a model from a collection of spheres, where `material_ids` holds 
+    /// the index into the material table of the material of each sphere.
+    pub fn from_spheres(spheres: Vec<Sphere<f32>>, material_ids: Vec<u32>, bvh: Bvh, materials: Vec<Arc<dyn Material>>) -> Self {
+        Self { 
+            handle: ModelHandle::new(Model::from_spheres(spheres, material_ids, bvh, materials)),
+        }
+    }
+
+    /// Construct a model from a collection of analytic shapes, where `material_ids` 
+    /// holds the index into the material table of the material of each shape.
+    pub fn from_shapes(shapes: Vec<Shape<f32>>, material_ids: Vec<u32>, bvh: Bvh, materials: Vec<Arc<dyn Material>>) -> Self {
+        Self { 
+            handle: ModelHandle::new(Model::from_shapes(shapes, material_ids, bvh, materials)),
+        }
+    }
+
+    /// Construct a model from a collection of implicit surfaces, where 
+    /// `material_ids` holds the index into the material table of the material of 
+    /// each surface.
+    pub fn from_implicit_surfaces(
+        surfaces: Vec<ImplicitSurface<f32>>, 
+        material_ids: Vec<u32>, 
+        bvh: Bvh, 
+        materials: Vec<Arc<dyn Material>>) -> Self
+    {
         Self { 
-            handle: Rc::new(RefCell::new(Model::new(mesh, bvh, texture))),
+            handle: ModelHandle::new(Model::from_implicit_surfaces(surfaces, material_ids, bvh, materials)),
+        }
+    }
+
+    /// Construct a model from a voxel grid, whose voxels hold indices into the 
+    /// material table.
+    pub fn from_voxels(voxel_grid: VoxelGrid<f32>, bvh: Bvh, materials: Vec<Arc<dyn Material>>) -> Self {
+        Self { 
+            handle: ModelHandle::new(Model::from_voxels(voxel_grid, bvh, materials)),
         }
     }
 
@@ -28,6 +112,10 @@ impl ModelInstance {
         self.handle.borrow().intersect(ray)
     }
 
+    pub fn occluded(&self, ray: &Ray<f32>) -> bool {
+        self.handle.borrow().occluded(ray)
+    }
+
     pub fn refit(&mut self) {
         self.handle.borrow_mut().refit()
     }
@@ -37,7 +125,7 @@ impl ModelInstance {
         self.handle.borrow().bounds()
     }
 
-    pub fn model(&self) -> Rc<RefCell<Model>> {
+    pub fn model(&self) -> ModelHandle {
         self.handle.clone()
     }
 
@@ -51,24 +139,208 @@ impl ModelInstance {
 }
 
 
+/// The primitives of a model. 
+/// 
+/// The primitives of a mesh and the voxels of a voxel grid carry their own 
+/// material ids. The other kinds of geometry hold the index into the material 
+/// table of the material of each primitive next to the primitives.
+#[derive(Clone, Debug)]
+pub enum ModelGeometry {
+    /// A triangle mesh.
+    Mesh(Mesh<f32>),
+    /// A collection of analytic spheres and their material ids.
+    Spheres(Vec<Sphere<f32>>, Vec<u32>),
+    /// A collection of other analytic shapes and their material ids.
+    Shapes(Vec<Shape<f32>>, Vec<u32>),
+    /// A collection of implicit surfaces and their material ids.
+    ImplicitSurfaces(Vec<ImplicitSurface<f32>>, Vec<u32>),
+    /// A voxel grid, which is a single primitive.
+    Voxels(VoxelGrid<f32>),
+}
+
+/// A model is either a triangle mesh, a collection of analytic spheres, a 
+/// collection of other analytic shapes, a collection of implicit surfaces, or a 
+/// voxel grid, together with the boundary volume hierarchy over its primitives. 
+/// 
+/// The primitive indices of a sphere, shape, or implicit surface model index its 
+/// spheres, shapes, or surfaces. A voxel grid is a single primitive, so every 
+/// intersection with a voxel model has primitive index zero.
 #[derive(Clone, Debug)]
 pub struct Model {
-    mesh: Mesh<f32>,
+    geometry: ModelGeometry,
     bvh: Bvh,
-    texture: TextureMaterial<Rgb<u8>>,
+    /// The material table of the model, indexed by the material ids of the 
+    /// primitives.
+    materials: Vec<Arc<dyn Material>>,
 }
 
 impl Model {
-    pub fn new(mesh: Mesh<f32>, bvh: Bvh, texture: TextureMaterial<Rgb<u8>>) -> Self {
-        Self { mesh, bvh, texture, }
+    /// Construct a new model.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if a primitive in the mesh has a material id that 
+    /// is not in the material table.
+    pub fn new(mesh: Mesh<f32>, bvh: Bvh, materials: Vec<Arc<dyn Material>>) -> Self {
+        Self::from_geometry(ModelGeometry::Mesh(mesh), bvh, materials)
+    }
+
+    /// Construct a new model from a collection of spheres.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if the number of material ids differs from the 
+    /// number of spheres, or if a sphere has a material id that is not in the 
+    /// material table.
+    pub fn from_spheres(spheres: Vec<Sphere<f32>>, material_ids: Vec<u32>, bvh: Bvh, materials: Vec<Arc<dyn Material>>) -> Self {
+        Self::from_geometry(ModelGeometry::Spheres(spheres, material_ids), bvh, materials)
+    }
+
+    /// Construct a new model from a collection of analytic shapes.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if the number of material ids differs from the 
+    /// number of shapes, or if a shape has a material id that is not in the 
+    /// material table.
+    pub fn from_shapes(shapes: Vec<Shape<f32>>, material_ids: Vec<u32>, bvh: Bvh, materials: Vec<Arc<dyn Material>>) -> Self {
+        Self::from_geometry(ModelGeometry::Shapes(shapes, material_ids), bvh, materials)
+    }
+
+    /// Construct a new model from a collection of implicit surfaces.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if the number of material ids differs from the 
+    /// number of surfaces, or if a surface has a material id that is not in the 
+    /// material table.
+    pub fn from_implicit_surfaces(
+        surfaces: Vec<ImplicitSurface<f32>>, 
+        material_ids: Vec<u32>, 
+        bvh: Bvh, 
+        materials: Vec<Arc<dyn Material>>) -> Self
+    {
+        Self::from_geometry(ModelGeometry::ImplicitSurfaces(surfaces, material_ids), bvh, materials)
+    }
+
+    /// Construct a new model from a voxel grid.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if a voxel has a material id that is not in the 
+    /// material table.
+    pub fn from_voxels(voxel_grid: VoxelGrid<f32>, bvh: Bvh, materials: Vec<Arc<dyn Material>>) -> Self {
+        Self::from_geometry(ModelGeometry::Voxels(voxel_grid), bvh, materials)
+    }
+
+    /// Construct a new model from its geometry.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if the number of material ids of a collection of 
+    /// spheres, shapes, or implicit surfaces differs from the number of 
+    /// primitives, or if a primitive has a material id that is not in the 
+    /// material table.
+    pub fn from_geometry(geometry: ModelGeometry, bvh: Bvh, materials: Vec<Arc<dyn Material>>) -> Self {
+        let in_table = |material_id: u32| (material_id as usize) < materials.len();
+        match &geometry {
+            ModelGeometry::Mesh(mesh) => assert!(
+                mesh.material_ids().iter().all(|&material_id| in_table(material_id)),
+                "Every material id in the mesh must refer to a material in the material table."
+            ),
+            ModelGeometry::Spheres(spheres, material_ids) => {
+                assert_eq!(
+                    spheres.len(), material_ids.len(),
+                    "Every sphere must have exactly one material id."
+                );
+                assert!(
+                    material_ids.iter().all(|&material_id| in_table(material_id)),
+                    "Every material id of the spheres must refer to a material in the material table."
+                );
+            }
+            ModelGeometry::Shapes(shapes, material_ids) => {
+                assert_eq!(
+                    shapes.len(), material_ids.len(),
+                    "Every shape must have exactly one material id."
+                );
+                assert!(
+                    material_ids.iter().all(|&material_id| in_table(material_id)),
+                    "Every material id of the shapes must refer to a material in the material table."
+                );
+            }
+            ModelGeometry::ImplicitSurfaces(surfaces, material_ids) => {
+                assert_eq!(
+                    surfaces.len(), material_ids.len(),
+                    "Every implicit surface must have exactly one material id."
+                );
+                assert!(
+                    material_ids.iter().all(|&material_id| in_table(material_id)),
+                    "Every material id of the implicit surfaces must refer to a material in the material table."
+                );
+            }
+            ModelGeometry::Voxels(voxel_grid) => assert!(
+                voxel_grid.material_ids().all(in_table),
+                "Every material id of the voxels must refer to a material in the material table."
+            ),
+        }
+
+        Self { geometry, bvh, materials, }
+    }
+
+    /// The primitives of the model.
+    #[inline]
+    pub const fn geometry(&self) -> &ModelGeometry {
+        &self.geometry
+    }
+
+    /// Determine whether the primitives of the model are spheres.
+    pub fn is_spheres(&self) -> bool {
+        matches!(self.geometry, ModelGeometry::Spheres(..))
+    }
+
+    /// Determine whether the primitives of the model are analytic shapes.
+    pub fn is_shapes(&self) -> bool {
+        matches!(self.geometry, ModelGeometry::Shapes(..))
+    }
+
+    /// Determine whether the primitives of the model are implicit surfaces.
+    pub fn is_implicit_surfaces(&self) -> bool {
+        matches!(self.geometry, ModelGeometry::ImplicitSurfaces(..))
+    }
+
+    /// Determine whether the model is a voxel grid.
+    pub fn is_voxels(&self) -> bool {
+        matches!(self.geometry, ModelGeometry::Voxels(..))
     }
 
     pub fn intersect(&self, ray: &Ray<f32>) -> Option<Intersection<f32>> {
-        self.bvh.intersect(&self.mesh.primitives(), ray)
+        match &self.geometry {
+            ModelGeometry::Mesh(mesh) => self.bvh.intersect_mesh(mesh, ray),
+            ModelGeometry::Spheres(spheres, _) => self.bvh.intersect(spheres, ray),
+            ModelGeometry::Shapes(shapes, _) => self.bvh.intersect(shapes, ray),
+            ModelGeometry::ImplicitSurfaces(surfaces, _) => self.bvh.intersect(surfaces, ray),
+            ModelGeometry::Voxels(voxel_grid) => self.bvh.intersect(slice::from_ref(voxel_grid), ray),
+        }
+    }
+
+    pub fn occluded(&self, ray: &Ray<f32>) -> bool {
+        match &self.geometry {
+            ModelGeometry::Mesh(mesh) => self.bvh.occluded_mesh(mesh, ray),
+            ModelGeometry::Spheres(spheres, _) => self.bvh.occluded(spheres, ray),
+            ModelGeometry::Shapes(shapes, _) => self.bvh.occluded(shapes, ray),
+            ModelGeometry::ImplicitSurfaces(surfaces, _) => self.bvh.occluded(surfaces, ray),
+            ModelGeometry::Voxels(voxel_grid) => self.bvh.occluded(slice::from_ref(voxel_grid), ray),
+        }
     }
 
     pub fn refit(&mut self) {
-        self.bvh.refit(&self.mesh.primitives())
+        match &self.geometry {
+            ModelGeometry::Mesh(mesh) => self.bvh.refit(mesh.primitives()),
+            ModelGeometry::Spheres(spheres, _) => self.bvh.refit(spheres),
+            ModelGeometry::Shapes(shapes, _) => self.bvh.refit(shapes),
+            ModelGeometry::ImplicitSurfaces(surfaces, _) => self.bvh.refit(surfaces),
+            ModelGeometry::Voxels(voxel_grid) => self.bvh.refit(slice::from_ref(voxel_grid)),
+        }
     }
 
     /// Returns the model space bounds for a model.
@@ -76,71 +348,492 @@ impl Model {
         self.bvh.bounds()
     }
 
+    /// The mesh of a mesh model.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if the model is not a mesh model.
     pub fn mesh(&self) -> &Mesh<f32> {
-        &self.mesh
+        match &self.geometry {
+            ModelGeometry::Mesh(mesh) => mesh,
+            _ => panic!("Only a mesh model has a mesh."),
+        }
     }
 
+    /// The triangles of the model, which is empty for a model of another kind.
     pub fn primitives(&self) -> &[Triangle<f32>] {
-        &self.mesh.primitives()
+        match &self.geometry {
+            ModelGeometry::Mesh(mesh) => mesh.primitives(),
+            _ => &[],
+        }
     }
 
+    /// The triangles of the model, which can be moved as long as the hierarchy 
+    /// is refit afterwards with [`Model::refit`].
     pub fn primitives_mut(&mut self) -> &mut [Triangle<f32>] {
-        self.mesh.primitives_mut()
+        match &mut self.geometry {
+            ModelGeometry::Mesh(mesh) => mesh.primitives_mut(),
+            _ => &mut [],
+        }
     }
 
+    /// The texture coordinates of the triangles of the model, which is empty for 
+    /// a model of another kind.
     pub fn tex_coords(&self) -> &[TextureCoordinates<f32, 3>] {
-        &self.mesh.tex_coords()
+        match &self.geometry {
+            ModelGeometry::Mesh(mesh) => mesh.tex_coords(),
+            _ => &[],
+        }
     }
 
+    /// The vertex normals of the triangles of the model, which is empty for a 
+    /// model of another kind.
     pub fn normals(&self) -> &[Normals<f32, 3>] {
-        &self.mesh.normals()
+        match &self.geometry {
+            ModelGeometry::Mesh(mesh) => mesh.normals(),
+            _ => &[],
+        }
+    }
+
+    /// The spheres of the model, which is empty for a model of another kind.
+    pub fn spheres(&self) -> &[Sphere<f32>] {
+        match &self.geometry {
+            ModelGeometry::Spheres(spheres, _) => spheres,
+            _ => &[],
+        }
+    }
+
+    /// The spheres of the model, which can be moved or resized as long as the 
+    /// model is refit afterwards.
+    pub fn spheres_mut(&mut self) -> &mut [Sphere<f32>] {
+        match &mut self.geometry {
+            ModelGeometry::Spheres(spheres, _) => spheres,
+            _ => &mut [],
+        }
+    }
+
+    /// The analytic shapes of the model, which is empty for a model of another 
+    /// kind.
+    pub fn shapes(&self) -> &[Shape<f32>] {
+        match &self.geometry {
+            ModelGeometry::Shapes(shapes, _) => shapes,
+            _ => &[],
+        }
+    }
+
+    /// The analytic shapes of the model, which can be moved or resized as long as 
+    /// the hierarchy is refit afterwards with [`Model::refit`].
+    pub fn shapes_mut(&mut self) -> &mut [Shape<f32>] {
+        match &mut self.geometry {
+            ModelGeometry::Shapes(shapes, _) => shapes,
+            _ => &mut [],
+        }
+    }
+
+    /// The implicit surfaces of the model, which is empty for a model of another 
+    /// kind.
+    pub fn implicit_surfaces(&self) -> &[ImplicitSurface<f32>] {
+        match &self.geometry {
+            ModelGeometry::ImplicitSurfaces(surfaces, _) => surfaces,
+            _ => &[],
+        }
     }
 
+    /// The implicit surfaces of the model, which can be changed as long as the 
+    /// hierarchy is refit afterwards with [`Model::refit`].
+    pub fn implicit_surfaces_mut(&mut self) -> &mut [ImplicitSurface<f32>] {
+        match &mut self.geometry {
+            ModelGeometry::ImplicitSurfaces(surfaces, _) => surfaces,
+            _ => &mut [],
+        }
+    }
+
+    /// The voxel grid of a voxel model.
+    pub fn voxel_grid(&self) -> Option<&VoxelGrid<f32>> {
+        match &self.geometry {
+            ModelGeometry::Voxels(voxel_grid) => Some(voxel_grid),
+            _ => None,
+        }
+    }
+
+    /// The voxel grid of a voxel model, whose voxels can be filled and emptied 
+    /// freely. The material ids of the voxels must stay inside the material table.
+    pub fn voxel_grid_mut(&mut self) -> Option<&mut VoxelGrid<f32>> {
+        match &mut self.geometry {
+            ModelGeometry::Voxels(voxel_grid) => Some(voxel_grid),
+            _ => None,
+        }
+    }
+
+    /// The voxel hit by an intersection with a voxel model, as found by 
+    /// [`Model::intersect`], whose ray is in model space. This is `None` for 
+    /// models that are not voxel grids.
+    pub fn voxel_hit(&self, intersection: &Intersection<f32>) -> Option<VoxelHit<f32>> {
+        let voxel_grid = self.voxel_grid()?;
+        let mut ray = intersection.ray;
+        ray.t = intersection.interaction.t;
+
+        voxel_grid.intersect_voxel(&ray)
+    }
+
+    /// The shading normal in model space at an intersection with the model, as 
+    /// found by [`Model::intersect`], whose ray is in model space. 
+    /// 
+    /// The normal is interpolated from the vertex normals of a mesh, and is zero 
+    /// for a mesh without vertex normals.
+    pub fn interpolated_normal(&self, intersection: &Intersection<f32>) -> Vector3<f32> {
+        let primitive_index = intersection.instance_primitive.primitive_index() as usize;
+        let position = intersection.ray.interpolate(intersection.interaction.t);
+        match &self.geometry {
+            ModelGeometry::Mesh(mesh) => {
+                let u = intersection.interaction.u;
+                let v = intersection.interaction.v;
+                let normals = mesh.normals()[primitive_index];
+
+                normals[0] * (1_f32 - u - v) + normals[1] * u + normals[2] * v
+            }
+            ModelGeometry::Spheres(spheres, _) => spheres[primitive_index].normal(&position),
+            ModelGeometry::Shapes(shapes, _) => shapes[primitive_index].normal(&position),
+            ModelGeometry::ImplicitSurfaces(surfaces, _) => surfaces[primitive_index].normal(&position),
+            ModelGeometry::Voxels(_) => {
+                self.voxel_hit(intersection).map(|hit| hit.normal).unwrap_or_else(Vector3::zero)
+            }
+        }
+    }
+
+    /// The texture coordinates at an intersection with the model.
+    pub fn interpolated_tex_coords(&self, intersection: &Intersection<f32>) -> Vector2<f32> {
+        let primitive_index = intersection.instance_primitive.primitive_index() as usize;
+        let u = intersection.interaction.u;
+        let v = intersection.interaction.v;
+        match &self.geometry {
+            ModelGeometry::Mesh(mesh) => {
+                let tex_coords = mesh.tex_coords()[primitive_index];
+
+                tex_coords[0] * (1_f32 - u - v) + tex_coords[1] * u + tex_coords[2] * v
+            }
+            _ => Vector2::new(u, v),
+        }
+    }
+
+    /// The number of vertices of a mesh model, or the number of primitives of a 
+    /// model of another kind.
     pub fn len(&self) -> usize {
-        self.mesh.len()
+        match &self.geometry {
+            ModelGeometry::Mesh(mesh) => mesh.len(),
+            _ => self.len_primitives(),
+        }
     }
 
+    /// The number of primitives in the boundary volume hierarchy of the model. A 
+    /// voxel grid is a single primitive.
     pub fn len_primitives(&self) -> usize {
-        self.mesh.len_primitives()
+        match &self.geometry {
+            ModelGeometry::Mesh(mesh) => mesh.len_primitives(),
+            ModelGeometry::Spheres(spheres, _) => spheres.len(),
+            ModelGeometry::Shapes(shapes, _) => shapes.len(),
+            ModelGeometry::ImplicitSurfaces(surfaces, _) => surfaces.len(),
+            ModelGeometry::Voxels(_) => 1,
+        }
+    }
+
+    /// The material table of the model.
+    pub fn materials(&self) -> &[Arc<dyn Material>] {
+        &self.materials
+    }
+
+    /// The material of the primitive with index `primitive_index`.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics for a voxel model, whose material varies across its 
+    /// single primitive. Use [`Model::material_at`] instead.
+    pub fn material(&self, primitive_index: usize) -> &Arc<dyn Material> {
+        let material_id = match &self.geometry {
+            ModelGeometry::Mesh(mesh) => mesh.material_ids()[primitive_index],
+            ModelGeometry::Spheres(_, material_ids) => material_ids[primitive_index],
+            ModelGeometry::Shapes(_, material_ids) => material_ids[primitive_index],
+            ModelGeometry::ImplicitSurfaces(_, material_ids) => material_ids[primitive_index],
+            ModelGeometry::Voxels(_) => {
+                panic!("The material of a voxel model depends on the voxel that was hit.")
+            }
+        };
+
+        &self.materials[material_id as usize]
     }
 
-    pub fn texture(&self) -> &TextureMaterial<Rgb<u8>> {
-        &self.texture
+    /// The material at an intersection with the model, as found by 
+    /// [`Model::intersect`].
+    pub fn material_at(&self, intersection: &Intersection<f32>) -> &Arc<dyn Material> {
+        if self.is_voxels() {
+            let material_id = self.voxel_hit(intersection)
+                .map(|hit| hit.material_id)
+                .unwrap_or(0);
+
+            return &self.materials[material_id as usize];
+        }
+
+        self.material(intersection.instance_primitive.primitive_index() as usize)
     }
 }
 
 
 pub struct ModelBuilder {
-    mesh: Mesh<f32>,
+    geometry: ModelGeometry,
     bvh_builder: BvhBuilder,
-    texture: TextureMaterial<Rgb<u8>>,
+    default_material: Arc<dyn Material>,
+    materials: Vec<(u32, Arc<dyn Material>)>,
+    named_materials: Vec<(String, Arc<dyn Material>)>,
+    cleanup: Option<MeshCleanup>,
 }
 
 impl ModelBuilder {
     pub fn new() -> Self {
         Self {
-            mesh: Mesh::from_parts(vec![], vec![], vec![]),
+            geometry: ModelGeometry::Mesh(Mesh::from_parts(vec![], vec![], vec![], vec![], vec![])),
             bvh_builder: BvhBuilder::new(),
-            texture: TextureMaterial::default(),
+            default_material: Arc::new(LambertianMaterial::new(Vector3::from_fill(DEFAULT_ALBEDO))),
+            materials: vec![],
+            named_materials: vec![],
+            cleanup: None,
         }
     }
 
+    /// Build the model from a triangle mesh, which is the default. This replaces 
+    /// any geometry given to the builder before.
     pub fn with_mesh(mut self, mesh: Mesh<f32>) -> Self {
-        self.mesh = mesh;
+        self.geometry = ModelGeometry::Mesh(mesh);
 
         self
     }
 
-    pub fn with_texture(mut self, texture: TextureMaterial<Rgb<u8>>) -> Self {
-        self.texture = texture;
+    /// Build the model from a collection of spheres instead of a mesh. Every 
+    /// sphere uses material id zero unless material ids are given with 
+    /// [`ModelBuilder::with_sphere_material_ids`]. This replaces any geometry 
+    /// given to the builder before.
+    pub fn with_spheres(mut self, spheres: Vec<Sphere<f32>>) -> Self {
+        let material_ids = vec![0; spheres.len()];
+        self.geometry = ModelGeometry::Spheres(spheres, material_ids);
+
+        self
+    }
+
+    /// Assign each sphere the material id at the same index in `material_ids`.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if the builder was not given spheres before.
+    pub fn with_sphere_material_ids(mut self, material_ids: Vec<u32>) -> Self {
+        match &mut self.geometry {
+            ModelGeometry::Spheres(_, sphere_material_ids) => *sphere_material_ids = material_ids,
+            _ => panic!("Sphere material ids need the spheres to be given first."),
+        }
 
         self
     }
 
-    pub fn build(mut self) -> ModelInstance {
-        let bvh = self.bvh_builder.build_for(self.mesh.primitives_mut());
+    /// Build the model from a collection of analytic shapes instead of a mesh. 
+    /// Every shape uses material id zero unless material ids are given with 
+    /// [`ModelBuilder::with_shape_material_ids`]. This replaces any geometry 
+    /// given to the builder before.
+    pub fn with_shapes(mut self, shapes: Vec<Shape<f32>>) -> Self {
+        let material_ids = vec![0; shapes.len()];
+        self.geometry = ModelGeometry::Shapes(shapes, material_ids);
 
-        ModelInstance::new(self.mesh, bvh, self.texture)
+        self
+    }
+
+    /// Assign each shape the material id at the same index in `material_ids`.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if the builder was not given shapes before.
+    pub fn with_shape_material_ids(mut self, material_ids: Vec<u32>) -> Self {
+        match &mut self.geometry {
+            ModelGeometry::Shapes(_, shape_material_ids) => *shape_material_ids = material_ids,
+            _ => panic!("Shape material ids need the shapes to be given first."),
+        }
+
+        self
+    }
+
+    /// Build the model from a collection of implicit surfaces instead of a mesh. 
+    /// Every surface uses material id zero unless material ids are given with 
+    /// [`ModelBuilder::with_implicit_surface_material_ids`]. This replaces any 
+    /// geometry given to the builder before.
+    pub fn with_implicit_surfaces(mut self, surfaces: Vec<ImplicitSurface<f32>>) -> Self {
+        let material_ids = vec![0; surfaces.len()];
+        self.geometry = ModelGeometry::ImplicitSurfaces(surfaces, material_ids);
+
+        self
+    }
+
+    /// Assign each implicit surface the material id at the same index in 
+    /// `material_ids`.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if the builder was not given implicit surfaces before.
+    pub fn with_implicit_surface_material_ids(mut self, material_ids: Vec<u32>) -> Self {
+        match &mut self.geometry {
+            ModelGeometry::ImplicitSurfaces(_, surface_material_ids) => *surface_material_ids = material_ids,
+            _ => panic!("Implicit surface material ids need the implicit surfaces to be given first."),
+        }
+
+        self
+    }
+
+    /// Build the model from a voxel grid instead of a mesh. The material ids of 
+    /// the voxels index the material table of the model. This replaces any 
+    /// geometry given to the builder before.
+    pub fn with_voxels(mut self, voxel_grid: VoxelGrid<f32>) -> Self {
+        self.geometry = ModelGeometry::Voxels(voxel_grid);
+
+        self
+    }
+
+    /// Use a diffuse material with the albedo `texture` for every primitive that 
+    /// is not assigned a material of its own. An empty texture leaves the default 
+    /// material in place.
+    pub fn with_texture(self, texture: TextureMaterial<Rgb<u8>>) -> Self {
+        if texture.is_empty() {
+            return self;
+        }
+
+        self.with_material(Arc::new(LambertianMaterial::new(texture)))
+    }
+
+    /// Use `material` for every primitive that is not assigned a material of its own.
+    pub fn with_material(mut self, material: Arc<dyn Material>) -> Self {
+        self.default_material = material;
+
+        self
+    }
+
+    /// Use `material` for the primitives with material id `material_id`.
+    pub fn with_material_id(mut self, material_id: u32, material: Arc<dyn Material>) -> Self {
+        self.materials.push((material_id, material));
+
+        self
+    }
+
+    /// Use `material` for the primitives whose material is named `name` in the mesh. 
+    /// Names that the mesh does not use are ignored.
+    pub fn with_named_material(mut self, name: &str, material: Arc<dyn Material>) -> Self {
+        self.named_materials.push((String::from(name), material));
+
+        self
+    }
+
+    /// Clean up the mesh with the fixes of `cleanup` before building the model. 
+    /// Meshes are used as they are by default.
+    pub fn with_cleanup(mut self, cleanup: MeshCleanup) -> Self {
+        self.cleanup = Some(cleanup);
+
+        self
+    }
+
+    /// Intersect rays with the triangles of the mesh using the triangle
+    /// intersection test `intersection` instead of the global default.
+    pub fn with_triangle_intersection(mut self, intersection: TriangleIntersection) -> Self {
+        self.bvh_builder = self.bvh_builder.with_triangle_intersection(intersection);
+
+        self
+    }
+
+    /// Construct the model.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if the number of sphere, shape, or implicit surface 
+    /// material ids differs from the number of spheres, shapes, or implicit 
+    /// surfaces.
+    pub fn build(self) -> ModelInstance {
+        match self.geometry {
+            ModelGeometry::Mesh(mut mesh) => {
+                if let Some(cleanup) = &self.cleanup {
+                    mesh.clean(cleanup);
+                }
+                let bvh = self.bvh_builder.build_for_mesh(&mut mesh);
+                let mut materials = material_table(mesh.material_ids().iter().copied(), self.default_material, self.materials);
+                for (name, material) in self.named_materials.into_iter() {
+                    if let Some(material_id) = mesh.material_id(&name) {
+                        materials[material_id as usize] = material;
+                    }
+                }
+
+                ModelInstance::new(mesh, bvh, materials)
+            }
+            ModelGeometry::Spheres(mut spheres, material_ids) => {
+                let (bvh, material_ids) = build_with_material_ids(self.bvh_builder, &mut spheres, material_ids);
+                let materials = material_table(material_ids.iter().copied(), self.default_material, self.materials);
+
+                ModelInstance::from_spheres(spheres, material_ids, bvh, materials)
+            }
+            ModelGeometry::Shapes(mut shapes, material_ids) => {
+                let (bvh, material_ids) = build_with_material_ids(self.bvh_builder, &mut shapes, material_ids);
+                let materials = material_table(material_ids.iter().copied(), self.default_material, self.materials);
+
+                ModelInstance::from_shapes(shapes, material_ids, bvh, materials)
+            }
+            ModelGeometry::ImplicitSurfaces(mut surfaces, material_ids) => {
+                let (bvh, material_ids) = build_with_material_ids(self.bvh_builder, &mut surfaces, material_ids);
+                let materials = material_table(material_ids.iter().copied(), self.default_material, self.materials);
+
+                ModelInstance::from_implicit_surfaces(surfaces, material_ids, bvh, materials)
+            }
+            ModelGeometry::Voxels(mut voxel_grid) => {
+                let bvh = self.bvh_builder.build_for(slice::from_mut(&mut voxel_grid));
+                let materials = material_table(voxel_grid.material_ids(), self.default_material, self.materials);
+
+                ModelInstance::from_voxels(voxel_grid, bvh, materials)
+            }
+        }
     }
 }
 
+/// Build the boundary volume hierarchy over `primitives`, and reorder their 
+/// material ids `material_ids` to follow the primitives, which the build reorders.
+/// 
+/// # Panics
+/// 
+/// This function panics if the number of material ids differs from the number 
+/// of primitives.
+fn build_with_material_ids<P>(bvh_builder: BvhBuilder, primitives: &mut [P], material_ids: Vec<u32>) -> (Bvh, Vec<u32>) 
+where
+    P: BoundedPrimitive<f32>,
+{
+    assert_eq!(
+        primitives.len(), material_ids.len(),
+        "Every primitive must have exactly one material id."
+    );
+    let (bvh, order) = bvh_builder.build_for_with_order(primitives);
+    let material_ids = order.iter()
+        .map(|&old_index| material_ids[old_index as usize])
+        .collect::<Vec<_>>();
+
+    (bvh, material_ids)
+}
+
+/// Assemble the material table for the primitives with the material ids 
+/// `material_ids`, using `default_material` for every material id that is not 
+/// assigned a material of its own.
+fn material_table<I>(
+    material_ids: I, 
+    default_material: Arc<dyn Material>, 
+    assigned_materials: Vec<(u32, Arc<dyn Material>)>) -> Vec<Arc<dyn Material>> 
+where
+    I: Iterator<Item = u32>,
+{
+    let table_len = material_ids
+        .chain(assigned_materials.iter().map(|&(material_id, _)| material_id))
+        .map(|material_id| material_id as usize + 1)
+        .max()
+        .unwrap_or(1);
+    let mut materials = vec![default_material; table_len];
+    for (material_id, material) in assigned_materials.into_iter() {
+        materials[material_id as usize] = material;
+    }
+
+    materials
+}
@@ -9,7 +9,7 @@ use std::fmt;
 use std::marker;
 
 
-pub trait ForceGenerator<S>: fmt::Debug {
+pub trait ForceGenerator<S>: fmt::Debug + Send + Sync {
     fn apply_force(&self, body: &mut RigidBody<S>, duration: S);
 }
 
@@ -102,8 +102,9 @@ where
         // Calculate the two ends in world space.
         let lws = body.get_point_in_world_space(&self.spring_connection_point);
         let ows = self.object
-            .rc()
-            .borrow()
+            .arc()
+            .read()
+            .unwrap()
             .get_point_in_world_space(&self.object_connection_point);
         let displacement = lws - ows;
         let force_magnitude = {
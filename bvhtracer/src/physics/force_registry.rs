@@ -3,39 +3,46 @@ use super::force_generator::*;
 use cglinalg::{
     SimdScalarFloat,
 };
-use std::cell::{
-    RefCell,
-};
-use std::rc::{
-    Rc,
+use std::sync::{
+    Arc,
+    RwLock,
 };
 
 
 #[repr(transparent)]
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct RigidBodyInstance<S> {
-    inner: Rc<RefCell<RigidBody<S>>>,
+    inner: Arc<RwLock<RigidBody<S>>>,
 }
 
 impl<S> RigidBodyInstance<S> {
-    pub const fn new(body: Rc<RefCell<RigidBody<S>>>) -> Self {
+    pub const fn new(body: Arc<RwLock<RigidBody<S>>>) -> Self {
         Self {
             inner: body,
         }
     }
 
-    pub (crate) fn rc(&self) -> Rc<RefCell<RigidBody<S>>> {
+    pub (crate) fn arc(&self) -> Arc<RwLock<RigidBody<S>>> {
         self.inner.clone()
     }
 }
 
+impl<S> PartialEq for RigidBodyInstance<S>
+where
+    RigidBody<S>: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner) || *self.inner.read().unwrap() == *other.inner.read().unwrap()
+    }
+}
+
 struct ForceRegistryEntry<S> {
     body: RigidBodyInstance<S>,
-    generator: Rc<dyn ForceGenerator<S>>,
+    generator: Arc<dyn ForceGenerator<S>>,
 }
 
 impl<S> ForceRegistryEntry<S> {
-    fn new(body: RigidBodyInstance<S>, generator: Rc<dyn ForceGenerator<S>>) -> Self {
+    fn new(body: RigidBodyInstance<S>, generator: Arc<dyn ForceGenerator<S>>) -> Self {
         Self { body, generator, }
     }
 }
@@ -51,7 +58,7 @@ impl<S> ForceRegistry<S> {
         }
     }
 
-    pub fn register(&mut self, body: RigidBodyInstance<S>, generator: Rc<dyn ForceGenerator<S>>) {
+    pub fn register(&mut self, body: RigidBodyInstance<S>, generator: Arc<dyn ForceGenerator<S>>) {
         self.entries.push(ForceRegistryEntry::new(body, generator));
     }
 
@@ -72,7 +79,7 @@ where
 {
     pub fn apply_forces(&mut self, duration: S) {
         for entry in self.entries.iter_mut() {
-            entry.generator.apply_force(&mut entry.body.inner.borrow_mut(), duration);
+            entry.generator.apply_force(&mut entry.body.inner.write().unwrap(), duration);
         }
     }
 }
@@ -4,11 +4,9 @@ use super::force_generator::*;
 use cglinalg::{
     SimdScalarFloat,
 };
-use std::cell::{
-    RefCell,
-};
-use std::rc::{
-    Rc,
+use std::sync::{
+    Arc,
+    RwLock,
 };
 
 
@@ -29,27 +27,27 @@ where
     }
 
     pub fn register_body(&mut self, body: RigidBody<S>) -> RigidBodyInstance<S> {
-        let body_instance = Rc::new(RefCell::new(body));
+        let body_instance = Arc::new(RwLock::new(body));
         let instance = RigidBodyInstance::new(body_instance);
         self.bodies.push(instance.clone());
 
         instance
     }
 
-    pub fn register_force_generator(&mut self, body: RigidBodyInstance<S>, generator: Rc<dyn ForceGenerator<S>>) {
+    pub fn register_force_generator(&mut self, body: RigidBodyInstance<S>, generator: Arc<dyn ForceGenerator<S>>) {
         self.registry.register(body, generator);
     }
 
     pub fn start_frame(&mut self) {
         for body in self.bodies.iter_mut() {
-            body.rc().borrow_mut().clear_accumulators();
-            body.rc().borrow_mut().calculate_derived_data();
+            body.arc().write().unwrap().clear_accumulators();
+            body.arc().write().unwrap().calculate_derived_data();
         }
     }
 
     fn integrate(&mut self, duration: S) {
         for body in self.bodies.iter_mut() {
-            body.rc().borrow_mut().integrate(duration);
+            body.arc().write().unwrap().integrate(duration);
         }
     }
 
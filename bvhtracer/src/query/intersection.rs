@@ -1,5 +1,6 @@
 use super::ray::*;
 use cglinalg::{
+    Vector3,
     SimdScalarFloat,
     SimdScalar,
 };
@@ -25,24 +26,87 @@ where
     }
 }
 
+#[cfg(not(feature = "wide_indices"))]
+mod packing {
+    /// The underlying storage for a packed instance primitive index.
+    pub type Storage = u32;
+    /// The number of bits used for the primitive index.
+    pub const PRIMITIVE_BITS: u32 = 20;
+    /// The number of bits used for the instance index.
+    pub const INSTANCE_BITS: u32 = 12;
+}
+
+#[cfg(feature = "wide_indices")]
+mod packing {
+    /// The underlying storage for a packed instance primitive index.
+    pub type Storage = u64;
+    /// The number of bits used for the primitive index.
+    pub const PRIMITIVE_BITS: u32 = 32;
+    /// The number of bits used for the instance index.
+    pub const INSTANCE_BITS: u32 = 32;
+}
+
+use packing::Storage;
+
+const PRIMITIVE_MASK: Storage = Storage::MAX >> (Storage::BITS - packing::PRIMITIVE_BITS);
+const INSTANCE_MASK: Storage = Storage::MAX >> (Storage::BITS - packing::INSTANCE_BITS);
+
 /// An instance primitive index that marks out which instance of a mesh in a 
 /// scene we are intersection querying, as well as which primitive in that mesh 
 /// we are making a query about.
+/// 
+/// By default, the instance and primitive indices are packed into 12 bits and 
+/// 20 bits respectively, which limits a scene to 4096 instances and a mesh to 
+/// 1048576 primitives. Enabling the `wide_indices` feature packs each index into 
+/// 32 bits instead, at the cost of a larger intersection record.
 #[repr(transparent)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct InstancePrimitiveIndex {
     /// The underlying storage for the instance and primitive indices. Instance and 
     /// primitive indices are packed into a single `u32`. This enables a `f32`
     /// intersection result to fit into one 16 byte cache line. This is optimizing 
-    /// memory footprint for the GPU as well as the CPU.
-    data: u32,
+    /// memory footprint for the GPU as well as the CPU. With the `wide_indices` 
+    /// feature enabled, the indices are packed into a single `u64`.
+    data: Storage,
 }
 
 impl InstancePrimitiveIndex {
+    /// The largest instance index that an instance primitive index can represent.
+    pub const MAX_INSTANCE_INDEX: u32 = INSTANCE_MASK as u32;
+
+    /// The largest primitive index that an instance primitive index can represent.
+    pub const MAX_PRIMITIVE_INDEX: u32 = PRIMITIVE_MASK as u32;
+
     /// Construct a new instance primitive index.
-    pub const fn new(instance_index: u32, primitive_index: u32) -> Self {        
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if either index is too large to represent. Use 
+    /// [`InstancePrimitiveIndex::try_new`] when the indices are not known to fit.
+    pub const fn new(instance_index: u32, primitive_index: u32) -> Self {
+        assert!(
+            instance_index <= Self::MAX_INSTANCE_INDEX,
+            "The instance index is too large for an instance primitive index."
+        );
+        assert!(
+            primitive_index <= Self::MAX_PRIMITIVE_INDEX,
+            "The primitive index is too large for an instance primitive index."
+        );
+        let instance_data = (instance_index as Storage) & INSTANCE_MASK;
+        let primitive_data = (primitive_index as Storage) & PRIMITIVE_MASK;
+
         Self { 
-            data: ((instance_index & 0x00000FFF) << 20) | (primitive_index & 0x000FFFFF),
+            data: (instance_data << packing::PRIMITIVE_BITS) | primitive_data,
+        }
+    }
+
+    /// Construct a new instance primitive index, returning `None` if either 
+    /// index is too large to represent.
+    pub const fn try_new(instance_index: u32, primitive_index: u32) -> Option<Self> {
+        if instance_index <= Self::MAX_INSTANCE_INDEX && primitive_index <= Self::MAX_PRIMITIVE_INDEX {
+            Some(Self::new(instance_index, primitive_index))
+        } else {
+            None
         }
     }
 
@@ -50,20 +114,30 @@ impl InstancePrimitiveIndex {
     /// for an intersection test.
     #[inline]
     pub const fn instance_index(self) -> u32 {
-        (self.data & 0xFFF00000) >> 20
+        ((self.data >> packing::PRIMITIVE_BITS) & INSTANCE_MASK) as u32
     }
 
     /// Get the indes of the primitive from the mesh that we are querying in 
     /// a scene for an intersection test.
     #[inline]
     pub const fn primitive_index(self) -> u32 {
-        self.data & 0x000FFFFF
+        (self.data & PRIMITIVE_MASK) as u32
     }
 
     #[inline]
     pub const fn from_primitive(primitive_index: u32) -> Self {
         Self::new(0, primitive_index)
     }
+
+    /// Replace the instance index, keeping the primitive index. 
+    /// 
+    /// A bottom level acceleration structure only knows about the primitives in its 
+    /// own mesh, so the top level acceleration structure uses this to record which 
+    /// instance in the scene a hit primitive belongs to.
+    #[inline]
+    pub const fn with_instance(self, instance_index: u32) -> Self {
+        Self::new(instance_index, self.primitive_index())
+    }
 }
 
 impl Default for InstancePrimitiveIndex {
@@ -106,5 +180,114 @@ where
             instance_primitive: InstancePrimitiveIndex::default(),
         }
     }
+
+    /// The position of the hit along the ray of the intersection.
+    #[inline]
+    pub fn position(&self) -> Vector3<S> {
+        self.ray.interpolate(self.interaction.t)
+    }
+
+    /// Construct a ray leaving the hit position of the intersection in the 
+    /// direction `direction`, where `normal` is the geometric normal of the surface 
+    /// at the hit. 
+    /// 
+    /// The ray lives in the same space as the ray of the intersection, i.e. in 
+    /// model space for an intersection found by a model or a scene. Like 
+    /// [`Ray::spawn`], the origin of the ray is pushed off the surface and the ray 
+    /// reports hits from distance zero on, but the offset also accounts for the 
+    /// rounding error of tracing the hit from the origin of the intersection's ray.
+    pub fn spawn_ray(&self, normal: &Vector3<S>, direction: &Vector3<S>) -> Ray<S> {
+        let position = self.position();
+        let magnitude = S::max(max_abs_component(&position), max_abs_component(&self.ray.origin));
+        let origin = offset_point(&position, normal, direction, magnitude);
+
+        Ray::from_interval(origin, *direction, S::zero(), S::max_value())
+    }
 }
 
+
+
+#[cfg(test)]
+mod instance_primitive_index_tests {
+    use super::*;
+
+
+    #[test]
+    fn test_instance_primitive_index_round_trip() {
+        let instance_primitive = InstancePrimitiveIndex::new(7, 1234);
+
+        assert_eq!(instance_primitive.instance_index(), 7);
+        assert_eq!(instance_primitive.primitive_index(), 1234);
+    }
+
+    #[test]
+    fn test_instance_primitive_index_max_indices_round_trip() {
+        let max_instance_index = InstancePrimitiveIndex::MAX_INSTANCE_INDEX;
+        let max_primitive_index = InstancePrimitiveIndex::MAX_PRIMITIVE_INDEX;
+        let instance_primitive = InstancePrimitiveIndex::new(max_instance_index, max_primitive_index);
+
+        assert_eq!(instance_primitive.instance_index(), max_instance_index);
+        assert_eq!(instance_primitive.primitive_index(), max_primitive_index);
+    }
+
+    #[test]
+    fn test_instance_primitive_index_with_instance() {
+        let instance_primitive = InstancePrimitiveIndex::from_primitive(42).with_instance(3);
+
+        assert_eq!(instance_primitive.instance_index(), 3);
+        assert_eq!(instance_primitive.primitive_index(), 42);
+    }
+
+    #[test]
+    fn test_instance_primitive_index_try_new_out_of_range() {
+        let max_instance_index = InstancePrimitiveIndex::MAX_INSTANCE_INDEX;
+        let max_primitive_index = InstancePrimitiveIndex::MAX_PRIMITIVE_INDEX;
+        
+        if max_instance_index < u32::MAX {
+            assert!(InstancePrimitiveIndex::try_new(max_instance_index + 1, 0).is_none());
+        }
+        if max_primitive_index < u32::MAX {
+            assert!(InstancePrimitiveIndex::try_new(0, max_primitive_index + 1).is_none());
+        }
+        assert!(InstancePrimitiveIndex::try_new(max_instance_index, max_primitive_index).is_some());
+    }
+
+    #[cfg(not(feature = "wide_indices"))]
+    #[test]
+    #[should_panic]
+    fn test_instance_primitive_index_new_out_of_range_instance() {
+        InstancePrimitiveIndex::new(InstancePrimitiveIndex::MAX_INSTANCE_INDEX + 1, 0);
+    }
+
+    #[cfg(not(feature = "wide_indices"))]
+    #[test]
+    #[should_panic]
+    fn test_instance_primitive_index_new_out_of_range_primitive() {
+        InstancePrimitiveIndex::new(0, InstancePrimitiveIndex::MAX_PRIMITIVE_INDEX + 1);
+    }
+
+    #[cfg(not(feature = "wide_indices"))]
+    #[test]
+    fn test_instance_primitive_index_compact_limits() {
+        assert_eq!(InstancePrimitiveIndex::MAX_INSTANCE_INDEX, 4095);
+        assert_eq!(InstancePrimitiveIndex::MAX_PRIMITIVE_INDEX, 1048575);
+    }
+
+    #[cfg(feature = "wide_indices")]
+    #[test]
+    fn test_instance_primitive_index_wide_limits() {
+        assert_eq!(InstancePrimitiveIndex::MAX_INSTANCE_INDEX, u32::MAX);
+        assert_eq!(InstancePrimitiveIndex::MAX_PRIMITIVE_INDEX, u32::MAX);
+    }
+
+    /// Primitive indices past the compact limit of one million primitives should
+    /// survive the round trip in wide mode.
+    #[cfg(feature = "wide_indices")]
+    #[test]
+    fn test_instance_primitive_index_wide_large_indices() {
+        let instance_primitive = InstancePrimitiveIndex::new(70000, 5_000_000);
+
+        assert_eq!(instance_primitive.instance_index(), 70000);
+        assert_eq!(instance_primitive.primitive_index(), 5_000_000);
+    }
+}
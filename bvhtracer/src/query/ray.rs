@@ -5,6 +5,14 @@ use cglinalg::{
 };
 
 
+/// The number of units in the last place of the largest coordinate of a point 
+/// that [`offset_ray_origin`] pushes the point off its surface by. This leaves a 
+/// wide margin over the few units of rounding error in a computed hit position.
+const ORIGIN_OFFSET_ULPS: f64 = 32_f64;
+
+
+/// A ray that only reports hits in the interval `(t_min, t)` of distances along 
+/// the ray.
 #[derive(Copy, Clone, Debug, PartialEq, Default)]
 pub struct Ray<S> 
 where
@@ -13,6 +21,10 @@ where
     pub origin: Vector3<S>,
     pub direction: Vector3<S>,
     pub recip_direction: Vector3<S>,
+    /// The distance along the ray below which hits are ignored, so that a ray 
+    /// leaving a surface does not hit that surface again at its origin.
+    pub t_min: S,
+    /// The distance along the ray beyond which hits are ignored.
     pub t: S,
 }
 
@@ -20,18 +32,57 @@ impl<S> Ray<S>
 where
     S: SimdScalarFloat
 {
+    /// Construct a new ray that ignores hits closer than 
+    /// [`Ray::default_t_min`] along the ray.
     pub fn new(origin: Vector3<S>, direction: Vector3<S>, t: S) -> Self {
+        Self::from_interval(origin, direction, Self::default_t_min(), t)
+    }
+
+    pub fn from_origin_dir(origin: Vector3<S>, direction: Vector3<S>) -> Self {
+        Self::new(origin, direction, S::max_value())
+    }
+
+    /// Construct a new ray that only reports hits between the distances `t_min` 
+    /// and `t_max` along the ray.
+    pub fn from_interval(origin: Vector3<S>, direction: Vector3<S>, t_min: S, t_max: S) -> Self {
         let recip_direction = Vector3::new(
             S::one() / direction.x, 
             S::one() / direction.y, 
             S::one() / direction.z
         );
 
-        Self { origin, direction, recip_direction, t, }
+        Self { origin, direction, recip_direction, t_min, t: t_max, }
     }
 
-    pub fn from_origin_dir(origin: Vector3<S>, direction: Vector3<S>) -> Self {
-        Self::new(origin, direction, S::max_value())
+    /// Construct a ray leaving the surface with geometric normal `normal` at the 
+    /// point `point` in the direction `direction`.
+    /// 
+    /// The origin of the ray is pushed off the surface with 
+    /// [`offset_ray_origin`], so the ray reports hits from distance zero on 
+    /// without hitting the surface it leaves.
+    pub fn spawn(point: &Vector3<S>, normal: &Vector3<S>, direction: &Vector3<S>) -> Self {
+        let origin = offset_ray_origin(point, normal, direction);
+
+        Self::from_interval(origin, *direction, S::zero(), S::max_value())
+    }
+
+    /// The default distance along a ray below which hits are ignored.
+    #[inline]
+    pub fn default_t_min() -> S {
+        num_traits::cast(0.0001_f64).unwrap()
+    }
+
+    /// Set the distance along the ray below which hits are ignored.
+    pub fn with_t_min(mut self, t_min: S) -> Self {
+        self.t_min = t_min;
+
+        self
+    }
+
+    /// Determine whether the ray reports hits at the distance `t` along the ray.
+    #[inline]
+    pub fn in_interval(&self, t: S) -> bool {
+        t > self.t_min && t < self.t
     }
 
     pub fn interpolate(&self, t: S) -> Vector3<S> {
@@ -39,3 +90,42 @@ where
     }
 }
 
+/// Push the point `point` on a surface with geometric normal `normal` off the 
+/// surface, to the side that a ray leaving the point in the direction `direction` 
+/// goes to.
+/// 
+/// A point computed from a ray hit is only accurate up to rounding, so it can 
+/// lie slightly on either side of the surface. The offset grows with the largest 
+/// coordinate of the point, since the rounding error does, which keeps the offset 
+/// large enough far from the origin and small enough for fine details near it.
+pub fn offset_ray_origin<S>(point: &Vector3<S>, normal: &Vector3<S>, direction: &Vector3<S>) -> Vector3<S> 
+where
+    S: SimdScalarFloat
+{
+    offset_point(point, normal, direction, max_abs_component(point))
+}
+
+/// The largest magnitude of the coordinates of `vector`.
+#[inline]
+pub(crate) fn max_abs_component<S>(vector: &Vector3<S>) -> S 
+where
+    S: SimdScalarFloat
+{
+    S::max(S::abs(vector.x), S::max(S::abs(vector.y), S::abs(vector.z)))
+}
+
+/// Push the point `point` along the normal `normal` by a multiple of the unit 
+/// in the last place of `magnitude`, to the side of the direction `direction`.
+pub(crate) fn offset_point<S>(point: &Vector3<S>, normal: &Vector3<S>, direction: &Vector3<S>, magnitude: S) -> Vector3<S> 
+where
+    S: SimdScalarFloat
+{
+    let ulps: S = num_traits::cast(ORIGIN_OFFSET_ULPS).unwrap();
+    let offset = magnitude * S::epsilon() * ulps;
+    if normal.dot(direction) < S::zero() {
+        point - normal * offset
+    } else {
+        point + normal * offset
+    }
+}
+
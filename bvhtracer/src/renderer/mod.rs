@@ -0,0 +1,8 @@
+mod renderer;
+mod thread_pool;
+mod tile;
+
+
+pub use renderer::*;
+pub use tile::*;
+
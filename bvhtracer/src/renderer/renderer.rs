@@ -0,0 +1,1118 @@
+use crate::texture_buffer::*;
+use crate::materials::*;
+use crate::scene::*;
+use crate::lights::{
+    SHADOW_RAY_LENGTH_SCALE,
+};
+use crate::mesh::{
+    TextureCoordinates,
+};
+use crate::model::{
+    Model,
+    ModelGeometry,
+};
+use super::tile::*;
+use super::thread_pool::*;
+use crate::query::{
+    Intersection,
+    Ray,
+};
+use cglinalg::{
+    Magnitude,
+    SimdScalarFloat,
+    Vector2,
+    Vector3,
+};
+use rand::{
+    Rng,
+    SeedableRng,
+};
+use rand_isaac::{
+    IsaacRng,
+};
+use std::f32;
+use std::sync::{
+    Arc,
+};
+
+
+/// A buffer of per-pixel radiance estimates that converges over many frames.
+/// 
+/// Each pixel holds the running sum of the radiance samples taken through it, 
+/// and the number of samples in the sum. The estimate of the radiance through 
+/// a pixel is the average of its samples.
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccumulationBuffer<S> {
+    data: Vec<Vector3<S>>,
+    sample_counts: Vec<u32>,
+    width: usize,
+    height: usize,
+}
+
+impl<S> AccumulationBuffer<S>
+where
+    S: SimdScalarFloat,
+{
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            data: vec![Vector3::zero(); width * height],
+            sample_counts: vec![0; width * height],
+            width,
+            height,
+        }
+    }
+
+    /// Construct an accumulation buffer where each pixel holds a single 
+    /// sample of `value`.
+    pub fn from_fill(width: usize, height: usize, value: Vector3<S>) -> Self {
+        Self {
+            data: vec![value; width * height],
+            sample_counts: vec![1; width * height],
+            width,
+            height,
+        }
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[inline]
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width, self.height)
+    }
+
+    #[inline]
+    fn pixel_address(&self, x: usize, y: usize) -> usize {
+        debug_assert!(x < self.width && y < self.height);
+
+        x + y * self.width
+    }
+
+    /// Add `sample_count` samples whose radiance sums to `radiance` to the 
+    /// pixel at `(x, y)`.
+    pub fn accumulate(&mut self, x: usize, y: usize, radiance: &Vector3<S>, sample_count: u32) {
+        let pixel_address = self.pixel_address(x, y);
+        self.data[pixel_address] += radiance;
+        self.sample_counts[pixel_address] += sample_count;
+    }
+
+    /// The running sum of the radiance samples of the pixel at `(x, y)`.
+    #[inline]
+    pub fn sum(&self, x: usize, y: usize) -> Vector3<S> {
+        self.data[self.pixel_address(x, y)]
+    }
+
+    /// The number of radiance samples accumulated for the pixel at `(x, y)`.
+    #[inline]
+    pub fn sample_count(&self, x: usize, y: usize) -> u32 {
+        self.sample_counts[self.pixel_address(x, y)]
+    }
+
+    /// The average radiance of the samples of the pixel at `(x, y)`. A pixel 
+    /// without any samples has zero radiance.
+    pub fn average(&self, x: usize, y: usize) -> Vector3<S> {
+        let pixel_address = self.pixel_address(x, y);
+        let sample_count = self.sample_counts[pixel_address];
+        if sample_count > 0 {
+            let sample_count_s: S = num_traits::cast(sample_count).unwrap();
+            self.data[pixel_address] / sample_count_s
+        } else {
+            Vector3::zero()
+        }
+    }
+
+    /// Discard every accumulated sample, e.g. after the camera or the scene 
+    /// changes so that the old samples no longer estimate the radiance through 
+    /// each pixel.
+    pub fn reset(&mut self) {
+        for sum in self.data.iter_mut() {
+            *sum = Vector3::zero();
+        }
+        for sample_count in self.sample_counts.iter_mut() {
+            *sample_count = 0;
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FrameBuffer<P> 
+where
+    P: Pixel,
+{
+    data: TextureBuffer2D<P, Vec<P::Subpixel>>,
+}
+
+impl<P> FrameBuffer<P> 
+where
+    P: Pixel,
+{
+    pub fn from_fill(width: usize, height: usize, value: P) -> Self {
+        Self {
+            data: TextureBuffer2D::from_fill(width, height, value),
+        }
+    }
+
+    pub fn flip_vertical(&mut self) {
+        self.data.flip_vertical();
+    }
+
+    pub fn as_buffer(&self) -> &TextureBuffer2D<P, Vec<P::Subpixel>> {
+        &self.data
+    }
+
+    #[inline]
+    pub fn width(&self) -> usize {
+        self.data.width()
+    }
+
+    #[inline]
+    pub fn height(&self) -> usize {
+        self.data.height()
+    }
+
+    #[inline]
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.width(), self.height())
+    }
+}
+
+pub struct RendererState {
+    pixel_shader: Box<dyn PixelShader>,
+    accumulator: Box<dyn Accumulator>,
+    accumulation_buffer: AccumulationBuffer<f32>,
+    frame_buffer: FrameBuffer<Rgba<u8>>,
+}
+
+impl RendererState {
+    pub fn new(accumulator: Box<dyn Accumulator>, pixel_shader: Box<dyn PixelShader>, width: usize, height: usize) -> Self {
+        let accumulation_buffer = AccumulationBuffer::new(width, height);
+        let frame_buffer = FrameBuffer::from_fill(
+            width, 
+            height,
+            Rgba::from([0, 0, 0, 255])
+        );
+
+        Self { pixel_shader, accumulator, accumulation_buffer, frame_buffer, }
+    }
+
+    pub fn frame_buffer(&self) -> &FrameBuffer<Rgba<u8>> {
+        &self.frame_buffer
+    }
+
+    pub fn frame_buffer_mut(&mut self) -> &mut FrameBuffer<Rgba<u8>> {
+        &mut self.frame_buffer
+    }
+
+    pub fn accumulation_buffer(&self) -> &AccumulationBuffer<f32> {
+        &self.accumulation_buffer
+    }
+
+    /// Discard the samples accumulated in previous frames. This should be called
+    /// whenever the camera or the scene changes.
+    pub fn reset_accumulation(&mut self) {
+        self.accumulation_buffer.reset();
+    }
+}
+
+pub trait Integrator {
+    fn evaluate(&mut self, renderer_state: &mut RendererState, scene: &Scene) -> usize;
+}
+
+pub trait Accumulator {
+    fn evaluate(&mut self, scene: &Scene, ray: &Ray<f32>) -> Vector3<f32>;
+}
+
+pub trait PixelShader {
+    /// Convert a radiance estimate into a color.
+    fn evaluate(&self, accumulation_buffer: &mut AccumulationBuffer<f32>, radiance: &Vector3<f32>) -> Rgba<u8>;
+
+    /// Compute the color of the pixel at `(x, y)` from the average of the 
+    /// samples accumulated for it.
+    fn resolve(&self, accumulation_buffer: &mut AccumulationBuffer<f32>, x: usize, y: usize) -> Rgba<u8> {
+        let radiance = accumulation_buffer.average(x, y);
+
+        self.evaluate(accumulation_buffer, &radiance)
+    }
+}
+
+pub struct RadianceToRgbShader {}
+
+impl RadianceToRgbShader {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl PixelShader for RadianceToRgbShader {
+    fn evaluate(&self, accumulation_buffer: &mut AccumulationBuffer<f32>, radiance: &Vector3<f32>) -> Rgba<u8> {
+        let r = u8::min(255, (255_f32 * radiance.x) as u8);
+        let g = u8::min(255, (255_f32 * radiance.y) as u8);
+        let b = u8::min(255, (255_f32 * radiance.z) as u8);
+
+        Rgba::new(r, g, b, 255)
+    }
+}
+
+pub struct IntersectionAccumulator {
+    hit_value: Vector3<f32>,
+    miss_value: Vector3<f32>,
+}
+
+impl IntersectionAccumulator {
+    pub fn new(hit_value: Vector3<f32>, miss_value: Vector3<f32>) -> Self {
+        Self { hit_value, miss_value, }
+    }
+}
+
+impl Accumulator for IntersectionAccumulator {
+    fn evaluate(&mut self, scene: &Scene, ray: &Ray<f32>) -> Vector3<f32> {
+        if let Some(_) = scene.intersect(&ray) {
+            self.hit_value
+        } else {
+            self.miss_value
+        }
+    }
+}
+
+pub struct IntersectionShader {
+    hit_value: Rgba<u8>,
+    miss_value: Rgba<u8>,
+}
+
+impl IntersectionShader {
+    pub fn new(hit_value: Rgba<u8>, miss_value: Rgba<u8>) -> Self {
+        Self { hit_value, miss_value, }
+    }
+}
+
+impl PixelShader for IntersectionShader {
+    fn evaluate(&self, accumulation_buffer: &mut AccumulationBuffer<f32>, radiance: &Vector3<f32>) -> Rgba<u8> {
+        if !radiance.is_zero() {
+            self.hit_value
+        } else {
+            self.miss_value
+        }
+    }
+}
+
+/// An accumulator that records the distance to the nearest surface along each 
+/// primary ray.
+/// 
+/// A hit is recorded as the distance in the first component and a coverage of one
+/// in the second, and a miss as zero in both. Misses then drop out of the running 
+/// sum of the distances, so the sums stay finite over any number of frames, and 
+/// the average distance of the hits is the ratio of the two components.
+pub struct DepthAccumulator {}
+
+impl DepthAccumulator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Accumulator for DepthAccumulator {
+    fn evaluate(&mut self, scene: &Scene, ray: &Ray<f32>) -> Vector3<f32> {
+        if let Some(intersection) = scene.intersect(&ray) {
+            Vector3::new(intersection.interaction.t, 1_f32, 0_f32)
+        } else {
+            Vector3::zero()
+        }
+    }
+}
+
+pub struct DepthMappingShader {
+    scale: f32,
+    offset: f32,
+}
+
+impl DepthMappingShader {
+    pub fn new(scale: f32, offset: f32) -> Self {
+        Self { scale, offset, }
+    }
+}
+
+impl PixelShader for DepthMappingShader {
+    fn evaluate(&self, accumulation_buffer: &mut AccumulationBuffer<f32>, radiance: &Vector3<f32>) -> Rgba<u8> {
+        // The radiance is the average of the samples of a `DepthAccumulator`, so the
+        // coverage is the fraction of the samples that hit a surface.
+        let coverage = radiance.y;
+        if coverage > 0_f32 {
+            let nearest_t = radiance.x / coverage;
+            let _color = 255 - (((nearest_t - self.offset) * self.scale) as i32) as u32;
+            let c = _color * 0x010101;
+            let r = ((c & 0x00FF0000) >> 16) as u8;
+            let g = ((c & 0x0000FF00) >> 8) as u8;
+            let b = (c & 0x000000FF) as u8;
+            
+            Rgba::new(r, g, b, 255)
+        } else {
+            Rgba::new(0, 0, 0, 255)
+        }
+    }
+}
+
+
+pub struct UvMappingAccumulator {}
+
+impl UvMappingAccumulator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Accumulator for UvMappingAccumulator {
+    fn evaluate(&mut self, scene: &Scene, ray: &Ray<f32>) -> Vector3<f32> {
+        if let Some(intersection) = scene.intersect(&ray) {
+            Vector3::new(
+                intersection.interaction.u, 
+                intersection.interaction.v, 
+                1_f32 - (intersection.interaction.u + intersection.interaction.v)
+            )
+        } else {
+            Vector3::zero()
+        }
+    }
+}
+
+
+pub struct NormalMappingAccumulator {}
+
+impl NormalMappingAccumulator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Accumulator for NormalMappingAccumulator {
+    fn evaluate(&mut self, scene: &Scene, ray: &Ray<f32>) -> Vector3<f32> {
+        if let Some(intersection) = scene.intersect(ray) {
+            let instance_index = intersection.instance_primitive.instance_index();
+            let normal = {
+                let _normal_model_space = {
+                    let model = scene.get_unchecked(instance_index as usize).model().model();
+                    let borrow = model.borrow();
+                    borrow.interpolated_normal(&intersection)
+                };
+                let object = scene.get_unchecked(instance_index as usize);
+                let _normal_world_space = object
+                    .get_transform()
+                    .transform_vector(&_normal_model_space);
+                let normalized = (_normal_world_space).normalize();
+                (normalized + Vector3::from_fill(1_f32)) * 0.5
+            };
+
+            normal
+        } else {
+            Vector3::zero()
+        }
+    }
+}
+
+
+pub struct TextureMaterialAccumulator {}
+
+impl TextureMaterialAccumulator {
+    pub fn new() -> Self {
+        Self {}
+    }
+}
+
+impl Accumulator for TextureMaterialAccumulator {
+    fn evaluate(&mut self, scene: &Scene, ray: &Ray<f32>) -> Vector3<f32> {
+        if let Some(intersection) = scene.intersect(ray) {
+            let instance_index = intersection.instance_primitive.instance_index();
+            let uv_coords = { 
+                let model = scene.get_unchecked(instance_index as usize).model().model();
+                let borrow = model.borrow();
+                borrow.interpolated_tex_coords(&intersection)
+            };
+            let albedo = {
+                let model = scene.get_unchecked(instance_index as usize).model().model();
+                let borrow = model.borrow();
+                let material = borrow.material_at(&intersection);
+                material.albedo(&uv_coords)
+            };
+
+            albedo
+        } else {
+            Vector3::zero()
+        }
+    }
+}
+
+
+/// Trace every pixel of the frame buffer tile by tile, adding the radiance computed
+/// by `radiance` for each pixel to the accumulation buffer as one sample.
+fn trace_tiles<F>(tiles: &[Tile], accumulation_buffer: &mut AccumulationBuffer<f32>, mut radiance: F) -> usize
+where
+    F: FnMut(usize, usize) -> Vector3<f32>,
+{
+    let mut rays_traced = 0;
+    for tile in tiles.iter() {
+        for (x, y) in tile.pixels() {
+            accumulation_buffer.accumulate(x, y, &radiance(x, y), 1);
+            rays_traced += 1;
+        }
+    }
+
+    rays_traced
+}
+
+/// Convert the radiance accumulated in the accumulation buffer into colors in the 
+/// frame buffer using the renderer's pixel shader.
+fn resolve_tiles(tiles: &[Tile], renderer_state: &mut RendererState) {
+    for tile in tiles.iter() {
+        for (x, y) in tile.pixels() {
+            let color = renderer_state.pixel_shader.resolve(&mut renderer_state.accumulation_buffer, x, y);
+            renderer_state.frame_buffer.data[(x, y)] = color;
+        }
+    }
+}
+
+
+/// An integrator that traces one primary ray per pixel and hands it to the 
+/// renderer's accumulator. This is useful for debug visualizations such as depth, 
+/// normal, and texture mapping.
+pub struct PrimaryRayTracer {
+    tile_scheduler: TileScheduler,
+}
+
+impl PrimaryRayTracer {
+    pub fn new() -> Self {
+        Self {
+            tile_scheduler: TileScheduler::default(),
+        }
+    }
+
+    /// Set how the frame buffer is partitioned into tiles, and the order in 
+    /// which the tiles are rendered.
+    pub fn with_tile_scheduler(mut self, tile_scheduler: TileScheduler) -> Self {
+        self.tile_scheduler = tile_scheduler;
+
+        self
+    }
+}
+
+impl Integrator for PrimaryRayTracer {
+    fn evaluate(&mut self, renderer_state: &mut RendererState, scene: &Scene) -> usize {
+        let (width, height) = renderer_state.frame_buffer.dimensions();
+        let tiles = self.tile_scheduler.schedule(width, height);
+        let accumulator = &mut renderer_state.accumulator;
+        let rays_traced = trace_tiles(&tiles, &mut renderer_state.accumulation_buffer, |x, y| {
+            let ray = scene.active_camera().get_ray_world(
+                x as f32 / width as f32,
+                y as f32 / height as f32,
+            );
+
+            accumulator.evaluate(scene, &ray)
+        });
+
+        resolve_tiles(&tiles, renderer_state);
+
+        rays_traced
+    }
+}
+
+
+/// The surface properties at a ray hit that the path tracer needs to continue a path.
+#[derive(Clone, Debug)]
+struct SurfaceData {
+    /// The intersection with its ray in world space, which spawns the rays 
+    /// leaving the surface.
+    intersection: Intersection<f32>,
+    /// The world space hit position.
+    position: Vector3<f32>,
+    /// The world space geometric normal, facing the incoming ray.
+    geometric_normal: Vector3<f32>,
+    /// The shading frame the material is evaluated in.
+    shading_frame: ShadingFrame,
+    /// The texture coordinates at the hit position.
+    uv: Vector2<f32>,
+    /// The material of the primitive that was hit.
+    material: Arc<dyn Material>,
+}
+
+/// Compute the partial derivatives of the position on a triangle with respect 
+/// to its texture coordinates. Returns `None` when the texture coordinates of 
+/// the triangle are degenerate.
+fn position_derivatives(
+    vertices: &[Vector3<f32>; 3], 
+    tex_coords: &TextureCoordinates<f32, 3>
+) -> Option<(Vector3<f32>, Vector3<f32>)> 
+{
+    let duv02 = tex_coords[0] - tex_coords[2];
+    let duv12 = tex_coords[1] - tex_coords[2];
+    let dp02 = vertices[0] - vertices[2];
+    let dp12 = vertices[1] - vertices[2];
+    let determinant = duv02.x * duv12.y - duv02.y * duv12.x;
+    if f32::abs(determinant) < 1e-12 {
+        return None;
+    }
+    let inv_determinant = 1_f32 / determinant;
+    let dpdu = (dp02 * duv12.y - dp12 * duv02.y) * inv_determinant;
+    let dpdv = (dp12 * duv02.x - dp02 * duv12.x) * inv_determinant;
+
+    Some((dpdu, dpdv))
+}
+
+/// The power heuristic weight of a sample drawn with density `pdf` by a sampling 
+/// strategy, combined with another strategy that draws it with density `other_pdf`.
+fn power_heuristic(pdf: f32, other_pdf: f32) -> f32 {
+    let pdf_squared = pdf * pdf;
+    let other_pdf_squared = other_pdf * other_pdf;
+    if pdf_squared + other_pdf_squared == 0_f32 {
+        return 0_f32;
+    }
+
+    pdf_squared / (pdf_squared + other_pdf_squared)
+}
+
+/// Express an intersection found by a scene with the world space ray that was 
+/// traced, in place of the model space ray that the models intersected.
+fn world_intersection(ray: &Ray<f32>, intersection: &Intersection<f32>) -> Intersection<f32> {
+    Intersection::new(*ray, intersection.interaction, intersection.instance_primitive)
+}
+
+fn surface_data(scene: &Scene, ray: &Ray<f32>, intersection: &Intersection<f32>) -> SurfaceData {
+    let instance_index = intersection.instance_primitive.instance_index() as usize;
+    let object = scene.get_unchecked(instance_index);
+    let model = object.model().model();
+    let borrow = model.borrow();
+    match borrow.geometry() {
+        ModelGeometry::Mesh(_) => mesh_surface_data(object, &borrow, ray, intersection),
+        ModelGeometry::Spheres(..) => sphere_surface_data(object, &borrow, ray, intersection),
+        ModelGeometry::Shapes(..) => shape_surface_data(object, &borrow, ray, intersection),
+        ModelGeometry::ImplicitSurfaces(..) => implicit_surface_data(object, &borrow, ray, intersection),
+        ModelGeometry::Voxels(_) => voxel_surface_data(object, &borrow, ray, intersection),
+    }
+}
+
+fn mesh_surface_data(object: &SceneObject, model: &Model, ray: &Ray<f32>, intersection: &Intersection<f32>) -> SurfaceData {
+    let primitive_index = intersection.instance_primitive.primitive_index() as usize;
+    let u = intersection.interaction.u;
+    let v = intersection.interaction.v;
+    let w = 1_f32 - u - v;
+
+    let position = ray.interpolate(intersection.interaction.t);
+    let vertices = {
+        let primitive = model.primitives()[primitive_index];
+        [
+            object.get_transform().transform_point(&primitive.vertices[0]),
+            object.get_transform().transform_point(&primitive.vertices[1]),
+            object.get_transform().transform_point(&primitive.vertices[2]),
+        ]
+    };
+    let outward_normal = (vertices[1] - vertices[0]).cross(&(vertices[2] - vertices[0])).normalize();
+    let geometric_normal = if outward_normal.dot(&ray.direction) > 0_f32 { 
+        -outward_normal 
+    } else { 
+        outward_normal 
+    };
+    let material = model.material(primitive_index).clone();
+    let shading_normal = {
+        let normals = model.normals()[primitive_index];
+        let normal_model_space = normals[0] * w + normals[1] * u + normals[2] * v;
+        let normal_world_space = object.get_transform().transform_vector(&normal_model_space);
+        if normal_world_space.magnitude_squared() > 0_f32 {
+            let normal = normal_world_space.normalize();
+            if material.is_transmissive() {
+                normal
+            } else if normal.dot(&geometric_normal) < 0_f32 { 
+                // Mesh normals can point away from the geometric normal on the side the 
+                // ray arrived from, so flip them to the same hemisphere.
+                -normal 
+            } else { 
+                normal 
+            }
+        } else if material.is_transmissive() {
+            outward_normal
+        } else {
+            // Meshes without vertex normals fall back to the geometric normal.
+            geometric_normal
+        }
+    };
+    let tex_coords = model.tex_coords()[primitive_index];
+    let uv = tex_coords[0] * w + tex_coords[1] * u + tex_coords[2] * v;
+    let shading_normal = match material.bump_map() {
+        Some(bump_map) => match position_derivatives(&vertices, &tex_coords) {
+            Some((dpdu, dpdv)) => bump_map.perturb_normal(&shading_normal, &dpdu, &dpdv, &uv),
+            None => shading_normal,
+        }
+        None => shading_normal,
+    };
+    let shading_frame = ShadingFrame::from_normal(&shading_normal);
+
+    SurfaceData { intersection: world_intersection(ray, intersection), position, geometric_normal, shading_frame, uv, material, }
+}
+
+fn voxel_surface_data(object: &SceneObject, model: &Model, ray: &Ray<f32>, intersection: &Intersection<f32>) -> SurfaceData {
+    let position = ray.interpolate(intersection.interaction.t);
+    let voxel_grid = model.voxel_grid().unwrap();
+    let hit = model.voxel_hit(intersection).unwrap();
+    // The face of the voxel faces the ray already.
+    let geometric_normal = {
+        let normal_matrix = object.get_transform_inv().compute_matrix().transpose();
+        (normal_matrix * hit.normal.extend(0_f32)).contract().normalize()
+    };
+    let material = model.material_at(intersection).clone();
+    let uv = Vector2::new(intersection.interaction.u, intersection.interaction.v);
+    let shading_normal = match material.bump_map() {
+        Some(bump_map) => {
+            // The face coordinates run along the two axes after the axis of the normal.
+            let entry_axis = (0..3).find(|&axis| hit.normal[axis] != 0_f32).unwrap();
+            let mut dpdu = Vector3::zero();
+            let mut dpdv = Vector3::zero();
+            dpdu[(entry_axis + 1) % 3] = voxel_grid.voxel_size();
+            dpdv[(entry_axis + 2) % 3] = voxel_grid.voxel_size();
+            let dpdu = object.get_transform().transform_vector(&dpdu);
+            let dpdv = object.get_transform().transform_vector(&dpdv);
+            bump_map.perturb_normal(&geometric_normal, &dpdu, &dpdv, &uv)
+        }
+        None => geometric_normal,
+    };
+    let shading_frame = ShadingFrame::from_normal(&shading_normal);
+
+    SurfaceData { intersection: world_intersection(ray, intersection), position, geometric_normal, shading_frame, uv, material, }
+}
+
+fn shape_surface_data(object: &SceneObject, model: &Model, ray: &Ray<f32>, intersection: &Intersection<f32>) -> SurfaceData {
+    let primitive_index = intersection.instance_primitive.primitive_index() as usize;
+    let shape = model.shapes()[primitive_index];
+    let position = ray.interpolate(intersection.interaction.t);
+    let position_model_space = object.get_transform_inv().transform_point(&position);
+    let outward_normal = {
+        let normal_model_space = shape.normal(&position_model_space);
+        let normal_matrix = object.get_transform_inv().compute_matrix().transpose();
+        (normal_matrix * normal_model_space.extend(0_f32)).contract().normalize()
+    };
+    let geometric_normal = if outward_normal.dot(&ray.direction) > 0_f32 { 
+        -outward_normal 
+    } else { 
+        outward_normal 
+    };
+    let material = model.material(primitive_index).clone();
+    let shading_normal = if material.is_transmissive() {
+        outward_normal
+    } else {
+        geometric_normal
+    };
+    let uv = Vector2::new(intersection.interaction.u, intersection.interaction.v);
+    let shading_normal = match material.bump_map() {
+        Some(bump_map) => {
+            let (dpdu, dpdv) = shape.position_derivatives(&position_model_space);
+            let dpdu = object.get_transform().transform_vector(&dpdu);
+            let dpdv = object.get_transform().transform_vector(&dpdv);
+            bump_map.perturb_normal(&shading_normal, &dpdu, &dpdv, &uv)
+        }
+        None => shading_normal,
+    };
+    let shading_frame = ShadingFrame::from_normal(&shading_normal);
+
+    SurfaceData { intersection: world_intersection(ray, intersection), position, geometric_normal, shading_frame, uv, material, }
+}
+
+fn implicit_surface_data(object: &SceneObject, model: &Model, ray: &Ray<f32>, intersection: &Intersection<f32>) -> SurfaceData {
+    let primitive_index = intersection.instance_primitive.primitive_index() as usize;
+    let position = ray.interpolate(intersection.interaction.t);
+    let outward_normal = {
+        let normal_model_space = model.interpolated_normal(intersection);
+        let normal_matrix = object.get_transform_inv().compute_matrix().transpose();
+        (normal_matrix * normal_model_space.extend(0_f32)).contract().normalize()
+    };
+    let geometric_normal = if outward_normal.dot(&ray.direction) > 0_f32 { 
+        -outward_normal 
+    } else { 
+        outward_normal 
+    };
+    let material = model.material(primitive_index).clone();
+    // An implicit surface has no texture coordinates to bump map it with.
+    let shading_normal = if material.is_transmissive() {
+        outward_normal
+    } else {
+        geometric_normal
+    };
+    let uv = Vector2::new(intersection.interaction.u, intersection.interaction.v);
+    let shading_frame = ShadingFrame::from_normal(&shading_normal);
+
+    SurfaceData { intersection: world_intersection(ray, intersection), position, geometric_normal, shading_frame, uv, material, }
+}
+
+fn sphere_surface_data(object: &SceneObject, model: &Model, ray: &Ray<f32>, intersection: &Intersection<f32>) -> SurfaceData {
+    let primitive_index = intersection.instance_primitive.primitive_index() as usize;
+    let sphere = model.spheres()[primitive_index];
+    let position = ray.interpolate(intersection.interaction.t);
+    let position_model_space = object.get_transform_inv().transform_point(&position);
+    let outward_normal = {
+        // Normals transform by the inverse transpose of the model transform.
+        let normal_model_space = sphere.normal(&position_model_space);
+        let normal_matrix = object.get_transform_inv().compute_matrix().transpose();
+        (normal_matrix * normal_model_space.extend(0_f32)).contract().normalize()
+    };
+    let geometric_normal = if outward_normal.dot(&ray.direction) > 0_f32 { 
+        -outward_normal 
+    } else { 
+        outward_normal 
+    };
+    let material = model.material(primitive_index).clone();
+    // The surface of a sphere is smooth, so it shades with its geometric normal.
+    let shading_normal = if material.is_transmissive() {
+        outward_normal
+    } else {
+        geometric_normal
+    };
+    let uv = Vector2::new(intersection.interaction.u, intersection.interaction.v);
+    let shading_normal = match material.bump_map() {
+        Some(bump_map) => {
+            let (dpdu, dpdv) = sphere.position_derivatives(&position_model_space);
+            let dpdu = object.get_transform().transform_vector(&dpdu);
+            let dpdv = object.get_transform().transform_vector(&dpdv);
+            bump_map.perturb_normal(&shading_normal, &dpdu, &dpdv, &uv)
+        }
+        None => shading_normal,
+    };
+    let shading_frame = ShadingFrame::from_normal(&shading_normal);
+
+    SurfaceData { intersection: world_intersection(ray, intersection), position, geometric_normal, shading_frame, uv, material, }
+}
+
+/// A unidirectional path tracer. 
+///
+/// Each camera path bounces through the scene by sampling the material of 
+/// each surface it hits, and collects the background radiance when it escapes 
+/// the scene. At every non-specular surface, the lights in the scene are 
+/// sampled directly. Lights with a surface, such as area lights, also add the 
+/// radiance they emit toward a path that runs into them, and the two ways of 
+/// finding a light are combined with multiple importance sampling, so a light 
+/// is seen directly by the camera and through mirrors and glass. Paths are 
+/// terminated after a maximum number of bounces, or earlier by Russian roulette 
+/// once they pass the Russian roulette depth.
+///
+/// The path tracer computes its own radiance samples for each pixel, so the 
+/// renderer state's accumulator is not used. The samples of each frame are added
+/// to the accumulation buffer, so a static scene converges over successive frames 
+/// until the accumulation is reset. The average is converted to a color using the
+/// renderer state's pixel shader.
+///
+/// Tiles are rendered in parallel on a pool of worker threads that share the 
+/// scene. Each tile draws its random numbers from its own generator, seeded 
+/// from the path tracer's seed, the frame number, and the tile's position, so 
+/// a frame renders identically for any number of worker threads.
+pub struct PathTracer {
+    max_depth: usize,
+    russian_roulette_depth: usize,
+    samples_per_pixel: usize,
+    background: Vector3<f32>,
+    tile_scheduler: TileScheduler,
+    thread_pool: ThreadPool,
+    seed: u64,
+    frame: u64,
+}
+
+impl PathTracer {
+    pub fn new() -> Self {
+        Self {
+            max_depth: 5,
+            russian_roulette_depth: 3,
+            samples_per_pixel: 1,
+            background: Vector3::from_fill(1_f32),
+            tile_scheduler: TileScheduler::default(),
+            thread_pool: ThreadPool::new(0),
+            seed: 0,
+            frame: 0,
+        }
+    }
+
+    /// Set the maximum number of surface interactions along a path.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+
+        self
+    }
+
+    /// Set the number of bounces after which Russian roulette may terminate a path.
+    pub fn with_russian_roulette_depth(mut self, russian_roulette_depth: usize) -> Self {
+        self.russian_roulette_depth = russian_roulette_depth;
+
+        self
+    }
+
+    /// Set the number of paths traced through each pixel per frame.
+    pub fn with_samples_per_pixel(mut self, samples_per_pixel: usize) -> Self {
+        assert!(samples_per_pixel > 0);
+        self.samples_per_pixel = samples_per_pixel;
+
+        self
+    }
+
+    /// Set the radiance arriving from outside the scene along paths that 
+    /// escape it.
+    pub fn with_background(mut self, background: Vector3<f32>) -> Self {
+        self.background = background;
+
+        self
+    }
+
+    /// Set how the frame buffer is partitioned into tiles, and the order in 
+    /// which the tiles are rendered.
+    pub fn with_tile_scheduler(mut self, tile_scheduler: TileScheduler) -> Self {
+        self.tile_scheduler = tile_scheduler;
+
+        self
+    }
+
+    /// Set the number of worker threads that render tiles. A worker count of zero
+    /// uses one worker per available hardware thread, which is the default.
+    pub fn with_worker_count(mut self, worker_count: usize) -> Self {
+        self.thread_pool = ThreadPool::new(worker_count);
+
+        self
+    }
+
+    /// Set the seed for the path tracer's random number generators.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+
+        self
+    }
+
+    /// The number of worker threads that render tiles.
+    pub fn worker_count(&self) -> usize {
+        self.thread_pool.worker_count()
+    }
+
+    /// Compute the seed of the random number generator for a tile in the current frame.
+    fn tile_seed(&self, tile: &Tile) -> u64 {
+        // The SplitMix64 finalizer, which scatters nearby inputs across the 
+        // entire range of seeds.
+        fn mix(value: u64) -> u64 {
+            let mut z = value.wrapping_add(0x9E37_79B9_7F4A_7C15);
+            z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+            z ^ (z >> 31)
+        }
+
+        let seed = mix(self.seed);
+        let seed = mix(seed ^ self.frame);
+        let seed = mix(seed ^ tile.x() as u64);
+        
+        mix(seed ^ tile.y() as u64)
+    }
+
+    /// Render the pixels of a tile in scanline order.
+    /// 
+    /// Returns the sum of the radiance samples for each pixel and the number of 
+    /// rays traced.
+    fn render_tile(&self, scene: &Scene, tile: &Tile, width: usize, height: usize) -> (Vec<Vector3<f32>>, usize) {
+        let mut rng = IsaacRng::seed_from_u64(self.tile_seed(tile));
+        let mut radiance_sums = Vec::with_capacity(tile.area());
+        let mut rays_traced = 0;
+        for (x, y) in tile.pixels() {
+            let mut radiance = Vector3::zero();
+            for _ in 0..self.samples_per_pixel {
+                let u = (x as f32 + rng.gen::<f32>()) / width as f32;
+                let v = (y as f32 + rng.gen::<f32>()) / height as f32;
+                let ray = scene.active_camera().get_ray_world(u, v);
+                let (path_radiance, path_rays_traced) = self.trace_path(scene, &ray, &mut rng);
+                radiance += path_radiance;
+                rays_traced += path_rays_traced;
+            }
+            radiance_sums.push(radiance);
+        }
+
+        (radiance_sums, rays_traced)
+    }
+
+    /// Estimate the light arriving at a surface directly from the lights in a scene, 
+    /// scattered toward the viewer along `wo` by the surface, with one shadow ray 
+    /// per light.
+    /// 
+    /// When `is_bsdf_sampled` is set, the path continues by sampling the material, 
+    /// and the lights with a surface are also found by that sample, so their 
+    /// contribution here is weighted against it.
+    fn sample_direct_lighting(
+        &self, 
+        scene: &Scene, 
+        surface: &SurfaceData, 
+        wo: &Vector3<f32>, 
+        is_bsdf_sampled: bool, 
+        rng: &mut IsaacRng
+    ) -> Vector3<f32> 
+    {
+        let origin = surface.intersection.spawn_ray(&surface.geometric_normal, &surface.geometric_normal).origin;
+        let mut direct_radiance = Vector3::zero();
+        for light in scene.lights().iter() {
+            let u = Vector2::new(rng.gen::<f32>(), rng.gen::<f32>());
+            let sample = match light.sample(&surface.position, &u) {
+                Some(sample) if sample.pdf > 0_f32 => sample,
+                _ => continue,
+            };
+            if sample.direction.dot(&surface.geometric_normal) <= 0_f32 {
+                continue;
+            }
+            let wi = surface.shading_frame.to_local(&sample.direction);
+            let bsdf = surface.material.eval(&surface.uv, wo, &wi);
+            if bsdf == Vector3::zero() {
+                continue;
+            }
+            if scene.occluded(&sample.shadow_ray(&origin)) {
+                continue;
+            }
+            let weight = if is_bsdf_sampled && !light.is_delta() {
+                power_heuristic(sample.pdf, surface.material.pdf(&surface.uv, wo, &wi))
+            } else {
+                1_f32
+            };
+            direct_radiance += bsdf.component_mul(&sample.radiance) * (weight * f32::abs(wi.z) / sample.pdf);
+        }
+
+        direct_radiance
+    }
+
+    /// The radiance emitted toward the origin of `ray` by the lights with a surface 
+    /// that the ray reaches before its closest hit in the scene, at distance 
+    /// `t_hit` along the ray.
+    /// 
+    /// The direction of the ray was sampled from the material at its origin with 
+    /// density `bsdf_pdf`, or is `None` for camera rays and specular bounces. Those 
+    /// cannot find the lights by sampling them directly, so they count the light 
+    /// in full, and the others are weighted against sampling the lights directly.
+    fn emitted_radiance(&self, scene: &Scene, ray: &Ray<f32>, t_hit: f32, bsdf_pdf: Option<f32>) -> Vector3<f32> {
+        let direction = ray.direction.normalize();
+        let distance_hit = t_hit * ray.direction.magnitude();
+        let mut emitted_radiance = Vector3::zero();
+        for light in scene.lights().iter() {
+            if light.is_delta() {
+                continue;
+            }
+            // A light is seen exactly when a shadow ray toward it would be unoccluded, 
+            // so a light on the surface of an object in the scene is seen when the ray 
+            // hits that object.
+            match light.distance(&ray.origin, &direction) {
+                Some(distance) if distance * SHADOW_RAY_LENGTH_SCALE < distance_hit => {}
+                _ => continue,
+            }
+            let radiance = light.eval(&ray.origin, &direction);
+            let weight = match bsdf_pdf {
+                Some(bsdf_pdf) => power_heuristic(bsdf_pdf, light.pdf(&ray.origin, &direction)),
+                None => 1_f32,
+            };
+            emitted_radiance += radiance * weight;
+        }
+
+        emitted_radiance
+    }
+
+    /// Estimate the radiance arriving at the origin of `ray` along the ray.
+    /// 
+    /// Returns the radiance estimate and the number of rays traced.
+    fn trace_path(&self, scene: &Scene, ray: &Ray<f32>, rng: &mut IsaacRng) -> (Vector3<f32>, usize) {
+        let mut radiance = Vector3::zero();
+        let mut throughput = Vector3::from_fill(1_f32);
+        let mut current_ray = *ray;
+        let mut bsdf_pdf = None;
+        let mut rays_traced = 0;
+        for depth in 0..self.max_depth {
+            rays_traced += 1;
+            let hit = scene.intersect(&current_ray);
+            let t_hit = hit.map(|intersection| intersection.interaction.t).unwrap_or(f32::MAX);
+            let emitted_radiance = self.emitted_radiance(scene, &current_ray, t_hit, bsdf_pdf);
+            radiance += throughput.component_mul(&emitted_radiance);
+            let intersection = match hit {
+                Some(intersection) => intersection,
+                None => {
+                    radiance += throughput.component_mul(&self.background);
+                    break;
+                }
+            };
+            let surface = surface_data(scene, &current_ray, &intersection);
+            let wo = surface.shading_frame.to_local(&(-current_ray.direction.normalize()));
+
+            // Sample the lights directly at every non-specular bounce. The path finds 
+            // the lights with a surface by running into them too, unless this is its 
+            // last bounce.
+            if !surface.material.is_specular() {
+                let is_bsdf_sampled = depth + 1 < self.max_depth;
+                let direct_radiance = self.sample_direct_lighting(scene, &surface, &wo, is_bsdf_sampled, rng);
+                radiance += throughput.component_mul(&direct_radiance);
+            }
+
+            let u = Vector2::new(rng.gen::<f32>(), rng.gen::<f32>());
+            let sample = match surface.material.sample(&surface.uv, &wo, &u) {
+                Some(sample) if sample.pdf > 0_f32 => sample,
+                _ => break,
+            };
+            let direction = surface.shading_frame.to_world(&sample.direction);
+            let cos_geometric = direction.dot(&surface.geometric_normal);
+            let is_reflection = sample.direction.z * wo.z > 0_f32;
+            if cos_geometric == 0_f32 || is_reflection != (cos_geometric > 0_f32) {
+                // The shading normal bent the sample to the wrong side of the surface.
+                break;
+            }
+            throughput = throughput.component_mul(&sample.weight());
+            bsdf_pdf = if sample.is_specular || surface.material.is_specular() {
+                None
+            } else {
+                Some(sample.pdf)
+            };
+
+            if depth + 1 >= self.russian_roulette_depth {
+                let max_component = f32::max(throughput.x, f32::max(throughput.y, throughput.z));
+                let survival_probability = f32::min(0.95_f32, max_component);
+                if rng.gen::<f32>() >= survival_probability {
+                    break;
+                }
+                throughput /= survival_probability;
+            }
+
+            current_ray = surface.intersection.spawn_ray(&surface.geometric_normal, &direction);
+        }
+
+        (radiance, rays_traced)
+    }
+}
+
+impl Integrator for PathTracer {
+    fn evaluate(&mut self, renderer_state: &mut RendererState, scene: &Scene) -> usize {
+        let (width, height) = renderer_state.frame_buffer.dimensions();
+        let tiles = self.tile_scheduler.schedule(width, height);
+        let rendered_tiles = self.thread_pool.map(&tiles, |tile| {
+            self.render_tile(scene, tile, width, height)
+        });
+        let samples_per_pixel = self.samples_per_pixel as u32;
+        let mut rays_traced = 0;
+        for (tile, (radiance_sums, tile_rays_traced)) in tiles.iter().zip(rendered_tiles.iter()) {
+            for ((x, y), radiance) in tile.pixels().zip(radiance_sums.iter()) {
+                renderer_state.accumulation_buffer.accumulate(x, y, radiance, samples_per_pixel);
+            }
+            rays_traced += tile_rays_traced;
+        }
+
+        resolve_tiles(&tiles, renderer_state);
+        self.frame += 1;
+
+        rays_traced
+    }
+}
+
+
+pub struct Renderer {
+    integrator: Box<dyn Integrator>,
+}
+
+impl Renderer {
+    pub fn new(pipeline: Box<dyn Integrator>) -> Self {
+        Self { integrator: pipeline, }
+    }
+
+    pub fn render(&mut self, renderer_state: &mut RendererState, scene: &Scene) -> usize {
+        self.integrator.evaluate(renderer_state, scene)
+    }
+}
+
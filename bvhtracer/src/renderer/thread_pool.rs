@@ -0,0 +1,165 @@
+use std::panic;
+use std::sync::atomic::{
+    AtomicUsize,
+    Ordering,
+};
+use std::thread;
+
+
+/// A pool of worker threads that process a batch of tasks in parallel.
+///
+/// The pool only holds the number of workers, so constructing one spawns no
+/// threads. The workers of a batch are scoped threads, so tasks can borrow data
+/// owned by the caller, such as a scene, without reference counting it. Each
+/// worker pulls the next unprocessed task from a shared counter, so the workload
+/// balances itself when some tasks are more expensive than others.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct ThreadPool {
+    worker_count: usize,
+}
+
+impl ThreadPool {
+    /// Construct a new thread pool. A worker count of zero uses one worker per
+    /// available hardware thread. A pool with a single worker runs its tasks on
+    /// the calling thread.
+    pub(crate) fn new(worker_count: usize) -> Self {
+        let worker_count = if worker_count == 0 {
+            thread::available_parallelism()
+                .map(|count| count.get())
+                .unwrap_or(1)
+        } else {
+            worker_count
+        };
+
+        Self { worker_count, }
+    }
+
+    #[inline]
+    pub(crate) const fn worker_count(&self) -> usize {
+        self.worker_count
+    }
+
+    /// Apply `f` to every task, returning the results in the same order as
+    /// the tasks regardless of which worker processed each one.
+    ///
+    /// # Panics
+    ///
+    /// If `f` panics on any task, this function panics with the same payload
+    /// once every worker has finished.
+    pub(crate) fn map<T, R, F>(&self, tasks: &[T], f: F) -> Vec<R>
+    where
+        T: Sync,
+        R: Send,
+        F: Fn(&T) -> R + Sync,
+    {
+        let worker_count = usize::min(self.worker_count, tasks.len());
+        if worker_count <= 1 {
+            return tasks.iter().map(f).collect();
+        }
+
+        let next_task = AtomicUsize::new(0);
+        let worker_results = thread::scope(|scope| {
+            let workers = (0..worker_count)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut results = vec![];
+                        loop {
+                            let index = next_task.fetch_add(1, Ordering::Relaxed);
+                            if index >= tasks.len() {
+                                break;
+                            }
+                            results.push((index, f(&tasks[index])));
+                        }
+
+                        results
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            // Join every worker before resuming a panic, so the scope does not
+            // replace its payload with one of its own.
+            workers.into_iter()
+                .map(|worker| worker.join())
+                .collect::<Vec<_>>()
+        });
+
+        let mut indexed_results = Vec::with_capacity(tasks.len());
+        for results in worker_results.into_iter() {
+            match results {
+                Ok(results) => indexed_results.extend(results),
+                Err(payload) => panic::resume_unwind(payload),
+            }
+        }
+        indexed_results.sort_unstable_by_key(|(index, _)| *index);
+
+        indexed_results.into_iter()
+            .map(|(_, result)| result)
+            .collect()
+    }
+}
+
+
+#[cfg(test)]
+mod thread_pool_tests {
+    use super::*;
+    use std::collections::HashSet;
+    use std::panic::{
+        AssertUnwindSafe,
+    };
+
+
+    #[test]
+    fn test_thread_pool_map_preserves_task_order() {
+        let thread_pool = ThreadPool::new(4);
+        let tasks = (0..100).collect::<Vec<usize>>();
+        let expected = tasks.iter().map(|task| task * task).collect::<Vec<_>>();
+        let result = thread_pool.map(&tasks, |task| task * task);
+
+        assert_eq!(result, expected);
+    }
+
+    /// Every batch runs on at most the worker count of threads, none of which is
+    /// the caller's.
+    #[test]
+    fn test_thread_pool_map_runs_on_workers() {
+        let thread_pool = ThreadPool::new(3);
+        let tasks = (0..64).collect::<Vec<usize>>();
+        for _ in 0..4 {
+            let thread_ids = thread_pool.map(&tasks, |_| thread::current().id())
+                .into_iter()
+                .collect::<HashSet<_>>();
+
+            assert!(thread_ids.len() <= thread_pool.worker_count());
+            assert!(!thread_ids.contains(&thread::current().id()));
+        }
+    }
+
+    #[test]
+    fn test_thread_pool_single_worker_runs_on_caller() {
+        let thread_pool = ThreadPool::new(1);
+        let tasks = (0..8).collect::<Vec<usize>>();
+        let thread_ids = thread_pool.map(&tasks, |_| thread::current().id());
+
+        assert!(thread_ids.iter().all(|thread_id| *thread_id == thread::current().id()));
+    }
+
+    /// A panicking task panics the caller with its payload once the batch is done,
+    /// and the pool keeps working afterwards.
+    #[test]
+    fn test_thread_pool_map_propagates_panics() {
+        let thread_pool = ThreadPool::new(2);
+        let tasks = (0..16).collect::<Vec<usize>>();
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            thread_pool.map(&tasks, |task| {
+                if *task == 7 {
+                    panic!("task 7");
+                }
+                *task
+            })
+        }));
+        let payload = result.unwrap_err();
+
+        assert_eq!(payload.downcast_ref::<&str>(), Some(&"task 7"));
+        assert_eq!(thread_pool.map(&tasks, |task| *task), tasks);
+    }
+}
@@ -0,0 +1,220 @@
+/// The order in which a tile scheduler visits the tiles covering a frame buffer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TileOrder {
+    /// Visit tiles row by row, left to right, starting from the first row.
+    Scanline,
+    /// Visit tiles along a Hilbert curve, so that consecutive tiles are
+    /// neighbors in the frame buffer.
+    Hilbert,
+    /// Visit tiles in a spiral, starting from the tile at the center of the
+    /// frame buffer and working outwards.
+    Spiral,
+}
+
+/// A rectangular block of pixels in a frame buffer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Tile {
+    x: usize,
+    y: usize,
+    width: usize,
+    height: usize,
+}
+
+impl Tile {
+    pub const fn new(x: usize, y: usize, width: usize, height: usize) -> Self {
+        Self { x, y, width, height, }
+    }
+
+    /// The horizontal pixel coordinate of the top left corner of the tile.
+    #[inline]
+    pub const fn x(&self) -> usize {
+        self.x
+    }
+
+    /// The vertical pixel coordinate of the top left corner of the tile.
+    #[inline]
+    pub const fn y(&self) -> usize {
+        self.y
+    }
+
+    /// The width of the tile in pixels.
+    #[inline]
+    pub const fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The height of the tile in pixels.
+    #[inline]
+    pub const fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The number of pixels in the tile.
+    #[inline]
+    pub const fn area(&self) -> usize {
+        self.width * self.height
+    }
+
+    /// Iterate over the pixel coordinates `(x, y)` of the tile in scanline order.
+    pub fn pixels(&self) -> impl Iterator<Item = (usize, usize)> {
+        let (x0, y0, width, height) = (self.x, self.y, self.width, self.height);
+
+        (y0..(y0 + height)).flat_map(move |y| (x0..(x0 + width)).map(move |x| (x, y)))
+    }
+}
+
+/// Partitions a frame buffer into tiles for rendering.
+///
+/// The tiles cover the frame buffer exactly once. When the frame buffer dimensions
+/// are not multiples of the tile dimensions, the tiles on the right and bottom
+/// edges of the frame buffer are clipped to fit inside it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TileScheduler {
+    tile_width: usize,
+    tile_height: usize,
+    order: TileOrder,
+}
+
+impl TileScheduler {
+    /// Construct a new tile scheduler.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if either tile dimension is zero.
+    pub fn new(tile_width: usize, tile_height: usize, order: TileOrder) -> Self {
+        assert!(tile_width > 0, "Tile width must be nonzero.");
+        assert!(tile_height > 0, "Tile height must be nonzero.");
+
+        Self { tile_width, tile_height, order, }
+    }
+
+    #[inline]
+    pub const fn tile_width(&self) -> usize {
+        self.tile_width
+    }
+
+    #[inline]
+    pub const fn tile_height(&self) -> usize {
+        self.tile_height
+    }
+
+    #[inline]
+    pub const fn order(&self) -> TileOrder {
+        self.order
+    }
+
+    /// The number of tiles along each axis needed to cover a frame buffer
+    /// of dimensions `width` by `height`.
+    pub const fn tile_counts(&self, width: usize, height: usize) -> (usize, usize) {
+        let tile_count_x = (width + self.tile_width - 1) / self.tile_width;
+        let tile_count_y = (height + self.tile_height - 1) / self.tile_height;
+
+        (tile_count_x, tile_count_y)
+    }
+
+    /// Compute the tiles covering a frame buffer of dimensions `width` by `height`
+    /// in the scheduler's tile order.
+    pub fn schedule(&self, width: usize, height: usize) -> Vec<Tile> {
+        let (tile_count_x, tile_count_y) = self.tile_counts(width, height);
+        let tile_indices = match self.order {
+            TileOrder::Scanline => scanline_order(tile_count_x, tile_count_y),
+            TileOrder::Hilbert => hilbert_order(tile_count_x, tile_count_y),
+            TileOrder::Spiral => spiral_order(tile_count_x, tile_count_y),
+        };
+
+        tile_indices.iter()
+            .map(|&(tile_x, tile_y)| {
+                let x = tile_x * self.tile_width;
+                let y = tile_y * self.tile_height;
+                let tile_width = usize::min(self.tile_width, width - x);
+                let tile_height = usize::min(self.tile_height, height - y);
+
+                Tile::new(x, y, tile_width, tile_height)
+            })
+            .collect()
+    }
+}
+
+impl Default for TileScheduler {
+    fn default() -> Self {
+        Self::new(8, 8, TileOrder::Scanline)
+    }
+}
+
+fn scanline_order(tile_count_x: usize, tile_count_y: usize) -> Vec<(usize, usize)> {
+    (0..tile_count_y)
+        .flat_map(|tile_y| (0..tile_count_x).map(move |tile_x| (tile_x, tile_y)))
+        .collect()
+}
+
+/// Convert a distance `d` along a Hilbert curve filling a `side` by `side` grid
+/// into grid coordinates. The side length must be a power of two.
+fn hilbert_curve_to_grid(side: usize, d: usize) -> (usize, usize) {
+    let mut x = 0;
+    let mut y = 0;
+    let mut t = d;
+    let mut s = 1;
+    while s < side {
+        let rx = 1 & (t / 2);
+        let ry = 1 & (t ^ rx);
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - x;
+                y = s - 1 - y;
+            }
+            core::mem::swap(&mut x, &mut y);
+        }
+        x += s * rx;
+        y += s * ry;
+        t /= 4;
+        s *= 2;
+    }
+
+    (x, y)
+}
+
+fn hilbert_order(tile_count_x: usize, tile_count_y: usize) -> Vec<(usize, usize)> {
+    // Walk a Hilbert curve over the smallest power of two sized square containing
+    // the tile grid, skipping the cells that fall outside of it.
+    let side = usize::max(tile_count_x, tile_count_y).next_power_of_two();
+    (0..(side * side))
+        .map(|d| hilbert_curve_to_grid(side, d))
+        .filter(|&(tile_x, tile_y)| tile_x < tile_count_x && tile_y < tile_count_y)
+        .collect()
+}
+
+fn spiral_order(tile_count_x: usize, tile_count_y: usize) -> Vec<(usize, usize)> {
+    let tile_count = tile_count_x * tile_count_y;
+    let mut tiles = Vec::with_capacity(tile_count);
+    if tile_count == 0 {
+        return tiles;
+    }
+
+    // Walk a square spiral outwards from the center tile with legs of length
+    // 1, 1, 2, 2, 3, 3, ..., keeping only the cells that land inside the tile grid.
+    let directions = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+    let mut tile_x = ((tile_count_x - 1) / 2) as isize;
+    let mut tile_y = ((tile_count_y - 1) / 2) as isize;
+    let mut leg_length = 1;
+    let mut direction = 0;
+    tiles.push((tile_x as usize, tile_y as usize));
+    while tiles.len() < tile_count {
+        for _ in 0..2 {
+            let (dx, dy) = directions[direction];
+            for _ in 0..leg_length {
+                tile_x += dx;
+                tile_y += dy;
+                let in_grid_x = tile_x >= 0 && (tile_x as usize) < tile_count_x;
+                let in_grid_y = tile_y >= 0 && (tile_y as usize) < tile_count_y;
+                if in_grid_x && in_grid_y {
+                    tiles.push((tile_x as usize, tile_y as usize));
+                }
+            }
+            direction = (direction + 1) % 4;
+        }
+        leg_length += 1;
+    }
+
+    tiles
+}
+
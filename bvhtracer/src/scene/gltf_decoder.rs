@@ -0,0 +1,600 @@
+use crate::camera::*;
+use crate::file_resolver::*;
+use crate::materials::*;
+use crate::mesh::*;
+use crate::model::*;
+use crate::physics::*;
+use crate::texture_buffer::*;
+use crate::transform::*;
+use crate::geometry::*;
+use super::scene::*;
+use super::scene_object::*;
+use cglinalg::{
+    Degrees,
+    Magnitude,
+    Matrix4x4,
+    Vector2,
+    Vector3,
+};
+use std::error;
+use std::fmt;
+use std::io;
+use std::io::{
+    Read,
+};
+use std::path::{
+    Path,
+    PathBuf,
+};
+use std::sync::{
+    Arc,
+};
+
+
+pub type GltfResult<T> = Result<T, GltfError>;
+
+/// The aspect ratio of a perspective camera that does not specify one.
+const DEFAULT_ASPECT_RATIO: f32 = 1_f32;
+
+/// The far plane of a perspective camera with an infinite projection.
+const DEFAULT_ZFAR: f32 = 10000_f32;
+
+/// The index of refraction of a material that does not specify one.
+const DEFAULT_IOR: f32 = 1.5;
+
+
+#[derive(Debug)]
+pub enum GltfError {
+    /// The glTF document or binary container could not be parsed.
+    Gltf(::gltf::Error),
+    /// A buffer or image could not be read from its file resolver.
+    Resolve { path: PathBuf, error: io::Error },
+    /// A texture could not be decoded.
+    Texture(TextureBufferError),
+    /// The contents of the document are not valid.
+    InvalidData(String),
+    IoError(io::Error),
+}
+
+impl fmt::Display for GltfError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            GltfError::Gltf(err) => {
+                write!(formatter, "Could not parse glTF document: {}", err)
+            }
+            GltfError::Resolve { path, error } => {
+                write!(formatter, "Could not read {}: {}", path.display(), error)
+            }
+            GltfError::Texture(err) => {
+                write!(formatter, "{}", err)
+            }
+            GltfError::InvalidData(message) => {
+                write!(formatter, "Invalid glTF data: {}", message)
+            }
+            GltfError::IoError(err) => {
+                write!(formatter, "An error occurred in reading from the reader: {}", err)
+            }
+        }
+    }
+}
+
+impl error::Error for GltfError {}
+
+
+/// A camera read from a glTF document, placed in the world by a node.
+#[derive(Clone, Debug)]
+pub enum GltfCamera {
+    Perspective {
+        name: String,
+        camera: Camera<f32, PerspectiveProjection<f32>>,
+    },
+    Orthographic {
+        name: String,
+        camera: Camera<f32, OrthographicProjection<f32>>,
+    },
+}
+
+impl GltfCamera {
+    pub fn name(&self) -> &str {
+        match self {
+            GltfCamera::Perspective { name, .. } => name,
+            GltfCamera::Orthographic { name, .. } => name,
+        }
+    }
+}
+
+/// A node of the node hierarchy of a glTF scene.
+#[derive(Clone, Debug)]
+pub struct GltfNode {
+    /// The name of the node, or an empty string for unnamed nodes.
+    pub name: String,
+    /// The index of the parent of the node in the list of nodes, if any.
+    pub parent: Option<usize>,
+    /// The transform from the model space of the node to world space, which
+    /// includes the transforms of every ancestor of the node.
+    pub transform: Transform3<f32>,
+    /// The index of the mesh that the node places in the world, if any.
+    pub mesh: Option<usize>,
+    /// The index of the camera that the node places in the world, if any.
+    pub camera: Option<usize>,
+}
+
+/// The contents of a glTF scene.
+#[derive(Clone, Debug)]
+pub struct GltfScene {
+    meshes: Vec<NamedMesh>,
+    models: Vec<Option<ModelInstance>>,
+    nodes: Vec<GltfNode>,
+    cameras: Vec<GltfCamera>,
+}
+
+impl GltfScene {
+    /// The meshes of the document, one for each glTF mesh, with the triangles
+    /// of every primitive of the glTF mesh.
+    pub fn meshes(&self) -> &[NamedMesh] {
+        &self.meshes
+    }
+
+    /// The model for the mesh with index `mesh_index`, or `None` if the mesh has
+    /// no triangles. A model is shared by every node that places its mesh.
+    pub fn model(&self, mesh_index: usize) -> Option<&ModelInstance> {
+        self.models[mesh_index].as_ref()
+    }
+
+    /// The nodes of the scene, in depth first order, with every parent before
+    /// its children.
+    pub fn nodes(&self) -> &[GltfNode] {
+        &self.nodes
+    }
+
+    /// The cameras placed in the scene by its nodes.
+    pub fn cameras(&self) -> &[GltfCamera] {
+        &self.cameras
+    }
+
+    /// The first perspective camera in the scene, if any.
+    pub fn perspective_camera(&self) -> Option<&Camera<f32, PerspectiveProjection<f32>>> {
+        self.cameras.iter().find_map(|camera| match camera {
+            GltfCamera::Perspective { camera, .. } => Some(camera),
+            _ => None,
+        })
+    }
+
+    /// Construct a scene object for every node that places a mesh, with a rigid
+    /// body registered in `physics`.
+    pub fn scene_objects(&self, physics: &mut World<f32>) -> Vec<SceneObject> {
+        self.nodes.iter()
+            .filter_map(|node| {
+                let model = node.mesh.and_then(|mesh_index| self.model(mesh_index))?;
+                let rigid_body_instance = physics.register_body(RigidBody::default());
+                let object = SceneObjectBuilder::new(model.clone(), rigid_body_instance)
+                    .with_transform(&node.transform)
+                    .build();
+
+                Some(object)
+            })
+            .collect()
+    }
+
+    /// Construct a scene with the scene objects of the glTF scene, viewed from
+    /// `camera`.
+    pub fn build_scene(&self, camera: Camera<f32, PerspectiveProjection<f32>>) -> Scene {
+        let mut physics = World::new();
+        let objects = self.scene_objects(&mut physics);
+
+        SceneBuilder::new(camera)
+            .with_physics(physics)
+            .with_objects(objects)
+            .build()
+    }
+}
+
+
+/// A decoder for glTF 2.0 scenes, in either the `.gltf` JSON format or the
+/// `.glb` binary format.
+///
+/// Buffers and images are read from the binary chunk of a `.glb` file, from
+/// base64 encoded data URIs, or through a [`FileResolver`] relative to
+/// `directory`, the directory of the glTF file in the resolver. The default
+/// scene of the document is read, or the first scene if there is no default.
+///
+/// Triangle lists, strips and fans are read from each mesh primitive, along with
+/// the normals and first set of texture coordinates when present. Primitives
+/// without normals get flat normals, as the glTF specification requires. Points
+/// and lines are skipped. The metallic-roughness materials of the document are
+/// mapped onto the crate's materials:
+///
+/// * Materials with a transmission factor above one half, from the
+///   `KHR_materials_transmission` extension, become a [`DielectricMaterial`].
+/// * Materials with a metallic factor of at least one half become a
+///   [`ConductorMaterial`].
+/// * All other materials become a [`PlasticMaterial`].
+///
+/// The base color texture replaces the base color factor when both are given.
+/// Metallic-roughness, normal, occlusion and emissive textures are not used.
+pub struct GltfDecoder<'b, R, F: ?Sized> {
+    reader: R,
+    resolver: &'b F,
+    directory: PathBuf,
+}
+
+impl<'b, R, F> GltfDecoder<'b, R, F>
+where
+    R: Read,
+    F: FileResolver + ?Sized,
+{
+    pub fn new(reader: R, resolver: &'b F) -> Self {
+        Self {
+            reader,
+            resolver,
+            directory: PathBuf::new(),
+        }
+    }
+
+    /// Set the directory of the glTF file in the file resolver.
+    pub fn with_directory<P>(mut self, directory: P) -> Self
+    where
+        P: AsRef<Path>
+    {
+        self.directory = directory.as_ref().to_path_buf();
+
+        self
+    }
+
+    pub fn read_scene(mut self) -> GltfResult<GltfScene> {
+        let mut data = vec![];
+        self.reader.read_to_end(&mut data).map_err(GltfError::IoError)?;
+        let gltf = ::gltf::Gltf::from_slice(&data).map_err(GltfError::Gltf)?;
+        let document = &gltf.document;
+        let buffers = document.buffers()
+            .map(|buffer| self.read_buffer(&buffer, gltf.blob.as_deref()))
+            .collect::<GltfResult<Vec<_>>>()?;
+        let materials = self.read_materials(document, &buffers)?;
+        let mut meshes = vec![];
+        let mut models = vec![];
+        for mesh in document.meshes() {
+            let (named_mesh, model) = read_mesh(&mesh, &buffers, &materials)?;
+            meshes.push(named_mesh);
+            models.push(model);
+        }
+        let mut nodes = vec![];
+        let mut cameras = vec![];
+        let scene = document.default_scene().or_else(|| document.scenes().next());
+        if let Some(scene) = scene {
+            for node in scene.nodes() {
+                read_node(&node, None, &Transform3::identity(), &mut nodes, &mut cameras);
+            }
+        }
+
+        Ok(GltfScene { meshes, models, nodes, cameras, })
+    }
+
+    fn read_uri(&self, uri: &str) -> GltfResult<Vec<u8>> {
+        if let Some(data) = decode_data_uri(uri) {
+            return data.ok_or_else(|| GltfError::InvalidData(String::from("malformed data URI")));
+        }
+        let path = self.directory.join(String::from_utf8_lossy(&percent_decode(uri)).as_ref());
+
+        self.resolver.resolve(&path).map_err(|error| GltfError::Resolve { path, error })
+    }
+
+    fn read_buffer(&self, buffer: &::gltf::Buffer, blob: Option<&[u8]>) -> GltfResult<Vec<u8>> {
+        let data = match buffer.source() {
+            ::gltf::buffer::Source::Bin => {
+                blob.map(|blob| blob.to_vec())
+                    .ok_or_else(|| GltfError::InvalidData(String::from("missing binary chunk")))?
+            }
+            ::gltf::buffer::Source::Uri(uri) => self.read_uri(uri)?,
+        };
+        if data.len() < buffer.length() {
+            return Err(GltfError::InvalidData(format!(
+                "buffer {} has length {} but {} bytes were read", buffer.index(), buffer.length(), data.len()
+            )));
+        }
+
+        Ok(data)
+    }
+
+    fn read_image(&self, image: &::gltf::Image, buffers: &[Vec<u8>]) -> GltfResult<TextureMaterial<Rgb<u8>>> {
+        let (data, is_jpeg) = match image.source() {
+            ::gltf::image::Source::View { view, mime_type } => {
+                let buffer = &buffers[view.buffer().index()];
+                let data = buffer.get(view.offset()..(view.offset() + view.length()))
+                    .ok_or_else(|| GltfError::InvalidData(format!("image {} is out of bounds", image.index())))?;
+
+                (data.to_vec(), mime_type == "image/jpeg")
+            }
+            ::gltf::image::Source::Uri { uri, mime_type } => {
+                let is_jpeg = match mime_type {
+                    Some(mime_type) => mime_type == "image/jpeg",
+                    None => {
+                        let uri = uri.to_ascii_lowercase();
+                        uri.starts_with("data:image/jpeg") || uri.ends_with(".jpg") || uri.ends_with(".jpeg")
+                    }
+                };
+
+                (self.read_uri(uri)?, is_jpeg)
+            }
+        };
+        let texture = decode_rgb_texture(&data, is_jpeg).map_err(GltfError::Texture)?;
+
+        Ok(TextureMaterial::new(texture))
+    }
+
+    fn read_materials(&self, document: &::gltf::Document, buffers: &[Vec<u8>]) -> GltfResult<Vec<Arc<dyn Material>>> {
+        let mut images: Vec<Option<TextureMaterial<Rgb<u8>>>> = vec![None; document.images().len()];
+        let mut materials: Vec<Arc<dyn Material>> = vec![];
+        for material in document.materials() {
+            let pbr = material.pbr_metallic_roughness();
+            let base_color: MaterialParameter<Vector3<f32>> = match pbr.base_color_texture() {
+                Some(info) => {
+                    let image = info.texture().source();
+                    if images[image.index()].is_none() {
+                        images[image.index()] = Some(self.read_image(&image, buffers)?);
+                    }
+
+                    images[image.index()].clone().unwrap().into()
+                }
+                None => {
+                    let [r, g, b, _] = pbr.base_color_factor();
+
+                    Vector3::new(r, g, b).into()
+                }
+            };
+            let roughness = pbr.roughness_factor();
+            let ior = material.ior().unwrap_or(DEFAULT_IOR);
+            let transmission = material.transmission()
+                .map(|transmission| transmission.transmission_factor())
+                .unwrap_or(0_f32);
+            let material: Arc<dyn Material> = if transmission > 0.5_f32 {
+                Arc::new(DielectricMaterial::new(ior).with_transmittance(base_color))
+            } else if pbr.metallic_factor() >= 0.5_f32 {
+                Arc::new(ConductorMaterial::new(base_color, roughness))
+            } else {
+                Arc::new(PlasticMaterial::new(base_color, roughness).with_eta(ior))
+            };
+            materials.push(material);
+        }
+
+        Ok(materials)
+    }
+}
+
+/// Read the triangles of every primitive of a glTF mesh into one mesh, and build
+/// a model for it if it has any triangles.
+fn read_mesh(
+    mesh: &::gltf::Mesh,
+    buffers: &[Vec<u8>],
+    materials: &[Arc<dyn Material>]
+) -> GltfResult<(NamedMesh, Option<ModelInstance>)>
+{
+    let mut builder = MeshBuilder::new();
+    // The material ids of the mesh index the glTF materials that the mesh uses.
+    let mut mesh_materials: Vec<Option<usize>> = vec![];
+    for primitive in mesh.primitives() {
+        let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| data.as_slice()));
+        let positions = match reader.read_positions() {
+            Some(positions) => positions.map(Vector3::from).collect::<Vec<_>>(),
+            None => continue,
+        };
+        let normals = reader.read_normals().map(|normals| normals.map(Vector3::from).collect::<Vec<_>>());
+        let tex_coords = reader.read_tex_coords(0)
+            .map(|tex_coords| tex_coords.into_f32().map(Vector2::from).collect::<Vec<_>>());
+        let indices = match reader.read_indices() {
+            Some(indices) => indices.into_u32().collect::<Vec<_>>(),
+            None => (0..(positions.len() as u32)).collect::<Vec<_>>(),
+        };
+        if indices.iter().any(|&index| index as usize >= positions.len()) {
+            return Err(GltfError::InvalidData(format!("mesh {} has an index out of bounds", mesh.index())));
+        }
+        let triangles = match primitive.mode() {
+            ::gltf::mesh::Mode::Triangles => {
+                indices.chunks_exact(3).map(|triangle| [triangle[0], triangle[1], triangle[2]]).collect::<Vec<_>>()
+            }
+            ::gltf::mesh::Mode::TriangleStrip => {
+                (0..indices.len().saturating_sub(2))
+                    .map(|i| if i % 2 == 0 {
+                        [indices[i], indices[i + 1], indices[i + 2]]
+                    } else {
+                        [indices[i + 1], indices[i], indices[i + 2]]
+                    })
+                    .collect::<Vec<_>>()
+            }
+            ::gltf::mesh::Mode::TriangleFan => {
+                (1..indices.len().saturating_sub(1))
+                    .map(|i| [indices[0], indices[i], indices[i + 1]])
+                    .collect::<Vec<_>>()
+            }
+            _ => continue,
+        };
+        let material_index = primitive.material().index();
+        let material_id = match mesh_materials.iter().position(|&index| index == material_index) {
+            Some(material_id) => material_id,
+            None => {
+                mesh_materials.push(material_index);
+                mesh_materials.len() - 1
+            }
+        };
+        builder = builder.with_material_id(material_id as u32);
+        for triangle in triangles.iter() {
+            let [i0, i1, i2] = triangle.map(|index| index as usize);
+            let primitive = Triangle::new(positions[i0], positions[i1], positions[i2]);
+            let primitive_normals = match &normals {
+                Some(normals) => Normals::from([normals[i0], normals[i1], normals[i2]]),
+                None => Normals::default(),
+            };
+            let primitive_tex_coords = match &tex_coords {
+                Some(tex_coords) => TextureCoordinates::from([tex_coords[i0], tex_coords[i1], tex_coords[i2]]),
+                None => TextureCoordinates::default(),
+            };
+            builder = builder.with_primitive(primitive, primitive_tex_coords, primitive_normals);
+        }
+    }
+    let mut mesh_data = builder.build();
+    mesh_data.compute_missing_normals(Degrees(0_f32));
+    let model = if mesh_data.len_primitives() > 0 {
+        let mut model_builder = ModelBuilder::new().with_mesh(mesh_data.clone());
+        for (material_id, material_index) in mesh_materials.iter().enumerate() {
+            if let Some(material) = material_index.and_then(|index| materials.get(index)) {
+                model_builder = model_builder.with_material_id(material_id as u32, material.clone());
+            }
+        }
+
+        Some(model_builder.build())
+    } else {
+        None
+    };
+    let named_mesh = NamedMesh::new(mesh.name().unwrap_or(""), mesh_data);
+
+    Ok((named_mesh, model))
+}
+
+/// Add a node and its descendants to the list of nodes, accumulating the
+/// transforms from the root of the hierarchy.
+fn read_node(
+    node: &::gltf::Node,
+    parent: Option<usize>,
+    parent_transform: &Transform3<f32>,
+    nodes: &mut Vec<GltfNode>,
+    cameras: &mut Vec<GltfCamera>
+) {
+    let local_transform = {
+        let m = node.transform().matrix();
+        let matrix = Matrix4x4::new(
+            m[0][0], m[0][1], m[0][2], m[0][3],
+            m[1][0], m[1][1], m[1][2], m[1][3],
+            m[2][0], m[2][1], m[2][2], m[2][3],
+            m[3][0], m[3][1], m[3][2], m[3][3],
+        );
+        Transform3::from_matrix(&matrix)
+    };
+    let transform = parent_transform * local_transform;
+    let camera = node.camera().map(|camera| {
+        cameras.push(read_camera(&camera, &transform));
+        cameras.len() - 1
+    });
+    let node_index = nodes.len();
+    nodes.push(GltfNode {
+        name: String::from(node.name().unwrap_or("")),
+        parent,
+        transform,
+        mesh: node.mesh().map(|mesh| mesh.index()),
+        camera,
+    });
+    for child in node.children() {
+        read_node(&child, Some(node_index), &transform, nodes, cameras);
+    }
+}
+
+/// Construct a camera placed by the node with the world transform `transform`.
+/// A glTF camera looks down its negative z-axis with its positive y-axis up.
+fn read_camera(camera: &::gltf::Camera, transform: &Transform3<f32>) -> GltfCamera {
+    let name = String::from(camera.name().unwrap_or(""));
+    let position = transform.transform_point(&Vector3::zero());
+    let forward = transform.transform_vector(&(-Vector3::unit_z())).normalize();
+    let right = transform.transform_vector(&Vector3::unit_x()).normalize();
+    let up = transform.transform_vector(&Vector3::unit_y()).normalize();
+    let attitude_spec = CameraAttitudeSpec::new(position, forward, right, up, forward);
+    match camera.projection() {
+        ::gltf::camera::Projection::Perspective(perspective) => {
+            let projection_spec = SymmetricFovSpec::new(
+                Degrees(perspective.yfov().to_degrees()),
+                perspective.aspect_ratio().unwrap_or(DEFAULT_ASPECT_RATIO),
+                perspective.znear(),
+                perspective.zfar().unwrap_or(DEFAULT_ZFAR),
+            );
+
+            GltfCamera::Perspective {
+                name,
+                camera: Camera::new(projection_spec, &attitude_spec),
+            }
+        }
+        ::gltf::camera::Projection::Orthographic(orthographic) => {
+            let projection_spec = BoxSpec::new(
+                -orthographic.xmag(),
+                 orthographic.xmag(),
+                -orthographic.ymag(),
+                 orthographic.ymag(),
+                 orthographic.znear(),
+                 orthographic.zfar(),
+            );
+
+            GltfCamera::Orthographic {
+                name,
+                camera: Camera::new(projection_spec, &attitude_spec),
+            }
+        }
+    }
+}
+
+/// Decode the payload of a data URI. Returns `None` if `uri` is not a data URI,
+/// and `Some(None)` if it is a malformed data URI.
+fn decode_data_uri(uri: &str) -> Option<Option<Vec<u8>>> {
+    let contents = uri.strip_prefix("data:")?;
+    let (header, payload) = match contents.split_once(',') {
+        Some(parts) => parts,
+        None => return Some(None),
+    };
+    if header.ends_with(";base64") {
+        Some(decode_base64(payload))
+    } else {
+        Some(Some(percent_decode(payload)))
+    }
+}
+
+/// Decode standard base64 with optional padding.
+fn decode_base64(data: &str) -> Option<Vec<u8>> {
+    fn sextet(byte: u8) -> Option<u32> {
+        match byte {
+            b'A'..=b'Z' => Some((byte - b'A') as u32),
+            b'a'..=b'z' => Some((byte - b'a') as u32 + 26),
+            b'0'..=b'9' => Some((byte - b'0') as u32 + 52),
+            b'+' | b'-' => Some(62),
+            b'/' | b'_' => Some(63),
+            _ => None,
+        }
+    }
+
+    let data = data.trim_end_matches('=').as_bytes();
+    let mut decoded = Vec::with_capacity(data.len() * 3 / 4);
+    for chunk in data.chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let mut bits = 0_u32;
+        for &byte in chunk.iter() {
+            bits = (bits << 6) | sextet(byte)?;
+        }
+        bits <<= 6 * (4 - chunk.len() as u32);
+        let bytes = [(bits >> 16) as u8, (bits >> 8) as u8, bits as u8];
+        decoded.extend_from_slice(&bytes[..(chunk.len() - 1)]);
+    }
+
+    Some(decoded)
+}
+
+/// Decode the percent encoded bytes of a URI. Malformed escapes are kept as 
+/// they are. 
+/// 
+/// The decoded bytes are not necessarily UTF-8, e.g. the payload of a data URI 
+/// holding a binary buffer.
+fn percent_decode(uri: &str) -> Vec<u8> {
+    let bytes = uri.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[(i + 1)..(i + 3)]).ok();
+            if let Some(byte) = hex.and_then(|hex| u8::from_str_radix(hex, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    decoded
+}
+
@@ -1,8 +1,10 @@
+mod gltf_decoder;
 mod scene_object;
 mod scene;
 mod tlas;
 
 
+pub use gltf_decoder::*;
 pub use scene_object::*;
 pub use scene::*;
 pub use tlas::*;
@@ -1,6 +1,7 @@
 use crate::camera::*;
 use crate::query::*;
 use crate::physics::*;
+use crate::lights::*;
 use super::scene_object::*;
 use super::tlas::*;
 
@@ -8,6 +9,7 @@ use super::tlas::*;
 pub struct Scene {
     tlas: Tlas,
     objects: Vec<SceneObject>,
+    lights: Vec<Box<dyn Light>>,
     active_camera: Camera<f32, PerspectiveProjection<f32>>,
     physics: World<f32>,
 }
@@ -21,6 +23,11 @@ impl Scene {
         &mut self.objects[index]
     }
 
+    /// The light sources in the scene.
+    pub fn lights(&self) -> &[Box<dyn Light>] {
+        &self.lights
+    }
+
     pub fn active_camera(&self) -> &Camera<f32, PerspectiveProjection<f32>> {
         &self.active_camera
     }
@@ -33,6 +40,12 @@ impl Scene {
         self.tlas.intersect(&self.objects, ray)
     }
 
+    /// Determine whether anything in the scene blocks the ray between distances 
+    /// `ray.t_min` and `ray.t` along the ray.
+    pub fn occluded(&self, ray: &Ray<f32>) -> bool {
+        self.tlas.occluded(&self.objects, ray)
+    }
+
     pub fn rebuild(&mut self) {
         self.tlas.rebuild(&self.objects);
     }
@@ -52,6 +65,7 @@ impl Scene {
 
 pub struct SceneBuilder {
     objects: Vec<SceneObject>,
+    lights: Vec<Box<dyn Light>>,
     physics: World<f32>,
     active_camera: Camera<f32, PerspectiveProjection<f32>>,
 }
@@ -60,6 +74,7 @@ impl SceneBuilder {
     pub fn new(camera: Camera<f32, PerspectiveProjection<f32>>) -> Self {
         Self {
             objects: vec![],
+            lights: vec![],
             physics: World::new(),
             active_camera: camera,
         }
@@ -77,6 +92,18 @@ impl SceneBuilder {
         self
     }
 
+    pub fn with_light(mut self, light: Box<dyn Light>) -> Self {
+        self.lights.push(light);
+
+        self
+    }
+
+    pub fn with_lights(mut self, new_lights: Vec<Box<dyn Light>>) -> Self {
+        self.lights = new_lights;
+
+        self
+    }
+
     pub fn with_physics(mut self, physics: World<f32>) -> Self {
         self.physics = physics;
 
@@ -90,6 +117,7 @@ impl SceneBuilder {
         Scene {
             tlas,
             objects: self.objects,
+            lights: self.lights,
             physics: self.physics,
             active_camera: self.active_camera,
         }
@@ -50,8 +50,8 @@ impl SceneObject {
     }
 
     pub fn update_transform(&mut self) {
-        let rigid_body = self.rigid_body.rc();
-        let borrow = rigid_body.borrow();
+        let rigid_body = self.rigid_body.arc();
+        let borrow = rigid_body.read().unwrap();
         let rigid_body_transform = borrow.get_transform();
         let new_transform = rigid_body_transform * self.transform_init;
         self.set_transform(&new_transform);
@@ -74,19 +74,33 @@ impl SceneObject {
         self.bounds = new_bounds;
     }
 
+    /// Convert a ray from world space to model space.
     #[inline]
-    pub fn intersect(&self, ray: &Ray<f32>) -> Option<Intersection<f32>> {
-        // Convert the ray from world space to model space.
+    fn ray_model_space(&self, ray: &Ray<f32>) -> Ray<f32> {
         let ray_model_space_origin = self
             .get_transform_inv()
             .transform_point(&ray.origin);
         let ray_model_space_direction = self
             .get_transform_inv()
             .transform_vector(&ray.direction); 
-        let ray_model_space = Ray::new(ray_model_space_origin, ray_model_space_direction, ray.t);
+        
+        Ray::new(ray_model_space_origin, ray_model_space_direction, ray.t)
+            .with_t_min(ray.t_min)
+    }
+
+    #[inline]
+    pub fn intersect(&self, ray: &Ray<f32>) -> Option<Intersection<f32>> {
+        let ray_model_space = self.ray_model_space(ray);
     
         self.model.intersect(&ray_model_space)
     }
+
+    #[inline]
+    pub fn occluded(&self, ray: &Ray<f32>) -> bool {
+        let ray_model_space = self.ray_model_space(ray);
+    
+        self.model.occluded(&ray_model_space)
+    }
 }
 
 pub struct SceneObjectBuilder {
@@ -8,27 +8,62 @@ use std::ops;
 
 
 
+#[cfg(not(feature = "wide_indices"))]
+mod packing {
+    /// The underlying storage for a packed pair of child node indices.
+    pub type Storage = u32;
+    /// The number of bits used for each child node index.
+    pub const INDEX_BITS: u32 = 16;
+}
+
+#[cfg(feature = "wide_indices")]
+mod packing {
+    /// The underlying storage for a packed pair of child node indices.
+    pub type Storage = u64;
+    /// The number of bits used for each child node index.
+    pub const INDEX_BITS: u32 = 32;
+}
+
+use packing::Storage;
+
+const INDEX_MASK: Storage = Storage::MAX >> (Storage::BITS - packing::INDEX_BITS);
+
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 struct LeftRightIndex {
-    /// The upper u16 is the left index, and the lower u16 is the right index.
-    data: u32,
+    /// The upper half is the left index, and the lower half is the right index.
+    /// Each index is 16 bits wide by default, and 32 bits wide with the 
+    /// `wide_indices` feature enabled.
+    data: Storage,
 }
 
 impl LeftRightIndex {
+    /// The largest node index that a left right index can represent.
+    const MAX_NODE_INDEX: u32 = INDEX_MASK as u32;
+
+    /// Construct a new left right index.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if either node index is too large to represent.
     fn new(left: u32, right: u32) -> Self {
+        assert!(left <= Self::MAX_NODE_INDEX, "The left node index is too large for a left right index.");
+        assert!(right <= Self::MAX_NODE_INDEX, "The right node index is too large for a left right index.");
+        let left_data = (left as Storage) & INDEX_MASK;
+        let right_data = (right as Storage) & INDEX_MASK;
+
         Self { 
-            data: left + (right << 16),
+            data: (left_data << packing::INDEX_BITS) | right_data,
         }
     }
 
     #[inline]
     const fn left(self) -> u32 {
-        (self.data & 0xFFFF0000) >> 16
+        ((self.data >> packing::INDEX_BITS) & INDEX_MASK) as u32
     }
 
     #[inline]
     const fn right(self) -> u32 {
-        self.data & 0x0000FFFF
+        (self.data & INDEX_MASK) as u32
     }
 
     #[inline]
@@ -120,6 +155,8 @@ impl Tlas {
         self.nodes_used as usize
     }
 
+    /// Find the closest intersection of a ray with the objects in the scene, 
+    /// between distances `ray.t_min` and `ray.t` along the ray.
     pub fn intersect(&self, blas: &[SceneObject], ray: &Ray<f32>) -> Option<Intersection<f32>> {
         let mut current_node = &self.nodes[0];
         let mut stack = vec![];
@@ -127,9 +164,11 @@ impl Tlas {
         let mut closest_intersection = None;
         loop {
             if current_node.is_leaf() {
-                if let Some(intersection) = blas[current_node.blas() as usize].intersect(&closest_ray) {
+                let instance_index = current_node.blas();
+                if let Some(mut intersection) = blas[instance_index as usize].intersect(&closest_ray) {
                     if intersection.ray.t < closest_ray.t {
                         closest_ray.t = intersection.ray.t;
+                        intersection.instance_primitive = intersection.instance_primitive.with_instance(instance_index);
                         closest_intersection = Some(intersection);
                     }
                 }
@@ -176,6 +215,35 @@ impl Tlas {
         }
     }
 
+    /// Determine whether the ray hits any object between distances `ray.t_min` 
+    /// and `ray.t` along the ray, stopping at the first hit.
+    pub fn occluded(&self, blas: &[SceneObject], ray: &Ray<f32>) -> bool {
+        let mut current_node = &self.nodes[0];
+        let mut stack = vec![];
+        loop {
+            if current_node.is_leaf() {
+                if blas[current_node.blas() as usize].occluded(ray) {
+                    return true;
+                }
+            } else {
+                let left_child = &self.nodes[current_node.left_blas()];
+                let right_child = &self.nodes[current_node.right_blas()];
+                if left_child.aabb.intersect(ray).is_some() {
+                    stack.push(left_child);
+                }
+                if right_child.aabb.intersect(ray).is_some() {
+                    stack.push(right_child);
+                }
+            }
+
+            if let Some(next_node) = stack.pop() {
+                current_node = next_node;
+            } else {
+                return false;
+            }
+        }
+    }
+
     fn find_best_match(&self, list: &[i32], n: i32, a: i32) -> i32 {
         let mut smallest = f32::MAX;
         let mut best_b: i32 = -1;
@@ -201,7 +269,26 @@ impl Tlas {
         best_b
     }
 
+    /// Rebuild the top level acceleration structure after the objects in it have moved.
+    /// 
+    /// # Panics
+    /// 
+    /// This function panics if there are more objects than an 
+    /// [`InstancePrimitiveIndex`] can address, or the tree needs more nodes than 
+    /// a TLAS node can address. Enable the `wide_indices` feature to raise both 
+    /// limits.
     pub fn rebuild(&mut self, blas: &[SceneObject]) {
+        assert!(
+            blas.len() <= (InstancePrimitiveIndex::MAX_INSTANCE_INDEX as usize) + 1,
+            "A TLAS can hold at most {} objects, but got {} objects.",
+            (InstancePrimitiveIndex::MAX_INSTANCE_INDEX as usize) + 1, blas.len()
+        );
+        assert!(
+            2 * blas.len() <= (LeftRightIndex::MAX_NODE_INDEX as usize) + 1,
+            "A TLAS can hold at most {} nodes, but {} objects need {} nodes.",
+            (LeftRightIndex::MAX_NODE_INDEX as usize) + 1, blas.len(), 2 * blas.len()
+        );
+
         // Assign a Tlasleaf node to each BLAS.
         let blas_count = blas.len();
         let mut node_index_count = blas_count;
@@ -42,6 +42,14 @@ where
         Self { matrix, }
     }
 
+    /// Construct a transform from the matrix of an affine transformation.
+    #[inline]
+    pub fn from_matrix(matrix: &Matrix4x4<S>) -> Self {
+        Self { 
+            matrix: *matrix, 
+        }
+    }
+
     #[inline]
     pub fn identity() -> Self {
         Self {
@@ -0,0 +1,159 @@
+use bvhtracer::{
+    AccumulationBuffer,
+    BoxSpec,
+    Camera,
+    CameraAttitudeSpec,
+    DepthAccumulator,
+    DepthMappingShader,
+    MeshBuilder,
+    ModelBuilder,
+    Normals,
+    PrimaryRayTracer,
+    Renderer,
+    RendererState,
+    RigidBody,
+    Rgba,
+    Scene,
+    SceneBuilder,
+    SceneObjectBuilder,
+    TextureCoordinates,
+    Transform3,
+    Triangle,
+    World,
+};
+use approx::{
+    assert_relative_eq,
+};
+use cglinalg::{
+    Vector3,
+};
+
+
+/// A scene with a single triangle around the origin in the xy-plane, seen 
+/// from a camera on the positive z-axis. The triangle covers the middle of the 
+/// view, and rays through the corners of the view miss it.
+fn triangle_scene() -> Scene {
+    let projection_spec = BoxSpec::new(-1_f32, 1_f32, -1_f32, 1_f32, 1_f32, 100_f32);
+    let attitude_spec = CameraAttitudeSpec::new(
+         Vector3::new(0_f32, 0_f32, 2_f32),
+        -Vector3::unit_z(),
+         Vector3::unit_x(),
+         Vector3::unit_y(),
+        -Vector3::unit_z()
+    );
+    let camera = Camera::new(&projection_spec, &attitude_spec);
+    let mesh = MeshBuilder::new()
+        .with_primitive(
+            Triangle::new(
+                Vector3::new(-1_f32, -1_f32, 0_f32),
+                Vector3::new(1_f32, -1_f32, 0_f32),
+                Vector3::new(0_f32, 1_f32, 0_f32),
+            ),
+            TextureCoordinates::default(),
+            Normals::default(),
+        )
+        .build();
+    let model = ModelBuilder::new().with_mesh(mesh).build();
+    let mut physics = World::new();
+    let rigid_body_instance = physics.register_body(RigidBody::default());
+    let scene_object = SceneObjectBuilder::new(model, rigid_body_instance)
+        .with_transform(&Transform3::identity())
+        .build();
+
+    SceneBuilder::new(camera)
+        .with_physics(physics)
+        .with_object(scene_object)
+        .build()
+}
+
+
+#[test]
+fn test_accumulation_buffer_new_has_no_samples() {
+    let buffer = AccumulationBuffer::<f32>::new(4, 3);
+
+    for y in 0..3 {
+        for x in 0..4 {
+            assert_eq!(buffer.sample_count(x, y), 0);
+            assert_eq!(buffer.sum(x, y), Vector3::zero());
+            assert_eq!(buffer.average(x, y), Vector3::zero());
+        }
+    }
+}
+
+#[test]
+fn test_accumulation_buffer_accumulate_running_sum() {
+    let mut buffer = AccumulationBuffer::new(4, 3);
+    buffer.accumulate(2, 1, &Vector3::new(1_f32, 2_f32, 3_f32), 1);
+    buffer.accumulate(2, 1, &Vector3::new(3_f32, 2_f32, 1_f32), 1);
+
+    assert_eq!(buffer.sum(2, 1), Vector3::new(4_f32, 4_f32, 4_f32));
+    assert_eq!(buffer.sample_count(2, 1), 2);
+}
+
+#[test]
+fn test_accumulation_buffer_average() {
+    let mut buffer = AccumulationBuffer::new(4, 3);
+    buffer.accumulate(3, 2, &Vector3::new(2_f32, 4_f32, 6_f32), 2);
+    buffer.accumulate(3, 2, &Vector3::new(4_f32, 2_f32, 0_f32), 2);
+    let expected = Vector3::new(1.5_f32, 1.5_f32, 1.5_f32);
+    let result = buffer.average(3, 2);
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn test_accumulation_buffer_accumulate_does_not_touch_other_pixels() {
+    let mut buffer = AccumulationBuffer::new(4, 3);
+    buffer.accumulate(1, 1, &Vector3::new(1_f32, 1_f32, 1_f32), 1);
+
+    for y in 0..3 {
+        for x in 0..4 {
+            if (x, y) != (1, 1) {
+                assert_eq!(buffer.sample_count(x, y), 0);
+                assert_eq!(buffer.sum(x, y), Vector3::zero());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_accumulation_buffer_reset() {
+    let mut buffer = AccumulationBuffer::from_fill(4, 3, Vector3::new(1_f32, 1_f32, 1_f32));
+    buffer.accumulate(0, 0, &Vector3::new(1_f32, 1_f32, 1_f32), 1);
+    buffer.reset();
+
+    assert_eq!(buffer, AccumulationBuffer::new(4, 3));
+}
+
+/// Accumulating depth over several frames keeps the averages finite, and the 
+/// pixels that miss the scene stay black.
+#[test]
+fn test_depth_accumulation_over_frames() {
+    let width = 32;
+    let height = 32;
+    let scene = triangle_scene();
+    let accumulator = Box::new(DepthAccumulator::new());
+    let pixel_shader = Box::new(DepthMappingShader::new(10_f32, 0.05_f32));
+    let mut renderer_state = RendererState::new(accumulator, pixel_shader, width, height);
+    let mut renderer = Renderer::new(Box::new(PrimaryRayTracer::new()));
+    renderer.render(&mut renderer_state, &scene);
+    let first_frame = renderer_state.frame_buffer().clone();
+    let first_depth = renderer_state.accumulation_buffer().average(width / 2, height / 2);
+    renderer.render(&mut renderer_state, &scene);
+    renderer.render(&mut renderer_state, &scene);
+    let accumulation_buffer = renderer_state.accumulation_buffer();
+
+    for y in 0..height {
+        for x in 0..width {
+            let average = accumulation_buffer.average(x, y);
+            assert!(average.x.is_finite() && average.y.is_finite(), "average = {:?}", average);
+        }
+    }
+    assert_eq!(accumulation_buffer.sample_count(0, 0), 3);
+    assert_eq!(accumulation_buffer.average(0, 0), Vector3::zero());
+    assert_relative_eq!(accumulation_buffer.average(width / 2, height / 2), first_depth, epsilon = 1e-5);
+    assert_eq!(first_depth.y, 1_f32);
+    assert_eq!(renderer_state.frame_buffer(), &first_frame);
+    assert_eq!(renderer_state.frame_buffer().as_buffer()[(0, 0)], Rgba::new(0, 0, 0, 255));
+    assert_ne!(renderer_state.frame_buffer().as_buffer()[(width / 2, height / 2)], Rgba::new(0, 0, 0, 255));
+}
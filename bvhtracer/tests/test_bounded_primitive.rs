@@ -0,0 +1,196 @@
+use bvhtracer::{
+    Aabb,
+    BoundedPrimitive,
+    BvhBuilder,
+    Ray,
+    SurfaceInteraction,
+};
+use cglinalg::{
+    Magnitude,
+    Vector3,
+};
+use std::cell::{
+    Cell,
+};
+
+
+/// A user defined primitive: a square in a plane of constant **z**, facing
+/// both ways, that counts how many times it was tested for a closest hit.
+struct Tile {
+    center: Vector3<f32>,
+    half_width: f32,
+    intersect_count: Cell<usize>,
+}
+
+impl Tile {
+    fn new(center: Vector3<f32>, half_width: f32) -> Self {
+        Self { center, half_width, intersect_count: Cell::new(0), }
+    }
+}
+
+impl BoundedPrimitive<f32> for Tile {
+    fn bounds(&self) -> Aabb<f32> {
+        let extent = Vector3::new(self.half_width, self.half_width, 0_f32);
+
+        Aabb::new(self.center - extent, self.center + extent)
+    }
+
+    fn centroid(&self) -> Vector3<f32> {
+        self.center
+    }
+
+    fn intersect(&self, ray: &Ray<f32>) -> Option<SurfaceInteraction<f32>> {
+        self.intersect_count.set(self.intersect_count.get() + 1);
+        if ray.direction.z == 0_f32 {
+            return None;
+        }
+        let t = (self.center.z - ray.origin.z) / ray.direction.z;
+        if t <= 0_f32 || t >= ray.t {
+            return None;
+        }
+        let offset = ray.interpolate(t) - self.center;
+        if offset.x.abs() > self.half_width || offset.y.abs() > self.half_width {
+            return None;
+        }
+        let u = 0.5_f32 + 0.5_f32 * offset.x / self.half_width;
+        let v = 0.5_f32 + 0.5_f32 * offset.y / self.half_width;
+
+        Some(SurfaceInteraction::new(t, u, v))
+    }
+}
+
+/// A stack of tiles shrinking with depth, so that each ray through the middle
+/// passes over several of them.
+fn tiles() -> Vec<Tile> {
+    let mut tiles = vec![];
+    for i in 0..10 {
+        for j in 0..10 {
+            for k in 0..5 {
+                let center = Vector3::new(3_f32 * (i as f32), 3_f32 * (j as f32), -2_f32 * (k as f32));
+                tiles.push(Tile::new(center, 1_f32 - 0.1_f32 * (k as f32)));
+            }
+        }
+    }
+
+    tiles
+}
+
+fn boxes() -> Vec<Aabb<f32>> {
+    let mut boxes = vec![];
+    for i in 0..10 {
+        for j in 0..10 {
+            for k in 0..10 {
+                let bounds_min = Vector3::new(i as f32, j as f32, k as f32) * 2_f32;
+                let size = 0.5_f32 + 0.1_f32 * (((i + 2 * j + 3 * k) % 10) as f32);
+                boxes.push(Aabb::new(bounds_min, bounds_min + Vector3::from_fill(size)));
+            }
+        }
+    }
+
+    boxes
+}
+
+fn rays() -> Vec<Ray<f32>> {
+    let mut rays = vec![];
+    for i in 0..30 {
+        for j in 0..30 {
+            let origin = Vector3::new(i as f32 - 1.7_f32, j as f32 - 1.3_f32, 40_f32);
+            let target = Vector3::new(10_f32 + 0.1_f32 * (i as f32), 9_f32, 0_f32);
+            rays.push(Ray::from_origin_dir(origin, (target - origin).normalize()));
+        }
+    }
+
+    rays
+}
+
+fn brute_force_t<P>(primitives: &[P], ray: &Ray<f32>) -> Option<f32>
+where
+    P: BoundedPrimitive<f32>,
+{
+    primitives.iter()
+        .filter_map(|primitive| primitive.intersect(ray))
+        .map(|interaction| interaction.t)
+        .min_by(|a, b| a.partial_cmp(b).unwrap())
+}
+
+
+#[test]
+fn test_user_defined_primitive_matches_brute_force() {
+    let mut tiles = tiles();
+    let bvh = BvhBuilder::new().build_for(&mut tiles);
+    for ray in rays().iter() {
+        let expected = brute_force_t(&tiles, ray);
+        let result = bvh.intersect(&tiles, ray).map(|intersection| intersection.interaction.t);
+
+        assert_eq!(result, expected);
+        assert_eq!(bvh.occluded(&tiles, ray), expected.is_some());
+    }
+}
+
+/// The hierarchy should spare most primitives from being tested against a ray.
+#[test]
+fn test_user_defined_primitive_traversal_culls_primitives() {
+    let mut tiles = tiles();
+    let bvh = BvhBuilder::new().build_for(&mut tiles);
+    let ray = Ray::from_origin_dir(Vector3::new(0.1_f32, 0.2_f32, 10_f32), -Vector3::unit_z());
+    let intersection = bvh.intersect(&tiles, &ray).unwrap();
+    let tested = tiles.iter().map(|tile| tile.intersect_count.get()).sum::<usize>();
+
+    assert_eq!(intersection.interaction.t, 10_f32);
+    assert!(tested < tiles.len() / 4, "tested {} of {} primitives", tested, tiles.len());
+}
+
+#[test]
+fn test_build_for_with_order() {
+    let expected = tiles();
+    let mut tiles = tiles();
+    let (_, order) = BvhBuilder::new().build_for_with_order(&mut tiles);
+
+    assert_eq!(order.len(), expected.len());
+    for (tile, &old_index) in tiles.iter().zip(order.iter()) {
+        assert_eq!(tile.center, expected[old_index as usize].center);
+    }
+}
+
+#[test]
+fn test_aabb_primitives_match_brute_force() {
+    let mut boxes = boxes();
+    let bvh = BvhBuilder::new().build_for(&mut boxes);
+    for ray in rays().iter() {
+        let expected = brute_force_t(&boxes, ray);
+        let result = bvh.intersect(&boxes, ray).map(|intersection| intersection.interaction.t);
+
+        assert_eq!(result, expected);
+        assert_eq!(bvh.occluded(&boxes, ray), expected.is_some());
+    }
+}
+
+#[test]
+fn test_aabb_primitive_hit_from_inside() {
+    let aabb = Aabb::new(Vector3::from_fill(-1_f32), Vector3::from_fill(1_f32));
+    let ray = Ray::from_origin_dir(Vector3::zero(), Vector3::unit_x());
+    let interaction = BoundedPrimitive::intersect(&aabb, &ray).unwrap();
+
+    assert_eq!(interaction.t, 1_f32);
+}
+
+#[test]
+fn test_aabb_primitive_refit() {
+    let mut boxes = boxes();
+    let mut bvh = BvhBuilder::new().build_for(&mut boxes);
+    let displacement = Vector3::new(0_f32, 0_f32, -50_f32);
+    for aabb in boxes.iter_mut() {
+        aabb.bounds_min += displacement;
+        aabb.bounds_max += displacement;
+    }
+    bvh.refit(&boxes);
+
+    assert!(bvh.bounds().bounds_max.z < -25_f32);
+    for ray in rays().iter() {
+        let ray = Ray::from_origin_dir(ray.origin + displacement, ray.direction);
+        let expected = brute_force_t(&boxes, &ray);
+        let result = bvh.intersect(&boxes, &ray).map(|intersection| intersection.interaction.t);
+
+        assert_eq!(result, expected);
+    }
+}
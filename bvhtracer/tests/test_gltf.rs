@@ -0,0 +1,360 @@
+use bvhtracer::{
+    GltfCamera,
+    GltfDecoder,
+    GltfError,
+    GltfScene,
+    MemoryFileResolver,
+    Ray,
+};
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+};
+
+
+/// A buffer holding the three vertices of a unit right triangle, followed
+/// by its indices as unsigned shorts.
+fn triangle_buffer() -> Vec<u8> {
+    let positions: [f32; 9] = [
+        0_f32, 0_f32, 0_f32,
+        1_f32, 0_f32, 0_f32,
+        0_f32, 1_f32, 0_f32,
+    ];
+    let indices: [u16; 3] = [0, 1, 2];
+    let mut buffer = vec![];
+    for position in positions.iter() {
+        buffer.extend_from_slice(&position.to_le_bytes());
+    }
+    for index in indices.iter() {
+        buffer.extend_from_slice(&index.to_le_bytes());
+    }
+
+    buffer
+}
+
+fn encode_base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let bits = ((bytes[0] as u32) << 16) | ((bytes[1] as u32) << 8) | (bytes[2] as u32);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[((bits >> (18 - 6 * i)) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+fn data_uri(data: &[u8]) -> String {
+    format!("data:application/octet-stream;base64,{}", encode_base64(data))
+}
+
+/// A data URI holding `data` percent encoded instead of base64 encoded.
+fn percent_encoded_data_uri(data: &[u8]) -> String {
+    let payload: String = data.iter().map(|byte| format!("%{:02X}", byte)).collect();
+
+    format!("data:application/octet-stream,{}", payload)
+}
+
+/// A document with a hierarchy of two nodes placing a mesh, a camera, and
+/// three materials. The first mesh has two primitives sharing the triangle
+/// buffer, and the second mesh draws the same vertices as a triangle fan.
+fn scene_gltf_file(buffer_uri: &str) -> String {
+    format!(r#"{{
+        "asset": {{ "version": "2.0" }},
+        "extensionsUsed": ["KHR_materials_transmission", "KHR_materials_ior"],
+        "scene": 0,
+        "scenes": [{{ "nodes": [0, 3] }}],
+        "nodes": [
+            {{ "name": "root", "translation": [0.0, 0.0, -5.0], "children": [1, 2] }},
+            {{ "name": "triangles", "scale": [2.0, 2.0, 2.0], "mesh": 0 }},
+            {{ "name": "fan", "translation": [10.0, 0.0, 0.0], "mesh": 1 }},
+            {{ "name": "eye", "translation": [0.0, 0.0, 3.0], "camera": 0 }}
+        ],
+        "cameras": [{{
+            "name": "main",
+            "type": "perspective",
+            "perspective": {{ "yfov": 1.0, "aspectRatio": 1.5, "znear": 0.1, "zfar": 100.0 }}
+        }}],
+        "meshes": [
+            {{
+                "name": "pair",
+                "primitives": [
+                    {{ "attributes": {{ "POSITION": 0 }}, "indices": 1, "material": 0 }},
+                    {{ "attributes": {{ "POSITION": 0 }}, "indices": 1, "material": 1 }}
+                ]
+            }},
+            {{
+                "name": "fan",
+                "primitives": [{{ "attributes": {{ "POSITION": 0 }}, "mode": 6, "material": 2 }}]
+            }}
+        ],
+        "materials": [
+            {{
+                "name": "red",
+                "pbrMetallicRoughness": {{ "baseColorFactor": [1.0, 0.0, 0.0, 1.0], "metallicFactor": 0.0 }}
+            }},
+            {{
+                "name": "gold",
+                "pbrMetallicRoughness": {{ "baseColorFactor": [1.0, 0.8, 0.3, 1.0], "metallicFactor": 1.0 }}
+            }},
+            {{
+                "name": "glass",
+                "pbrMetallicRoughness": {{ "metallicFactor": 0.0 }},
+                "extensions": {{
+                    "KHR_materials_transmission": {{ "transmissionFactor": 1.0 }},
+                    "KHR_materials_ior": {{ "ior": 1.33 }}
+                }}
+            }}
+        ],
+        "buffers": [{{ "byteLength": 42, "uri": "{}" }}],
+        "bufferViews": [
+            {{ "buffer": 0, "byteOffset": 0, "byteLength": 36 }},
+            {{ "buffer": 0, "byteOffset": 36, "byteLength": 6 }}
+        ],
+        "accessors": [
+            {{
+                "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                "min": [0.0, 0.0, 0.0], "max": [1.0, 1.0, 0.0]
+            }},
+            {{ "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }}
+        ]
+    }}"#, buffer_uri)
+}
+
+/// A document with a single triangle whose buffer has no URI, so that it
+/// refers to the binary chunk of a `.glb` file.
+fn triangle_glb_json() -> String {
+    String::from(r#"{
+        "asset": { "version": "2.0" },
+        "scenes": [{ "nodes": [0] }],
+        "nodes": [{ "mesh": 0 }],
+        "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0 }, "indices": 1 }] }],
+        "buffers": [{ "byteLength": 42 }],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+            { "buffer": 0, "byteOffset": 36, "byteLength": 6 }
+        ],
+        "accessors": [
+            {
+                "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+                "min": [0.0, 0.0, 0.0], "max": [1.0, 1.0, 0.0]
+            },
+            { "bufferView": 1, "componentType": 5123, "count": 3, "type": "SCALAR" }
+        ]
+    }"#)
+}
+
+fn glb_file(json: &str, bin: &[u8]) -> Vec<u8> {
+    let mut json_chunk = json.as_bytes().to_vec();
+    json_chunk.resize(json_chunk.len().next_multiple_of(4), b' ');
+    let mut bin_chunk = bin.to_vec();
+    bin_chunk.resize(bin_chunk.len().next_multiple_of(4), 0);
+    let length = 12 + 8 + json_chunk.len() + 8 + bin_chunk.len();
+    let mut glb = vec![];
+    glb.extend_from_slice(b"glTF");
+    glb.extend_from_slice(&2_u32.to_le_bytes());
+    glb.extend_from_slice(&(length as u32).to_le_bytes());
+    glb.extend_from_slice(&(json_chunk.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"JSON");
+    glb.extend_from_slice(&json_chunk);
+    glb.extend_from_slice(&(bin_chunk.len() as u32).to_le_bytes());
+    glb.extend_from_slice(b"BIN\0");
+    glb.extend_from_slice(&bin_chunk);
+
+    glb
+}
+
+fn read_scene(gltf_file: &str) -> GltfScene {
+    let resolver = MemoryFileResolver::new();
+
+    GltfDecoder::new(gltf_file.as_bytes(), &resolver).read_scene().unwrap()
+}
+
+fn assert_vector_eq(result: Vector3<f32>, expected: Vector3<f32>) {
+    assert!((result - expected).magnitude() < 1e-5, "{:?} != {:?}", result, expected);
+}
+
+
+#[test]
+fn test_gltf_mesh_triangles() {
+    let gltf_file = scene_gltf_file(&data_uri(&triangle_buffer()));
+    let scene = read_scene(&gltf_file);
+    let meshes = scene.meshes();
+    let expected = [
+        Vector3::new(0_f32, 0_f32, 0_f32),
+        Vector3::new(1_f32, 0_f32, 0_f32),
+        Vector3::new(0_f32, 1_f32, 0_f32),
+    ];
+
+    assert_eq!(meshes.len(), 2);
+    assert_eq!(meshes[0].name, "pair");
+    assert_eq!(meshes[0].mesh.len_primitives(), 2);
+    assert_eq!(meshes[1].mesh.len_primitives(), 1);
+    assert_eq!(meshes[0].mesh.primitives()[0].vertices, expected);
+    assert_eq!(meshes[1].mesh.primitives()[0].vertices, expected);
+}
+
+/// Percent encoded buffers hold bytes that are not valid UTF-8, such as the
+/// `0x80` byte in the encoding of `1.0`, which must be read as they are.
+#[test]
+fn test_gltf_percent_encoded_binary_buffer() {
+    let buffer = triangle_buffer();
+    let gltf_file = scene_gltf_file(&percent_encoded_data_uri(&buffer));
+    let scene = read_scene(&gltf_file);
+    let meshes = scene.meshes();
+    let expected = [
+        Vector3::new(0_f32, 0_f32, 0_f32),
+        Vector3::new(1_f32, 0_f32, 0_f32),
+        Vector3::new(0_f32, 1_f32, 0_f32),
+    ];
+
+    assert!(buffer.iter().any(|&byte| byte >= 0x80));
+    assert_eq!(meshes[0].mesh.len_primitives(), 2);
+    assert_eq!(meshes[0].mesh.primitives()[0].vertices, expected);
+}
+
+/// Missing normals are calculated as flat normals, and missing texture 
+/// coordinates are read as zero.
+#[test]
+fn test_gltf_missing_attributes() {
+    let gltf_file = scene_gltf_file(&data_uri(&triangle_buffer()));
+    let scene = read_scene(&gltf_file);
+    let mesh = &scene.meshes()[0].mesh;
+
+    assert_eq!(mesh.normals()[0][0], Vector3::unit_z());
+    assert_eq!(mesh.normals()[0][1], Vector3::unit_z());
+    assert_eq!(mesh.normals()[0][2], Vector3::unit_z());
+    assert_eq!(mesh.tex_coords()[0][0], Vector2::zero());
+}
+
+#[test]
+fn test_gltf_node_hierarchy() {
+    let gltf_file = scene_gltf_file(&data_uri(&triangle_buffer()));
+    let scene = read_scene(&gltf_file);
+    let nodes = scene.nodes();
+    let names = nodes.iter().map(|node| node.name.as_str()).collect::<Vec<_>>();
+
+    assert_eq!(names, vec!["root", "triangles", "fan", "eye"]);
+    assert_eq!(nodes[0].parent, None);
+    assert_eq!(nodes[1].parent, Some(0));
+    assert_eq!(nodes[2].parent, Some(0));
+    assert_eq!(nodes[3].parent, None);
+    assert_eq!(nodes[1].mesh, Some(0));
+    assert_eq!(nodes[3].camera, Some(0));
+}
+
+/// The transform of a node includes the transforms of its ancestors.
+#[test]
+fn test_gltf_node_world_transforms() {
+    let gltf_file = scene_gltf_file(&data_uri(&triangle_buffer()));
+    let scene = read_scene(&gltf_file);
+    let nodes = scene.nodes();
+    let point = Vector3::new(1_f32, 0_f32, 0_f32);
+
+    assert_vector_eq(nodes[1].transform.transform_point(&point), Vector3::new(2_f32, 0_f32, -5_f32));
+    assert_vector_eq(nodes[2].transform.transform_point(&point), Vector3::new(11_f32, 0_f32, -5_f32));
+}
+
+#[test]
+fn test_gltf_perspective_camera() {
+    let gltf_file = scene_gltf_file(&data_uri(&triangle_buffer()));
+    let scene = read_scene(&gltf_file);
+    let camera = scene.perspective_camera().unwrap();
+
+    assert_eq!(scene.cameras().len(), 1);
+    assert_eq!(scene.cameras()[0].name(), "main");
+    assert!(matches!(scene.cameras()[0], GltfCamera::Perspective { .. }));
+    assert_vector_eq(camera.position(), Vector3::new(0_f32, 0_f32, 3_f32));
+    assert_vector_eq(camera.forward_axis_world(), -Vector3::unit_z());
+}
+
+#[test]
+fn test_gltf_materials() {
+    let gltf_file = scene_gltf_file(&data_uri(&triangle_buffer()));
+    let scene = read_scene(&gltf_file);
+    let pair = scene.model(0).unwrap().model();
+    let pair = pair.borrow();
+    let fan = scene.model(1).unwrap().model();
+    let fan = fan.borrow();
+    let red = pair.material(0);
+    let gold = pair.material(1);
+    let glass = fan.material(0);
+
+    assert_eq!(red.albedo(&Vector2::zero()), Vector3::new(1_f32, 0_f32, 0_f32));
+    assert!(!red.is_transmissive());
+    assert!(!gold.is_transmissive());
+    assert!(glass.is_transmissive());
+    assert!(glass.is_specular());
+}
+
+#[test]
+fn test_gltf_scene_intersection() {
+    let gltf_file = scene_gltf_file(&data_uri(&triangle_buffer()));
+    let gltf_scene = read_scene(&gltf_file);
+    let camera = gltf_scene.perspective_camera().unwrap().clone();
+    let scene = gltf_scene.build_scene(camera);
+    let ray = Ray::new(Vector3::new(0.5_f32, 0.5_f32, 0_f32), -Vector3::unit_z(), f32::MAX);
+    let intersection = scene.intersect(&ray).unwrap();
+
+    assert!((intersection.interaction.t - 5_f32).abs() < 1e-5);
+}
+
+#[test]
+fn test_gltf_external_buffer() {
+    let resolver = MemoryFileResolver::new()
+        .with_file("models/triangle scene.bin", triangle_buffer());
+    let gltf_file = scene_gltf_file("triangle%20scene.bin");
+    let scene = GltfDecoder::new(gltf_file.as_bytes(), &resolver)
+        .with_directory("models")
+        .read_scene()
+        .unwrap();
+
+    assert_eq!(scene.meshes()[0].mesh.len_primitives(), 2);
+}
+
+#[test]
+fn test_gltf_binary_container() {
+    let glb = glb_file(&triangle_glb_json(), &triangle_buffer());
+    let resolver = MemoryFileResolver::new();
+    let scene = GltfDecoder::new(glb.as_slice(), &resolver).read_scene().unwrap();
+
+    assert_eq!(scene.meshes().len(), 1);
+    assert_eq!(scene.meshes()[0].mesh.len_primitives(), 1);
+    assert!(scene.model(0).is_some());
+}
+
+#[test]
+fn test_gltf_missing_external_buffer() {
+    let resolver = MemoryFileResolver::new();
+    let gltf_file = scene_gltf_file("missing.bin");
+    let result = GltfDecoder::new(gltf_file.as_bytes(), &resolver).read_scene();
+
+    assert!(matches!(result, Err(GltfError::Resolve { .. })));
+}
+
+#[test]
+fn test_gltf_index_out_of_bounds() {
+    let mut buffer = triangle_buffer();
+    let last = buffer.len() - 2;
+    buffer[last..].copy_from_slice(&7_u16.to_le_bytes());
+    let gltf_file = scene_gltf_file(&data_uri(&buffer));
+    let resolver = MemoryFileResolver::new();
+    let result = GltfDecoder::new(gltf_file.as_bytes(), &resolver).read_scene();
+
+    assert!(matches!(result, Err(GltfError::InvalidData(_))));
+}
+
+#[test]
+fn test_gltf_malformed_document() {
+    let resolver = MemoryFileResolver::new();
+    let result = GltfDecoder::new("{ not json".as_bytes(), &resolver).read_scene();
+
+    assert!(matches!(result, Err(GltfError::Gltf(_))));
+}
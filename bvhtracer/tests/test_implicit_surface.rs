@@ -0,0 +1,417 @@
+use bvhtracer::{
+    Aabb,
+    Accumulator,
+    BoxSpec,
+    CameraAttitudeSpec,
+    Camera,
+    ImplicitSurface,
+    LambertianMaterial,
+    MeshBuilder,
+    ModelBuilder,
+    ModelInstance,
+    NormalMappingAccumulator,
+    Normals,
+    Ray,
+    RigidBody,
+    Scene,
+    SceneBuilder,
+    SceneObjectBuilder,
+    Sdf,
+    Sphere,
+    TextureCoordinates,
+    TextureMaterialAccumulator,
+    Transform3,
+    Triangle,
+    World,
+};
+use approx::{
+    assert_relative_eq,
+};
+use cglinalg::{
+    Magnitude,
+    Rotation3,
+    Vector3,
+};
+use std::sync::{
+    Arc,
+};
+
+
+/// The tolerance of the distance of a sphere traced hit from the surface.
+const TOLERANCE: f32 = 1e-3;
+
+
+fn unit_cube() -> Aabb<f32> {
+    Aabb::new(Vector3::from_fill(-1_f32), Vector3::from_fill(1_f32))
+}
+
+fn sphere_surface() -> ImplicitSurface<f32> {
+    let sdf = Sdf::sphere(Vector3::new(1_f32, 2_f32, 3_f32), 2_f32);
+    let bounds = Aabb::new(Vector3::new(-1_f32, 0_f32, 1_f32), Vector3::new(3_f32, 4_f32, 5_f32));
+
+    ImplicitSurface::new(sdf, bounds)
+}
+
+fn rays() -> Vec<Ray<f32>> {
+    let mut rays = vec![];
+    for i in 0..20 {
+        for j in 0..20 {
+            let origin = Vector3::new(-5_f32 + 0.55_f32 * (i as f32), 10_f32, -4_f32 + 0.45_f32 * (j as f32));
+            let target = Vector3::new(1_f32, 2_f32, 3_f32) + Vector3::new(0.11_f32 * (j as f32) - 1_f32, 0_f32, 0.09_f32 * (i as f32) - 1_f32);
+            rays.push(Ray::from_origin_dir(origin, (target - origin).normalize()));
+        }
+    }
+
+    rays
+}
+
+
+#[test]
+fn test_sdf_sphere_distance() {
+    let sdf = Sdf::sphere(Vector3::new(1_f32, 0_f32, 0_f32), 2_f32);
+
+    assert_eq!(sdf.distance(&Vector3::new(1_f32, 0_f32, 0_f32)), -2_f32);
+    assert_eq!(sdf.distance(&Vector3::new(3_f32, 0_f32, 0_f32)), 0_f32);
+    assert_eq!(sdf.distance(&Vector3::new(1_f32, 5_f32, 0_f32)), 3_f32);
+}
+
+#[test]
+fn test_sdf_cuboid_distance() {
+    let sdf = Sdf::cuboid(Vector3::zero(), Vector3::new(1_f32, 2_f32, 3_f32));
+
+    assert_eq!(sdf.distance(&Vector3::zero()), -1_f32);
+    assert_eq!(sdf.distance(&Vector3::new(0_f32, 4_f32, 0_f32)), 2_f32);
+    // Outside a corner, the nearest point is the corner.
+    assert_relative_eq!(sdf.distance(&Vector3::new(4_f32, 6_f32, 3_f32)), 5_f32);
+}
+
+#[test]
+fn test_sdf_torus_distance() {
+    let sdf = Sdf::torus(Vector3::zero(), 3_f32, 1_f32);
+
+    assert_eq!(sdf.distance(&Vector3::new(3_f32, 0_f32, 0_f32)), -1_f32);
+    assert_eq!(sdf.distance(&Vector3::new(0_f32, 0_f32, -4_f32)), 0_f32);
+    assert_eq!(sdf.distance(&Vector3::zero()), 2_f32);
+    assert_eq!(sdf.distance(&Vector3::new(3_f32, 2_f32, 0_f32)), 1_f32);
+}
+
+#[test]
+fn test_sdf_union_and_subtraction() {
+    let left = Sdf::sphere(Vector3::new(-1_f32, 0_f32, 0_f32), 1.5_f32);
+    let right = Sdf::sphere(Vector3::new(1_f32, 0_f32, 0_f32), 1.5_f32);
+    let union = left.clone().union(right.clone());
+    let difference = left.subtract(right);
+    let point = Vector3::zero();
+
+    assert_eq!(union.distance(&point), -0.5_f32);
+    assert_eq!(union.distance(&Vector3::new(3_f32, 0_f32, 0_f32)), 0.5_f32);
+    // The point lies inside both spheres, so it is cut away with the right sphere.
+    assert_eq!(difference.distance(&point), 0.5_f32);
+    assert_eq!(difference.distance(&Vector3::new(-1.5_f32, 0_f32, 0_f32)), -1_f32);
+}
+
+#[test]
+fn test_sdf_smooth_union() {
+    let left = Sdf::sphere(Vector3::new(-1.5_f32, 0_f32, 0_f32), 1_f32);
+    let right = Sdf::sphere(Vector3::new(1.5_f32, 0_f32, 0_f32), 1_f32);
+    let union = left.clone().union(right.clone());
+    let smooth_union = left.smooth_union(right, 0.5_f32);
+
+    // Far from where the shapes meet, the smooth union is the union.
+    let point = Vector3::new(-4_f32, 0_f32, 0_f32);
+    assert_eq!(smooth_union.distance(&point), union.distance(&point));
+    // Between the shapes, the smooth union fills in the gap.
+    let point = Vector3::zero();
+    assert!(smooth_union.distance(&point) < union.distance(&point));
+}
+
+#[test]
+fn test_sdf_repetition() {
+    let sdf = Sdf::sphere(Vector3::zero(), 0.5_f32).repeat(Vector3::new(2_f32, 0_f32, 2_f32));
+
+    assert_eq!(sdf.distance(&Vector3::new(4_f32, 0_f32, -6_f32)), -0.5_f32);
+    assert_eq!(sdf.distance(&Vector3::new(1_f32, 0_f32, 0_f32)), 0.5_f32);
+    // The y-axis has a period of zero, so it is not repeated.
+    assert_eq!(sdf.distance(&Vector3::new(0_f32, 2_f32, 0_f32)), 1.5_f32);
+}
+
+#[test]
+fn test_sdf_from_fn() {
+    let sdf = Sdf::from_fn(|point: &Vector3<f32>| point.y);
+    let sdf = sdf.union(Sdf::sphere(Vector3::new(0_f32, 3_f32, 0_f32), 1_f32));
+
+    assert_eq!(sdf.distance(&Vector3::new(5_f32, 1_f32, 5_f32)), 1_f32);
+    assert_eq!(sdf.distance(&Vector3::new(0_f32, 3.5_f32, 0_f32)), -0.5_f32);
+}
+
+#[test]
+fn test_implicit_surface_intersect_from_outside() {
+    let surface = sphere_surface();
+    let ray = Ray::from_origin_dir(Vector3::new(1_f32, 2_f32, 10_f32), -Vector3::unit_z());
+    let interaction = surface.intersect(&ray).unwrap();
+
+    assert_relative_eq!(interaction.t, 5_f32, epsilon = TOLERANCE);
+    assert_eq!(interaction.u, 0_f32);
+    assert_eq!(interaction.v, 0_f32);
+}
+
+#[test]
+fn test_implicit_surface_intersect_from_inside() {
+    let surface = sphere_surface();
+    let ray = Ray::from_origin_dir(Vector3::new(1_f32, 2_f32, 3_f32), Vector3::unit_x());
+    let interaction = surface.intersect(&ray).unwrap();
+
+    assert_relative_eq!(interaction.t, 2_f32, epsilon = TOLERANCE);
+}
+
+#[test]
+fn test_implicit_surface_miss() {
+    let surface = sphere_surface();
+    let ray = Ray::from_origin_dir(Vector3::new(4_f32, 2_f32, 10_f32), -Vector3::unit_z());
+
+    assert!(surface.intersect(&ray).is_none());
+    assert!(!surface.occluded(&ray));
+}
+
+#[test]
+fn test_implicit_surface_beyond_ray_distance() {
+    let surface = sphere_surface();
+    let ray = Ray::new(Vector3::new(1_f32, 2_f32, 10_f32), -Vector3::unit_z(), 4.9_f32);
+
+    assert!(surface.intersect(&ray).is_none());
+    assert!(!surface.occluded(&ray));
+}
+
+/// A ray leaving the surface does not hit the point it left.
+#[test]
+fn test_implicit_surface_ray_leaving_surface() {
+    let surface = sphere_surface();
+    let ray = Ray::from_origin_dir(Vector3::new(1_f32, 2_f32, 5_f32), Vector3::new(1_f32, 0_f32, 1_f32).normalize());
+
+    assert!(surface.intersect(&ray).is_none());
+
+    // Leaving the surface into the sphere hits the other side.
+    let ray = Ray::from_origin_dir(Vector3::new(1_f32, 2_f32, 5_f32), -Vector3::unit_z());
+    let interaction = surface.intersect(&ray).unwrap();
+
+    assert_relative_eq!(interaction.t, 4_f32, epsilon = TOLERANCE);
+}
+
+/// Steps along a ray whose direction is not a unit vector, like a ray
+/// transformed into the space of a scaled model, cover the same distance.
+#[test]
+fn test_implicit_surface_non_unit_ray_direction() {
+    let surface = sphere_surface();
+    let ray = Ray::from_origin_dir(Vector3::new(1_f32, 2_f32, 10_f32), Vector3::new(0_f32, 0_f32, -0.5_f32));
+    let interaction = surface.intersect(&ray).unwrap();
+
+    assert_relative_eq!(interaction.t, 10_f32, epsilon = TOLERANCE);
+}
+
+/// Sphere tracing a sphere should find the same hits as the analytic sphere.
+#[test]
+fn test_implicit_surface_matches_analytic_sphere() {
+    let surface = sphere_surface();
+    let sphere = Sphere::new(Vector3::new(1_f32, 2_f32, 3_f32), 2_f32);
+    let mut hit_count = 0;
+    for ray in rays().iter() {
+        let result = surface.intersect(ray);
+        let expected = sphere.intersect(ray);
+        match (result, expected) {
+            (Some(result), Some(expected)) => {
+                let position = ray.interpolate(result.t);
+                assert_relative_eq!(result.t, expected.t, epsilon = TOLERANCE);
+                assert_relative_eq!(surface.normal(&position), sphere.normal(&position), epsilon = TOLERANCE);
+                hit_count += 1;
+            }
+            (None, None) => {}
+            (result, expected) => panic!("expected {:?} but got {:?}", expected, result),
+        }
+    }
+
+    assert!(hit_count > 100);
+}
+
+#[test]
+fn test_implicit_surface_torus() {
+    let surface = ImplicitSurface::new(Sdf::torus(Vector3::zero(), 3_f32, 1_f32), Aabb::new(
+        Vector3::new(-4_f32, -1_f32, -4_f32),
+        Vector3::new(4_f32, 1_f32, 4_f32),
+    ));
+    // Straight down through the hole.
+    let ray = Ray::from_origin_dir(Vector3::new(0_f32, 5_f32, 0_f32), -Vector3::unit_y());
+
+    assert!(surface.intersect(&ray).is_none());
+
+    // Straight down onto the top of the tube.
+    let ray = Ray::from_origin_dir(Vector3::new(3_f32, 5_f32, 0_f32), -Vector3::unit_y());
+    let interaction = surface.intersect(&ray).unwrap();
+    let position = ray.interpolate(interaction.t);
+
+    assert_relative_eq!(interaction.t, 4_f32, epsilon = TOLERANCE);
+    assert_relative_eq!(surface.normal(&position), Vector3::unit_y(), epsilon = TOLERANCE);
+}
+
+#[test]
+fn test_implicit_surface_subtraction() {
+    let sdf = Sdf::cuboid(Vector3::zero(), Vector3::from_fill(1_f32))
+        .subtract(Sdf::sphere(Vector3::new(0_f32, 1_f32, 0_f32), 0.5_f32));
+    let surface = ImplicitSurface::new(sdf, unit_cube());
+    // Next to the hole, the ray hits the top of the box.
+    let ray = Ray::from_origin_dir(Vector3::new(0.75_f32, 5_f32, 0_f32), -Vector3::unit_y());
+    let interaction = surface.intersect(&ray).unwrap();
+
+    assert_relative_eq!(interaction.t, 4_f32, epsilon = TOLERANCE);
+
+    // Through the middle of the hole, the ray hits its bottom.
+    let ray = Ray::from_origin_dir(Vector3::new(0_f32, 5_f32, 0_f32), -Vector3::unit_y());
+    let interaction = surface.intersect(&ray).unwrap();
+    let position = ray.interpolate(interaction.t);
+
+    assert_relative_eq!(interaction.t, 4.5_f32, epsilon = TOLERANCE);
+    assert_relative_eq!(surface.normal(&position), Vector3::unit_y(), epsilon = TOLERANCE);
+}
+
+#[test]
+fn test_implicit_surface_repetition_bounded_by_aabb() {
+    let sdf = Sdf::sphere(Vector3::zero(), 0.5_f32).repeat(Vector3::new(2_f32, 0_f32, 0_f32));
+    let bounds = Aabb::new(Vector3::new(-3_f32, -1_f32, -1_f32), Vector3::new(3_f32, 1_f32, 1_f32));
+    let surface = ImplicitSurface::new(sdf, bounds);
+    for x in [-2_f32, 0_f32, 2_f32] {
+        let ray = Ray::from_origin_dir(Vector3::new(x, 5_f32, 0_f32), -Vector3::unit_y());
+        let interaction = surface.intersect(&ray).unwrap();
+
+        assert_relative_eq!(interaction.t, 4.5_f32, epsilon = TOLERANCE);
+    }
+
+    // The copies outside the bounding box are cut off.
+    let ray = Ray::from_origin_dir(Vector3::new(4_f32, 5_f32, 0_f32), -Vector3::unit_y());
+
+    assert!(surface.intersect(&ray).is_none());
+}
+
+#[test]
+fn test_implicit_surface_model_material_ids_follow_surfaces() {
+    let surfaces = [-4_f32, 0_f32, 4_f32].iter()
+        .map(|&x| {
+            let center = Vector3::new(x, 0_f32, 0_f32);
+            let sdf = Sdf::cuboid(center, Vector3::from_fill(0.5_f32));
+
+            ImplicitSurface::new(sdf, Aabb::new(center - Vector3::from_fill(1_f32), center + Vector3::from_fill(1_f32)))
+        })
+        .collect::<Vec<_>>();
+    let model = ModelBuilder::new()
+        .with_material_id(0, Arc::new(LambertianMaterial::new(Vector3::unit_x())))
+        .with_material_id(1, Arc::new(LambertianMaterial::new(Vector3::unit_y())))
+        .with_material_id(2, Arc::new(LambertianMaterial::new(Vector3::unit_z())))
+        .with_implicit_surfaces(surfaces)
+        .with_implicit_surface_material_ids(vec![0, 1, 2])
+        .build();
+
+    assert_eq!(model.len_primitives(), 3);
+    let handle = model.model();
+    let borrow = handle.borrow();
+    assert!(borrow.is_implicit_surfaces());
+    for (x, albedo) in [-4_f32, 0_f32, 4_f32].iter().zip([Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()]) {
+        let ray = Ray::from_origin_dir(Vector3::new(*x, 0_f32, 5_f32), -Vector3::unit_z());
+        let intersection = borrow.intersect(&ray).unwrap();
+        let material = borrow.material_at(&intersection);
+
+        assert_relative_eq!(intersection.interaction.t, 4.5_f32, epsilon = TOLERANCE);
+        assert_eq!(material.albedo(&borrow.interpolated_tex_coords(&intersection)), albedo);
+    }
+}
+
+fn floor() -> ModelInstance {
+    let normals = Normals::from([Vector3::unit_y(); 3]);
+    let mesh = MeshBuilder::new()
+        .with_primitive(
+            Triangle::new(
+                Vector3::new(-10_f32, 0_f32,  10_f32),
+                Vector3::new( 10_f32, 0_f32,  10_f32),
+                Vector3::new( 10_f32, 0_f32, -10_f32),
+            ),
+            TextureCoordinates::default(),
+            normals
+        )
+        .with_primitive(
+            Triangle::new(
+                Vector3::new(-10_f32, 0_f32,  10_f32),
+                Vector3::new( 10_f32, 0_f32, -10_f32),
+                Vector3::new(-10_f32, 0_f32, -10_f32),
+            ),
+            TextureCoordinates::default(),
+            normals
+        )
+        .build();
+
+    ModelBuilder::new()
+        .with_mesh(mesh)
+        .build()
+}
+
+/// A scene with a triangle floor and a blob of two smoothly joined spheres above
+/// it, scaled and moved into place by its instance transform.
+fn scene() -> Scene {
+    let projection_spec = BoxSpec::new(-1_f32, 1_f32, -1_f32, 1_f32, 1_f32, 100_f32);
+    let attitude_spec = CameraAttitudeSpec::new(
+         Vector3::new(0_f32, 5_f32, 20_f32),
+        -Vector3::unit_z(),
+         Vector3::unit_x(),
+         Vector3::unit_y(),
+        -Vector3::unit_z()
+    );
+    let camera = Camera::new(&projection_spec, &attitude_spec);
+    let mut physics = World::new();
+    let floor = SceneObjectBuilder::new(floor(), physics.register_body(RigidBody::default()))
+        .build();
+    let sdf = Sdf::sphere(Vector3::new(-1_f32, 0_f32, 0_f32), 1_f32)
+        .smooth_union(Sdf::sphere(Vector3::new(1_f32, 0_f32, 0_f32), 1_f32), 0.5_f32);
+    let bounds = Aabb::new(Vector3::new(-2_f32, -1.5_f32, -1.5_f32), Vector3::new(2_f32, 1.5_f32, 1.5_f32));
+    let blob = ModelBuilder::new()
+        .with_material(Arc::new(LambertianMaterial::new(Vector3::new(0.2_f32, 0.4_f32, 0.6_f32))))
+        .with_implicit_surfaces(vec![ImplicitSurface::new(sdf, bounds)])
+        .build();
+    let transform = Transform3::new(
+        &Vector3::from_fill(2_f32),
+        &Vector3::new(0_f32, 4_f32, 0_f32),
+        Rotation3::identity()
+    );
+    let blob = SceneObjectBuilder::new(blob, physics.register_body(RigidBody::default()))
+        .with_transform(&transform)
+        .build();
+
+    SceneBuilder::new(camera)
+        .with_physics(physics)
+        .with_objects(vec![floor, blob])
+        .build()
+}
+
+#[test]
+fn test_scene_with_implicit_surfaces_and_triangles() {
+    let scene = scene();
+    let ray = Ray::from_origin_dir(Vector3::new(-2_f32, 20_f32, 0_f32), -Vector3::unit_y());
+    let intersection = scene.intersect(&ray).unwrap();
+
+    assert_eq!(intersection.instance_primitive.instance_index(), 1);
+    // The top of the left sphere is at `y == 4 + 2 * 1` after the instance transform.
+    assert_relative_eq!(intersection.interaction.t, 14_f32, epsilon = 2_f32 * TOLERANCE);
+
+    let ray = Ray::from_origin_dir(Vector3::new(-6_f32, 20_f32, 0_f32), -Vector3::unit_y());
+    let intersection = scene.intersect(&ray).unwrap();
+
+    assert_eq!(intersection.instance_primitive.instance_index(), 0);
+    assert_relative_eq!(intersection.interaction.t, 20_f32, epsilon = 1e-4);
+}
+
+#[test]
+fn test_scene_implicit_surface_normals_and_materials() {
+    let scene = scene();
+    let ray = Ray::from_origin_dir(Vector3::new(-2_f32, 20_f32, 0_f32), -Vector3::unit_y());
+    let mut normals = NormalMappingAccumulator::new();
+    let mut materials = TextureMaterialAccumulator::new();
+    let expected = (Vector3::unit_y() + Vector3::from_fill(1_f32)) * 0.5_f32;
+
+    assert_relative_eq!(normals.evaluate(&scene, &ray), expected, epsilon = TOLERANCE);
+    assert_eq!(materials.evaluate(&scene, &ray), Vector3::new(0.2_f32, 0.4_f32, 0.6_f32));
+}
@@ -0,0 +1,181 @@
+use bvhtracer::{
+    BvhBuilder,
+    IndexedMesh,
+    IndexedMeshBuilder,
+    Mesh,
+    MeshDecoder,
+    ObjMeshDecoder,
+    Ray,
+};
+use cglinalg::{
+    Vector2,
+    Vector3,
+};
+use std::fs::{
+    File,
+};
+
+
+const PI: f32 = std::f32::consts::PI;
+
+
+fn cube_mesh() -> Mesh<f32> {
+    let file = File::open("assets/cube.obj").unwrap();
+
+    ObjMeshDecoder::new(file).read_mesh().unwrap()
+}
+
+/// A unit sphere whose vertices are shared by the neighboring triangles of a
+/// latitude-longitude grid. The northern hemisphere uses material one.
+fn indexed_sphere(x_segments: u32, y_segments: u32) -> IndexedMesh<f32> {
+    let mut builder = IndexedMeshBuilder::new();
+    for y in 0..(y_segments + 1) {
+        for x in 0..(x_segments + 1) {
+            let u = x as f32 / x_segments as f32;
+            let v = y as f32 / y_segments as f32;
+            let position = Vector3::new(
+                f32::cos(u * 2_f32 * PI) * f32::sin(v * PI),
+                f32::cos(v * PI),
+                f32::sin(u * 2_f32 * PI) * f32::sin(v * PI),
+            );
+            builder = builder.with_vertex(position, Vector2::new(u, v), position);
+        }
+    }
+    for y in 0..y_segments {
+        let material_id = if y < y_segments / 2 { 1 } else { 0 };
+        builder = builder.with_material_id(material_id);
+        for x in 0..x_segments {
+            let i0 = y * (x_segments + 1) + x;
+            let i1 = i0 + 1;
+            let i2 = i0 + x_segments + 1;
+            let i3 = i2 + 1;
+            builder = builder
+                .with_triangle([i0, i2, i1])
+                .with_triangle([i1, i2, i3]);
+        }
+    }
+
+    builder.build()
+}
+
+/// Rays from a ring of points around the sphere aimed at points scattered
+/// around its center.
+fn rays() -> Vec<Ray<f32>> {
+    (0..200)
+        .map(|i| {
+            let angle = (i as f32) * 0.37_f32;
+            let origin = Vector3::new(5_f32 * f32::cos(angle), 0.1_f32 * (i % 17) as f32 - 0.8_f32, 5_f32 * f32::sin(angle));
+            let target = Vector3::new(0.01_f32 * (i % 7) as f32, 0.02_f32 * (i % 5) as f32, 0_f32) * 4_f32;
+            let direction = target - origin;
+
+            Ray::new(origin, direction, f32::MAX)
+        })
+        .collect()
+}
+
+
+#[test]
+fn test_indexed_mesh_from_cube_merges_vertices() {
+    let mesh = cube_mesh();
+    let indexed_mesh = IndexedMesh::from_mesh(&mesh);
+
+    assert_eq!(indexed_mesh.len_primitives(), 12);
+    // Each corner of the cube has a different normal on each of its three faces.
+    assert_eq!(indexed_mesh.len_vertices(), 24);
+    assert_eq!(indexed_mesh.indices().len(), 36);
+}
+
+#[test]
+fn test_indexed_mesh_round_trip() {
+    let mesh = cube_mesh();
+    let result = IndexedMesh::from_mesh(&mesh).to_mesh();
+
+    assert_eq!(result, mesh);
+}
+
+#[test]
+fn test_indexed_mesh_round_trip_keeps_materials() {
+    let mesh = indexed_sphere(8, 6).to_mesh();
+    let result = IndexedMesh::from_mesh(&mesh).to_mesh();
+
+    assert_eq!(result.material_ids(), mesh.material_ids());
+    assert!(result == mesh);
+}
+
+#[test]
+fn test_indexed_mesh_primitive_attributes() {
+    let indexed_mesh = indexed_sphere(8, 6);
+    let mesh = indexed_mesh.to_mesh();
+    for i in 0..indexed_mesh.len_primitives() {
+        assert_eq!(indexed_mesh.primitive(i), mesh.primitives()[i]);
+        assert_eq!(indexed_mesh.primitive_tex_coords(i), mesh.tex_coords()[i]);
+        assert_eq!(indexed_mesh.primitive_normals(i), mesh.normals()[i]);
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_indexed_mesh_builder_index_out_of_bounds() {
+    let _ = IndexedMeshBuilder::<f32>::new()
+        .with_vertex(Vector3::zero(), Vector2::zero(), Vector3::unit_z())
+        .with_triangle([0, 0, 1])
+        .build();
+}
+
+/// The indexed BVH finds the same intersections as the BVH of the equivalent
+/// triangle soup.
+#[test]
+fn test_indexed_bvh_intersect_matches_soup_bvh() {
+    let mut indexed_mesh = indexed_sphere(32, 16);
+    let mut mesh = indexed_mesh.to_mesh();
+    let indexed_bvh = BvhBuilder::new().build_for_indexed_mesh(&mut indexed_mesh);
+    let bvh = BvhBuilder::new().build_for_mesh(&mut mesh);
+    for ray in rays().iter() {
+        let expected = bvh.intersect(mesh.primitives(), ray).map(|intersection| intersection.interaction.t);
+        let result = indexed_bvh.intersect_indexed(&indexed_mesh, ray).map(|intersection| intersection.interaction.t);
+
+        assert!(expected.is_some());
+        assert_eq!(result, expected);
+    }
+}
+
+#[test]
+fn test_indexed_bvh_occluded() {
+    let mut indexed_mesh = indexed_sphere(32, 16);
+    let bvh = BvhBuilder::new().build_for_indexed_mesh(&mut indexed_mesh);
+    let hitting_ray = Ray::new(Vector3::new(0_f32, 0_f32, 5_f32), -Vector3::unit_z(), f32::MAX);
+    let short_ray = Ray::new(Vector3::new(0_f32, 0_f32, 5_f32), -Vector3::unit_z(), 1_f32);
+    let missing_ray = Ray::new(Vector3::new(0_f32, 0_f32, 5_f32), Vector3::unit_z(), f32::MAX);
+
+    assert!(bvh.occluded_indexed(&indexed_mesh, &hitting_ray));
+    assert!(!bvh.occluded_indexed(&indexed_mesh, &short_ray));
+    assert!(!bvh.occluded_indexed(&indexed_mesh, &missing_ray));
+}
+
+/// Building a BVH reorders the triangles of the mesh, and the material ids
+/// have to follow them.
+#[test]
+fn test_indexed_bvh_build_keeps_material_ids_aligned() {
+    let mut indexed_mesh = indexed_sphere(32, 16);
+    let _ = BvhBuilder::new().build_for_indexed_mesh(&mut indexed_mesh);
+    for i in 0..indexed_mesh.len_primitives() {
+        let expected = if indexed_mesh.primitive(i).centroid().y > 0_f32 { 1 } else { 0 };
+
+        assert_eq!(indexed_mesh.material_ids()[i], expected);
+    }
+}
+
+#[test]
+fn test_indexed_bvh_refit() {
+    let mut indexed_mesh = indexed_sphere(32, 16);
+    let mut bvh = BvhBuilder::new().build_for_indexed_mesh(&mut indexed_mesh);
+    for vertex in indexed_mesh.vertices_mut().iter_mut() {
+        *vertex *= 2_f32;
+    }
+    bvh.refit_indexed(&indexed_mesh);
+    let ray = Ray::new(Vector3::new(0_f32, 0_f32, 5_f32), -Vector3::unit_z(), f32::MAX);
+    let intersection = bvh.intersect_indexed(&indexed_mesh, &ray).unwrap();
+
+    assert_eq!(bvh.bounds().bounds_max.x, 2_f32);
+    assert!((intersection.interaction.t - 3_f32).abs() < 1e-2);
+}
@@ -0,0 +1,579 @@
+use bvhtracer::{
+    Scene,
+    Camera,
+    CameraAttitudeSpec,
+    PerspectiveProjection,
+    SimpleModelDecoder,
+    ModelDecoder,
+    SceneObjectBuilder,
+    SceneBuilder,
+    BoxSpec,
+    World,
+    RigidBody,
+    Transform3,
+    PathTracer,
+    Renderer,
+    RendererState,
+    RadianceToRgbShader,
+    DepthAccumulator,
+    Rgba,
+    Light,
+    PointLight,
+    DirectionalLight,
+    SpotLight,
+    AreaLight,
+    ShapeLight,
+    Quad,
+    Disk,
+    MeshBuilder,
+    ModelBuilder,
+    Mesh,
+    Triangle,
+    TextureCoordinates,
+    Normals,
+};
+use approx::{
+    assert_relative_eq,
+};
+use cglinalg::{
+    Degrees,
+    Vector2,
+    Vector3,
+    Magnitude,
+    Rotation3,
+};
+use std::fs::{
+    File,
+};
+
+
+fn camera() -> Camera<f32, PerspectiveProjection<f32>> {
+    let projection_spec = BoxSpec::new(
+        -1_f32,
+        1_f32,
+        -1_f32,
+        1_f32,
+        1_f32,
+        100_f32,
+    );
+    let position = Vector3::new(0_f32, 4_f32, 0_f32);
+    let forward = (Vector3::zero() - position).normalize();
+    let attitude_spec = CameraAttitudeSpec::new(
+        position,
+        forward,
+        -Vector3::unit_x(),
+        Vector3::unit_z(),
+        -forward
+    );
+    
+    Camera::new(&projection_spec, &attitude_spec)
+}
+
+fn scene(lights: Vec<Box<dyn Light>>) -> Scene {
+    let mesh_reader = File::open("assets/cube.obj").unwrap();
+    let material_reader = File::open("assets/bricks_rgb.png").unwrap();
+    let model = SimpleModelDecoder::new(mesh_reader, material_reader)
+        .read_model()
+        .unwrap();
+    let transform = {
+        let scale = Vector3::from_fill(2_f32);
+        let translation = Vector3::new(-1_f32, -1_f32, -1_f32);
+        let rotation = Rotation3::identity();
+        Transform3::new(&scale, &translation, rotation)
+    };
+    let mut physics = World::new();
+    let rigid_body_instance = physics.register_body(RigidBody::default());
+    let scene_object = SceneObjectBuilder::new(model, rigid_body_instance)
+        .with_transform(&transform)
+        .build();
+    let active_scene = SceneBuilder::new(camera())
+        .with_physics(physics)
+        .with_object(scene_object)
+        .with_lights(lights)
+        .build();
+
+    active_scene
+}
+
+/// A scene containing a four by four untextured floor at `y == 0` with no 
+/// vertex normals.
+fn floor_scene(lights: Vec<Box<dyn Light>>) -> Scene {
+    floor_scene_with_camera(camera(), lights)
+}
+
+/// The floor scene of [`floor_scene`] seen through the camera `camera`.
+fn floor_scene_with_camera(camera: Camera<f32, PerspectiveProjection<f32>>, lights: Vec<Box<dyn Light>>) -> Scene {
+    let mesh = MeshBuilder::new()
+        .with_primitive(
+            Triangle::new(
+                Vector3::new(-1_f32, 0_f32, -1_f32),
+                Vector3::new(1_f32, 0_f32, 1_f32),
+                Vector3::new(1_f32, 0_f32, -1_f32),
+            ),
+            TextureCoordinates::default(),
+            Normals::default(),
+        )
+        .with_primitive(
+            Triangle::new(
+                Vector3::new(-1_f32, 0_f32, -1_f32),
+                Vector3::new(-1_f32, 0_f32, 1_f32),
+                Vector3::new(1_f32, 0_f32, 1_f32),
+            ),
+            TextureCoordinates::default(),
+            Normals::default(),
+        )
+        .build();
+    let model = ModelBuilder::new()
+        .with_mesh(mesh)
+        .build();
+    let transform = Transform3::from_scale(2_f32);
+    let mut physics = World::new();
+    let rigid_body_instance = physics.register_body(RigidBody::default());
+    let scene_object = SceneObjectBuilder::new(model, rigid_body_instance)
+        .with_transform(&transform)
+        .build();
+    let active_scene = SceneBuilder::new(camera)
+        .with_physics(physics)
+        .with_object(scene_object)
+        .with_lights(lights)
+        .build();
+
+    active_scene
+}
+
+/// A two by two square panel at `y == 1` facing down the negative y-axis.
+fn ceiling_panel() -> Mesh<f32> {
+    MeshBuilder::new()
+        .with_primitive(
+            Triangle::new(
+                Vector3::new(-1_f32, 1_f32, -1_f32),
+                Vector3::new(1_f32, 1_f32, -1_f32),
+                Vector3::new(1_f32, 1_f32, 1_f32),
+            ),
+            TextureCoordinates::default(),
+            Normals::default(),
+        )
+        .with_primitive(
+            Triangle::new(
+                Vector3::new(-1_f32, 1_f32, -1_f32),
+                Vector3::new(1_f32, 1_f32, 1_f32),
+                Vector3::new(-1_f32, 1_f32, 1_f32),
+            ),
+            TextureCoordinates::default(),
+            Normals::default(),
+        )
+        .build()
+}
+
+
+#[test]
+fn test_point_light_inverse_square_falloff() {
+    let light = PointLight::new(Vector3::new(0_f32, 2_f32, 0_f32), Vector3::from_fill(8_f32));
+    let u = Vector2::new(0.5_f32, 0.5_f32);
+    let sample_near = light.sample(&Vector3::new(0_f32, 1_f32, 0_f32), &u).unwrap();
+    let sample_far = light.sample(&Vector3::new(0_f32, 0_f32, 0_f32), &u).unwrap();
+
+    assert_eq!(sample_near.radiance, Vector3::from_fill(8_f32));
+    assert_eq!(sample_far.radiance, Vector3::from_fill(2_f32));
+    assert_eq!(sample_far.direction, Vector3::unit_y());
+    assert_eq!(sample_far.distance, 2_f32);
+    assert!(light.is_delta());
+}
+
+#[test]
+fn test_point_light_cannot_be_hit() {
+    let light = PointLight::new(Vector3::new(0_f32, 2_f32, 0_f32), Vector3::from_fill(8_f32));
+    let point = Vector3::zero();
+    let direction = Vector3::unit_y();
+
+    assert_eq!(light.pdf(&point, &direction), 0_f32);
+    assert_eq!(light.eval(&point, &direction), Vector3::zero());
+}
+
+#[test]
+fn test_directional_light_sample() {
+    let light = DirectionalLight::new(Vector3::new(0_f32, -2_f32, 0_f32), Vector3::from_fill(3_f32));
+    let u = Vector2::new(0.5_f32, 0.5_f32);
+    let sample = light.sample(&Vector3::new(5_f32, 0_f32, -7_f32), &u).unwrap();
+
+    assert_eq!(sample.radiance, Vector3::from_fill(3_f32));
+    assert_eq!(sample.direction, Vector3::unit_y());
+    assert_eq!(sample.distance, f32::MAX);
+    assert_eq!(sample.shadow_ray(&Vector3::zero()).t, f32::MAX);
+    assert!(light.is_delta());
+}
+
+#[test]
+fn test_spot_light_inside_and_outside_cone() {
+    let light = SpotLight::new(
+        Vector3::new(0_f32, 2_f32, 0_f32),
+        -Vector3::unit_y(),
+        Vector3::from_fill(4_f32),
+        Degrees(30_f32),
+        Degrees(20_f32),
+    );
+    let u = Vector2::new(0.5_f32, 0.5_f32);
+    let sample_center = light.sample(&Vector3::zero(), &u).unwrap();
+    let sample_outside = light.sample(&Vector3::new(10_f32, 0_f32, 0_f32), &u);
+
+    assert_eq!(sample_center.radiance, Vector3::from_fill(1_f32));
+    assert!(sample_outside.is_none());
+}
+
+#[test]
+fn test_spot_light_falloff_is_between_full_and_zero() {
+    let light = SpotLight::new(
+        Vector3::new(0_f32, 1_f32, 0_f32),
+        -Vector3::unit_y(),
+        Vector3::from_fill(1_f32),
+        Degrees(30_f32),
+        Degrees(20_f32),
+    );
+    let u = Vector2::new(0.5_f32, 0.5_f32);
+    // The point lies 25 degrees off the spot light axis.
+    let point = Vector3::new(f32::tan(25_f32.to_radians()), 0_f32, 0_f32);
+    let sample = light.sample(&point, &u).unwrap();
+    let unattenuated = 1_f32 / (point - Vector3::unit_y()).magnitude_squared();
+
+    assert!(sample.radiance.x > 0_f32);
+    assert!(sample.radiance.x < unattenuated);
+}
+
+#[test]
+fn test_area_light_area() {
+    let light = AreaLight::new(&ceiling_panel(), &Transform3::identity(), Vector3::from_fill(1_f32));
+
+    assert_relative_eq!(light.area(), 4_f32, epsilon = 1e-6);
+    assert!(!light.is_delta());
+}
+
+#[test]
+fn test_area_light_transform() {
+    let transform = Transform3::new(
+        &Vector3::from_fill(2_f32), 
+        &Vector3::new(0_f32, 3_f32, 0_f32), 
+        Rotation3::identity()
+    );
+    let light = AreaLight::new(&ceiling_panel(), &transform, Vector3::from_fill(1_f32));
+
+    assert_relative_eq!(light.area(), 16_f32, epsilon = 1e-5);
+    for primitive in light.primitives().iter() {
+        for vertex in primitive.vertices.iter() {
+            assert_relative_eq!(vertex.y, 5_f32, epsilon = 1e-6);
+        }
+    }
+}
+
+/// Points sampled on an area light should lie on the light's surface.
+#[test]
+fn test_area_light_samples_lie_on_light() {
+    let light = AreaLight::new(&ceiling_panel(), &Transform3::identity(), Vector3::from_fill(1_f32));
+    let point = Vector3::new(0.25_f32, -1_f32, 0.5_f32);
+    for i in 0..16 {
+        for j in 0..16 {
+            let u = Vector2::new((i as f32 + 0.5_f32) / 16_f32, (j as f32 + 0.5_f32) / 16_f32);
+            let sample = light.sample(&point, &u).unwrap();
+            let light_point = point + sample.direction * sample.distance;
+
+            assert_relative_eq!(light_point.y, 1_f32, epsilon = 1e-5);
+            assert!(light_point.x >= -1_f32 - 1e-5 && light_point.x <= 1_f32 + 1e-5);
+            assert!(light_point.z >= -1_f32 - 1e-5 && light_point.z <= 1_f32 + 1e-5);
+        }
+    }
+}
+
+/// The density that an area light reports for a direction should match the 
+/// density of sampling that direction.
+#[test]
+fn test_area_light_pdf_matches_sample() {
+    let light = AreaLight::new(&ceiling_panel(), &Transform3::identity(), Vector3::from_fill(1_f32));
+    let point = Vector3::new(0.25_f32, -1_f32, 0.5_f32);
+    for i in 0..8 {
+        for j in 0..8 {
+            let u = Vector2::new((i as f32 + 0.5_f32) / 8_f32, (j as f32 + 0.5_f32) / 8_f32);
+            let sample = light.sample(&point, &u).unwrap();
+            let pdf = light.pdf(&point, &sample.direction);
+
+            assert_relative_eq!(pdf, sample.pdf, max_relative = 1e-3);
+        }
+    }
+}
+
+#[test]
+fn test_area_light_pdf_straight_below() {
+    let light = AreaLight::new(&ceiling_panel(), &Transform3::identity(), Vector3::from_fill(1_f32));
+    let point = Vector3::zero();
+    let direction = Vector3::unit_y();
+
+    // The squared distance of one over the cosine of one times the area of four.
+    assert_relative_eq!(light.pdf(&point, &direction), 0.25_f32, epsilon = 1e-6);
+}
+
+/// A one sided area light only emits from its front face.
+#[test]
+fn test_area_light_one_sided() {
+    let radiance = Vector3::new(1_f32, 2_f32, 3_f32);
+    let light = AreaLight::new(&ceiling_panel(), &Transform3::identity(), radiance);
+    let below = Vector3::zero();
+    let above = Vector3::new(0_f32, 2_f32, 0_f32);
+    let u = Vector2::new(0.5_f32, 0.5_f32);
+
+    assert_eq!(light.eval(&below, &Vector3::unit_y()), radiance);
+    assert_eq!(light.eval(&above, &(-Vector3::unit_y())), Vector3::zero());
+    assert_eq!(light.pdf(&above, &(-Vector3::unit_y())), 0_f32);
+    assert!(light.sample(&above, &u).is_none());
+}
+
+#[test]
+fn test_area_light_two_sided() {
+    let radiance = Vector3::new(1_f32, 2_f32, 3_f32);
+    let light = AreaLight::new(&ceiling_panel(), &Transform3::identity(), radiance)
+        .with_two_sided(true);
+    let above = Vector3::new(0_f32, 2_f32, 0_f32);
+    let u = Vector2::new(0.5_f32, 0.5_f32);
+
+    assert_eq!(light.eval(&above, &(-Vector3::unit_y())), radiance);
+    assert!(light.sample(&above, &u).is_some());
+}
+
+#[test]
+fn test_area_light_eval_miss() {
+    let light = AreaLight::new(&ceiling_panel(), &Transform3::identity(), Vector3::from_fill(1_f32));
+    let point = Vector3::zero();
+    let direction = Vector3::unit_x();
+
+    assert_eq!(light.eval(&point, &direction), Vector3::zero());
+    assert_eq!(light.pdf(&point, &direction), 0_f32);
+}
+
+#[test]
+fn test_area_light_from_scene_object() {
+    let scene = scene(vec![]);
+    let light = AreaLight::from_scene_object(scene.get_unchecked(0), Vector3::from_fill(1_f32));
+
+    // The cube has six faces of side length two.
+    assert_relative_eq!(light.area(), 24_f32, epsilon = 1e-4);
+}
+
+/// A two by two quad at `y == 1` facing down, like the ceiling panel.
+fn ceiling_quad() -> Quad<f32> {
+    Quad::new(Vector3::new(-1_f32, 1_f32, -1_f32), Vector3::new(2_f32, 0_f32, 0_f32), Vector3::new(0_f32, 0_f32, 2_f32))
+}
+
+/// A disk of radius one at `y == 1` facing down.
+fn ceiling_disk() -> Disk<f32> {
+    Disk::new(Vector3::new(0_f32, 1_f32, 0_f32), -Vector3::unit_y(), 1_f32)
+}
+
+#[test]
+fn test_shape_light_area() {
+    let quad_light = ShapeLight::from_quad(ceiling_quad(), Vector3::from_fill(1_f32));
+    let disk_light = ShapeLight::from_disk(ceiling_disk(), Vector3::from_fill(1_f32));
+
+    assert_relative_eq!(quad_light.area(), 4_f32, epsilon = 1e-6);
+    assert_relative_eq!(disk_light.area(), std::f32::consts::PI, epsilon = 1e-6);
+    assert!(!quad_light.is_delta());
+    assert!(!disk_light.is_delta());
+}
+
+/// Points sampled on a shape light should lie on the light's surface.
+#[test]
+fn test_shape_light_samples_lie_on_light() {
+    let quad_light = ShapeLight::from_quad(ceiling_quad(), Vector3::from_fill(1_f32));
+    let disk_light = ShapeLight::from_disk(ceiling_disk(), Vector3::from_fill(1_f32));
+    let point = Vector3::new(0.25_f32, -1_f32, 0.5_f32);
+    for i in 0..16 {
+        for j in 0..16 {
+            let u = Vector2::new((i as f32 + 0.5_f32) / 16_f32, (j as f32 + 0.5_f32) / 16_f32);
+            let sample = quad_light.sample(&point, &u).unwrap();
+            let light_point = point + sample.direction * sample.distance;
+
+            assert_relative_eq!(light_point.y, 1_f32, epsilon = 1e-5);
+            assert!(light_point.x >= -1_f32 - 1e-5 && light_point.x <= 1_f32 + 1e-5);
+            assert!(light_point.z >= -1_f32 - 1e-5 && light_point.z <= 1_f32 + 1e-5);
+
+            let sample = disk_light.sample(&point, &u).unwrap();
+            let light_point = point + sample.direction * sample.distance;
+
+            assert_relative_eq!(light_point.y, 1_f32, epsilon = 1e-5);
+            assert!(Vector2::new(light_point.x, light_point.z).magnitude() <= 1_f32 + 1e-5);
+        }
+    }
+}
+
+/// Sampling a disk light uniformly by area should put a quarter of the samples
+/// inside half of its radius.
+#[test]
+fn test_shape_light_disk_samples_uniform_by_area() {
+    let light = ShapeLight::from_disk(ceiling_disk(), Vector3::from_fill(1_f32));
+    let point = Vector3::zero();
+    let mut inside = 0;
+    for i in 0..32 {
+        for j in 0..32 {
+            let u = Vector2::new((i as f32 + 0.5_f32) / 32_f32, (j as f32 + 0.5_f32) / 32_f32);
+            let sample = light.sample(&point, &u).unwrap();
+            let light_point = point + sample.direction * sample.distance;
+            if Vector2::new(light_point.x, light_point.z).magnitude() < 0.5_f32 {
+                inside += 1;
+            }
+        }
+    }
+
+    assert_eq!(inside, 32 * 32 / 4);
+}
+
+/// The density that a shape light reports for a direction should match the
+/// density of sampling that direction.
+#[test]
+fn test_shape_light_pdf_matches_sample() {
+    let lights = [
+        ShapeLight::from_quad(ceiling_quad(), Vector3::from_fill(1_f32)),
+        ShapeLight::from_disk(ceiling_disk(), Vector3::from_fill(1_f32)),
+    ];
+    let point = Vector3::new(0.25_f32, -1_f32, 0.5_f32);
+    for light in lights.iter() {
+        for i in 0..8 {
+            for j in 0..8 {
+                let u = Vector2::new((i as f32 + 0.5_f32) / 8_f32, (j as f32 + 0.5_f32) / 8_f32);
+                let sample = light.sample(&point, &u).unwrap();
+                let pdf = light.pdf(&point, &sample.direction);
+
+                assert_relative_eq!(pdf, sample.pdf, max_relative = 1e-3);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_shape_light_pdf_straight_below() {
+    let light = ShapeLight::from_disk(ceiling_disk(), Vector3::from_fill(1_f32));
+    let point = Vector3::zero();
+    let direction = Vector3::unit_y();
+
+    // The squared distance of one over the cosine of one times the area of pi.
+    assert_relative_eq!(light.pdf(&point, &direction), 1_f32 / std::f32::consts::PI, epsilon = 1e-6);
+}
+
+/// A shape light only emits from its front face unless it is two sided.
+#[test]
+fn test_shape_light_one_and_two_sided() {
+    let radiance = Vector3::new(1_f32, 2_f32, 3_f32);
+    let light = ShapeLight::from_quad(ceiling_quad(), radiance);
+    let below = Vector3::zero();
+    let above = Vector3::new(0_f32, 2_f32, 0_f32);
+    let u = Vector2::new(0.5_f32, 0.5_f32);
+
+    assert_eq!(light.eval(&below, &Vector3::unit_y()), radiance);
+    assert_eq!(light.eval(&above, &(-Vector3::unit_y())), Vector3::zero());
+    assert!(light.sample(&above, &u).is_none());
+
+    let light = light.with_two_sided(true);
+
+    assert!(light.is_two_sided());
+    assert_eq!(light.eval(&above, &(-Vector3::unit_y())), radiance);
+    assert!(light.sample(&above, &u).is_some());
+}
+
+#[test]
+fn test_scene_lights() {
+    let lights: Vec<Box<dyn Light>> = vec![
+        Box::new(PointLight::new(Vector3::new(0_f32, 3_f32, 0_f32), Vector3::from_fill(1_f32))),
+        Box::new(DirectionalLight::new(-Vector3::unit_y(), Vector3::from_fill(1_f32))),
+    ];
+    let scene = scene(lights);
+
+    assert_eq!(scene.lights().len(), 2);
+}
+
+/// A light that is blocked from a point by the scene geometry should not 
+/// illuminate it.
+#[test]
+fn test_light_sample_shadow_ray_occluded() {
+    let light = PointLight::new(Vector3::new(0_f32, 3_f32, 0_f32), Vector3::from_fill(1_f32));
+    let scene = scene(vec![]);
+    let u = Vector2::new(0.5_f32, 0.5_f32);
+    let point_below = Vector3::new(0_f32, -3_f32, 0_f32);
+    let point_beside = Vector3::new(3_f32, 0_f32, 0_f32);
+    let sample_below = light.sample(&point_below, &u).unwrap();
+    let sample_beside = light.sample(&point_beside, &u).unwrap();
+
+    assert!(scene.occluded(&sample_below.shadow_ray(&point_below)));
+    assert!(!scene.occluded(&sample_beside.shadow_ray(&point_beside)));
+}
+
+/// With a black background, the only light in the scene comes from the 
+/// scene's light sources.
+#[test]
+fn test_path_tracer_point_light_illuminates_scene() {
+    let width = 64;
+    let height = 64;
+    let render = |lights| {
+        let scene = floor_scene(lights);
+        let accumulator = Box::new(DepthAccumulator::new());
+        let pixel_shader = Box::new(RadianceToRgbShader::new());
+        let mut renderer_state = RendererState::new(accumulator, pixel_shader, width, height);
+        let path_tracer = PathTracer::new()
+            .with_background(Vector3::zero());
+        let mut renderer = Renderer::new(Box::new(path_tracer));
+        renderer.render(&mut renderer_state, &scene);
+        
+        renderer_state
+    };
+    let light = PointLight::new(Vector3::new(0_f32, 3_f32, 0_f32), Vector3::from_fill(10_f32));
+    let renderer_state_lit = render(vec![Box::new(light)]);
+    let renderer_state_unlit = render(vec![]);
+    let result_lit = renderer_state_lit.frame_buffer().as_buffer()[(width / 2, height / 2)];
+    let result_unlit = renderer_state_unlit.frame_buffer().as_buffer()[(width / 2, height / 2)];
+    let result_miss = renderer_state_lit.frame_buffer().as_buffer()[(0, 0)];
+    let black = Rgba::new(0, 0, 0, 255);
+
+    assert_ne!(result_lit, black);
+    assert_eq!(result_unlit, black);
+    assert_eq!(result_miss, black);
+}
+
+/// A floor seen from a camera far away is lit evenly by a light straight above 
+/// it. The hit positions are near the origin, but they carry the rounding error 
+/// of the long primary rays, so shadow rays offset only by the size of the hit 
+/// position start under the floor and shadow it.
+#[test]
+fn test_path_tracer_distant_camera_floor_has_no_shadow_acne() {
+    let width = 64;
+    let height = 64;
+    let projection_spec = BoxSpec::new(
+        -0.001_f32,
+        0.001_f32,
+        -0.001_f32,
+        0.001_f32,
+        1_f32,
+        10000_f32,
+    );
+    let position = Vector3::new(600_f32, 800_f32, 0_f32);
+    let forward = (Vector3::zero() - position).normalize();
+    let attitude_spec = CameraAttitudeSpec::new(
+        position,
+        forward,
+        -Vector3::unit_z(),
+        Vector3::new(-0.8_f32, 0.6_f32, 0_f32),
+        -forward
+    );
+    let camera = Camera::new(&projection_spec, &attitude_spec);
+    let light = DirectionalLight::new(-Vector3::unit_y(), Vector3::from_fill(1_f32));
+    let scene = floor_scene_with_camera(camera, vec![Box::new(light)]);
+    let accumulator = Box::new(DepthAccumulator::new());
+    let pixel_shader = Box::new(RadianceToRgbShader::new());
+    let mut renderer_state = RendererState::new(accumulator, pixel_shader, width, height);
+    let path_tracer = PathTracer::new()
+        .with_background(Vector3::zero());
+    let mut renderer = Renderer::new(Box::new(path_tracer));
+    renderer.render(&mut renderer_state, &scene);
+    let frame_buffer = renderer_state.frame_buffer().as_buffer();
+    let expected = frame_buffer[(width / 2, height / 2)];
+
+    assert_ne!(expected, Rgba::new(0, 0, 0, 255));
+    for y in 0..height {
+        for x in 0..width {
+            assert_eq!(frame_buffer[(x, y)], expected, "pixel = ({}, {})", x, y);
+        }
+    }
+}
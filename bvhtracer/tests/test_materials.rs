@@ -0,0 +1,341 @@
+use bvhtracer::{
+    Material,
+    LambertianMaterial,
+    MirrorMaterial,
+    ConductorMaterial,
+    DielectricMaterial,
+    PlasticMaterial,
+    MaterialParameter,
+    ShadingFrame,
+    TextureMaterial,
+    TextureBuffer2D,
+    Rgb,
+};
+use approx::{
+    assert_relative_eq,
+};
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+};
+use std::f32;
+
+
+const GRID_SIZE: usize = 64;
+
+/// A unit direction above the surface at polar angle `theta` from the normal.
+fn direction(theta: f32, phi: f32) -> Vector3<f32> {
+    Vector3::new(
+        f32::sin(theta) * f32::cos(phi), 
+        f32::sin(theta) * f32::sin(phi), 
+        f32::cos(theta)
+    )
+}
+
+/// Stratified random numbers in `[0, 1)^2`.
+fn grid() -> impl Iterator<Item = Vector2<f32>> {
+    (0..GRID_SIZE).flat_map(|i| (0..GRID_SIZE).map(move |j| {
+        Vector2::new(
+            (i as f32 + 0.5_f32) / (GRID_SIZE as f32),
+            (j as f32 + 0.5_f32) / (GRID_SIZE as f32),
+        )
+    }))
+}
+
+/// Estimate the fraction of light arriving from `wo` that a material reflects.
+fn directional_albedo(material: &dyn Material, wo: &Vector3<f32>) -> Vector3<f32> {
+    let uv = Vector2::zero();
+    let mut albedo = Vector3::zero();
+    for u in grid() {
+        if let Some(sample) = material.sample(&uv, wo, &u) {
+            albedo += sample.weight();
+        }
+    }
+
+    albedo / ((GRID_SIZE * GRID_SIZE) as f32)
+}
+
+/// Sampled directions should agree with the densities and values the material 
+/// reports for them.
+fn assert_sample_consistent(material: &dyn Material, wo: &Vector3<f32>) {
+    let uv = Vector2::zero();
+    for u in grid() {
+        if let Some(sample) = material.sample(&uv, wo, &u) {
+            let pdf = material.pdf(&uv, wo, &sample.direction);
+            let value = material.eval(&uv, wo, &sample.direction);
+
+            assert!(!sample.is_specular);
+            assert_relative_eq!(sample.direction.magnitude(), 1_f32, epsilon = 1e-4);
+            assert_relative_eq!(pdf, sample.pdf, max_relative = 1e-3);
+            assert_relative_eq!(value, sample.value, max_relative = 1e-3);
+        }
+    }
+}
+
+
+#[test]
+fn test_lambertian_eval() {
+    let material = LambertianMaterial::new(Vector3::new(0.2_f32, 0.4_f32, 0.6_f32));
+    let uv = Vector2::zero();
+    let wo = direction(0.3_f32, 0_f32);
+    let wi = direction(0.7_f32, 1_f32);
+    let expected = Vector3::new(0.2_f32, 0.4_f32, 0.6_f32) / f32::consts::PI;
+
+    assert_relative_eq!(material.eval(&uv, &wo, &wi), expected, epsilon = 1e-6);
+    assert_relative_eq!(material.pdf(&uv, &wo, &wi), wi.z / f32::consts::PI, epsilon = 1e-6);
+    assert!(!material.is_specular());
+}
+
+#[test]
+fn test_lambertian_below_surface() {
+    let material = LambertianMaterial::new(Vector3::from_fill(0.5_f32));
+    let uv = Vector2::zero();
+    let wo = direction(0.3_f32, 0_f32);
+    let wi = -direction(0.7_f32, 1_f32);
+    let u = Vector2::new(0.5_f32, 0.5_f32);
+
+    assert_eq!(material.eval(&uv, &wo, &wi), Vector3::zero());
+    assert!(material.sample(&uv, &(-wo), &u).is_none());
+}
+
+/// Cosine-weighted sampling cancels the cosine term and the factor of pi in a 
+/// diffuse material, leaving only its albedo.
+#[test]
+fn test_lambertian_sample_weight_is_albedo() {
+    let albedo = Vector3::new(0.2_f32, 0.4_f32, 0.6_f32);
+    let material = LambertianMaterial::new(albedo);
+    let uv = Vector2::zero();
+    let wo = direction(0.3_f32, 0_f32);
+    for u in grid() {
+        let sample = material.sample(&uv, &wo, &u).unwrap();
+
+        assert!(sample.direction.z > 0_f32);
+        assert_relative_eq!(sample.weight(), albedo, max_relative = 1e-4);
+    }
+}
+
+#[test]
+fn test_lambertian_textured_albedo() {
+    let texture = TextureBuffer2D::from_fill(1, 1, Rgb::new(255, 0, 51));
+    let material = LambertianMaterial::new(TextureMaterial::new(texture));
+    let uv = Vector2::new(0.5_f32, 0.5_f32);
+    let wo = direction(0.3_f32, 0_f32);
+    let wi = direction(0.7_f32, 1_f32);
+    let expected = Vector3::new(1_f32, 0_f32, 0.2_f32) / f32::consts::PI;
+
+    assert_relative_eq!(material.eval(&uv, &wo, &wi), expected, epsilon = 1e-6);
+}
+
+#[test]
+fn test_material_parameter_scalar_texture() {
+    let texture = TextureBuffer2D::from_fill(2, 2, Rgb::new(51, 255, 255));
+    let roughness = MaterialParameter::<f32>::from(TextureMaterial::new(texture));
+
+    assert_relative_eq!(roughness.evaluate(&Vector2::new(0.75_f32, 0.25_f32)), 0.2_f32, epsilon = 1e-6);
+    assert_eq!(MaterialParameter::from(0.3_f32).evaluate(&Vector2::zero()), 0.3_f32);
+}
+
+/// Texture coordinates outside of `[0, 1)` wrap around the texture in both 
+/// directions instead of clamping to its edge.
+#[test]
+fn test_texture_material_wraps_negative_uv() {
+    let texture = TextureBuffer2D::from_fn(4, 1, |x, _y| Rgb::new(x as u8, 0, 0));
+    let texture = TextureMaterial::new(texture);
+
+    assert_eq!(texture.evaluate(Vector2::new(0.3_f32, 0.5_f32)), Rgb::new(1, 0, 0));
+    assert_eq!(texture.evaluate(Vector2::new(1.3_f32, 0.5_f32)), Rgb::new(1, 0, 0));
+    assert_eq!(texture.evaluate(Vector2::new(-0.3_f32, 0.5_f32)), Rgb::new(2, 0, 0));
+    assert_eq!(texture.evaluate(Vector2::new(-0.1_f32, -0.5_f32)), Rgb::new(3, 0, 0));
+    assert_eq!(texture.evaluate(Vector2::new(-1e-9_f32, 0.5_f32)), Rgb::new(0, 0, 0));
+}
+
+#[test]
+fn test_material_parameter_empty_texture() {
+    let texture = TextureMaterial::<Rgb<u8>>::default();
+    let albedo = MaterialParameter::<Vector3<f32>>::from(texture.clone());
+    let roughness = MaterialParameter::<f32>::from(texture);
+    let uv = Vector2::new(0.25_f32, 0.75_f32);
+
+    assert_eq!(albedo.evaluate(&uv), Vector3::zero());
+    assert_eq!(roughness.evaluate(&uv), 0_f32);
+}
+
+#[test]
+fn test_mirror_sample() {
+    let reflectance = Vector3::new(0.9_f32, 0.8_f32, 0.7_f32);
+    let material = MirrorMaterial::new(reflectance);
+    let uv = Vector2::zero();
+    let wo = direction(0.5_f32, 0.25_f32);
+    let u = Vector2::new(0.5_f32, 0.5_f32);
+    let sample = material.sample(&uv, &wo, &u).unwrap();
+    let expected_direction = Vector3::new(-wo.x, -wo.y, wo.z);
+
+    assert!(sample.is_specular);
+    assert!(material.is_specular());
+    assert_relative_eq!(sample.direction, expected_direction, epsilon = 1e-6);
+    assert_relative_eq!(sample.weight(), reflectance, epsilon = 1e-6);
+    assert_eq!(material.eval(&uv, &wo, &sample.direction), Vector3::zero());
+    assert_eq!(material.pdf(&uv, &wo, &sample.direction), 0_f32);
+}
+
+#[test]
+fn test_conductor_sample_consistent() {
+    for roughness in [0.1_f32, 0.5_f32, 0.9_f32] {
+        let material = ConductorMaterial::new(Vector3::new(0.95_f32, 0.64_f32, 0.54_f32), roughness);
+        for theta in [0_f32, 0.5_f32, 1.2_f32] {
+            assert_sample_consistent(&material, &direction(theta, 0.3_f32));
+        }
+    }
+}
+
+#[test]
+fn test_conductor_reciprocity() {
+    let material = ConductorMaterial::new(Vector3::new(0.95_f32, 0.64_f32, 0.54_f32), 0.4_f32);
+    let uv = Vector2::zero();
+    let wo = direction(0.4_f32, 0.1_f32);
+    let wi = direction(0.9_f32, 2_f32);
+
+    assert_relative_eq!(material.eval(&uv, &wo, &wi), material.eval(&uv, &wi, &wo), max_relative = 1e-4);
+}
+
+/// A conductor should never reflect more light than arrives at it.
+#[test]
+fn test_conductor_energy_conservation() {
+    for roughness in [0.1_f32, 0.5_f32, 1_f32] {
+        let material = ConductorMaterial::new(Vector3::from_fill(1_f32), roughness);
+        for theta in [0_f32, 0.7_f32, 1.4_f32] {
+            let albedo = directional_albedo(&material, &direction(theta, 0_f32));
+
+            assert!(albedo.x <= 1.01_f32, "roughness = {}, theta = {}, albedo = {:?}", roughness, theta, albedo);
+        }
+    }
+}
+
+/// A smooth conductor reflects nearly all light arriving at normal incidence 
+/// in proportion to its reflectance.
+#[test]
+fn test_conductor_smooth_normal_incidence() {
+    let reflectance = Vector3::new(0.95_f32, 0.64_f32, 0.54_f32);
+    let material = ConductorMaterial::new(reflectance, 0.05_f32);
+    let albedo = directional_albedo(&material, &Vector3::unit_z());
+
+    assert_relative_eq!(albedo, reflectance, epsilon = 1e-2);
+}
+
+#[test]
+fn test_dielectric_normal_incidence() {
+    let material = DielectricMaterial::new(1.5_f32);
+    let uv = Vector2::zero();
+    let wo = Vector3::unit_z();
+    let reflection = material.sample(&uv, &wo, &Vector2::new(0.01_f32, 0.5_f32)).unwrap();
+    let transmission = material.sample(&uv, &wo, &Vector2::new(0.5_f32, 0.5_f32)).unwrap();
+
+    assert!(material.is_specular());
+    assert!(reflection.is_specular);
+    assert_relative_eq!(reflection.direction, Vector3::unit_z(), epsilon = 1e-6);
+    assert_relative_eq!(reflection.pdf, 0.04_f32, epsilon = 1e-6);
+    assert_relative_eq!(reflection.weight(), Vector3::from_fill(1_f32), epsilon = 1e-5);
+    assert!(transmission.is_specular);
+    assert_relative_eq!(transmission.direction, -Vector3::unit_z(), epsilon = 1e-6);
+    assert_relative_eq!(transmission.pdf, 0.96_f32, epsilon = 1e-6);
+    assert_relative_eq!(transmission.weight(), Vector3::from_fill(1_f32 / 2.25_f32), epsilon = 1e-5);
+}
+
+/// Refracted directions should obey Snell's law.
+#[test]
+fn test_dielectric_snells_law() {
+    let eta = 1.5_f32;
+    let material = DielectricMaterial::new(eta);
+    let uv = Vector2::zero();
+    let theta_o = f32::consts::FRAC_PI_4;
+    let wo = direction(theta_o, 0_f32);
+    let sample = material.sample(&uv, &wo, &Vector2::new(0.99_f32, 0.5_f32)).unwrap();
+    let sin_theta_t = f32::sqrt(sample.direction.x * sample.direction.x + sample.direction.y * sample.direction.y);
+
+    assert!(sample.direction.z < 0_f32);
+    assert_relative_eq!(sin_theta_t, f32::sin(theta_o) / eta, epsilon = 1e-5);
+    assert!(sample.direction.x < 0_f32);
+}
+
+/// Light inside a dense medium past the critical angle is always reflected.
+#[test]
+fn test_dielectric_total_internal_reflection() {
+    let material = DielectricMaterial::new(1.5_f32);
+    let uv = Vector2::zero();
+    let wo = -direction(1.2_f32, 0_f32);
+    for u in grid() {
+        let sample = material.sample(&uv, &wo, &u).unwrap();
+
+        assert!(sample.direction.z < 0_f32);
+        assert_relative_eq!(sample.pdf, 1_f32, epsilon = 1e-6);
+    }
+}
+
+#[test]
+fn test_dielectric_transmittance_tints_transmission() {
+    let transmittance = Vector3::new(1_f32, 0.5_f32, 0.25_f32);
+    let material = DielectricMaterial::new(1_f32)
+        .with_transmittance(transmittance);
+    let uv = Vector2::zero();
+    let wo = direction(0.4_f32, 0_f32);
+    let sample = material.sample(&uv, &wo, &Vector2::new(0.5_f32, 0.5_f32)).unwrap();
+
+    // With matched indices of refraction, light passes straight through.
+    assert_relative_eq!(sample.direction, -wo, epsilon = 1e-6);
+    assert_relative_eq!(sample.weight(), transmittance, epsilon = 1e-5);
+}
+
+#[test]
+fn test_plastic_sample_consistent() {
+    for roughness in [0.1_f32, 0.5_f32, 0.9_f32] {
+        let material = PlasticMaterial::new(Vector3::new(0.8_f32, 0.1_f32, 0.1_f32), roughness);
+        for theta in [0_f32, 0.5_f32, 1.2_f32] {
+            assert_sample_consistent(&material, &direction(theta, 0.3_f32));
+        }
+    }
+}
+
+#[test]
+fn test_plastic_reciprocity() {
+    let material = PlasticMaterial::new(Vector3::new(0.8_f32, 0.1_f32, 0.1_f32), 0.3_f32);
+    let uv = Vector2::zero();
+    let wo = direction(0.4_f32, 0.1_f32);
+    let wi = direction(0.9_f32, 2_f32);
+
+    assert_relative_eq!(material.eval(&uv, &wo, &wi), material.eval(&uv, &wi, &wo), max_relative = 1e-4);
+}
+
+#[test]
+fn test_plastic_energy_conservation() {
+    for roughness in [0.1_f32, 0.5_f32, 1_f32] {
+        let material = PlasticMaterial::new(Vector3::from_fill(1_f32), roughness);
+        for theta in [0_f32, 0.7_f32, 1.4_f32] {
+            let albedo = directional_albedo(&material, &direction(theta, 0_f32));
+
+            assert!(albedo.x <= 1.01_f32, "roughness = {}, theta = {}, albedo = {:?}", roughness, theta, albedo);
+        }
+    }
+}
+
+/// A black plastic only reflects light off of its coating.
+#[test]
+fn test_plastic_black_base_reflects_coating_only() {
+    let material = PlasticMaterial::new(Vector3::zero(), 0.2_f32);
+    let albedo = directional_albedo(&material, &Vector3::unit_z());
+
+    assert!(albedo.x > 0_f32);
+    assert!(albedo.x < 0.1_f32);
+}
+
+#[test]
+fn test_shading_frame_round_trip() {
+    let normal = Vector3::new(1_f32, 2_f32, -3_f32).normalize();
+    let frame = ShadingFrame::from_normal(&normal);
+    let vector = Vector3::new(-0.3_f32, 0.7_f32, 0.2_f32);
+
+    assert_relative_eq!(frame.to_local(&normal), Vector3::unit_z(), epsilon = 1e-6);
+    assert_relative_eq!(frame.to_world(&frame.to_local(&vector)), vector, epsilon = 1e-6);
+    assert_relative_eq!(frame.tangent().dot(&frame.bitangent()), 0_f32, epsilon = 1e-6);
+    assert_relative_eq!(frame.tangent().dot(&normal), 0_f32, epsilon = 1e-6);
+}
@@ -0,0 +1,225 @@
+use bvhtracer::{
+    Mesh,
+    MeshBuilder,
+    MeshDecoder,
+    MeshEncoder,
+    ModelBuilder,
+    Normals,
+    ObjMeshDecoder,
+    ObjMeshEncoder,
+    PlyMeshDecoder,
+    PlyMeshEncoder,
+    RigidBody,
+    SceneObjectBuilder,
+    TextureCoordinates,
+    Transform3,
+    Triangle,
+    TriMeshDecoder,
+    TriMeshEncoder,
+    World,
+};
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+};
+use std::fs::{
+    File,
+};
+
+
+/// A quad split into two triangles sharing an edge, with a different material
+/// for each triangle.
+fn quad_mesh() -> Mesh<f32> {
+    let normal = Vector3::unit_z();
+    let normals = Normals::from([normal, normal, normal]);
+
+    MeshBuilder::new()
+        .with_material_name("red")
+        .with_primitive(
+            Triangle::new(
+                Vector3::new(0_f32, 0_f32, 0_f32),
+                Vector3::new(1_f32, 0_f32, 0_f32),
+                Vector3::new(1_f32, 1_f32, 0_f32),
+            ),
+            TextureCoordinates::from([
+                Vector2::new(0_f32, 0_f32),
+                Vector2::new(1_f32, 0_f32),
+                Vector2::new(1_f32, 1_f32),
+            ]),
+            normals,
+        )
+        .with_material_name("blue")
+        .with_primitive(
+            Triangle::new(
+                Vector3::new(0_f32, 0_f32, 0_f32),
+                Vector3::new(1_f32, 1_f32, 0_f32),
+                Vector3::new(0_f32, 1_f32, 0_f32),
+            ),
+            TextureCoordinates::from([
+                Vector2::new(0_f32, 0_f32),
+                Vector2::new(1_f32, 1_f32),
+                Vector2::new(0_f32, 1_f32),
+            ]),
+            normals,
+        )
+        .build()
+}
+
+fn cube_mesh() -> Mesh<f32> {
+    let file = File::open("assets/cube.obj").unwrap();
+
+    ObjMeshDecoder::new(file).read_mesh().unwrap()
+}
+
+fn transform() -> Transform3<f32> {
+    Transform3::from_scale_translation(
+        &Vector3::new(2_f32, 3_f32, 4_f32),
+        &Vector3::new(1_f32, -1_f32, 5_f32)
+    )
+}
+
+fn encode_obj(mesh: &Mesh<f32>) -> Vec<u8> {
+    let mut buffer = vec![];
+    ObjMeshEncoder::new(&mut buffer).write_mesh(mesh).unwrap();
+
+    buffer
+}
+
+fn encode_ply(mesh: &Mesh<f32>) -> Vec<u8> {
+    let mut buffer = vec![];
+    PlyMeshEncoder::new(&mut buffer).write_mesh(mesh).unwrap();
+
+    buffer
+}
+
+fn encode_tri(mesh: &Mesh<f32>) -> Vec<u8> {
+    let mut buffer = vec![];
+    TriMeshEncoder::new(&mut buffer).write_mesh(mesh).unwrap();
+
+    buffer
+}
+
+
+#[test]
+fn test_obj_round_trip() {
+    let mesh = quad_mesh();
+    let buffer = encode_obj(&mesh);
+    let result = ObjMeshDecoder::new(buffer.as_slice()).read_mesh().unwrap();
+
+    assert_eq!(result, mesh);
+}
+
+#[test]
+fn test_obj_round_trip_cube() {
+    let mesh = cube_mesh();
+    let buffer = encode_obj(&mesh);
+    let result = ObjMeshDecoder::new(buffer.as_slice()).read_mesh().unwrap();
+
+    assert_eq!(result, mesh);
+}
+
+/// Vertices shared between primitives are written once.
+#[test]
+fn test_obj_shares_vertices() {
+    let buffer = encode_obj(&cube_mesh());
+    let obj_file = String::from_utf8(buffer).unwrap();
+    let vertex_count = obj_file.lines().filter(|line| line.starts_with("v ")).count();
+    let normal_count = obj_file.lines().filter(|line| line.starts_with("vn ")).count();
+    let face_count = obj_file.lines().filter(|line| line.starts_with("f ")).count();
+
+    assert_eq!(vertex_count, 8);
+    assert_eq!(normal_count, 6);
+    assert_eq!(face_count, 12);
+}
+
+#[test]
+fn test_ply_round_trip() {
+    let mesh = quad_mesh();
+    let buffer = encode_ply(&mesh);
+    let result = PlyMeshDecoder::new(buffer.as_slice()).read_mesh().unwrap();
+
+    assert_eq!(result.primitives(), mesh.primitives());
+    assert_eq!(result.tex_coords(), mesh.tex_coords());
+    assert_eq!(result.normals(), mesh.normals());
+}
+
+#[test]
+fn test_ply_round_trip_cube() {
+    let mesh = cube_mesh();
+    let buffer = encode_ply(&mesh);
+    let result = PlyMeshDecoder::new(buffer.as_slice()).read_mesh().unwrap();
+
+    assert_eq!(result.primitives(), mesh.primitives());
+    assert_eq!(result.normals(), mesh.normals());
+}
+
+#[test]
+fn test_tri_round_trip() {
+    let mesh = cube_mesh();
+    let buffer = encode_tri(&mesh);
+    let result = TriMeshDecoder::new(buffer.as_slice()).read_mesh().unwrap();
+
+    assert_eq!(result.primitives(), mesh.primitives());
+}
+
+#[test]
+fn test_encoder_bakes_transform() {
+    let mesh = quad_mesh();
+    let transform = transform();
+    let mut buffer = vec![];
+    ObjMeshEncoder::new(&mut buffer)
+        .with_transform(&transform)
+        .write_mesh(&mesh)
+        .unwrap();
+    let result = ObjMeshDecoder::new(buffer.as_slice()).read_mesh().unwrap();
+    for (result_primitive, primitive) in result.primitives().iter().zip(mesh.primitives().iter()) {
+        for i in 0..3 {
+            let expected = transform.transform_point(&primitive.vertices[i]);
+
+            assert_eq!(result_primitive.vertices[i], expected);
+        }
+    }
+    for normals in result.normals().iter() {
+        for i in 0..3 {
+            assert!((normals[i] - Vector3::unit_z()).magnitude() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn test_encoder_bakes_scene_object_transform() {
+    let mesh = cube_mesh();
+    let model = ModelBuilder::new().with_mesh(mesh.clone()).build();
+    let mut physics = World::new();
+    let rigid_body_instance = physics.register_body(RigidBody::default());
+    let object = SceneObjectBuilder::new(model, rigid_body_instance)
+        .with_transform(&transform())
+        .build();
+    let mut buffer = vec![];
+    TriMeshEncoder::new(&mut buffer)
+        .with_transform(object.get_transform())
+        .write_mesh(&mesh)
+        .unwrap();
+    let result = TriMeshDecoder::new(buffer.as_slice()).read_mesh().unwrap();
+    let expected = mesh.transformed(object.get_transform());
+
+    assert_eq!(result.primitives(), expected.primitives());
+}
+
+/// A reflection reverses the winding order of each primitive so that the
+/// geometric normal keeps pointing the same way as the shading normals.
+#[test]
+fn test_transformed_mesh_reflection_keeps_winding() {
+    let mesh = quad_mesh();
+    let reflection = Transform3::from_nonuniform_scale(&Vector3::new(1_f32, 1_f32, -1_f32));
+    let result = mesh.transformed(&reflection);
+    for (primitive, normals) in result.primitives().iter().zip(result.normals().iter()) {
+        let edge1 = primitive.vertices[1] - primitive.vertices[0];
+        let edge2 = primitive.vertices[2] - primitive.vertices[0];
+        let geometric_normal = edge1.cross(&edge2);
+
+        assert_eq!(normals[0], -Vector3::unit_z());
+        assert!(geometric_normal.dot(&normals[0]) > 0_f32);
+    }
+}
@@ -0,0 +1,309 @@
+use bvhtracer::{
+    Mesh,
+    MeshBuilder,
+    MeshDecoder,
+    Normals,
+    ObjMeshDecoder,
+    PlyMeshDecoder,
+    StlMeshDecoder,
+    TextureCoordinates,
+    Transform3,
+    Triangle,
+};
+use cglinalg::{
+    Degrees,
+    Magnitude,
+    Vector2,
+    Vector3,
+};
+use std::fs::{
+    File,
+};
+use std::io::{
+    Cursor,
+};
+
+
+fn cube() -> Mesh<f32> {
+    let file = File::open("assets/cube_ascii.ply").unwrap();
+
+    PlyMeshDecoder::new(file).read_mesh().unwrap()
+}
+
+/// A unit square in the **xy-plane** facing the positive **z-axis**, whose
+/// texture coordinates are given by `tex_coord`.
+fn square<F>(tex_coord: F) -> Mesh<f32>
+where
+    F: Fn(Vector3<f32>) -> Vector2<f32>,
+{
+    let vertices = [
+        Vector3::new(0_f32, 0_f32, 0_f32),
+        Vector3::new(1_f32, 0_f32, 0_f32),
+        Vector3::new(1_f32, 1_f32, 0_f32),
+        Vector3::new(0_f32, 1_f32, 0_f32),
+    ];
+    let normals = Normals::from([Vector3::unit_z(); 3]);
+    let mut builder = MeshBuilder::new();
+    for [i0, i1, i2] in [[0, 1, 2], [0, 2, 3]] {
+        let primitive = Triangle::new(vertices[i0], vertices[i1], vertices[i2]);
+        let tex_coords = TextureCoordinates::from([
+            tex_coord(vertices[i0]),
+            tex_coord(vertices[i1]),
+            tex_coord(vertices[i2]),
+        ]);
+        builder = builder.with_primitive(primitive, tex_coords, normals);
+    }
+
+    builder.build()
+}
+
+/// A unit sphere with latitude-longitude texture coordinates.
+fn sphere(x_segments: usize, y_segments: usize) -> Mesh<f32> {
+    let point = |i: usize, j: usize| {
+        let u = i as f32 / x_segments as f32;
+        let v = j as f32 / y_segments as f32;
+        let theta = v * std::f32::consts::PI;
+        let phi = u * 2_f32 * std::f32::consts::PI;
+        let position = Vector3::new(theta.sin() * phi.cos(), theta.cos(), -theta.sin() * phi.sin());
+
+        (position, Vector2::new(u, 1_f32 - v))
+    };
+    let mut builder = MeshBuilder::new();
+    for j in 0..y_segments {
+        for i in 0..x_segments {
+            let corners = [point(i, j), point(i, j + 1), point(i + 1, j + 1), point(i + 1, j)];
+            for [i0, i1, i2] in [[0, 1, 2], [0, 2, 3]] {
+                // Skip the triangles with two corners at a pole.
+                if (j == 0 && i1 == 2) || (j + 1 == y_segments && i1 == 1) {
+                    continue;
+                }
+                let primitive = Triangle::new(corners[i0].0, corners[i1].0, corners[i2].0);
+                let tex_coords = TextureCoordinates::from([corners[i0].1, corners[i1].1, corners[i2].1]);
+                builder = builder.with_primitive(primitive, tex_coords, Normals::default());
+            }
+        }
+    }
+
+    builder.build()
+}
+
+fn face_normal(primitive: &Triangle<f32>) -> Vector3<f32> {
+    let edge1 = primitive.vertices[1] - primitive.vertices[0];
+    let edge2 = primitive.vertices[2] - primitive.vertices[0];
+
+    edge1.cross(&edge2).normalize()
+}
+
+fn assert_close(result: Vector3<f32>, expected: Vector3<f32>) {
+    assert!(
+        (result - expected).magnitude() < 1e-5,
+        "expected {:?} but got {:?}", expected, result
+    );
+}
+
+
+/// The edges of a cube are sharper than the crease angle, so its faces stay flat.
+#[test]
+fn test_smooth_normals_keep_creases() {
+    let mut mesh = cube();
+    mesh.compute_smooth_normals(Degrees(60_f32));
+    for (primitive, normals) in mesh.primitives().iter().zip(mesh.normals().iter()) {
+        for i in 0..3 {
+            assert_close(normals[i], face_normal(primitive));
+        }
+    }
+}
+
+/// Each face of the cube meets a corner at a right angle, whether one or two of
+/// its triangles touch the corner, so angle weighted normals point away from the
+/// center of the cube.
+#[test]
+fn test_smooth_normals_are_angle_weighted() {
+    let mut mesh = cube();
+    mesh.compute_smooth_normals(Degrees(180_f32));
+    let center = Vector3::from_fill(0.5_f32);
+    for (primitive, normals) in mesh.primitives().iter().zip(mesh.normals().iter()) {
+        for i in 0..3 {
+            assert_close(normals[i], (primitive.vertices[i] - center).normalize());
+        }
+    }
+}
+
+#[test]
+fn test_smooth_normals_sphere() {
+    let mut mesh = sphere(32, 16);
+    mesh.compute_smooth_normals(Degrees(60_f32));
+    for (primitive, normals) in mesh.primitives().iter().zip(mesh.normals().iter()) {
+        for i in 0..3 {
+            assert!(normals[i].dot(&primitive.vertices[i]) > 0.99_f32);
+        }
+    }
+}
+
+/// Smooth normals of primitives that share no vertices are exactly their flat
+/// face normals, and flipping them reverses every normal.
+#[test]
+fn test_flip_normals() {
+    let primitive = Triangle::new(
+        Vector3::new(0_f32, 0_f32, 0_f32),
+        Vector3::new(2_f32, 0_f32, 0_f32),
+        Vector3::new(0_f32, 3_f32, 0_f32),
+    );
+    let mut mesh = MeshBuilder::new()
+        .with_primitive(primitive, TextureCoordinates::default(), Normals::default())
+        .build();
+    mesh.compute_smooth_normals(Degrees(60_f32));
+
+    assert_eq!(mesh.normals()[0], Normals::from([Vector3::unit_z(); 3]));
+
+    mesh.compute_tangents();
+    mesh.flip_normals();
+
+    assert_eq!(mesh.normals()[0], Normals::from([-Vector3::unit_z(); 3]));
+    assert!(!mesh.has_tangents());
+}
+
+#[test]
+fn test_compute_missing_normals_keeps_existing_normals() {
+    let existing_normal = Vector3::new(0_f32, 0.6_f32, 0.8_f32);
+    let primitive = Triangle::new(
+        Vector3::new(0_f32, 0_f32, 0_f32),
+        Vector3::new(1_f32, 0_f32, 0_f32),
+        Vector3::new(0_f32, 1_f32, 0_f32),
+    );
+    let normals = Normals::from([existing_normal, Vector3::zero(), Vector3::from_fill(f32::NAN)]);
+    let mut mesh = MeshBuilder::new()
+        .with_primitive(primitive, TextureCoordinates::default(), normals)
+        .build();
+
+    assert!(mesh.has_missing_normals());
+
+    mesh.compute_missing_normals(Degrees(60_f32));
+
+    assert!(!mesh.has_missing_normals());
+    assert_eq!(mesh.normals()[0][0], existing_normal);
+    assert_eq!(mesh.normals()[0][1], Vector3::unit_z());
+    assert_eq!(mesh.normals()[0][2], Vector3::unit_z());
+}
+
+#[test]
+fn test_obj_missing_normals_are_calculated() {
+    let obj_file = "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0 1 0\nf 1 2 3 4\n";
+    let mesh = ObjMeshDecoder::new(Cursor::new(obj_file)).read_mesh().unwrap();
+
+    assert_eq!(mesh.len_primitives(), 2);
+    for normals in mesh.normals().iter() {
+        for i in 0..3 {
+            assert_close(normals[i], Vector3::unit_z());
+        }
+    }
+}
+
+#[test]
+fn test_stl_zero_facet_normals_are_calculated() {
+    let stl_file = r"
+        solid triangle
+          facet normal 0 0 0
+            outer loop
+              vertex 0 0 0
+              vertex 0 1 0
+              vertex 1 0 0
+            endloop
+          endfacet
+        endsolid triangle
+    ";
+    let mesh = StlMeshDecoder::new(Cursor::new(stl_file)).read_mesh().unwrap();
+    for i in 0..3 {
+        assert_close(mesh.normals()[0][i], -Vector3::unit_z());
+    }
+}
+
+#[test]
+fn test_tangents_follow_tex_coords() {
+    let mut mesh = square(|vertex| Vector2::new(vertex.x, vertex.y));
+
+    assert!(!mesh.has_tangents());
+    assert!(mesh.tangents().is_empty());
+
+    mesh.compute_tangents();
+
+    assert!(mesh.has_tangents());
+    assert_eq!(mesh.tangents().len(), mesh.len_primitives());
+    for (tangents, bitangents) in mesh.tangents().iter().zip(mesh.bitangents().iter()) {
+        for i in 0..3 {
+            assert_close(tangents[i], Vector3::unit_x());
+            assert_close(bitangents[i], Vector3::unit_y());
+        }
+    }
+}
+
+/// Mirroring the texture flips the tangent but not the bitangent, so the frame
+/// changes handedness.
+#[test]
+fn test_tangents_of_mirrored_tex_coords() {
+    let mut mesh = square(|vertex| Vector2::new(1_f32 - vertex.x, vertex.y));
+    mesh.compute_tangents();
+    for (tangents, bitangents) in mesh.tangents().iter().zip(mesh.bitangents().iter()) {
+        for i in 0..3 {
+            assert_close(tangents[i], -Vector3::unit_x());
+            assert_close(bitangents[i], Vector3::unit_y());
+        }
+    }
+}
+
+#[test]
+fn test_tangents_without_tex_coords_are_orthonormal() {
+    let mut mesh = square(|_| Vector2::zero());
+    mesh.compute_tangents();
+    for (tangents, bitangents) in mesh.tangents().iter().zip(mesh.bitangents().iter()) {
+        for i in 0..3 {
+            assert!((tangents[i].magnitude() - 1_f32).abs() < 1e-6);
+            assert!(tangents[i].dot(&Vector3::unit_z()).abs() < 1e-6);
+            assert_close(bitangents[i], Vector3::unit_z().cross(&tangents[i]));
+        }
+    }
+}
+
+#[test]
+fn test_tangent_frames_on_sphere_are_orthonormal() {
+    let mut mesh = sphere(32, 16);
+    mesh.compute_smooth_normals(Degrees(60_f32));
+    mesh.compute_tangents();
+    let frames = mesh.normals().iter()
+        .zip(mesh.tangents().iter())
+        .zip(mesh.bitangents().iter());
+    for ((normals, tangents), bitangents) in frames {
+        for i in 0..3 {
+            assert!((tangents[i].magnitude() - 1_f32).abs() < 1e-5);
+            assert!((bitangents[i].magnitude() - 1_f32).abs() < 1e-5);
+            assert!(tangents[i].dot(&normals[i]).abs() < 1e-5);
+            assert!(bitangents[i].dot(&normals[i]).abs() < 1e-5);
+            assert!(tangents[i].dot(&bitangents[i]).abs() < 1e-5);
+        }
+    }
+}
+
+#[test]
+fn test_smooth_normals_discard_tangents() {
+    let mut mesh = square(|vertex| Vector2::new(vertex.x, vertex.y));
+    mesh.compute_tangents();
+    mesh.compute_smooth_normals(Degrees(60_f32));
+
+    assert!(!mesh.has_tangents());
+}
+
+#[test]
+fn test_transformed_mesh_keeps_tangents() {
+    let mut mesh = square(|vertex| Vector2::new(vertex.x, vertex.y));
+    mesh.compute_tangents();
+    let transform = Transform3::from_nonuniform_scale(&Vector3::new(2_f32, -3_f32, 1_f32));
+    let result = mesh.transformed(&transform);
+
+    assert!(result.has_tangents());
+    for (tangents, bitangents) in result.tangents().iter().zip(result.bitangents().iter()) {
+        for i in 0..3 {
+            assert_close(tangents[i], Vector3::unit_x());
+            assert_close(bitangents[i], -Vector3::unit_y());
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use bvhtracer::{
+    Mesh,
+    MeshBuilder,
+    MeshCleanup,
+    MeshDecoder,
+    MeshIssue,
+    ModelBuilder,
+    Normals,
+    PlyMeshDecoder,
+    TextureCoordinates,
+    Triangle,
+};
+use cglinalg::{
+    Vector3,
+};
+use std::fs::{
+    File,
+};
+
+
+fn cube() -> Mesh<f32> {
+    let file = File::open("assets/cube_ascii.ply").unwrap();
+
+    PlyMeshDecoder::new(file).read_mesh().unwrap()
+}
+
+/// Rebuild a mesh, passing each primitive and its normals through `map`.
+fn map_primitives<F>(mesh: &Mesh<f32>, map: F) -> Mesh<f32>
+where
+    F: Fn(usize, Triangle<f32>, Normals<f32, 3>) -> (Triangle<f32>, Normals<f32, 3>),
+{
+    let mut builder = MeshBuilder::new();
+    for primitive_index in 0..mesh.len_primitives() {
+        let (primitive, normals) = map(primitive_index, mesh.primitives()[primitive_index], mesh.normals()[primitive_index]);
+        builder = builder
+            .with_material_id(primitive_index as u32)
+            .with_primitive(primitive, mesh.tex_coords()[primitive_index], normals);
+    }
+
+    builder.build()
+}
+
+fn reversed(primitive: Triangle<f32>) -> Triangle<f32> {
+    Triangle::new(primitive.vertices[0], primitive.vertices[2], primitive.vertices[1])
+}
+
+fn triangles(primitives: &[Triangle<f32>]) -> Mesh<f32> {
+    let mut builder = MeshBuilder::new();
+    for (primitive_index, primitive) in primitives.iter().enumerate() {
+        builder = builder
+            .with_material_id(primitive_index as u32)
+            .with_primitive(*primitive, TextureCoordinates::default(), Normals::default());
+    }
+
+    builder.build()
+}
+
+fn assert_faces_point_outward(mesh: &Mesh<f32>) {
+    let center = Vector3::from_fill(0.5_f32);
+    for primitive in mesh.primitives().iter() {
+        let edge1 = primitive.vertices[1] - primitive.vertices[0];
+        let edge2 = primitive.vertices[2] - primitive.vertices[0];
+
+        assert!(edge1.cross(&edge2).dot(&(primitive.centroid() - center)) > 0_f32);
+    }
+}
+
+
+#[test]
+fn test_cube_is_valid() {
+    let mesh = cube();
+
+    assert_eq!(mesh.validate(), vec![]);
+}
+
+#[test]
+fn test_validate_degenerate_and_non_finite_primitives() {
+    let mesh = triangles(&[
+        Triangle::new(Vector3::new(0_f32, 0_f32, 0_f32), Vector3::new(1_f32, 0_f32, 0_f32), Vector3::new(0_f32, 1_f32, 0_f32)),
+        Triangle::new(Vector3::new(0_f32, 0_f32, 0_f32), Vector3::new(1_f32, 0_f32, 0_f32), Vector3::new(2_f32, 0_f32, 0_f32)),
+        Triangle::new(Vector3::new(0_f32, 0_f32, 0_f32), Vector3::new(f32::NAN, 0_f32, 0_f32), Vector3::new(0_f32, 1_f32, 0_f32)),
+        Triangle::new(Vector3::new(3_f32, 3_f32, 3_f32), Vector3::new(3_f32, 3_f32, 3_f32), Vector3::new(3_f32, 3_f32, 3_f32)),
+    ]);
+    let expected = vec![
+        MeshIssue::Degenerate { primitive: 1 },
+        MeshIssue::NonFinite { primitive: 2 },
+        MeshIssue::Degenerate { primitive: 3 },
+    ];
+
+    assert_eq!(mesh.validate(), expected);
+}
+
+#[test]
+fn test_remove_degenerate_primitives_keeps_attributes_aligned() {
+    let mut mesh = triangles(&[
+        Triangle::new(Vector3::new(0_f32, 0_f32, 0_f32), Vector3::new(1_f32, 0_f32, 0_f32), Vector3::new(2_f32, 0_f32, 0_f32)),
+        Triangle::new(Vector3::new(0_f32, 0_f32, 0_f32), Vector3::new(1_f32, 0_f32, 0_f32), Vector3::new(0_f32, 1_f32, 0_f32)),
+        Triangle::new(Vector3::new(0_f32, 0_f32, 0_f32), Vector3::new(f32::INFINITY, 0_f32, 0_f32), Vector3::new(0_f32, 1_f32, 0_f32)),
+        Triangle::new(Vector3::new(0_f32, 0_f32, 1_f32), Vector3::new(1_f32, 0_f32, 1_f32), Vector3::new(0_f32, 1_f32, 1_f32)),
+    ]);
+    let removed = mesh.remove_degenerate_primitives();
+
+    assert_eq!(removed, 2);
+    assert_eq!(mesh.len_primitives(), 2);
+    assert_eq!(mesh.material_ids(), &[1, 3]);
+    assert_eq!(mesh.primitives()[1].vertices[0], Vector3::new(0_f32, 0_f32, 1_f32));
+    assert_eq!(mesh.validate(), vec![]);
+}
+
+#[test]
+fn test_validate_non_manifold_edge() {
+    let vertex0 = Vector3::new(0_f32, 0_f32, 0_f32);
+    let vertex1 = Vector3::new(1_f32, 0_f32, 0_f32);
+    let mesh = triangles(&[
+        Triangle::new(vertex0, vertex1, Vector3::new(0_f32, 1_f32, 0_f32)),
+        Triangle::new(vertex1, vertex0, Vector3::new(0_f32, -1_f32, 0_f32)),
+        Triangle::new(vertex1, vertex0, Vector3::new(0_f32, 0_f32, 1_f32)),
+    ]);
+    let issues = mesh.validate();
+
+    assert_eq!(issues.len(), 1);
+    match &issues[0] {
+        MeshIssue::NonManifoldEdge { edge, primitives } => {
+            assert!(edge.contains(&vertex0));
+            assert!(edge.contains(&vertex1));
+            assert_eq!(primitives, &vec![0, 1, 2]);
+        }
+        issue => panic!("expected a non-manifold edge but got {:?}", issue),
+    }
+}
+
+#[test]
+fn test_validate_flipped_primitive() {
+    let mesh = map_primitives(&cube(), |primitive_index, primitive, normals| {
+        if primitive_index == 4 { (reversed(primitive), normals) } else { (primitive, normals) }
+    });
+    let issues = mesh.validate();
+
+    assert!(issues.contains(&MeshIssue::FlippedNormals { primitive: 4 }));
+    let inconsistent_edges = issues.iter()
+        .filter(|issue| matches!(issue, MeshIssue::InconsistentWinding { primitives } if primitives.contains(&4)))
+        .count();
+
+    assert_eq!(inconsistent_edges, 3);
+    assert_eq!(issues.len(), 4);
+}
+
+#[test]
+fn test_unify_winding_follows_normals() {
+    let mut mesh = map_primitives(&cube(), |primitive_index, primitive, normals| {
+        if primitive_index == 4 { (reversed(primitive), normals) } else { (primitive, normals) }
+    });
+    let flipped = mesh.unify_winding();
+
+    assert_eq!(flipped, 1);
+    assert_eq!(mesh.validate(), vec![]);
+    assert_faces_point_outward(&mesh);
+}
+
+/// A closed mesh without normals is turned so that it faces outward.
+#[test]
+fn test_unify_winding_turns_closed_mesh_outward() {
+    let mut mesh = map_primitives(&cube(), |primitive_index, primitive, _| {
+        let primitive = if primitive_index % 3 == 0 { primitive } else { reversed(primitive) };
+
+        (primitive, Normals::default())
+    });
+    let flipped = mesh.unify_winding();
+
+    assert_eq!(flipped, 8);
+    assert_eq!(mesh.validate(), vec![]);
+    assert_faces_point_outward(&mesh);
+}
+
+#[test]
+fn test_weld_vertices() {
+    let jitter = |vertex: Vector3<f32>| vertex + Vector3::new(1e-6_f32, -1e-6_f32, 1e-6_f32) * vertex.x;
+    let mut mesh = map_primitives(&cube(), |primitive_index, primitive, normals| {
+        if primitive_index % 2 == 0 {
+            (Triangle::new(jitter(primitive.vertices[0]), jitter(primitive.vertices[1]), jitter(primitive.vertices[2])), normals)
+        } else {
+            (primitive, normals)
+        }
+    });
+
+    assert!(mesh.validate().is_empty());
+
+    let moved = mesh.weld_vertices(1e-4_f32);
+
+    assert!(moved > 0);
+    let cube = cube();
+    for (result, expected) in mesh.primitives().iter().zip(cube.primitives().iter()) {
+        for i in 0..3 {
+            assert!((result.vertices[i] - expected.vertices[i]).x.abs() <= 1e-5_f32);
+        }
+    }
+    // Every corner of the cube is now exactly one position.
+    let mut corners = mesh.primitives().iter()
+        .flat_map(|primitive| primitive.vertices.iter())
+        .map(|vertex| [vertex.x.to_bits(), vertex.y.to_bits(), vertex.z.to_bits()])
+        .collect::<Vec<_>>();
+    corners.sort();
+    corners.dedup();
+
+    assert_eq!(corners.len(), 8);
+}
+
+#[test]
+fn test_weld_vertices_collapses_small_primitives() {
+    let mut mesh = triangles(&[
+        Triangle::new(Vector3::new(0_f32, 0_f32, 0_f32), Vector3::new(1_f32, 0_f32, 0_f32), Vector3::new(0_f32, 1_f32, 0_f32)),
+        Triangle::new(Vector3::new(5_f32, 5_f32, 5_f32), Vector3::new(5.001_f32, 5_f32, 5_f32), Vector3::new(5_f32, 5.001_f32, 5_f32)),
+    ]);
+    mesh.clean(&MeshCleanup::new().with_weld_tolerance(0.01_f32));
+
+    assert_eq!(mesh.len_primitives(), 1);
+    assert_eq!(mesh.material_ids(), &[0]);
+}
+
+#[test]
+fn test_model_builder_cleanup_is_optional() {
+    let mesh = triangles(&[
+        Triangle::new(Vector3::new(0_f32, 0_f32, 0_f32), Vector3::new(1_f32, 0_f32, 0_f32), Vector3::new(0_f32, 1_f32, 0_f32)),
+        Triangle::new(Vector3::new(0_f32, 0_f32, 0_f32), Vector3::new(1_f32, 0_f32, 0_f32), Vector3::new(2_f32, 0_f32, 0_f32)),
+    ]);
+    let model = ModelBuilder::new()
+        .with_mesh(mesh.clone())
+        .build();
+    let cleaned_model = ModelBuilder::new()
+        .with_mesh(mesh)
+        .with_cleanup(MeshCleanup::new())
+        .build();
+
+    assert_eq!(model.len_primitives(), 2);
+    assert_eq!(cleaned_model.len_primitives(), 1);
+}
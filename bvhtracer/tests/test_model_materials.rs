@@ -0,0 +1,260 @@
+use bvhtracer::{
+    Mesh,
+    MeshBuilder,
+    ModelBuilder,
+    Triangle,
+    Normals,
+    TextureCoordinates,
+    TextureMaterial,
+    TextureBuffer2D,
+    Rgb,
+    ObjMeshDecoder,
+    MeshDecoder,
+    Material,
+    LambertianMaterial,
+    MirrorMaterial,
+    DEFAULT_MATERIAL_NAME,
+};
+use cglinalg::{
+    Vector2,
+    Vector3,
+};
+use std::sync::{
+    Arc,
+};
+
+
+fn triangle_at(x: f32) -> Triangle<f32> {
+    Triangle::new(
+        Vector3::new(x, 0_f32, 0_f32),
+        Vector3::new(x + 0.5_f32, 0_f32, 0_f32),
+        Vector3::new(x, 0.5_f32, 0_f32),
+    )
+}
+
+/// A row of triangles where the material id of each triangle is its position 
+/// in the row.
+fn row_mesh(primitive_count: u32) -> Mesh<f32> {
+    let mut builder = MeshBuilder::new();
+    for i in (0..primitive_count).rev() {
+        builder = builder
+            .with_material_id(i)
+            .with_primitive(triangle_at(i as f32), TextureCoordinates::default(), Normals::default());
+    }
+
+    builder.build()
+}
+
+fn diffuse(value: f32) -> Arc<dyn Material> {
+    Arc::new(LambertianMaterial::new(Vector3::from_fill(value)))
+}
+
+fn obj_file() -> String {
+    String::from(r"
+        v  0.0  0.0  0.0
+        v  1.0  0.0  0.0
+        v  0.0  1.0  0.0
+        v  1.0  1.0  0.0
+        f  1 2 3
+        usemtl paint
+        f  2 4 3
+        usemtl glass
+        f  1 3 4
+        usemtl paint
+        f  1 2 4
+    ")
+}
+
+
+#[test]
+fn test_mesh_builder_material_ids() {
+    let mesh = MeshBuilder::new()
+        .with_primitive(triangle_at(0_f32), TextureCoordinates::default(), Normals::default())
+        .with_material_id(3)
+        .with_primitive(triangle_at(1_f32), TextureCoordinates::default(), Normals::default())
+        .with_primitive(triangle_at(2_f32), TextureCoordinates::default(), Normals::default())
+        .build();
+
+    assert_eq!(mesh.material_ids(), &[0, 3, 3]);
+    assert!(mesh.material_names().is_empty());
+}
+
+#[test]
+fn test_mesh_builder_material_names() {
+    let mesh = MeshBuilder::new()
+        .with_material_name("paint")
+        .with_primitive(triangle_at(0_f32), TextureCoordinates::default(), Normals::default())
+        .with_material_name("glass")
+        .with_primitive(triangle_at(1_f32), TextureCoordinates::default(), Normals::default())
+        .with_material_name("paint")
+        .with_primitive(triangle_at(2_f32), TextureCoordinates::default(), Normals::default())
+        .build();
+
+    assert_eq!(mesh.material_ids(), &[0, 1, 0]);
+    assert_eq!(mesh.material_names(), &[String::from("paint"), String::from("glass")]);
+    assert_eq!(mesh.material_id("glass"), Some(1));
+    assert_eq!(mesh.material_id("chrome"), None);
+}
+
+#[test]
+fn test_obj_usemtl_assigns_material_ids() {
+    let obj_file = obj_file();
+    let mesh = ObjMeshDecoder::new(obj_file.as_bytes()).read_mesh().unwrap();
+    let expected_names = vec![
+        String::from(DEFAULT_MATERIAL_NAME), 
+        String::from("paint"), 
+        String::from("glass"),
+    ];
+
+    assert_eq!(mesh.material_ids(), &[0, 1, 2, 1]);
+    assert_eq!(mesh.material_names(), &expected_names[..]);
+}
+
+#[test]
+fn test_obj_without_usemtl_has_no_material_names() {
+    let obj_file = String::from(r"
+        v  0.0  0.0  0.0
+        v  1.0  0.0  0.0
+        v  0.0  1.0  0.0
+        f  1 2 3
+    ");
+    let mesh = ObjMeshDecoder::new(obj_file.as_bytes()).read_mesh().unwrap();
+
+    assert_eq!(mesh.material_ids(), &[0]);
+    assert!(mesh.material_names().is_empty());
+}
+
+#[test]
+fn test_model_default_material() {
+    let model = ModelBuilder::new()
+        .with_mesh(row_mesh(1))
+        .build();
+    let handle = model.model();
+    let borrow = handle.borrow();
+    let albedo = borrow.material(0).albedo(&Vector2::zero());
+
+    assert_eq!(borrow.materials().len(), 1);
+    assert_eq!(albedo, Vector3::from_fill(0.8_f32));
+}
+
+#[test]
+fn test_model_with_material_replaces_default() {
+    let model = ModelBuilder::new()
+        .with_mesh(row_mesh(4))
+        .with_material(diffuse(0.25_f32))
+        .build();
+    let handle = model.model();
+    let borrow = handle.borrow();
+
+    assert_eq!(borrow.materials().len(), 4);
+    for primitive_index in 0..4 {
+        let albedo = borrow.material(primitive_index).albedo(&Vector2::zero());
+
+        assert_eq!(albedo, Vector3::from_fill(0.25_f32));
+    }
+}
+
+#[test]
+fn test_model_with_texture() {
+    let texture = TextureBuffer2D::from_fill(1, 1, Rgb::new(255, 0, 0));
+    let model = ModelBuilder::new()
+        .with_mesh(row_mesh(1))
+        .with_texture(TextureMaterial::new(texture))
+        .build();
+    let handle = model.model();
+    let borrow = handle.borrow();
+    let albedo = borrow.material(0).albedo(&Vector2::new(0.5_f32, 0.5_f32));
+
+    assert_eq!(albedo, Vector3::new(1_f32, 0_f32, 0_f32));
+}
+
+/// Building the BVH reorders the primitives of a mesh, and the material ids 
+/// must be reordered along with them.
+#[test]
+fn test_model_material_follows_primitive_after_bvh_build() {
+    let primitive_count = 64;
+    let mut builder = ModelBuilder::new()
+        .with_mesh(row_mesh(primitive_count));
+    for material_id in 0..primitive_count {
+        builder = builder.with_material_id(material_id, diffuse(material_id as f32));
+    }
+    let model = builder.build();
+    let handle = model.model();
+    let borrow = handle.borrow();
+    for (primitive_index, primitive) in borrow.primitives().iter().enumerate() {
+        let albedo = borrow.material(primitive_index).albedo(&Vector2::zero());
+
+        assert_eq!(albedo.x, primitive.vertices[0].x);
+    }
+}
+
+/// The texture coordinates and normals must be reordered along with the 
+/// primitives when building the BVH.
+#[test]
+fn test_model_attributes_follow_primitive_after_bvh_build() {
+    let primitive_count = 64;
+    let mut builder = MeshBuilder::new();
+    for i in (0..primitive_count).rev() {
+        let x = i as f32;
+        let tex_coords = TextureCoordinates::from([Vector2::new(x, 0_f32); 3]);
+        let normals = Normals::from([Vector3::new(x, 0_f32, 1_f32); 3]);
+        builder = builder.with_primitive(triangle_at(x), tex_coords, normals);
+    }
+    let model = ModelBuilder::new()
+        .with_mesh(builder.build())
+        .build();
+    let handle = model.model();
+    let borrow = handle.borrow();
+    for (primitive_index, primitive) in borrow.primitives().iter().enumerate() {
+        let x = primitive.vertices[0].x;
+
+        assert_eq!(borrow.tex_coords()[primitive_index][0].x, x);
+        assert_eq!(borrow.normals()[primitive_index][0].x, x);
+    }
+}
+
+#[test]
+fn test_model_named_materials_from_obj() {
+    let obj_file = obj_file();
+    let mesh = ObjMeshDecoder::new(obj_file.as_bytes()).read_mesh().unwrap();
+    let model = ModelBuilder::new()
+        .with_mesh(mesh)
+        .with_named_material("paint", diffuse(0.5_f32))
+        .with_named_material("glass", Arc::new(MirrorMaterial::new(Vector3::from_fill(1_f32))))
+        .with_named_material("chrome", diffuse(0.1_f32))
+        .build();
+    let handle = model.model();
+    let borrow = handle.borrow();
+    let mesh = borrow.mesh();
+    for primitive_index in 0..borrow.len_primitives() {
+        let material = borrow.material(primitive_index);
+        let material_id = mesh.material_ids()[primitive_index];
+        let material_name = mesh.material_names()[material_id as usize].as_str();
+        match material_name {
+            "paint" => {
+                assert!(!material.is_specular());
+                assert_eq!(material.albedo(&Vector2::zero()), Vector3::from_fill(0.5_f32));
+            }
+            "glass" => {
+                assert!(material.is_specular());
+            }
+            _ => {
+                assert_eq!(material_name, DEFAULT_MATERIAL_NAME);
+                assert_eq!(material.albedo(&Vector2::zero()), Vector3::from_fill(0.8_f32));
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic]
+fn test_model_material_table_must_cover_material_ids() {
+    use bvhtracer::{
+        BvhBuilder,
+        ModelInstance,
+    };
+
+    let mut mesh = row_mesh(4);
+    let bvh = BvhBuilder::new().build_for_mesh(&mut mesh);
+    let _ = ModelInstance::new(mesh, bvh, vec![diffuse(0.5_f32)]);
+}
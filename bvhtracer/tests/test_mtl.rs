@@ -0,0 +1,303 @@
+use bvhtracer::{
+    BumpMap,
+    DirectoryFileResolver,
+    FileResolver,
+    MemoryFileResolver,
+    ModelDecoder,
+    ModelError,
+    MtlDecoder,
+    MtlError,
+    MtlMaterial,
+    ObjModelDecoder,
+    Rgb,
+    TextureBuffer2D,
+    TextureMaterial,
+};
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+};
+use std::fs;
+use std::path::{
+    Path,
+};
+
+
+fn read_materials(mtl_file: &str) -> Vec<MtlMaterial> {
+    MtlDecoder::new(mtl_file.as_bytes()).read_materials().unwrap()
+}
+
+fn quad_obj_file(mtllib: &str) -> String {
+    format!(r"
+        mtllib {}
+        v  0.0  0.0  0.0
+        v  1.0  0.0  0.0
+        v  1.0  1.0  0.0
+        v  0.0  1.0  0.0
+        vt 0.0  0.0
+        vt 1.0  0.0
+        vt 1.0  1.0
+        vt 0.0  1.0
+        usemtl red
+        f  1/1 2/2 3/3
+        usemtl bricks
+        f  1/1 3/3 4/4
+    ", mtllib)
+}
+
+fn quad_mtl_file() -> String {
+    String::from(r"
+        newmtl red
+        Kd 1.0 0.0 0.0
+
+        newmtl bricks
+        Kd 1.0 1.0 1.0
+        map_Kd textures/bricks.png
+
+        newmtl unused
+        Kd 0.0 0.0 1.0
+        map_Kd missing.png
+    ")
+}
+
+
+#[test]
+fn test_mtl_statements() {
+    let mtl_file = r"
+        # A material with every supported statement.
+        newmtl shiny red
+        Ka 0.1 0.1 0.1
+        Kd 0.9 0.1 0.2
+        Ks 0.5 0.5 0.5
+        Ns 250.0
+        d 0.75
+        Ni 1.45
+        illum 2
+        map_Kd -s 2 2 1 -clamp on red.png
+        map_Bump -bm 0.25 red_bump.png
+    ";
+    let materials = read_materials(mtl_file);
+    let material = &materials[0];
+
+    assert_eq!(materials.len(), 1);
+    assert_eq!(material.name, "shiny red");
+    assert_eq!(material.diffuse, Vector3::new(0.9, 0.1, 0.2));
+    assert_eq!(material.specular, Vector3::new(0.5, 0.5, 0.5));
+    assert_eq!(material.specular_exponent, 250_f32);
+    assert_eq!(material.dissolve, 0.75_f32);
+    assert_eq!(material.optical_density, 1.45_f32);
+    assert_eq!(material.illumination_model, 2);
+    assert_eq!(material.diffuse_map.as_deref(), Some("red.png"));
+    assert_eq!(material.bump_map.as_deref(), Some("red_bump.png"));
+    assert_eq!(material.bump_multiplier, 0.25_f32);
+}
+
+#[test]
+fn test_mtl_defaults() {
+    let materials = read_materials("newmtl plain");
+
+    assert_eq!(materials[0], MtlMaterial::new("plain"));
+}
+
+#[test]
+fn test_mtl_multiple_materials() {
+    let mtl_file = r"
+        newmtl first
+        Kd 1 0 0
+        newmtl second
+        Kd 0.5
+        Tr 0.25
+        bump second_bump.png
+    ";
+    let materials = read_materials(mtl_file);
+
+    assert_eq!(materials.len(), 2);
+    assert_eq!(materials[0].diffuse, Vector3::new(1_f32, 0_f32, 0_f32));
+    assert_eq!(materials[1].diffuse, Vector3::from_fill(0.5_f32));
+    assert_eq!(materials[1].dissolve, 0.75_f32);
+    assert_eq!(materials[1].bump_map.as_deref(), Some("second_bump.png"));
+}
+
+#[test]
+fn test_mtl_malformed_number() {
+    let mtl_file = "newmtl broken\nKd 1.0 zero 0.0\n";
+    let result = MtlDecoder::new(mtl_file.as_bytes()).read_materials();
+
+    assert!(matches!(result, Err(MtlError::Parse { line_number: 2, .. })));
+}
+
+#[test]
+fn test_mtl_statement_before_newmtl() {
+    let mtl_file = "Kd 1.0 0.0 0.0\n";
+    let result = MtlDecoder::new(mtl_file.as_bytes()).read_materials();
+
+    assert!(matches!(result, Err(MtlError::Parse { line_number: 1, .. })));
+}
+
+#[test]
+fn test_mtl_diffuse_material() {
+    let materials = read_materials("newmtl red\nKd 1 0 0\n");
+    let resolver = MemoryFileResolver::new();
+    let material = materials[0].to_material(&resolver, Path::new("")).unwrap();
+
+    assert!(!material.is_specular());
+    assert!(!material.is_transmissive());
+    assert_eq!(material.albedo(&Vector2::zero()), Vector3::new(1_f32, 0_f32, 0_f32));
+}
+
+#[test]
+fn test_mtl_glossy_material() {
+    let materials = read_materials("newmtl plastic\nKd 0 0 1\nKs 1 1 1\nNs 100\nillum 2\n");
+    let resolver = MemoryFileResolver::new();
+    let material = materials[0].to_material(&resolver, Path::new("")).unwrap();
+
+    assert!(!material.is_specular());
+    assert_eq!(material.albedo(&Vector2::zero()), Vector3::new(0_f32, 0_f32, 1_f32));
+}
+
+#[test]
+fn test_mtl_mirror_material() {
+    let materials = read_materials("newmtl mirror\nKd 0 0 0\nKs 0.9 0.9 0.9\nillum 3\n");
+    let resolver = MemoryFileResolver::new();
+    let material = materials[0].to_material(&resolver, Path::new("")).unwrap();
+
+    assert!(material.is_specular());
+    assert!(!material.is_transmissive());
+}
+
+#[test]
+fn test_mtl_glass_material() {
+    let materials = read_materials("newmtl glass\nKs 1 1 1\nNi 1.5\nillum 7\n");
+    let resolver = MemoryFileResolver::new();
+    let material = materials[0].to_material(&resolver, Path::new("")).unwrap();
+
+    assert!(material.is_specular());
+    assert!(material.is_transmissive());
+}
+
+#[test]
+fn test_mtl_diffuse_texture() {
+    let texture_file = fs::read("assets/bricks_rgb.png").unwrap();
+    let resolver = MemoryFileResolver::new()
+        .with_file("materials/bricks.png", texture_file);
+    let materials = read_materials("newmtl bricks\nmap_Kd bricks.png\n");
+    let material = materials[0].to_material(&resolver, Path::new("materials")).unwrap();
+    let albedo = material.albedo(&Vector2::new(0.5_f32, 0.5_f32));
+
+    assert!(albedo.x > 0_f32 || albedo.y > 0_f32 || albedo.z > 0_f32);
+    assert!(material.bump_map().is_none());
+}
+
+/// Textures with an alpha channel are loaded with the alpha channel dropped.
+#[test]
+fn test_mtl_diffuse_texture_with_alpha() {
+    let resolver = DirectoryFileResolver::new("assets");
+    let materials = read_materials("newmtl bricks\nmap_Kd bricks.png\n");
+    let material = materials[0].to_material(&resolver, Path::new("")).unwrap();
+    let albedo = material.albedo(&Vector2::new(0.5_f32, 0.5_f32));
+
+    assert!(albedo.x > 0_f32 || albedo.y > 0_f32 || albedo.z > 0_f32);
+}
+
+#[test]
+fn test_mtl_bump_map_material() {
+    let texture_file = fs::read("assets/bricks_rgb.png").unwrap();
+    let resolver = MemoryFileResolver::new()
+        .with_file("bricks.png", texture_file);
+    let materials = read_materials("newmtl bricks\nKd 1 1 1\nmap_Bump -bm 0.5 bricks.png\n");
+    let material = materials[0].to_material(&resolver, Path::new("")).unwrap();
+    let bump_map = material.bump_map().unwrap();
+
+    assert_eq!(bump_map.scale(), 0.5_f32);
+    assert_eq!(material.albedo(&Vector2::zero()), Vector3::from_fill(1_f32));
+}
+
+#[test]
+fn test_mtl_missing_texture() {
+    let resolver = MemoryFileResolver::new();
+    let materials = read_materials("newmtl bricks\nmap_Kd bricks.png\n");
+    let result = materials[0].to_material(&resolver, Path::new(""));
+
+    assert!(matches!(result, Err(MtlError::Resolve { .. })));
+}
+
+#[test]
+fn test_memory_file_resolver_relative_paths() {
+    let resolver = MemoryFileResolver::new()
+        .with_file("models/textures/bricks.png", vec![1, 2, 3]);
+    let expected = vec![1, 2, 3];
+    let result = resolver.resolve(Path::new("models/materials/../textures/./bricks.png")).unwrap();
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn test_bump_map_constant_height_keeps_normal() {
+    let bump_map = BumpMap::new(0.5_f32);
+    let normal = Vector3::unit_z();
+    let result = bump_map.perturb_normal(
+        &normal,
+        &Vector3::unit_x(),
+        &Vector3::unit_y(),
+        &Vector2::new(0.25_f32, 0.25_f32)
+    );
+
+    assert_eq!(result, normal);
+}
+
+/// A height field that rises along the `u` direction tilts the normal
+/// back against the `u` direction.
+#[test]
+fn test_bump_map_slope_tilts_normal() {
+    let texture = TextureBuffer2D::from_fn(2, 1, |x, _y| {
+        let height = (x * 255) as u8;
+        Rgb::new(height, height, height)
+    });
+    let bump_map = BumpMap::new(TextureMaterial::new(texture));
+    let normal = Vector3::unit_z();
+    let result = bump_map.perturb_normal(
+        &normal,
+        &Vector3::unit_x(),
+        &Vector3::unit_y(),
+        &Vector2::new(0.25_f32, 0.25_f32)
+    );
+
+    assert!(result.x < 0_f32);
+    assert!(result.z > 0_f32);
+    assert!((result.magnitude() - 1_f32).abs() < 1e-6);
+}
+
+#[test]
+fn test_obj_model_decoder_materials() {
+    let texture_file = fs::read("assets/bricks_rgb.png").unwrap();
+    let resolver = MemoryFileResolver::new()
+        .with_file("models/quad.mtl", quad_mtl_file())
+        .with_file("models/textures/bricks.png", texture_file);
+    let obj_file = quad_obj_file("quad.mtl");
+    let model = ObjModelDecoder::new(obj_file.as_bytes(), &resolver)
+        .with_directory("models")
+        .read_model()
+        .unwrap();
+    let handle = model.model();
+    let borrow = handle.borrow();
+    let mesh = borrow.mesh();
+    for primitive_index in 0..borrow.len_primitives() {
+        let material_id = mesh.material_ids()[primitive_index];
+        let material_name = mesh.material_names()[material_id as usize].as_str();
+        let albedo = borrow.material(primitive_index).albedo(&Vector2::new(0.5_f32, 0.5_f32));
+        match material_name {
+            "red" => assert_eq!(albedo, Vector3::new(1_f32, 0_f32, 0_f32)),
+            _ => assert_eq!(material_name, "bricks"),
+        }
+    }
+}
+
+#[test]
+fn test_obj_model_decoder_missing_material_library() {
+    let resolver = MemoryFileResolver::new();
+    let obj_file = quad_obj_file("quad.mtl");
+    let result = ObjModelDecoder::new(obj_file.as_bytes(), &resolver).read_model();
+
+    assert!(matches!(result, Err(ModelError::Resolve { .. })));
+}
@@ -0,0 +1,200 @@
+use bvhtracer::{
+    MeshDecoder,
+    ObjMeshDecoder,
+    ObjMeshSplit,
+    triangulate_polygon,
+};
+use cglinalg::{
+    Vector3,
+};
+
+
+/// Two objects, where the second object has two groups, one of which is
+/// shared with the first object.
+fn obj_file() -> String {
+    String::from(r"
+        o first
+        v  0.0  0.0  0.0
+        v  1.0  0.0  0.0
+        v  0.0  1.0  0.0
+        g shared
+        f  1 2 3
+
+        o second
+        v  0.0  0.0  1.0
+        v  1.0  0.0  1.0
+        v  1.0  1.0  1.0
+        v  0.0  1.0  1.0
+        g top
+        f  4 5 6
+        f  4 5 7
+        g shared
+        f  4 6 7
+    ")
+}
+
+fn quad_obj_file() -> String {
+    String::from(r"
+        v  0.0  0.0  0.0
+        v  1.0  0.0  0.0
+        v  1.0  1.0  0.0
+        v  0.0  1.0  0.0
+        vt 0.0  0.0
+        vt 1.0  0.0
+        vt 1.0  1.0
+        vt 0.0  1.0
+        f  1/1 2/2 3/3 4/4
+    ")
+}
+
+/// An L-shaped hexagon, which a triangle fan about the first vertex
+/// triangulates incorrectly.
+fn concave_polygon() -> Vec<Vector3<f32>> {
+    vec![
+        Vector3::new(0_f32, 0_f32, 0_f32),
+        Vector3::new(2_f32, 0_f32, 0_f32),
+        Vector3::new(2_f32, 1_f32, 0_f32),
+        Vector3::new(1_f32, 1_f32, 0_f32),
+        Vector3::new(1_f32, 2_f32, 0_f32),
+        Vector3::new(0_f32, 2_f32, 0_f32),
+    ]
+}
+
+fn triangle_area(vertices: &[Vector3<f32>], triangle: &[usize; 3]) -> Vector3<f32> {
+    let edge1 = vertices[triangle[1]] - vertices[triangle[0]];
+    let edge2 = vertices[triangle[2]] - vertices[triangle[0]];
+
+    edge1.cross(&edge2) * 0.5_f32
+}
+
+
+#[test]
+fn test_obj_merged_mesh_contains_every_object() {
+    let obj_file = obj_file();
+    let mesh = ObjMeshDecoder::new(obj_file.as_bytes()).read_mesh().unwrap();
+
+    assert_eq!(mesh.len_primitives(), 4);
+}
+
+#[test]
+fn test_obj_meshes_by_object() {
+    let obj_file = obj_file();
+    let meshes = ObjMeshDecoder::new(obj_file.as_bytes())
+        .read_meshes(ObjMeshSplit::Objects)
+        .unwrap();
+    let names = meshes.iter().map(|named_mesh| named_mesh.name.as_str()).collect::<Vec<_>>();
+
+    assert_eq!(names, vec!["first", "second"]);
+    assert_eq!(meshes[0].mesh.len_primitives(), 1);
+    assert_eq!(meshes[1].mesh.len_primitives(), 3);
+}
+
+#[test]
+fn test_obj_meshes_by_group() {
+    let obj_file = obj_file();
+    let meshes = ObjMeshDecoder::new(obj_file.as_bytes())
+        .read_meshes(ObjMeshSplit::Groups)
+        .unwrap();
+    let names = meshes.iter().map(|named_mesh| named_mesh.name.as_str()).collect::<Vec<_>>();
+
+    assert_eq!(names, vec!["shared", "top"]);
+    assert_eq!(meshes[0].mesh.len_primitives(), 2);
+    assert_eq!(meshes[1].mesh.len_primitives(), 2);
+}
+
+#[test]
+fn test_obj_vertex_indices_span_objects() {
+    let obj_file = obj_file();
+    let meshes = ObjMeshDecoder::new(obj_file.as_bytes())
+        .read_meshes(ObjMeshSplit::Objects)
+        .unwrap();
+    let expected = [
+        Vector3::new(0_f32, 0_f32, 1_f32),
+        Vector3::new(1_f32, 1_f32, 1_f32),
+        Vector3::new(0_f32, 1_f32, 1_f32),
+    ];
+    let result = meshes[1].mesh.primitives()[2].vertices;
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn test_obj_quad_is_triangulated() {
+    let obj_file = quad_obj_file();
+    let mesh = ObjMeshDecoder::new(obj_file.as_bytes()).read_mesh().unwrap();
+    let area: f32 = mesh.primitives()
+        .iter()
+        .map(|primitive| {
+            let edge1 = primitive.vertices[1] - primitive.vertices[0];
+            let edge2 = primitive.vertices[2] - primitive.vertices[0];
+            edge1.cross(&edge2).z * 0.5_f32
+        })
+        .sum();
+
+    assert_eq!(mesh.len_primitives(), 2);
+    assert_eq!(area, 1_f32);
+}
+
+#[test]
+fn test_obj_quad_triangulation_keeps_tex_coords() {
+    let obj_file = quad_obj_file();
+    let mesh = ObjMeshDecoder::new(obj_file.as_bytes()).read_mesh().unwrap();
+    for (primitive, tex_coords) in mesh.primitives().iter().zip(mesh.tex_coords().iter()) {
+        for i in 0..3 {
+            assert_eq!(tex_coords[i].x, primitive.vertices[i].x);
+            assert_eq!(tex_coords[i].y, primitive.vertices[i].y);
+        }
+    }
+}
+
+#[test]
+fn test_triangulate_triangle() {
+    let triangle = vec![
+        Vector3::new(0_f32, 0_f32, 0_f32),
+        Vector3::new(1_f32, 0_f32, 0_f32),
+        Vector3::new(0_f32, 1_f32, 0_f32),
+    ];
+
+    assert_eq!(triangulate_polygon(&triangle), vec![[0, 1, 2]]);
+}
+
+#[test]
+fn test_triangulate_degenerate_polygon() {
+    let line = vec![Vector3::new(0_f32, 0_f32, 0_f32), Vector3::new(1_f32, 0_f32, 0_f32)];
+
+    assert!(triangulate_polygon(&line).is_empty());
+}
+
+/// The triangles of a concave polygon should cover exactly the area of the
+/// polygon, with the winding order of the polygon.
+#[test]
+fn test_triangulate_concave_polygon() {
+    let polygon = concave_polygon();
+    let triangles = triangulate_polygon(&polygon);
+    let total_area: f32 = triangles.iter().map(|triangle| triangle_area(&polygon, triangle).z).sum();
+
+    assert_eq!(triangles.len(), polygon.len() - 2);
+    assert_eq!(total_area, 3_f32);
+    for triangle in triangles.iter() {
+        assert!(triangle_area(&polygon, triangle).z > 0_f32);
+    }
+}
+
+/// Triangulation should work for polygons in any plane, and with either
+/// winding order.
+#[test]
+fn test_triangulate_concave_polygon_reversed_in_yz_plane() {
+    let polygon = concave_polygon()
+        .iter()
+        .rev()
+        .map(|vertex| Vector3::new(0_f32, vertex.x, vertex.y))
+        .collect::<Vec<_>>();
+    let triangles = triangulate_polygon(&polygon);
+    let total_area: f32 = triangles.iter().map(|triangle| triangle_area(&polygon, triangle).x).sum();
+
+    assert_eq!(triangles.len(), polygon.len() - 2);
+    assert_eq!(total_area, -3_f32);
+    for triangle in triangles.iter() {
+        assert!(triangle_area(&polygon, triangle).x < 0_f32);
+    }
+}
@@ -0,0 +1,240 @@
+use bvhtracer::{
+    Scene,
+    Camera,
+    CameraAttitudeSpec,
+    SimpleModelDecoder,
+    ModelDecoder,
+    ModelInstance,
+    ModelBuilder,
+    MeshBuilder,
+    Normals,
+    TextureCoordinates,
+    SceneObjectBuilder,
+    SceneBuilder,
+    BoxSpec,
+    Ray,
+    World,
+    RigidBody,
+    Transform3,
+    Triangle,
+};
+use cglinalg::{
+    Magnitude,
+    Rotation3,
+    Vector3,
+};
+use std::fs::{
+    File,
+};
+
+
+fn triangle() -> Triangle<f32> {
+    Triangle::new(
+        Vector3::new(0_f32, 1_f32 / 2_f32, 0_f32),
+        Vector3::new(-1_f32 / f32::sqrt(3_f32), -1_f32 / 2_f32, 0_f32),
+        Vector3::new(1_f32 / f32::sqrt(3_f32), -1_f32 / 2_f32, 0_f32),
+    )
+}
+
+fn model() -> ModelInstance {
+    let top = triangle();
+    let mesh = (0..100).fold(MeshBuilder::new(), |builder, i| {
+            let displacement = Vector3::new(0_f32, 0_f32, i as f32);
+            let primitive = Triangle::new(
+                top.vertices[0] - displacement,
+                top.vertices[1] - displacement,
+                top.vertices[2] - displacement,
+            );
+            let tex_coords = TextureCoordinates::default();
+            let normals = Normals::default();
+
+            builder.with_primitive(primitive, tex_coords, normals)
+        })
+        .build();
+    let builder = ModelBuilder::new();
+    
+    builder.with_mesh(mesh).build()
+}
+
+fn scene() -> Scene {
+    let projection_spec = BoxSpec::new(
+        -1_f32, 
+        1_f32, 
+        -1_f32, 
+        1_f32, 
+        1_f32, 
+        100_f32, 
+    );
+    let position = Vector3::new(0_f32, 4_f32, 0_f32);
+    let forward = (Vector3::zero() - position).normalize();
+    let attitude_spec = CameraAttitudeSpec::new(
+        position,
+        forward,
+        -Vector3::unit_x(),
+        Vector3::unit_z(),
+        -forward
+    );
+    let camera = Camera::new(&projection_spec, &attitude_spec);
+    let mesh_reader = File::open("assets/cube.obj").unwrap();
+    let material_reader = File::open("assets/bricks_rgb.png").unwrap();
+    let model = SimpleModelDecoder::new(mesh_reader, material_reader)
+        .read_model()
+        .unwrap();
+    let transform = {
+        let scale = Vector3::from_fill(2_f32);
+        let translation = Vector3::new(-1_f32, -1_f32, -1_f32);
+        let rotation = Rotation3::identity();
+        Transform3::new(&scale, &translation, rotation)
+    };
+    let mut physics = World::new();
+    let rigid_body_instance = physics.register_body(RigidBody::default());
+    let scene_object = SceneObjectBuilder::new(model, rigid_body_instance)
+        .with_transform(&transform)
+        .build();
+    let active_scene = SceneBuilder::new(camera)
+        .with_physics(physics)
+        .with_object(scene_object)
+        .build();
+
+    active_scene
+}
+
+
+#[test]
+fn test_triangle_occluded_hit() {
+    let triangle = triangle();
+    let ray_origin = Vector3::new(0_f32, 0_f32, 5_f32);
+    let ray_direction = (triangle.centroid() - ray_origin).normalize();
+    let ray = Ray::from_origin_dir(ray_origin, ray_direction);
+
+    assert!(triangle.occluded(&ray));
+}
+
+#[test]
+fn test_triangle_occluded_miss() {
+    let triangle = triangle();
+    let ray_origin = Vector3::new(0_f32, 0_f32, 5_f32);
+    let ray_direction = (ray_origin - triangle.centroid()).normalize();
+    let ray = Ray::from_origin_dir(ray_origin, ray_direction);
+
+    assert!(!triangle.occluded(&ray));
+}
+
+/// A triangle past the end of the ray does not occlude it.
+#[test]
+fn test_triangle_occluded_beyond_max_distance() {
+    let triangle = triangle();
+    let ray_origin = Vector3::new(0_f32, 0_f32, 5_f32);
+    let ray_direction = (triangle.centroid() - ray_origin).normalize();
+    let ray = Ray::new(ray_origin, ray_direction, 4_f32);
+
+    assert!(!triangle.occluded(&ray));
+}
+
+/// The occlusion query should agree with the closest hit query.
+#[test]
+fn test_triangle_occluded_agrees_with_intersect() {
+    let triangle = triangle();
+    let ray_origin = Vector3::new(0_f32, 0_f32, 5_f32);
+    for i in 0..20 {
+        for j in 0..20 {
+            let target = Vector3::new(-1_f32 + i as f32 / 10_f32, -1_f32 + j as f32 / 10_f32, 0_f32);
+            let ray_direction = (target - ray_origin).normalize();
+            let ray = Ray::from_origin_dir(ray_origin, ray_direction);
+
+            assert_eq!(triangle.occluded(&ray), triangle.intersect(&ray).is_some());
+        }
+    }
+}
+
+#[test]
+fn test_bvh_occluded_hit() {
+    let model = model();
+    let ray_origin = Vector3::new(0_f32, 0_f32, 5_f32);
+    let ray_direction = (triangle().centroid() - ray_origin).normalize();
+    let ray = Ray::from_origin_dir(ray_origin, ray_direction);
+
+    assert!(model.occluded(&ray));
+}
+
+#[test]
+fn test_bvh_occluded_miss() {
+    let model = model();
+    let ray_origin = Vector3::new(0_f32, 0_f32, 5_f32);
+    let ray_direction = (ray_origin - triangle().centroid()).normalize();
+    let ray = Ray::from_origin_dir(ray_origin, ray_direction);
+
+    assert!(!model.occluded(&ray));
+}
+
+/// The ray stops short of the closest triangle in the stack.
+#[test]
+fn test_bvh_occluded_short_ray() {
+    let model = model();
+    let ray_origin = Vector3::new(0_f32, 0_f32, 5_f32);
+    let ray_direction = -Vector3::unit_z();
+    let ray = Ray::new(ray_origin, ray_direction, 4.5_f32);
+
+    assert!(!model.occluded(&ray));
+}
+
+/// A ray starting between two triangles in the stack is occluded by the one 
+/// in front of it.
+#[test]
+fn test_bvh_occluded_inside_stack() {
+    let model = model();
+    let ray_origin = Vector3::new(0_f32, 0_f32, -50.5_f32);
+    let ray_direction = -Vector3::unit_z();
+    let ray = Ray::new(ray_origin, ray_direction, 1_f32);
+
+    assert!(model.occluded(&ray));
+}
+
+#[test]
+fn test_scene_occluded_hit() {
+    let scene = scene();
+    let ray_origin = Vector3::new(0_f32, 4_f32, 0_f32);
+    let target = Vector3::new(0.5, 1.0, -0.5);
+    let ray_direction = (target - ray_origin).normalize();
+    let ray = Ray::from_origin_dir(ray_origin, ray_direction);
+
+    assert!(scene.occluded(&ray));
+}
+
+#[test]
+fn test_scene_occluded_miss() {
+    let scene = scene();
+    let ray_origin = Vector3::new(0_f32, 4_f32, 0_f32);
+    let ray_direction = Vector3::unit_y();
+    let ray = Ray::from_origin_dir(ray_origin, ray_direction);
+
+    assert!(!scene.occluded(&ray));
+}
+
+/// A shadow ray toward a point in front of the cube is not occluded by it.
+#[test]
+fn test_scene_occluded_before_max_distance() {
+    let scene = scene();
+    let ray_origin = Vector3::new(0_f32, 4_f32, 0_f32);
+    let ray_direction = -Vector3::unit_y();
+    let ray = Ray::new(ray_origin, ray_direction, 2.5_f32);
+
+    assert!(!scene.occluded(&ray));
+}
+
+/// A ray from inside the cube always hits one of its faces.
+#[test]
+fn test_scene_occluded_inside_cube() {
+    let scene = scene();
+    let ray_origin = Vector3::zero();
+    let directions = [
+        Vector3::unit_x(), -Vector3::unit_x(),
+        Vector3::unit_y(), -Vector3::unit_y(),
+        Vector3::unit_z(), -Vector3::unit_z(),
+    ];
+    for ray_direction in directions {
+        let ray = Ray::from_origin_dir(ray_origin, ray_direction);
+
+        assert!(scene.occluded(&ray));
+    }
+}
@@ -0,0 +1,411 @@
+use bvhtracer::{
+    Scene,
+    Camera,
+    PerspectiveProjection,
+    CameraAttitudeSpec,
+    SimpleModelDecoder,
+    ModelDecoder,
+    SceneObjectBuilder,
+    SceneBuilder,
+    BoxSpec,
+    World,
+    RigidBody,
+    Transform3,
+    PathTracer,
+    TileScheduler,
+    TileOrder,
+    Renderer,
+    RendererState,
+    RadianceToRgbShader,
+    DepthAccumulator,
+    Rgba,
+    AreaLight,
+    LambertianMaterial,
+    MirrorMaterial,
+    Material,
+    Mesh,
+    MeshBuilder,
+    ModelBuilder,
+    Normals,
+    SceneObject,
+    TextureCoordinates,
+    Triangle,
+};
+use cglinalg::{
+    Vector3,
+    Magnitude,
+    Rotation3,
+};
+use std::fs::{
+    File,
+};
+use std::sync::{
+    Arc,
+};
+
+
+const WIDTH: usize = 640;
+const HEIGHT: usize = 640;
+
+fn camera() -> Camera<f32, PerspectiveProjection<f32>> {
+    let projection_spec = BoxSpec::new(
+        -1_f32,
+        1_f32,
+        -1_f32,
+        1_f32,
+        1_f32,
+        100_f32,
+    );
+    let position = Vector3::new(0_f32, 4_f32, 0_f32);
+    let forward = (Vector3::zero() - position).normalize();
+    let attitude_spec = CameraAttitudeSpec::new(
+        position,
+        forward,
+        -Vector3::unit_x(),
+        Vector3::unit_z(),
+        -forward
+    );
+
+    Camera::new(&projection_spec, &attitude_spec)
+}
+
+fn scene() -> Scene {
+    let camera = camera();
+    let mesh_reader = File::open("assets/cube.obj").unwrap();
+    let material_reader = File::open("assets/bricks_rgb.png").unwrap();
+    let model = SimpleModelDecoder::new(mesh_reader, material_reader)
+        .read_model()
+        .unwrap();
+    let transform = {
+        let scale = Vector3::from_fill(2_f32);
+        let translation = Vector3::new(-1_f32, -1_f32, -1_f32);
+        let rotation = Rotation3::identity();
+        Transform3::new(&scale, &translation, rotation)
+    };
+    let mut physics = World::new();
+    let rigid_body_instance = physics.register_body(RigidBody::default());
+    let scene_object = SceneObjectBuilder::new(model, rigid_body_instance)
+        .with_transform(&transform)
+        .build();
+    let active_scene = SceneBuilder::new(camera)
+        .with_physics(physics)
+        .with_object(scene_object)
+        .build();
+
+    active_scene
+}
+
+/// A one by one square panel in the plane at height `y`, spanning `x_min` to 
+/// `x_min + 1` along the x-axis, with its front face facing up or down the y-axis.
+fn panel(x_min: f32, y: f32, faces_up: bool) -> Mesh<f32> {
+    let corners = [
+        Vector3::new(x_min, y, -0.5_f32),
+        Vector3::new(x_min + 1_f32, y, -0.5_f32),
+        Vector3::new(x_min + 1_f32, y, 0.5_f32),
+        Vector3::new(x_min, y, 0.5_f32),
+    ];
+    let triangles = if faces_up {
+        [
+            Triangle::new(corners[0], corners[2], corners[1]),
+            Triangle::new(corners[0], corners[3], corners[2]),
+        ]
+    } else {
+        [
+            Triangle::new(corners[0], corners[1], corners[2]),
+            Triangle::new(corners[0], corners[2], corners[3]),
+        ]
+    };
+
+    triangles.iter()
+        .fold(MeshBuilder::new(), |builder, triangle| {
+            builder.with_primitive(*triangle, TextureCoordinates::default(), Normals::default())
+        })
+        .build()
+}
+
+fn panel_object(physics: &mut World<f32>, mesh: Mesh<f32>, material: Arc<dyn Material>) -> SceneObject {
+    let model = ModelBuilder::new()
+        .with_mesh(mesh)
+        .with_material(material)
+        .build();
+    let rigid_body_instance = physics.register_body(RigidBody::default());
+
+    SceneObjectBuilder::new(model, rigid_body_instance)
+        .with_transform(&Transform3::identity())
+        .build()
+}
+
+/// A scene lit only by a black light panel glowing with radiance `radiance`, 
+/// facing up toward the camera from the plane `y == 0` at `-1.5 <= x <= -0.5`, 
+/// and a black light panel facing down from the plane `y == 2` at `1.2 <= x <= 2.2`, 
+/// which the camera only sees the back of. When `with_mirror` is set, a mirror 
+/// on the plane `y == 0` at `0.5 <= x <= 1.5` reflects the downward facing panel 
+/// toward the camera.
+fn emitter_scene(radiance: Vector3<f32>, with_mirror: bool) -> Scene {
+    let black = Arc::new(LambertianMaterial::new(Vector3::zero()));
+    let mut physics = World::new();
+    let visible_panel = panel_object(&mut physics, panel(-1.5_f32, 0_f32, true), black.clone());
+    let hidden_panel = panel_object(&mut physics, panel(1.2_f32, 2_f32, false), black);
+    let visible_light = AreaLight::from_scene_object(&visible_panel, radiance);
+    let hidden_light = AreaLight::from_scene_object(&hidden_panel, radiance);
+    let builder = SceneBuilder::new(camera())
+        .with_object(visible_panel)
+        .with_object(hidden_panel)
+        .with_light(Box::new(visible_light))
+        .with_light(Box::new(hidden_light));
+    let builder = if with_mirror {
+        let mirror = Arc::new(MirrorMaterial::new(Vector3::from_fill(1_f32)));
+        builder.with_object(panel_object(&mut physics, panel(0.5_f32, 0_f32, true), mirror))
+    } else {
+        builder
+    };
+
+    builder.with_physics(physics).build()
+}
+
+fn render_scene(scene: &Scene, path_tracer: PathTracer, width: usize, height: usize) -> RendererState {
+    let accumulator = Box::new(DepthAccumulator::new());
+    let pixel_shader = Box::new(RadianceToRgbShader::new());
+    let mut renderer_state = RendererState::new(accumulator, pixel_shader, width, height);
+    let mut renderer = Renderer::new(Box::new(path_tracer));
+    renderer.render(&mut renderer_state, scene);
+
+    renderer_state
+}
+
+fn render(path_tracer: PathTracer) -> RendererState {
+    let scene = scene();
+    let accumulator = Box::new(DepthAccumulator::new());
+    let pixel_shader = Box::new(RadianceToRgbShader::new());
+    let mut renderer_state = RendererState::new(accumulator, pixel_shader, WIDTH, HEIGHT);
+    let mut renderer = Renderer::new(Box::new(path_tracer));
+    renderer.render(&mut renderer_state, &scene);
+
+    renderer_state
+}
+
+
+/// With no light arriving from the background, a scene without emitters
+/// should render black.
+#[test]
+fn test_path_tracer_black_background_renders_black() {
+    let path_tracer = PathTracer::new()
+        .with_background(Vector3::zero());
+    let renderer_state = render(path_tracer);
+    let frame_buffer = renderer_state.frame_buffer().as_buffer();
+    let expected = Rgba::new(0, 0, 0, 255);
+
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            assert_eq!(frame_buffer[(x, y)], expected);
+        }
+    }
+}
+
+/// A path with no surface interactions carries no radiance.
+#[test]
+fn test_path_tracer_zero_max_depth_renders_black() {
+    let path_tracer = PathTracer::new()
+        .with_max_depth(0);
+    let renderer_state = render(path_tracer);
+    let frame_buffer = renderer_state.frame_buffer().as_buffer();
+    let expected = Rgba::new(0, 0, 0, 255);
+
+    for y in 0..HEIGHT {
+        for x in 0..WIDTH {
+            assert_eq!(frame_buffer[(x, y)], expected);
+        }
+    }
+}
+
+/// Camera rays that miss the scene should see the background directly.
+#[test]
+fn test_path_tracer_miss_sees_background() {
+    let path_tracer = PathTracer::new()
+        .with_background(Vector3::from_fill(1_f32));
+    let renderer_state = render(path_tracer);
+    let frame_buffer = renderer_state.frame_buffer().as_buffer();
+    let expected = Rgba::new(255, 255, 255, 255);
+
+    assert_eq!(frame_buffer[(0, 0)], expected);
+    assert_eq!(frame_buffer[(WIDTH - 1, 0)], expected);
+    assert_eq!(frame_buffer[(0, HEIGHT - 1)], expected);
+    assert_eq!(frame_buffer[(WIDTH - 1, HEIGHT - 1)], expected);
+}
+
+/// Surfaces lit only by the background reflect less light than the background
+/// emits, since their albedo is less than one.
+#[test]
+fn test_path_tracer_hit_is_darker_than_background() {
+    let path_tracer = PathTracer::new()
+        .with_background(Vector3::from_fill(1_f32))
+        .with_samples_per_pixel(4);
+    let renderer_state = render(path_tracer);
+    let frame_buffer = renderer_state.frame_buffer().as_buffer();
+    let result = frame_buffer[(WIDTH / 2, HEIGHT / 2)];
+
+    assert!(result.r() < 255 || result.g() < 255 || result.b() < 255);
+}
+
+/// The path tracer should render frame buffers whose dimensions are not
+/// multiples of the tile size.
+#[test]
+fn test_path_tracer_odd_frame_buffer_dimensions() {
+    let width = 97;
+    let height = 61;
+    let scene = scene();
+    let accumulator = Box::new(DepthAccumulator::new());
+    let pixel_shader = Box::new(RadianceToRgbShader::new());
+    let mut renderer_state = RendererState::new(accumulator, pixel_shader, width, height);
+    let path_tracer = PathTracer::new()
+        .with_background(Vector3::from_fill(1_f32))
+        .with_tile_scheduler(TileScheduler::new(16, 16, TileOrder::Hilbert));
+    let mut renderer = Renderer::new(Box::new(path_tracer));
+    renderer.render(&mut renderer_state, &scene);
+    let frame_buffer = renderer_state.frame_buffer().as_buffer();
+    let expected = Rgba::new(255, 255, 255, 255);
+
+    assert_eq!(frame_buffer[(0, 0)], expected);
+    assert_eq!(frame_buffer[(width - 1, 0)], expected);
+    assert_eq!(frame_buffer[(0, height - 1)], expected);
+    assert_eq!(frame_buffer[(width - 1, height - 1)], expected);
+}
+
+/// The scene is shared read-only between the worker threads that render tiles.
+#[test]
+fn test_scene_is_send_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_send_sync::<Scene>();
+}
+
+/// A frame should render identically regardless of the number of threads.
+#[test]
+fn test_path_tracer_deterministic_across_worker_counts() {
+    let width = 64;
+    let height = 48;
+    let scene = scene();
+    let render_with_worker_count = |worker_count| {
+        let accumulator = Box::new(DepthAccumulator::new());
+        let pixel_shader = Box::new(RadianceToRgbShader::new());
+        let mut renderer_state = RendererState::new(accumulator, pixel_shader, width, height);
+        let path_tracer = PathTracer::new()
+            .with_seed(7)
+            .with_worker_count(worker_count)
+            .with_tile_scheduler(TileScheduler::new(8, 8, TileOrder::Spiral));
+        let mut renderer = Renderer::new(Box::new(path_tracer));
+        renderer.render(&mut renderer_state, &scene);
+
+        renderer_state.frame_buffer().clone()
+    };
+    let expected = render_with_worker_count(1);
+
+    for worker_count in [2, 3, 8] {
+        let result = render_with_worker_count(worker_count);
+
+        assert_eq!(result, expected, "worker_count = {}", worker_count);
+    }
+}
+
+/// Each frame adds its samples to the samples of the previous frames.
+#[test]
+fn test_path_tracer_accumulates_samples_across_frames() {
+    let width = 32;
+    let height = 32;
+    let scene = scene();
+    let accumulator = Box::new(DepthAccumulator::new());
+    let pixel_shader = Box::new(RadianceToRgbShader::new());
+    let mut renderer_state = RendererState::new(accumulator, pixel_shader, width, height);
+    let path_tracer = PathTracer::new()
+        .with_samples_per_pixel(2);
+    let mut renderer = Renderer::new(Box::new(path_tracer));
+    renderer.render(&mut renderer_state, &scene);
+    renderer.render(&mut renderer_state, &scene);
+    renderer.render(&mut renderer_state, &scene);
+    let accumulation_buffer = renderer_state.accumulation_buffer();
+
+    for y in 0..height {
+        for x in 0..width {
+            assert_eq!(accumulation_buffer.sample_count(x, y), 6);
+        }
+    }
+}
+
+/// Resetting the accumulation discards the samples of previous frames.
+#[test]
+fn test_path_tracer_reset_accumulation() {
+    let width = 32;
+    let height = 32;
+    let scene = scene();
+    let accumulator = Box::new(DepthAccumulator::new());
+    let pixel_shader = Box::new(RadianceToRgbShader::new());
+    let mut renderer_state = RendererState::new(accumulator, pixel_shader, width, height);
+    let path_tracer = PathTracer::new()
+        .with_samples_per_pixel(2);
+    let mut renderer = Renderer::new(Box::new(path_tracer));
+    renderer.render(&mut renderer_state, &scene);
+    renderer.render(&mut renderer_state, &scene);
+    renderer_state.reset_accumulation();
+    renderer.render(&mut renderer_state, &scene);
+    let accumulation_buffer = renderer_state.accumulation_buffer();
+
+    for y in 0..height {
+        for x in 0..width {
+            assert_eq!(accumulation_buffer.sample_count(x, y), 2);
+        }
+    }
+}
+
+/// The average of the accumulated samples of a pixel that only sees the 
+/// background is the background.
+#[test]
+fn test_path_tracer_progressive_miss_converges_to_background() {
+    let width = 32;
+    let height = 32;
+    let scene = scene();
+    let accumulator = Box::new(DepthAccumulator::new());
+    let pixel_shader = Box::new(RadianceToRgbShader::new());
+    let mut renderer_state = RendererState::new(accumulator, pixel_shader, width, height);
+    let background = Vector3::new(0.25_f32, 0.5_f32, 1_f32);
+    let path_tracer = PathTracer::new()
+        .with_background(background);
+    let mut renderer = Renderer::new(Box::new(path_tracer));
+    for _ in 0..4 {
+        renderer.render(&mut renderer_state, &scene);
+    }
+    let result = renderer_state.accumulation_buffer().average(0, 0);
+
+    assert_eq!(result, background);
+}
+
+/// The camera sees an emitter directly and through a mirror, at the radiance it 
+/// emits, and does not see the back of an emitter.
+#[test]
+fn test_path_tracer_sees_emitters_directly_and_in_mirror() {
+    let width = 64;
+    let height = 64;
+    let radiance = Vector3::new(0.5_f32, 0.25_f32, 0.75_f32);
+    let render_emitters = |with_mirror| {
+        let scene = emitter_scene(radiance, with_mirror);
+        let path_tracer = PathTracer::new()
+            .with_background(Vector3::zero());
+
+        render_scene(&scene, path_tracer, width, height)
+    };
+    let renderer_state = render_emitters(false);
+    let renderer_state_mirror = render_emitters(true);
+    let frame_buffer = renderer_state.frame_buffer().as_buffer();
+    let frame_buffer_mirror = renderer_state_mirror.frame_buffer().as_buffer();
+    let emitter_pixel = (40, height / 2);
+    let reflection_pixel = (23, height / 2);
+    let back_pixel = (6, height / 2);
+    let expected = Rgba::new(127, 63, 191, 255);
+    let black = Rgba::new(0, 0, 0, 255);
+
+    assert_eq!(frame_buffer[emitter_pixel], expected);
+    assert_eq!(frame_buffer[reflection_pixel], black);
+    assert_eq!(frame_buffer[back_pixel], black);
+    assert_eq!(frame_buffer_mirror[emitter_pixel], expected);
+    assert_eq!(frame_buffer_mirror[reflection_pixel], expected);
+    assert_eq!(frame_buffer_mirror[back_pixel], black);
+}
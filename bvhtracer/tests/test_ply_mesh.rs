@@ -0,0 +1,195 @@
+use bvhtracer::{
+    Mesh,
+    MeshDecoder,
+    MeshError,
+    PlyMeshDecoder,
+};
+use cglinalg::{
+    Magnitude,
+    Vector2,
+    Vector3,
+};
+use std::fs::{
+    File,
+};
+
+
+fn read_mesh(path: &str) -> Mesh<f32> {
+    let file = File::open(path).unwrap();
+
+    PlyMeshDecoder::new(file).read_mesh().unwrap()
+}
+
+fn triangle_ply_file(format: &str) -> String {
+    format!(r"ply
+format {}
+element vertex 3
+property float x
+property float y
+property float z
+element face 1
+property list uchar int vertex_indices
+end_header
+", format)
+}
+
+/// The total area of a mesh, which is six for a unit cube.
+fn surface_area(mesh: &Mesh<f32>) -> f32 {
+    mesh.primitives()
+        .iter()
+        .map(|primitive| {
+            let edge1 = primitive.vertices[1] - primitive.vertices[0];
+            let edge2 = primitive.vertices[2] - primitive.vertices[0];
+            edge1.cross(&edge2).magnitude() * 0.5_f32
+        })
+        .sum()
+}
+
+/// Every face of the cube fixtures winds counterclockwise when viewed from
+/// outside the cube.
+fn assert_faces_point_outward(mesh: &Mesh<f32>) {
+    let center = Vector3::from_fill(0.5_f32);
+    for primitive in mesh.primitives().iter() {
+        let edge1 = primitive.vertices[1] - primitive.vertices[0];
+        let edge2 = primitive.vertices[2] - primitive.vertices[0];
+        let normal = edge1.cross(&edge2);
+
+        assert!(normal.dot(&(primitive.centroid() - center)) > 0_f32);
+    }
+}
+
+
+#[test]
+fn test_ply_ascii_cube() {
+    let mesh = read_mesh("assets/cube_ascii.ply");
+
+    assert_eq!(mesh.len_primitives(), 12);
+    assert_eq!(surface_area(&mesh), 6_f32);
+    assert_faces_point_outward(&mesh);
+}
+
+#[test]
+fn test_ply_ascii_cube_tex_coords() {
+    let mesh = read_mesh("assets/cube_ascii.ply");
+    for (primitive, tex_coords) in mesh.primitives().iter().zip(mesh.tex_coords().iter()) {
+        for i in 0..3 {
+            let expected = Vector2::new(primitive.vertices[i].x, primitive.vertices[i].y);
+
+            assert_eq!(tex_coords[i], expected);
+        }
+    }
+}
+
+/// Missing normals are calculated from the faces of the mesh, and the edges of
+/// the cube are sharper than the crease angle, so every face stays flat.
+#[test]
+fn test_ply_ascii_cube_missing_normals() {
+    let mesh = read_mesh("assets/cube_ascii.ply");
+    for (primitive, normals) in mesh.primitives().iter().zip(mesh.normals().iter()) {
+        let edge1 = primitive.vertices[1] - primitive.vertices[0];
+        let edge2 = primitive.vertices[2] - primitive.vertices[0];
+        let expected = edge1.cross(&edge2).normalize();
+        for i in 0..3 {
+            assert!((normals[i] - expected).magnitude() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn test_ply_binary_little_endian_cube() {
+    let mesh = read_mesh("assets/cube_binary_le.ply");
+
+    assert_eq!(mesh.len_primitives(), 12);
+    assert_eq!(surface_area(&mesh), 6_f32);
+    assert_faces_point_outward(&mesh);
+}
+
+#[test]
+fn test_ply_binary_little_endian_cube_normals() {
+    let mesh = read_mesh("assets/cube_binary_le.ply");
+    for (primitive, normals) in mesh.primitives().iter().zip(mesh.normals().iter()) {
+        for i in 0..3 {
+            let expected = (primitive.vertices[i] - Vector3::from_fill(0.5_f32)).normalize();
+
+            assert!((normals[i] - expected).magnitude() < 1e-6);
+        }
+    }
+}
+
+#[test]
+fn test_ply_binary_endianness_agrees() {
+    let little_endian = read_mesh("assets/cube_binary_le.ply");
+    let big_endian = read_mesh("assets/cube_binary_be.ply");
+
+    assert_eq!(little_endian, big_endian);
+}
+
+#[test]
+fn test_ply_ascii_and_binary_positions_agree() {
+    let ascii = read_mesh("assets/cube_ascii.ply");
+    let binary = read_mesh("assets/cube_binary_le.ply");
+
+    assert_eq!(ascii.primitives(), binary.primitives());
+}
+
+/// Elements other than vertices and faces, and extra vertex properties, are
+/// skipped.
+#[test]
+fn test_ply_skips_unknown_elements_and_properties() {
+    let ply_file = r"ply
+format ascii 1.0
+comment Vertices with a color, and an element for the edges.
+element vertex 3
+property float x
+property float y
+property float z
+property uchar red
+element edge 1
+property int vertex1
+property int vertex2
+element face 1
+property list uchar uint vertex_index
+end_header
+0 0 0 255
+1 0 0 0
+0 1 0 0
+0 1
+3 0 1 2
+";
+    let mesh = PlyMeshDecoder::new(ply_file.as_bytes()).read_mesh().unwrap();
+
+    assert_eq!(mesh.len_primitives(), 1);
+    assert_eq!(mesh.primitives()[0].vertices[1], Vector3::new(1_f32, 0_f32, 0_f32));
+}
+
+#[test]
+fn test_ply_not_a_ply_file() {
+    let result = PlyMeshDecoder::new("solid cube\nendsolid cube\n".as_bytes()).read_mesh();
+
+    assert!(matches!(result, Err(MeshError::Decoding(_))));
+}
+
+#[test]
+fn test_ply_unknown_property_type() {
+    let ply_file = "ply\nformat ascii 1.0\nelement vertex 1\nproperty quad x\nend_header\n0\n";
+    let result = PlyMeshDecoder::new(ply_file.as_bytes()).read_mesh();
+
+    assert!(matches!(result, Err(MeshError::Decoding(_))));
+}
+
+#[test]
+fn test_ply_vertex_index_out_of_bounds() {
+    let ply_file = triangle_ply_file("ascii 1.0") + "0 0 0\n1 0 0\n0 1 0\n3 0 1 3\n";
+    let result = PlyMeshDecoder::new(ply_file.as_bytes()).read_mesh();
+
+    assert!(matches!(result, Err(MeshError::Decoding(_))));
+}
+
+#[test]
+fn test_ply_truncated_binary_body() {
+    let mut ply_file = triangle_ply_file("binary_little_endian 1.0").into_bytes();
+    ply_file.extend_from_slice(&[0_u8; 20]);
+    let result = PlyMeshDecoder::new(ply_file.as_slice()).read_mesh();
+
+    assert!(matches!(result, Err(MeshError::Decoding(_))));
+}
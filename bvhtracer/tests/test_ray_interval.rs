@@ -0,0 +1,319 @@
+use bvhtracer::{
+    Aabb,
+    Scene,
+    Camera,
+    CameraAttitudeSpec,
+    ModelInstance,
+    ModelBuilder,
+    MeshBuilder,
+    Normals,
+    TextureCoordinates,
+    SceneObjectBuilder,
+    SceneBuilder,
+    BoxSpec,
+    Intersection,
+    Ray,
+    World,
+    RigidBody,
+    Sphere,
+    Transform3,
+    Triangle,
+    TriangleIntersection,
+};
+use approx::{
+    assert_relative_eq,
+};
+use cglinalg::{
+    Magnitude,
+    Vector3,
+};
+
+
+/// The distances down the negative z-axis of the layers of the layered fixtures. 
+/// The gaps between the layers differ, so each hit distance belongs to exactly 
+/// one layer.
+const LAYER_DEPTHS: [f32; 4] = [0_f32, 1_f32, 3_f32, 6_f32];
+
+/// The origin of the rays fired down the negative z-axis through the layers.
+const LAYER_RAY_ORIGIN_Z: f32 = 5_f32;
+
+/// A triangle in the plane `z == -depth` facing the positive z-axis, covering 
+/// the z-axis.
+fn layer(depth: f32) -> Triangle<f32> {
+    Triangle::new(
+        Vector3::new(-1_f32, -1_f32, -depth),
+        Vector3::new(1_f32, -1_f32, -depth),
+        Vector3::new(0_f32, 1_f32, -depth),
+    )
+}
+
+/// A model with one layer at each of the layer depths.
+fn layered_model() -> ModelInstance {
+    let mesh = LAYER_DEPTHS.iter()
+        .fold(MeshBuilder::new(), |builder, depth| {
+            builder.with_primitive(layer(*depth), TextureCoordinates::default(), Normals::default())
+        })
+        .build();
+
+    ModelBuilder::new().with_mesh(mesh).build()
+}
+
+/// A scene with one object at each of the layer depths, so that the layers are 
+/// found by the top level acceleration structure instead of a single model.
+fn layered_scene() -> Scene {
+    let projection_spec = BoxSpec::new(-1_f32, 1_f32, -1_f32, 1_f32, 1_f32, 100_f32);
+    let attitude_spec = CameraAttitudeSpec::new(
+         Vector3::new(0_f32, 0_f32, LAYER_RAY_ORIGIN_Z),
+        -Vector3::unit_z(),
+         Vector3::unit_x(),
+         Vector3::unit_y(),
+        -Vector3::unit_z()
+    );
+    let camera = Camera::new(&projection_spec, &attitude_spec);
+    let mut physics = World::new();
+    let scene_objects = LAYER_DEPTHS.iter()
+        .map(|depth| {
+            let mesh = MeshBuilder::new()
+                .with_primitive(layer(0_f32), TextureCoordinates::default(), Normals::default())
+                .build();
+            let model = ModelBuilder::new().with_mesh(mesh).build();
+            let rigid_body_instance = physics.register_body(RigidBody::default());
+            let transform = Transform3::from_translation(&Vector3::new(0_f32, 0_f32, -depth));
+
+            SceneObjectBuilder::new(model, rigid_body_instance)
+                .with_transform(&transform)
+                .build()
+        })
+        .collect::<Vec<_>>();
+    let builder = scene_objects.into_iter()
+        .fold(SceneBuilder::new(camera), |builder, scene_object| builder.with_object(scene_object));
+
+    builder.with_physics(physics).build()
+}
+
+/// A triangle far from the origin, where the rounding error of a hit position
+/// is much larger than the default minimum distance of a ray.
+fn distant_triangle() -> Triangle<f32> {
+    let center = Vector3::new(1000_f32, 1200_f32, 900_f32);
+
+    Triangle::new(
+        center + Vector3::new(-3_f32, -2_f32, 1_f32),
+        center + Vector3::new(4_f32, -1_f32, -2_f32),
+        center + Vector3::new(-1_f32, 5_f32, 1_f32),
+    )
+}
+
+fn triangle_normal(triangle: &Triangle<f32>) -> Vector3<f32> {
+    let edge1 = triangle.vertices[1] - triangle.vertices[0];
+    let edge2 = triangle.vertices[2] - triangle.vertices[0];
+
+    edge1.cross(&edge2).normalize()
+}
+
+fn reflect(direction: &Vector3<f32>, normal: &Vector3<f32>) -> Vector3<f32> {
+    direction - normal * (2_f32 * direction.dot(normal))
+}
+
+
+#[test]
+fn test_ray_from_interval() {
+    let ray = Ray::from_interval(Vector3::<f32>::zero(), Vector3::unit_z(), 1_f32, 2_f32);
+
+    assert_eq!(ray.t_min, 1_f32);
+    assert_eq!(ray.t, 2_f32);
+    assert!(!ray.in_interval(0.5_f32));
+    assert!(!ray.in_interval(1_f32));
+    assert!(ray.in_interval(1.5_f32));
+    assert!(!ray.in_interval(2_f32));
+    assert!(!ray.in_interval(2.5_f32));
+}
+
+#[test]
+fn test_ray_default_interval() {
+    let ray = Ray::from_origin_dir(Vector3::<f32>::zero(), Vector3::unit_z());
+
+    assert_eq!(ray.t_min, Ray::<f32>::default_t_min());
+    assert_eq!(ray.t, f32::MAX);
+
+    let ray = Ray::new(Vector3::<f32>::zero(), Vector3::unit_z(), 3_f32);
+
+    assert_eq!(ray.t_min, Ray::<f32>::default_t_min());
+    assert_eq!(ray.t, 3_f32);
+}
+
+/// A box lying entirely outside the interval of a ray is missed, and a box
+/// that the interval starts inside of is hit.
+#[test]
+fn test_aabb_ray_interval() {
+    let aabb = Aabb::new(Vector3::from_fill(-1_f32), Vector3::from_fill(1_f32));
+    let origin = Vector3::new(0_f32, 0_f32, 5_f32);
+    let direction = -Vector3::unit_z();
+
+    assert!(aabb.intersect(&Ray::from_interval(origin, direction, 0_f32, 10_f32)).is_some());
+    assert!(aabb.intersect(&Ray::from_interval(origin, direction, 5_f32, 10_f32)).is_some());
+    assert!(aabb.intersect(&Ray::from_interval(origin, direction, 7_f32, 10_f32)).is_none());
+    assert!(aabb.intersect(&Ray::from_interval(origin, direction, 0_f32, 3_f32)).is_none());
+}
+
+/// A triangle is hit and occludes a ray only when its distance lies strictly 
+/// inside the interval of the ray, with either triangle intersection test.
+#[test]
+fn test_triangle_ray_interval() {
+    let triangle = layer(0_f32);
+    let origin = Vector3::new(0_f32, 0_f32, LAYER_RAY_ORIGIN_Z);
+    let direction = -Vector3::unit_z();
+    for intersection in [TriangleIntersection::MollerTrumbore, TriangleIntersection::Watertight] {
+        let result = triangle.intersect_with(&Ray::from_interval(origin, direction, 4_f32, f32::MAX), intersection).unwrap();
+
+        assert_relative_eq!(result.t, 5_f32, epsilon = 1e-6);
+
+        let result = triangle.intersect_with(&Ray::from_interval(origin, direction, 4_f32, 6_f32), intersection).unwrap();
+
+        assert_relative_eq!(result.t, 5_f32, epsilon = 1e-6);
+        assert!(triangle.intersect_with(&Ray::from_interval(origin, direction, 5.5_f32, f32::MAX), intersection).is_none());
+        assert!(triangle.intersect_with(&Ray::from_interval(origin, direction, 0_f32, 4.5_f32), intersection).is_none());
+        assert!(triangle.occluded_with(&Ray::from_interval(origin, direction, 4_f32, 6_f32), intersection));
+        assert!(!triangle.occluded_with(&Ray::from_interval(origin, direction, 5.5_f32, 7_f32), intersection));
+        assert!(!triangle.occluded_with(&Ray::from_interval(origin, direction, 0_f32, 4.5_f32), intersection));
+    }
+}
+
+/// Raising the minimum distance of a ray past each layer of a model in turn 
+/// walks the closest hit of the boundary volume hierarchy through the layers 
+/// behind it.
+#[test]
+fn test_bvh_ray_interval() {
+    let model = layered_model();
+    let origin = Vector3::new(0_f32, 0_f32, LAYER_RAY_ORIGIN_Z);
+    let direction = -Vector3::unit_z();
+    for depth in LAYER_DEPTHS.iter() {
+        let expected = LAYER_RAY_ORIGIN_Z + depth;
+        let t_min = expected - 0.5_f32;
+        let result = model.intersect(&Ray::from_interval(origin, direction, t_min, f32::MAX)).unwrap();
+
+        assert_relative_eq!(result.interaction.t, expected, epsilon = 1e-5);
+        assert!(model.occluded(&Ray::from_interval(origin, direction, t_min, expected + 0.25_f32)));
+        assert!(!model.occluded(&Ray::from_interval(origin, direction, t_min, expected - 0.25_f32)));
+    }
+
+    let t_last = LAYER_RAY_ORIGIN_Z + LAYER_DEPTHS[LAYER_DEPTHS.len() - 1];
+
+    assert!(model.intersect(&Ray::from_interval(origin, direction, t_last + 0.5_f32, f32::MAX)).is_none());
+}
+
+/// Raising the minimum distance of a ray past each object of a scene in turn 
+/// walks the closest hit of the top level acceleration structure through the 
+/// objects behind it.
+#[test]
+fn test_tlas_ray_interval() {
+    let scene = layered_scene();
+    let origin = Vector3::new(0_f32, 0_f32, LAYER_RAY_ORIGIN_Z);
+    let direction = -Vector3::unit_z();
+    for depth in LAYER_DEPTHS.iter() {
+        let expected = LAYER_RAY_ORIGIN_Z + depth;
+        let t_min = expected - 0.5_f32;
+        let result = scene.intersect(&Ray::from_interval(origin, direction, t_min, f32::MAX)).unwrap();
+
+        assert_relative_eq!(result.interaction.t, expected, epsilon = 1e-5);
+        assert!(scene.occluded(&Ray::from_interval(origin, direction, t_min, expected + 0.25_f32)));
+        assert!(!scene.occluded(&Ray::from_interval(origin, direction, t_min, expected - 0.25_f32)));
+    }
+
+    let t_last = LAYER_RAY_ORIGIN_Z + LAYER_DEPTHS[LAYER_DEPTHS.len() - 1];
+
+    assert!(scene.intersect(&Ray::from_interval(origin, direction, t_last + 0.5_f32, f32::MAX)).is_none());
+}
+
+#[test]
+fn test_sphere_ray_interval() {
+    let sphere = Sphere::new(Vector3::zero(), 1_f32);
+    let origin = Vector3::new(0_f32, 0_f32, 5_f32);
+    let direction = -Vector3::unit_z();
+
+    let result = sphere.intersect(&Ray::from_interval(origin, direction, 4.5_f32, f32::MAX)).unwrap();
+
+    assert_relative_eq!(result.t, 6_f32, epsilon = 1e-5);
+    assert!(sphere.intersect(&Ray::from_interval(origin, direction, 6.5_f32, f32::MAX)).is_none());
+    assert!(!sphere.occluded(&Ray::from_interval(origin, direction, 4.5_f32, 5.5_f32)));
+}
+
+/// A spawned ray starts on the side of the surface it leaves toward.
+#[test]
+fn test_ray_spawn_side() {
+    let point = Vector3::new(0.1_f32, 0.2_f32, 0_f32);
+    let normal = Vector3::unit_z();
+    let reflected = Vector3::new(1_f32, 0_f32, 1_f32).normalize();
+    let transmitted = Vector3::new(1_f32, 0_f32, -1_f32).normalize();
+
+    let ray = Ray::spawn(&point, &normal, &reflected);
+
+    assert!(ray.origin.z > 0_f32);
+    assert_eq!(ray.t_min, 0_f32);
+    assert_eq!(ray.direction, reflected);
+
+    let ray = Ray::spawn(&point, &normal, &transmitted);
+
+    assert!(ray.origin.z < 0_f32);
+    assert_eq!(ray.t_min, 0_f32);
+}
+
+/// The offset of a spawned ray grows with the magnitude of the point it leaves.
+#[test]
+fn test_ray_spawn_offset_scale() {
+    let normal = Vector3::unit_z();
+    let near = Vector3::new(1_f32, 1_f32, 0_f32);
+    let far = Vector3::new(1000_f32, 1000_f32, 0_f32);
+    let near_ray = Ray::spawn(&near, &normal, &normal);
+    let far_ray = Ray::spawn(&far, &normal, &normal);
+
+    assert!(far_ray.origin.z > near_ray.origin.z);
+    assert!(far_ray.origin.z < 1e-3_f32 * 1000_f32);
+}
+
+/// Rays spawned from hits on a triangle far from the origin do not hit the
+/// triangle again, whether they reflect off of it or pass through it.
+#[test]
+fn test_intersection_spawn_ray_no_self_intersection() {
+    let triangle = distant_triangle();
+    let normal = triangle_normal(&triangle);
+    let origins = [
+        triangle.centroid() + normal * 50_f32 + Vector3::new(3_f32, -7_f32, 11_f32),
+        triangle.centroid() - normal * 80_f32 + Vector3::new(-13_f32, 5_f32, 2_f32),
+        Vector3::zero(),
+    ];
+    for origin in origins.iter() {
+        for i in 0..16 {
+            for j in 0..16 {
+                let s = (i as f32 + 0.5_f32) / 16_f32;
+                let t = (j as f32 + 0.5_f32) / 16_f32;
+                if s + t >= 1_f32 {
+                    continue;
+                }
+                let target = triangle.vertices[0]
+                    + (triangle.vertices[1] - triangle.vertices[0]) * s
+                    + (triangle.vertices[2] - triangle.vertices[0]) * t;
+                let direction = (target - origin).normalize();
+                let ray = Ray::from_origin_dir(*origin, direction);
+                let interaction = match triangle.intersect(&ray) {
+                    Some(interaction) => interaction,
+                    None => continue,
+                };
+                let intersection = Intersection::from_ray_interaction(ray, interaction);
+                let reflected = reflect(&direction, &normal);
+                let reflected_ray = intersection.spawn_ray(&normal, &reflected);
+                let transmitted_ray = intersection.spawn_ray(&normal, &direction);
+
+                assert_eq!(reflected_ray.t_min, 0_f32);
+                assert_eq!(transmitted_ray.t_min, 0_f32);
+                assert!(triangle.intersect(&reflected_ray).is_none());
+                assert!(!triangle.occluded(&reflected_ray));
+                assert!(triangle.intersect(&transmitted_ray).is_none());
+                assert!(!triangle.occluded(&transmitted_ray));
+                assert!(
+                    (reflected_ray.origin - intersection.position()).magnitude() < 1e-2_f32,
+                    "spawned ray origin {:?} far from hit {:?}", reflected_ray.origin, intersection.position()
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,249 @@
+use bvhtracer::{
+    Accumulator,
+    BoxSpec,
+    CameraAttitudeSpec,
+    Camera,
+    Triangle,
+    TextureCoordinates,
+    TextureMaterialAccumulator,
+    TextureBuffer2D,
+    Normals,
+    MeshBuilder,
+    ModelBuilder,
+    ModelInstance,
+    Scene,
+    SceneBuilder,
+    SceneObjectBuilder,
+    TextureMaterial,
+    Rgb,
+    Ray,
+    World,
+    RigidBody,
+    Transform3,
+};
+use approx::{
+    assert_relative_eq,
+};
+use cglinalg::{
+    Vector2,
+    Vector3,
+    Rotation3,
+};
+
+
+/// The horizontal positions of the centers of the quads in the scene.
+const QUAD_POSITIONS: [f32; 3] = [-3_f32, 0_f32, 3_f32];
+
+/// The color of each quad in the scene.
+const QUAD_COLORS: [[u8; 3]; 3] = [[255, 0, 0], [0, 255, 0], [0, 0, 255]];
+
+
+fn quad(color: [u8; 3]) -> ModelInstance {
+    let mesh = MeshBuilder::new()
+        .with_primitive(
+            Triangle::new(
+                Vector3::new(-1.0, -1.0, 0.0), 
+                Vector3::new( 1.0,  1.0, 0.0), 
+                Vector3::new(-1.0,  1.0, 0.0),
+            ),
+            TextureCoordinates::from([
+                Vector2::new(0.0, 0.0),
+                Vector2::new(1.0, 1.0),
+                Vector2::new(0.0, 1.0),
+            ]),
+            Normals::from([
+                Vector3::new(0.0, 0.0, 1.0),
+                Vector3::new(0.0, 0.0, 1.0),
+                Vector3::new(0.0, 0.0, 1.0),
+            ])
+        )
+        .with_primitive(
+            Triangle::new(
+                Vector3::new(-1.0, -1.0, 0.0),
+                Vector3::new( 1.0, -1.0, 0.0),
+                Vector3::new( 1.0,  1.0, 0.0),
+            ),
+            TextureCoordinates::from([
+                Vector2::new(0.0, 0.0),
+                Vector2::new(1.0, 0.0),
+                Vector2::new(1.0, 1.0),
+            ]),
+            Normals::from([
+                Vector3::new(0.0, 0.0, 1.0),
+                Vector3::new(0.0, 0.0, 1.0),
+                Vector3::new(0.0, 0.0, 1.0),
+            ])
+        )
+        .build();
+    let texture = TextureBuffer2D::from_fill(1, 1, Rgb::new(color[0], color[1], color[2]));
+    
+    ModelBuilder::new()
+        .with_mesh(mesh)
+        .with_texture(TextureMaterial::new(texture))
+        .build()
+}
+
+fn scene() -> Scene {
+    let projection_spec = BoxSpec::new(
+        -1_f32, 
+         1_f32, 
+        -1_f32, 
+         1_f32, 
+         1_f32, 
+         100_f32, 
+    );
+    let attitude_spec = CameraAttitudeSpec::new(
+         Vector3::new(0_f32, 0_f32, 5_f32),
+        -Vector3::unit_z(),
+         Vector3::unit_x(),
+         Vector3::unit_y(),
+        -Vector3::unit_z()
+    );
+    let camera = Camera::new(&projection_spec, &attitude_spec);
+    let mut physics = World::new();
+    let objects = QUAD_POSITIONS.iter().zip(QUAD_COLORS.iter())
+        .map(|(&position, &color)| {
+            let transform = Transform3::new(
+                &Vector3::from_fill(1_f32), 
+                &Vector3::new(position, 0_f32, 0_f32), 
+                Rotation3::identity()
+            );
+            let rigid_body_instance = physics.register_body(RigidBody::default());
+
+            SceneObjectBuilder::new(quad(color), rigid_body_instance)
+                .with_transform(&transform)
+                .build()
+        })
+        .collect::<Vec<_>>();
+
+    SceneBuilder::new(camera)
+        .with_physics(physics)
+        .with_objects(objects)
+        .build()
+}
+
+fn ray_toward(position: f32) -> Ray<f32> {
+    let ray_origin = Vector3::new(position, 0.25_f32, 5_f32);
+    let ray_direction = -Vector3::unit_z();
+
+    Ray::from_origin_dir(ray_origin, ray_direction)
+}
+
+
+#[test]
+fn test_scene_instances_instance_index() {
+    let scene = scene();
+    for (expected, &position) in QUAD_POSITIONS.iter().enumerate() {
+        let ray = ray_toward(position);
+        let intersection = scene.intersect(&ray).unwrap();
+        let result = intersection.instance_primitive.instance_index();
+
+        assert_eq!(result, expected as u32);
+    }
+}
+
+#[test]
+fn test_scene_instances_primitive_index_in_range() {
+    let scene = scene();
+    for &position in QUAD_POSITIONS.iter() {
+        let ray = ray_toward(position);
+        let intersection = scene.intersect(&ray).unwrap();
+        let result = intersection.instance_primitive.primitive_index();
+
+        assert!(result < 2);
+    }
+}
+
+#[test]
+fn test_scene_instances_t() {
+    let scene = scene();
+    for &position in QUAD_POSITIONS.iter() {
+        let ray = ray_toward(position);
+        let intersection = scene.intersect(&ray).unwrap();
+
+        assert_eq!(intersection.interaction.t, 5_f32);
+    }
+}
+
+/// Each hit should be shaded with the texture of the instance that was hit, not
+/// the texture of the first instance in the scene.
+#[test]
+fn test_scene_instances_shading() {
+    let scene = scene();
+    let mut accumulator = TextureMaterialAccumulator::new();
+    for (&position, &color) in QUAD_POSITIONS.iter().zip(QUAD_COLORS.iter()) {
+        let ray = ray_toward(position);
+        let expected = Vector3::new(
+            color[0] as f32 / 255_f32,
+            color[1] as f32 / 255_f32,
+            color[2] as f32 / 255_f32,
+        );
+        let result = accumulator.evaluate(&scene, &ray);
+
+        assert_relative_eq!(result, expected, epsilon = 1e-6);
+    }
+}
+
+#[test]
+fn test_scene_instances_miss_between_quads() {
+    let scene = scene();
+    let ray = ray_toward(1.5_f32);
+
+    assert!(scene.intersect(&ray).is_none());
+}
+
+fn scene_with_object_count(object_count: usize) -> Scene {
+    let projection_spec = BoxSpec::new(-1_f32, 1_f32, -1_f32, 1_f32, 1_f32, 100_f32);
+    let attitude_spec = CameraAttitudeSpec::new(
+         Vector3::new(0_f32, 0_f32, 5_f32),
+        -Vector3::unit_z(),
+         Vector3::unit_x(),
+         Vector3::unit_y(),
+        -Vector3::unit_z()
+    );
+    let camera = Camera::new(&projection_spec, &attitude_spec);
+    let model = quad([255, 255, 255]);
+    let mut physics = World::new();
+    let objects = (0..object_count)
+        .map(|i| {
+            let transform = Transform3::new(
+                &Vector3::from_fill(1_f32), 
+                &Vector3::new(3_f32 * (i as f32), 0_f32, 0_f32), 
+                Rotation3::identity()
+            );
+            let rigid_body_instance = physics.register_body(RigidBody::default());
+
+            SceneObjectBuilder::new(model.clone(), rigid_body_instance)
+                .with_transform(&transform)
+                .build()
+        })
+        .collect::<Vec<_>>();
+
+    SceneBuilder::new(camera)
+        .with_physics(physics)
+        .with_objects(objects)
+        .build()
+}
+
+/// Building a scene with more objects than an intersection record can address
+/// should fail loudly instead of silently wrapping the instance indices around.
+#[cfg(not(feature = "wide_indices"))]
+#[test]
+#[should_panic]
+fn test_scene_instances_too_many_objects() {
+    let _scene = scene_with_object_count(4097);
+}
+
+/// With wide indices, instance indices past the compact limit of 4096 objects
+/// should be reported correctly.
+#[cfg(feature = "wide_indices")]
+#[test]
+fn test_scene_instances_wide_instance_index() {
+    let scene = scene_with_object_count(4097);
+    let ray = ray_toward(3_f32 * 4096_f32);
+    let intersection = scene.intersect(&ray).unwrap();
+    let expected = 4096;
+    let result = intersection.instance_primitive.instance_index();
+
+    assert_eq!(result, expected);
+}
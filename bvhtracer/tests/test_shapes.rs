@@ -0,0 +1,453 @@
+use bvhtracer::{
+    Accumulator,
+    BoxSpec,
+    BvhBuilder,
+    CameraAttitudeSpec,
+    Camera,
+    Cone,
+    Cylinder,
+    Disk,
+    LambertianMaterial,
+    MeshBuilder,
+    ModelBuilder,
+    ModelInstance,
+    NormalMappingAccumulator,
+    Normals,
+    Quad,
+    Ray,
+    RigidBody,
+    Scene,
+    SceneBuilder,
+    SceneObjectBuilder,
+    Shape,
+    TextureCoordinates,
+    TextureMaterialAccumulator,
+    Transform3,
+    Triangle,
+    World,
+};
+use approx::{
+    assert_relative_eq,
+};
+use cglinalg::{
+    Magnitude,
+    Rotation3,
+    Vector2,
+    Vector3,
+};
+use std::sync::{
+    Arc,
+};
+
+
+fn quad() -> Quad<f32> {
+    Quad::new(Vector3::new(-1_f32, 0_f32, 1_f32), Vector3::new(2_f32, 0_f32, 0_f32), Vector3::new(0_f32, 0_f32, -4_f32))
+}
+
+fn disk() -> Disk<f32> {
+    Disk::new(Vector3::new(1_f32, 2_f32, 3_f32), Vector3::unit_y(), 2_f32)
+}
+
+fn cylinder() -> Cylinder<f32> {
+    Cylinder::new(Vector3::zero(), Vector3::new(0_f32, 4_f32, 0_f32), 1_f32)
+}
+
+fn cone() -> Cone<f32> {
+    Cone::new(Vector3::zero(), Vector3::new(0_f32, 2_f32, 0_f32), 1_f32)
+}
+
+/// Shapes in every orientation, so that no shape lines up with the axes.
+fn tilted_shapes() -> Vec<Shape<f32>> {
+    let axis = Vector3::new(1_f32, 2_f32, -0.5_f32).normalize();
+    vec![
+        Shape::from(Quad::new(
+            Vector3::new(-3_f32, -1_f32, 0_f32),
+            Vector3::new(2_f32, 0.5_f32, 0.3_f32),
+            Vector3::new(-0.2_f32, 0.4_f32, 1.5_f32)
+        )),
+        Shape::from(Disk::new(Vector3::new(3_f32, 0_f32, 1_f32), axis, 1.5_f32)),
+        Shape::from(Cylinder::new(Vector3::new(0_f32, -2_f32, -3_f32), axis * 3_f32, 0.75_f32)),
+        Shape::from(Cone::new(Vector3::new(0_f32, 1_f32, 3_f32), Vector3::new(0.5_f32, 3_f32, 2_f32), 1.25_f32)),
+    ]
+}
+
+fn rays() -> Vec<Ray<f32>> {
+    let mut rays = vec![];
+    for i in 0..30 {
+        for j in 0..30 {
+            let origin = Vector3::new(-4_f32 + 0.27_f32 * (i as f32), -3.1_f32 + 0.23_f32 * (j as f32), 12_f32);
+            let target = Vector3::new(0.13_f32 * (j as f32) - 2_f32, 0.11_f32 * (i as f32) - 1.5_f32, -2_f32);
+            rays.push(Ray::from_origin_dir(origin, (target - origin).normalize()));
+        }
+    }
+
+    rays
+}
+
+
+#[test]
+fn test_quad_intersect() {
+    let quad = quad();
+    let ray = Ray::from_origin_dir(Vector3::new(0.5_f32, 5_f32, -2_f32), -Vector3::unit_y());
+    let interaction = quad.intersect(&ray).unwrap();
+
+    assert_eq!(interaction.t, 5_f32);
+    assert_relative_eq!(interaction.u, 0.75_f32, epsilon = 1e-6);
+    assert_relative_eq!(interaction.v, 0.75_f32, epsilon = 1e-6);
+    assert_relative_eq!(quad.point_at_uv(&Vector2::new(interaction.u, interaction.v)), ray.interpolate(interaction.t), epsilon = 1e-6);
+}
+
+#[test]
+fn test_quad_miss() {
+    let quad = quad();
+    let ray = Ray::from_origin_dir(Vector3::new(1.5_f32, 5_f32, -2_f32), -Vector3::unit_y());
+
+    assert!(quad.intersect(&ray).is_none());
+    assert!(!quad.occluded(&ray));
+
+    // A ray in the plane of the quad never crosses it.
+    let ray = Ray::from_origin_dir(Vector3::new(-5_f32, 0_f32, -2_f32), Vector3::unit_x());
+
+    assert!(quad.intersect(&ray).is_none());
+}
+
+#[test]
+fn test_quad_normal_area_and_bounds() {
+    let quad = quad();
+
+    assert_eq!(quad.normal(), Vector3::unit_y());
+    assert_eq!(quad.area(), 8_f32);
+    assert_eq!(quad.bounds().bounds_min, Vector3::new(-1_f32, 0_f32, -3_f32));
+    assert_eq!(quad.bounds().bounds_max, Vector3::new(1_f32, 0_f32, 1_f32));
+}
+
+#[test]
+fn test_disk_intersect() {
+    let disk = disk();
+    let ray = Ray::from_origin_dir(Vector3::new(2.5_f32, 7_f32, 3_f32), -Vector3::unit_y());
+    let interaction = disk.intersect(&ray).unwrap();
+    let point = ray.interpolate(interaction.t);
+
+    assert_eq!(interaction.t, 5_f32);
+    assert_relative_eq!(interaction.v, 0.75_f32, epsilon = 1e-6);
+    assert_relative_eq!(disk.point_at_uv(&Vector2::new(interaction.u, interaction.v)), point, epsilon = 1e-5);
+    assert_eq!(disk.normal(), Vector3::unit_y());
+}
+
+#[test]
+fn test_disk_miss_beyond_rim() {
+    let disk = disk();
+    let ray = Ray::from_origin_dir(Vector3::new(3.1_f32, 7_f32, 3_f32), -Vector3::unit_y());
+
+    assert!(disk.intersect(&ray).is_none());
+}
+
+/// The bounds of a tilted disk should be tight around its rim.
+#[test]
+fn test_disk_bounds_tilted() {
+    let disk = Disk::new(Vector3::new(1_f32, -1_f32, 2_f32), Vector3::new(1_f32, 1_f32, 0.5_f32).normalize(), 3_f32);
+    let bounds = disk.bounds();
+    let mut rim_bounds_min = Vector3::from_fill(f32::MAX);
+    let mut rim_bounds_max = Vector3::from_fill(-f32::MAX);
+    for i in 0..3600 {
+        let point = disk.point_at_uv(&Vector2::new((i as f32) / 3600_f32, 1_f32));
+        for axis in 0..3 {
+            rim_bounds_min[axis] = f32::min(rim_bounds_min[axis], point[axis]);
+            rim_bounds_max[axis] = f32::max(rim_bounds_max[axis], point[axis]);
+        }
+    }
+
+    assert_relative_eq!(bounds.bounds_min, rim_bounds_min, epsilon = 1e-3);
+    assert_relative_eq!(bounds.bounds_max, rim_bounds_max, epsilon = 1e-3);
+}
+
+#[test]
+fn test_cylinder_intersect_side() {
+    let cylinder = cylinder();
+    let ray = Ray::from_origin_dir(Vector3::new(5_f32, 1_f32, 0_f32), -Vector3::unit_x());
+    let interaction = cylinder.intersect(&ray).unwrap();
+    let point = ray.interpolate(interaction.t);
+
+    assert_relative_eq!(interaction.t, 4_f32, epsilon = 1e-6);
+    assert_relative_eq!(interaction.v, 0.25_f32, epsilon = 1e-6);
+    assert_relative_eq!(cylinder.normal(&point), Vector3::unit_x(), epsilon = 1e-6);
+}
+
+#[test]
+fn test_cylinder_intersect_caps() {
+    let cylinder = cylinder();
+    let ray = Ray::from_origin_dir(Vector3::new(0.5_f32, 10_f32, 0_f32), -Vector3::unit_y());
+    let interaction = cylinder.intersect(&ray).unwrap();
+    let point = ray.interpolate(interaction.t);
+
+    assert_relative_eq!(interaction.t, 6_f32, epsilon = 1e-6);
+    assert_relative_eq!(interaction.v, 0.5_f32, epsilon = 1e-6);
+    assert_eq!(cylinder.normal(&point), Vector3::unit_y());
+
+    let ray = Ray::from_origin_dir(Vector3::new(0.5_f32, -10_f32, 0_f32), Vector3::unit_y());
+    let interaction = cylinder.intersect(&ray).unwrap();
+    let point = ray.interpolate(interaction.t);
+
+    assert_relative_eq!(interaction.t, 10_f32, epsilon = 1e-6);
+    assert_eq!(cylinder.normal(&point), -Vector3::unit_y());
+}
+
+#[test]
+fn test_cylinder_intersect_from_inside() {
+    let cylinder = cylinder();
+    let ray = Ray::from_origin_dir(Vector3::new(0_f32, 1_f32, 0_f32), Vector3::unit_z());
+    let interaction = cylinder.intersect(&ray).unwrap();
+
+    assert_relative_eq!(interaction.t, 1_f32, epsilon = 1e-6);
+}
+
+#[test]
+fn test_cylinder_miss() {
+    let cylinder = cylinder();
+    // Past the top of the cylinder.
+    let ray = Ray::from_origin_dir(Vector3::new(5_f32, 4.5_f32, 0_f32), -Vector3::unit_x());
+
+    assert!(cylinder.intersect(&ray).is_none());
+
+    // Beside the cylinder.
+    let ray = Ray::from_origin_dir(Vector3::new(1.5_f32, 10_f32, 0_f32), -Vector3::unit_y());
+
+    assert!(cylinder.intersect(&ray).is_none());
+    assert!(!cylinder.occluded(&ray));
+}
+
+#[test]
+fn test_cylinder_area() {
+    let cylinder = cylinder();
+    let expected = 2_f32 * std::f32::consts::PI * (4_f32 + 1_f32);
+
+    assert_relative_eq!(cylinder.area(), expected, epsilon = 1e-5);
+}
+
+#[test]
+fn test_cone_intersect_side() {
+    let cone = cone();
+    let ray = Ray::from_origin_dir(Vector3::new(5_f32, 1_f32, 0_f32), -Vector3::unit_x());
+    let interaction = cone.intersect(&ray).unwrap();
+    let point = ray.interpolate(interaction.t);
+    let expected_normal = Vector3::new(2_f32, 1_f32, 0_f32).normalize();
+
+    assert_relative_eq!(interaction.t, 4.5_f32, epsilon = 1e-6);
+    assert_relative_eq!(interaction.v, 0.5_f32, epsilon = 1e-6);
+    assert_relative_eq!(cone.normal(&point), expected_normal, epsilon = 1e-6);
+}
+
+#[test]
+fn test_cone_intersect_base() {
+    let cone = cone();
+    let ray = Ray::from_origin_dir(Vector3::new(0.25_f32, -3_f32, 0.5_f32), Vector3::unit_y());
+    let interaction = cone.intersect(&ray).unwrap();
+    let point = ray.interpolate(interaction.t);
+
+    assert_relative_eq!(interaction.t, 3_f32, epsilon = 1e-6);
+    assert_eq!(cone.normal(&point), -Vector3::unit_y());
+}
+
+/// The mirror image of the cone beyond its apex is not part of the cone.
+#[test]
+fn test_cone_miss_above_apex() {
+    let cone = cone();
+    let ray = Ray::from_origin_dir(Vector3::new(5_f32, 3_f32, 0_f32), -Vector3::unit_x());
+
+    assert!(cone.intersect(&ray).is_none());
+}
+
+#[test]
+fn test_cone_area() {
+    let cone = cone();
+    let slant_height = f32::sqrt(5_f32);
+    let expected = std::f32::consts::PI * (slant_height + 1_f32);
+
+    assert_relative_eq!(cone.area(), expected, epsilon = 1e-5);
+}
+
+/// Every hit should lie on the surface of the shape, with a unit normal facing
+/// against the ray when the ray starts outside the shape, and with position
+/// derivatives tangent to the surface.
+#[test]
+fn test_shapes_hits_lie_on_surface() {
+    let shapes = tilted_shapes();
+    for shape in shapes.iter() {
+        let mut hit_count = 0;
+        for ray in rays().iter() {
+            if let Some(interaction) = shape.intersect(ray) {
+                let point = ray.interpolate(interaction.t);
+                let normal = shape.normal(&point);
+                let (dpdu, dpdv) = shape.position_derivatives(&point);
+                let uv = shape.uv(&point);
+
+                assert_relative_eq!(normal.magnitude(), 1_f32, epsilon = 1e-5);
+                let bounds = shape.bounds();
+                for axis in 0..3 {
+                    assert!(point[axis] >= bounds.bounds_min[axis] - 1e-4 && point[axis] <= bounds.bounds_max[axis] + 1e-4);
+                }
+                assert_relative_eq!(uv, Vector2::new(interaction.u, interaction.v), epsilon = 1e-5);
+                assert!(f32::abs(dpdu.dot(&normal)) <= 1e-3 * dpdu.magnitude() + 1e-5, "{:?} {:?}", shape, point);
+                assert!(f32::abs(dpdv.dot(&normal)) <= 1e-3 * dpdv.magnitude() + 1e-5, "{:?} {:?}", shape, point);
+                if let Shape::Quad(_) | Shape::Disk(_) = shape {
+                    continue;
+                }
+                assert!(normal.dot(&ray.direction) < 0_f32, "{:?} {:?}", shape, point);
+                hit_count += 1;
+            }
+        }
+        if let Shape::Cylinder(_) | Shape::Cone(_) = shape {
+            assert!(hit_count > 10, "{:?}", shape);
+        }
+    }
+}
+
+#[test]
+fn test_shape_bvh_matches_brute_force() {
+    let mut shapes = tilted_shapes();
+    let bvh = BvhBuilder::new().build_for(&mut shapes);
+    for ray in rays().iter() {
+        let expected = shapes.iter()
+            .filter_map(|shape| shape.intersect(ray))
+            .map(|interaction| interaction.t)
+            .min_by(|a, b| a.partial_cmp(b).unwrap());
+        let result = bvh.intersect(&shapes, ray).map(|intersection| intersection.interaction.t);
+
+        assert_eq!(result, expected);
+        assert_eq!(bvh.occluded(&shapes, ray), expected.is_some());
+    }
+}
+
+#[test]
+fn test_shape_model_material_ids_follow_shapes() {
+    let shapes = vec![
+        Shape::from(Disk::new(Vector3::new(-4_f32, 0_f32, 0_f32), Vector3::unit_z(), 1_f32)),
+        Shape::from(Cylinder::new(Vector3::new(0_f32, -1_f32, 0_f32), Vector3::new(0_f32, 2_f32, 0_f32), 1_f32)),
+        Shape::from(Cone::new(Vector3::new(4_f32, -1_f32, 0_f32), Vector3::new(4_f32, 1_f32, 0_f32), 1_f32)),
+    ];
+    let albedos = [Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()];
+    let model = ModelBuilder::new()
+        .with_material_id(0, Arc::new(LambertianMaterial::new(albedos[0])))
+        .with_material_id(1, Arc::new(LambertianMaterial::new(albedos[1])))
+        .with_material_id(2, Arc::new(LambertianMaterial::new(albedos[2])))
+        .with_shapes(shapes)
+        .with_shape_material_ids(vec![0, 1, 2])
+        .build();
+
+    assert_eq!(model.len_primitives(), 3);
+    let handle = model.model();
+    let borrow = handle.borrow();
+    assert!(borrow.is_shapes());
+    for (x, albedo) in [-4_f32, 0_f32, 4_f32].iter().zip(albedos.iter()) {
+        let ray = Ray::from_origin_dir(Vector3::new(*x, -0.5_f32, 5_f32), -Vector3::unit_z());
+        let intersection = borrow.intersect(&ray).unwrap();
+        let material = borrow.material_at(&intersection);
+
+        assert_eq!(material.albedo(&borrow.interpolated_tex_coords(&intersection)), *albedo);
+        assert!(borrow.interpolated_normal(&intersection).z > 0_f32);
+    }
+}
+
+fn triangle_wall() -> ModelInstance {
+    let normals = Normals::from([Vector3::unit_z(); 3]);
+    let mesh = MeshBuilder::new()
+        .with_primitive(
+            Triangle::new(
+                Vector3::new(-10_f32, 0_f32, -5_f32),
+                Vector3::new( 10_f32, 0_f32, -5_f32),
+                Vector3::new(  0_f32, 10_f32, -5_f32),
+            ),
+            TextureCoordinates::default(),
+            normals
+        )
+        .build();
+
+    ModelBuilder::new()
+        .with_mesh(mesh)
+        .build()
+}
+
+/// A scene with a triangle wall behind an analytic ground plane and a pipe lying
+/// on it, placed by their instance transforms.
+fn scene() -> Scene {
+    let projection_spec = BoxSpec::new(-1_f32, 1_f32, -1_f32, 1_f32, 1_f32, 100_f32);
+    let attitude_spec = CameraAttitudeSpec::new(
+         Vector3::new(0_f32, 5_f32, 20_f32),
+        -Vector3::unit_z(),
+         Vector3::unit_x(),
+         Vector3::unit_y(),
+        -Vector3::unit_z()
+    );
+    let camera = Camera::new(&projection_spec, &attitude_spec);
+    let mut physics = World::new();
+    let wall = SceneObjectBuilder::new(triangle_wall(), physics.register_body(RigidBody::default()))
+        .with_transform(&Transform3::identity())
+        .build();
+    let ground = ModelBuilder::new()
+        .with_material(Arc::new(LambertianMaterial::new(Vector3::new(0.5_f32, 0.5_f32, 0.5_f32))))
+        .with_shapes(vec![Shape::from(Quad::new(
+            Vector3::new(-10_f32, 0_f32, 10_f32),
+            Vector3::new(20_f32, 0_f32, 0_f32),
+            Vector3::new(0_f32, 0_f32, -20_f32),
+        ))])
+        .build();
+    let ground = SceneObjectBuilder::new(ground, physics.register_body(RigidBody::default()))
+        .with_transform(&Transform3::identity())
+        .build();
+    // A unit pipe along the y-axis, laid along the x-axis and doubled in size.
+    let pipe = ModelBuilder::new()
+        .with_material(Arc::new(LambertianMaterial::new(Vector3::new(0.8_f32, 0.2_f32, 0.1_f32))))
+        .with_shapes(vec![Shape::from(Cylinder::new(Vector3::zero(), Vector3::new(0_f32, 2_f32, 0_f32), 0.5_f32))])
+        .build();
+    let transform = Transform3::new(
+        &Vector3::from_fill(2_f32),
+        &Vector3::new(-2_f32, 1_f32, 0_f32),
+        Rotation3::from_angle_z(-cglinalg::Degrees(90_f32))
+    );
+    let pipe = SceneObjectBuilder::new(pipe, physics.register_body(RigidBody::default()))
+        .with_transform(&transform)
+        .build();
+
+    SceneBuilder::new(camera)
+        .with_physics(physics)
+        .with_objects(vec![wall, ground, pipe])
+        .build()
+}
+
+#[test]
+fn test_scene_with_shapes_and_triangles() {
+    let scene = scene();
+    // The top of the pipe is at `y == 2`.
+    let ray = Ray::from_origin_dir(Vector3::new(0_f32, 10_f32, 0_f32), -Vector3::unit_y());
+    let intersection = scene.intersect(&ray).unwrap();
+
+    assert_eq!(intersection.instance_primitive.instance_index(), 2);
+    assert_relative_eq!(intersection.interaction.t, 8_f32, epsilon = 1e-4);
+
+    // Beside the pipe, the ray falls through to the ground.
+    let ray = Ray::from_origin_dir(Vector3::new(0_f32, 10_f32, 3_f32), -Vector3::unit_y());
+    let intersection = scene.intersect(&ray).unwrap();
+
+    assert_eq!(intersection.instance_primitive.instance_index(), 1);
+    assert_relative_eq!(intersection.interaction.t, 10_f32, epsilon = 1e-4);
+
+    // Above the ground, the ray flies on to the wall.
+    let ray = Ray::from_origin_dir(Vector3::new(0_f32, 5_f32, 10_f32), -Vector3::unit_z());
+    let intersection = scene.intersect(&ray).unwrap();
+
+    assert_eq!(intersection.instance_primitive.instance_index(), 0);
+    assert_relative_eq!(intersection.interaction.t, 15_f32, epsilon = 1e-4);
+}
+
+#[test]
+fn test_scene_shape_normals_and_materials() {
+    let scene = scene();
+    let mut normals = NormalMappingAccumulator::new();
+    let mut materials = TextureMaterialAccumulator::new();
+    // The front of the pipe faces the positive z-axis.
+    let ray = Ray::from_origin_dir(Vector3::new(0_f32, 1_f32, 10_f32), -Vector3::unit_z());
+    let expected = (Vector3::unit_z() + Vector3::from_fill(1_f32)) * 0.5_f32;
+
+    assert_relative_eq!(normals.evaluate(&scene, &ray), expected, epsilon = 1e-5);
+    assert_eq!(materials.evaluate(&scene, &ray), Vector3::new(0.8_f32, 0.2_f32, 0.1_f32));
+}
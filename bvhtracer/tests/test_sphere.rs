@@ -0,0 +1,387 @@
+use bvhtracer::{
+    Accumulator,
+    Aabb,
+    BoxSpec,
+    BvhBuilder,
+    CameraAttitudeSpec,
+    Camera,
+    LambertianMaterial,
+    MeshBuilder,
+    ModelBuilder,
+    ModelInstance,
+    NormalMappingAccumulator,
+    Normals,
+    Ray,
+    RigidBody,
+    Scene,
+    SceneBuilder,
+    SceneObjectBuilder,
+    Sphere,
+    TextureCoordinates,
+    Transform3,
+    Triangle,
+    World,
+};
+use approx::{
+    assert_relative_eq,
+};
+use cglinalg::{
+    Magnitude,
+    Rotation3,
+    Vector2,
+    Vector3,
+};
+use std::sync::{
+    Arc,
+};
+
+
+fn sphere() -> Sphere<f32> {
+    Sphere::new(Vector3::new(1_f32, 2_f32, 3_f32), 2_f32)
+}
+
+/// A lattice of small spheres of varying radii, like the particles of a simulation.
+fn particles() -> Vec<Sphere<f32>> {
+    let mut spheres = vec![];
+    for i in 0..20 {
+        for j in 0..20 {
+            for k in 0..20 {
+                let center = Vector3::new(i as f32, j as f32, k as f32) * 0.5_f32;
+                let radius = 0.05_f32 + 0.01_f32 * (((i * 7 + j * 3 + k) % 10) as f32);
+                spheres.push(Sphere::new(center, radius));
+            }
+        }
+    }
+
+    spheres
+}
+
+fn brute_force_t(spheres: &[Sphere<f32>], ray: &Ray<f32>) -> Option<f32> {
+    spheres.iter()
+        .filter_map(|sphere| sphere.intersect(ray))
+        .map(|interaction| interaction.t)
+        .min_by(|a, b| a.partial_cmp(b).unwrap())
+}
+
+fn particle_rays() -> Vec<Ray<f32>> {
+    let mut rays = vec![];
+    for i in 0..25 {
+        for j in 0..25 {
+            let origin = Vector3::new(-5_f32, 0.4_f32 * (i as f32) - 0.3_f32, 0.4_f32 * (j as f32) - 0.2_f32);
+            let direction = (Vector3::new(5_f32, 5_f32, 5_f32) - origin + Vector3::new(0_f32, 0.1_f32 * (j as f32), 0_f32)).normalize();
+            rays.push(Ray::from_origin_dir(origin, direction));
+        }
+    }
+
+    rays
+}
+
+
+#[test]
+fn test_sphere_intersect_from_outside() {
+    let sphere = sphere();
+    let ray = Ray::from_origin_dir(Vector3::new(1_f32, 2_f32, 10_f32), -Vector3::unit_z());
+    let interaction = sphere.intersect(&ray).unwrap();
+
+    assert_relative_eq!(interaction.t, 5_f32, epsilon = 1e-6);
+}
+
+#[test]
+fn test_sphere_intersect_from_inside() {
+    let sphere = sphere();
+    let ray = Ray::from_origin_dir(sphere.center, Vector3::unit_x());
+    let interaction = sphere.intersect(&ray).unwrap();
+
+    assert_relative_eq!(interaction.t, 2_f32, epsilon = 1e-6);
+}
+
+#[test]
+fn test_sphere_miss() {
+    let sphere = sphere();
+    let ray = Ray::from_origin_dir(Vector3::new(1_f32, 4.01_f32, 10_f32), -Vector3::unit_z());
+
+    assert!(sphere.intersect(&ray).is_none());
+    assert!(!sphere.occluded(&ray));
+}
+
+#[test]
+fn test_sphere_behind_ray() {
+    let sphere = sphere();
+    let ray = Ray::from_origin_dir(Vector3::new(1_f32, 2_f32, 10_f32), Vector3::unit_z());
+
+    assert!(sphere.intersect(&ray).is_none());
+    assert!(!sphere.occluded(&ray));
+}
+
+#[test]
+fn test_sphere_beyond_ray_distance() {
+    let sphere = sphere();
+    let ray = Ray::new(Vector3::new(1_f32, 2_f32, 10_f32), -Vector3::unit_z(), 4.9_f32);
+
+    assert!(sphere.intersect(&ray).is_none());
+    assert!(!sphere.occluded(&ray));
+
+    let ray = Ray::new(Vector3::new(1_f32, 2_f32, 10_f32), -Vector3::unit_z(), 5.1_f32);
+
+    assert!(sphere.occluded(&ray));
+}
+
+/// A small sphere far from the ray origin should still be hit precisely.
+#[test]
+fn test_sphere_intersect_small_distant_sphere() {
+    let sphere = Sphere::new(Vector3::new(0_f32, 0_f32, -10000_f32), 0.01_f32);
+    let ray = Ray::from_origin_dir(Vector3::zero(), -Vector3::unit_z());
+    let interaction = sphere.intersect(&ray).unwrap();
+
+    assert_relative_eq!(interaction.t, 9999.99_f32, epsilon = 1e-3);
+}
+
+#[test]
+fn test_sphere_bounds_and_centroid() {
+    let sphere = sphere();
+    let expected = Aabb::new(Vector3::new(-1_f32, 0_f32, 1_f32), Vector3::new(3_f32, 4_f32, 5_f32));
+
+    assert_eq!(sphere.bounds(), expected);
+    assert_eq!(sphere.centroid(), sphere.center);
+}
+
+#[test]
+fn test_sphere_normal() {
+    let sphere = sphere();
+    let point = sphere.center + Vector3::new(0_f32, 0_f32, 2_f32);
+
+    assert_eq!(sphere.normal(&point), Vector3::unit_z());
+}
+
+#[test]
+fn test_sphere_uv_poles() {
+    let sphere = sphere();
+    let bottom = sphere.center - Vector3::new(0_f32, 2_f32, 0_f32);
+    let top = sphere.center + Vector3::new(0_f32, 2_f32, 0_f32);
+
+    assert_relative_eq!(sphere.uv(&bottom).y, 0_f32, epsilon = 1e-6);
+    assert_relative_eq!(sphere.uv(&top).y, 1_f32, epsilon = 1e-6);
+}
+
+#[test]
+fn test_sphere_normal_at_uv_inverts_uv() {
+    let sphere = sphere();
+    for i in 1..16 {
+        for j in 1..8 {
+            let uv = Vector2::new(i as f32 / 16_f32, j as f32 / 8_f32);
+            let normal = sphere.normal_at_uv(&uv);
+            let result = sphere.uv(&(sphere.center + normal * sphere.radius));
+
+            assert_relative_eq!(result, uv, epsilon = 1e-5);
+        }
+    }
+}
+
+#[test]
+fn test_sphere_intersection_reports_uv() {
+    let sphere = sphere();
+    let ray = Ray::from_origin_dir(Vector3::new(1_f32, 2.5_f32, 10_f32), -Vector3::unit_z());
+    let interaction = sphere.intersect(&ray).unwrap();
+    let expected = sphere.uv(&ray.interpolate(interaction.t));
+
+    assert_eq!(Vector2::new(interaction.u, interaction.v), expected);
+}
+
+#[test]
+fn test_sphere_position_derivatives_are_tangent() {
+    let sphere = sphere();
+    let h = 1e-3_f32;
+    for i in 1..8 {
+        for j in 1..4 {
+            let uv = Vector2::new(i as f32 / 8_f32, j as f32 / 4_f32);
+            let point = |uv: Vector2<f32>| sphere.center + sphere.normal_at_uv(&uv) * sphere.radius;
+            let (dpdu, dpdv) = sphere.position_derivatives(&point(uv));
+            let expected_dpdu = (point(uv + Vector2::new(h, 0_f32)) - point(uv - Vector2::new(h, 0_f32))) / (2_f32 * h);
+            let expected_dpdv = (point(uv + Vector2::new(0_f32, h)) - point(uv - Vector2::new(0_f32, h))) / (2_f32 * h);
+
+            assert!((dpdu - expected_dpdu).magnitude() < 1e-2 * expected_dpdu.magnitude());
+            assert!((dpdv - expected_dpdv).magnitude() < 1e-2 * expected_dpdv.magnitude());
+        }
+    }
+}
+
+#[test]
+fn test_sphere_bvh_matches_brute_force() {
+    let spheres = particles();
+    let mut bvh_spheres = spheres.clone();
+    let (bvh, order) = BvhBuilder::new().build_for_with_order(&mut bvh_spheres);
+    for (new_index, &old_index) in order.iter().enumerate() {
+        assert_eq!(bvh_spheres[new_index], spheres[old_index as usize]);
+    }
+    for ray in particle_rays().iter() {
+        let expected = brute_force_t(&spheres, ray);
+        let result = bvh.intersect(&bvh_spheres, ray).map(|intersection| intersection.interaction.t);
+
+        assert_eq!(result, expected);
+        assert_eq!(bvh.occluded(&bvh_spheres, ray), expected.is_some());
+    }
+}
+
+#[test]
+fn test_sphere_bvh_refit() {
+    let mut spheres = particles();
+    let (mut bvh, _) = BvhBuilder::new().build_for_with_order(&mut spheres);
+    let displacement = Vector3::new(0_f32, 0_f32, 100_f32);
+    for sphere in spheres.iter_mut() {
+        sphere.center += displacement;
+    }
+    bvh.refit(&spheres);
+    let bounds = bvh.bounds();
+
+    assert!(bounds.bounds_min.z > 99_f32);
+    for ray in particle_rays().iter() {
+        let ray = Ray::from_origin_dir(ray.origin + displacement, ray.direction);
+        let expected = brute_force_t(&spheres, &ray);
+        let result = bvh.intersect(&spheres, &ray).map(|intersection| intersection.interaction.t);
+
+        assert_eq!(result, expected);
+    }
+}
+
+#[test]
+fn test_sphere_model_material_ids_follow_spheres() {
+    let spheres = vec![
+        Sphere::new(Vector3::new(-4_f32, 0_f32, 0_f32), 1_f32),
+        Sphere::new(Vector3::new(0_f32, 0_f32, 0_f32), 1_f32),
+        Sphere::new(Vector3::new(4_f32, 0_f32, 0_f32), 1_f32),
+    ];
+    let materials = [
+        Vector3::new(1_f32, 0_f32, 0_f32),
+        Vector3::new(0_f32, 1_f32, 0_f32),
+        Vector3::new(0_f32, 0_f32, 1_f32),
+    ];
+    let model = materials.iter()
+        .enumerate()
+        .fold(ModelBuilder::new(), |builder, (material_id, &albedo)| {
+            builder.with_material_id(material_id as u32, Arc::new(LambertianMaterial::new(albedo)))
+        })
+        .with_spheres(spheres)
+        .with_sphere_material_ids(vec![0, 1, 2])
+        .build();
+
+    assert_eq!(model.len_primitives(), 3);
+    let handle = model.model();
+    let borrow = handle.borrow();
+    assert!(borrow.is_spheres());
+    for (x, albedo) in [-4_f32, 0_f32, 4_f32].iter().zip(materials.iter()) {
+        let ray = Ray::from_origin_dir(Vector3::new(*x, 0_f32, 5_f32), -Vector3::unit_z());
+        let intersection = borrow.intersect(&ray).unwrap();
+        let primitive_index = intersection.instance_primitive.primitive_index() as usize;
+        let material = borrow.material(primitive_index);
+
+        assert_eq!(borrow.spheres()[primitive_index].center.x, *x);
+        assert_eq!(material.albedo(&Vector2::zero()), *albedo);
+    }
+}
+
+/// A model built from no spheres is still a sphere model.
+#[test]
+fn test_sphere_model_without_spheres() {
+    let model = ModelBuilder::new()
+        .with_spheres(vec![])
+        .build();
+    let handle = model.model();
+    let borrow = handle.borrow();
+    let ray = Ray::from_origin_dir(Vector3::new(0_f32, 0_f32, 5_f32), -Vector3::unit_z());
+
+    assert!(borrow.is_spheres());
+    assert_eq!(model.len_primitives(), 0);
+    assert!(borrow.intersect(&ray).is_none());
+}
+
+fn floor() -> ModelInstance {
+    let normals = Normals::from([Vector3::unit_y(); 3]);
+    let mesh = MeshBuilder::new()
+        .with_primitive(
+            Triangle::new(
+                Vector3::new(-10_f32, 0_f32,  10_f32),
+                Vector3::new( 10_f32, 0_f32,  10_f32),
+                Vector3::new( 10_f32, 0_f32, -10_f32),
+            ),
+            TextureCoordinates::default(),
+            normals
+        )
+        .with_primitive(
+            Triangle::new(
+                Vector3::new(-10_f32, 0_f32,  10_f32),
+                Vector3::new( 10_f32, 0_f32, -10_f32),
+                Vector3::new(-10_f32, 0_f32, -10_f32),
+            ),
+            TextureCoordinates::default(),
+            normals
+        )
+        .build();
+
+    ModelBuilder::new()
+        .with_mesh(mesh)
+        .build()
+}
+
+/// A scene with a triangle floor and a cloud of spheres above it, where the
+/// spheres are scaled and moved into place by their instance transform.
+fn scene() -> Scene {
+    let projection_spec = BoxSpec::new(-1_f32, 1_f32, -1_f32, 1_f32, 1_f32, 100_f32);
+    let attitude_spec = CameraAttitudeSpec::new(
+         Vector3::new(0_f32, 5_f32, 20_f32),
+        -Vector3::unit_z(),
+         Vector3::unit_x(),
+         Vector3::unit_y(),
+        -Vector3::unit_z()
+    );
+    let camera = Camera::new(&projection_spec, &attitude_spec);
+    let mut physics = World::new();
+    let floor = SceneObjectBuilder::new(floor(), physics.register_body(RigidBody::default()))
+        .build();
+    let particles = ModelBuilder::new()
+        .with_spheres(particles())
+        .build();
+    let transform = Transform3::new(
+        &Vector3::from_fill(2_f32),
+        &Vector3::new(-10_f32, 1_f32, -10_f32),
+        Rotation3::identity()
+    );
+    let particles = SceneObjectBuilder::new(particles, physics.register_body(RigidBody::default()))
+        .with_transform(&transform)
+        .build();
+
+    SceneBuilder::new(camera)
+        .with_physics(physics)
+        .with_objects(vec![floor, particles])
+        .build()
+}
+
+#[test]
+fn test_scene_with_spheres_and_triangles() {
+    let scene = scene();
+    let ray = Ray::from_origin_dir(Vector3::new(-10_f32, 50_f32, -10_f32), -Vector3::unit_y());
+    let intersection = scene.intersect(&ray).unwrap();
+
+    assert_eq!(intersection.instance_primitive.instance_index(), 1);
+    // The ray hits the top of the sphere at lattice index `(0, 19, 0)`, whose 
+    // center is at `y == 1 + 2 * 9.5` after the instance transform.
+    let radius = 2_f32 * (0.05_f32 + 0.01_f32 * (((19 * 3) % 10) as f32));
+    assert_relative_eq!(intersection.interaction.t, 50_f32 - (20_f32 + radius), epsilon = 1e-4);
+
+    // Between the columns of spheres the ray falls through to the floor.
+    let ray = Ray::from_origin_dir(Vector3::new(-9.5_f32, 30_f32, -9.5_f32), -Vector3::unit_y());
+    let intersection = scene.intersect(&ray).unwrap();
+
+    assert_eq!(intersection.instance_primitive.instance_index(), 0);
+    assert_relative_eq!(intersection.interaction.t, 30_f32, epsilon = 1e-4);
+}
+
+#[test]
+fn test_scene_sphere_normals() {
+    let scene = scene();
+    let mut accumulator = NormalMappingAccumulator::new();
+    let ray = Ray::from_origin_dir(Vector3::new(-10_f32, 50_f32, -10_f32), -Vector3::unit_y());
+    let result = accumulator.evaluate(&scene, &ray);
+    let expected = (Vector3::unit_y() + Vector3::from_fill(1_f32)) * 0.5_f32;
+
+    assert_relative_eq!(result, expected, epsilon = 1e-5);
+}
@@ -0,0 +1,101 @@
+use bvhtracer::{
+    Mesh,
+    MeshDecoder,
+    MeshError,
+    StlMeshDecoder,
+};
+use cglinalg::{
+    Vector3,
+};
+use std::fs::{
+    File,
+};
+
+
+fn read_mesh(path: &str) -> Mesh<f32> {
+    let file = File::open(path).unwrap();
+
+    StlMeshDecoder::new(file).read_mesh().unwrap()
+}
+
+
+#[test]
+fn test_stl_ascii_cube() {
+    let mesh = read_mesh("assets/cube_ascii.stl");
+
+    assert_eq!(mesh.len_primitives(), 12);
+}
+
+/// The fixture has a header starting with `solid`, so the format has to be
+/// detected from the length of the file.
+#[test]
+fn test_stl_binary_cube() {
+    let mesh = read_mesh("assets/cube_binary.stl");
+
+    assert_eq!(mesh.len_primitives(), 12);
+}
+
+#[test]
+fn test_stl_ascii_and_binary_agree() {
+    let ascii = read_mesh("assets/cube_ascii.stl");
+    let binary = read_mesh("assets/cube_binary.stl");
+
+    assert_eq!(ascii, binary);
+}
+
+#[test]
+fn test_stl_facet_normals() {
+    let mesh = read_mesh("assets/cube_ascii.stl");
+    let expected = Vector3::new(-1_f32, 0_f32, 0_f32);
+    let normals = mesh.normals()[0];
+
+    assert_eq!(normals[0], expected);
+    assert_eq!(normals[1], expected);
+    assert_eq!(normals[2], expected);
+}
+
+#[test]
+fn test_stl_ascii_multiple_solids() {
+    let stl_file = r"
+        solid first part
+          facet normal 0 0 1
+            outer loop
+              vertex 0 0 0
+              vertex 1 0 0
+              vertex 0 1 0
+            endloop
+          endfacet
+        endsolid first part
+        solid second
+          facet normal 0 0 1
+            outer loop
+              vertex 0 0 1
+              vertex 1 0 1
+              vertex 0 1 1
+            endloop
+          endfacet
+        endsolid second
+    ";
+    let mesh = StlMeshDecoder::new(stl_file.as_bytes()).read_mesh().unwrap();
+
+    assert_eq!(mesh.len_primitives(), 2);
+    assert_eq!(mesh.primitives()[1].vertices[0], Vector3::new(0_f32, 0_f32, 1_f32));
+}
+
+#[test]
+fn test_stl_ascii_malformed_facet() {
+    let stl_file = "solid broken\nfacet normal 0 0 1\nouter loop\nvertex 0 0 0\nendloop\nendfacet\nendsolid broken\n";
+    let result = StlMeshDecoder::new(stl_file.as_bytes()).read_mesh();
+
+    assert!(matches!(result, Err(MeshError::Decoding(_))));
+}
+
+#[test]
+fn test_stl_binary_truncated() {
+    let mut stl_file = vec![0_u8; 80];
+    stl_file.extend_from_slice(&2_u32.to_le_bytes());
+    stl_file.extend_from_slice(&[0_u8; 50]);
+    let result = StlMeshDecoder::new(stl_file.as_slice()).read_mesh();
+
+    assert!(matches!(result, Err(MeshError::Decoding(_))));
+}
@@ -0,0 +1,124 @@
+use bvhtracer::{
+    Tile,
+    TileOrder,
+    TileScheduler,
+};
+
+
+fn coverage(tiles: &[Tile], width: usize, height: usize) -> Vec<usize> {
+    let mut coverage = vec![0; width * height];
+    for tile in tiles.iter() {
+        for (x, y) in tile.pixels() {
+            coverage[x + y * width] += 1;
+        }
+    }
+
+    coverage
+}
+
+fn is_adjacent(tile1: &Tile, tile2: &Tile) -> bool {
+    let dx = usize::abs_diff(tile1.x(), tile2.x());
+    let dy = usize::abs_diff(tile1.y(), tile2.y());
+
+    (dx == 0 && dy == tile1.height()) || (dx == tile1.width() && dy == 0)
+}
+
+
+#[test]
+fn test_tile_schedule_scanline_covers_frame_buffer_once() {
+    let scheduler = TileScheduler::new(16, 16, TileOrder::Scanline);
+    let tiles = scheduler.schedule(1920, 1080);
+    let result = coverage(&tiles, 1920, 1080);
+
+    assert!(result.iter().all(|&count| count == 1));
+}
+
+#[test]
+fn test_tile_schedule_hilbert_covers_frame_buffer_once() {
+    let scheduler = TileScheduler::new(16, 16, TileOrder::Hilbert);
+    let tiles = scheduler.schedule(1920, 1080);
+    let result = coverage(&tiles, 1920, 1080);
+
+    assert!(result.iter().all(|&count| count == 1));
+}
+
+#[test]
+fn test_tile_schedule_spiral_covers_frame_buffer_once() {
+    let scheduler = TileScheduler::new(16, 16, TileOrder::Spiral);
+    let tiles = scheduler.schedule(1920, 1080);
+    let result = coverage(&tiles, 1920, 1080);
+
+    assert!(result.iter().all(|&count| count == 1));
+}
+
+#[test]
+fn test_tile_schedule_odd_dimensions_covers_frame_buffer_once() {
+    let orders = [TileOrder::Scanline, TileOrder::Hilbert, TileOrder::Spiral];
+    for order in orders {
+        let scheduler = TileScheduler::new(8, 5, order);
+        let tiles = scheduler.schedule(37, 23);
+        let result = coverage(&tiles, 37, 23);
+
+        assert!(result.iter().all(|&count| count == 1), "order = {:?}", order);
+    }
+}
+
+#[test]
+fn test_tile_schedule_partial_edge_tiles() {
+    let scheduler = TileScheduler::new(8, 8, TileOrder::Scanline);
+    let tiles = scheduler.schedule(20, 10);
+    let expected = vec![
+        Tile::new(0, 0, 8, 8), Tile::new(8, 0, 8, 8), Tile::new(16, 0, 4, 8),
+        Tile::new(0, 8, 8, 2), Tile::new(8, 8, 8, 2), Tile::new(16, 8, 4, 2),
+    ];
+
+    assert_eq!(tiles, expected);
+}
+
+#[test]
+fn test_tile_schedule_tile_smaller_than_tile_size() {
+    let scheduler = TileScheduler::new(64, 64, TileOrder::Spiral);
+    let tiles = scheduler.schedule(3, 2);
+    let expected = vec![Tile::new(0, 0, 3, 2)];
+
+    assert_eq!(tiles, expected);
+}
+
+#[test]
+fn test_tile_schedule_empty_frame_buffer() {
+    let orders = [TileOrder::Scanline, TileOrder::Hilbert, TileOrder::Spiral];
+    for order in orders {
+        let scheduler = TileScheduler::new(8, 8, order);
+        let tiles = scheduler.schedule(0, 0);
+
+        assert!(tiles.is_empty());
+    }
+}
+
+/// Consecutive tiles along a Hilbert curve share an edge.
+#[test]
+fn test_tile_schedule_hilbert_consecutive_tiles_adjacent() {
+    let scheduler = TileScheduler::new(8, 8, TileOrder::Hilbert);
+    let tiles = scheduler.schedule(128, 128);
+
+    assert!(tiles.windows(2).all(|pair| is_adjacent(&pair[0], &pair[1])));
+}
+
+/// A spiral starts from the center of the frame buffer.
+#[test]
+fn test_tile_schedule_spiral_starts_at_center() {
+    let scheduler = TileScheduler::new(10, 10, TileOrder::Spiral);
+    let tiles = scheduler.schedule(50, 30);
+    let expected = Tile::new(20, 10, 10, 10);
+    let result = tiles[0];
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn test_tile_schedule_spiral_consecutive_tiles_adjacent_square() {
+    let scheduler = TileScheduler::new(8, 8, TileOrder::Spiral);
+    let tiles = scheduler.schedule(72, 72);
+
+    assert!(tiles.windows(2).all(|pair| is_adjacent(&pair[0], &pair[1])));
+}
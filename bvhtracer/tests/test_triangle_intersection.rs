@@ -193,6 +193,7 @@ fn test_triangle_closest_t2() {
 }
 
 
+/// A ray that ends before reaching the triangle misses it.
 #[test]
 fn test_triangle_closest_t3() {
     let scene = scene();
@@ -201,8 +202,8 @@ fn test_triangle_closest_t3() {
     let ray_direction = (scene_origin - ray_origin).normalize();
     let ray_t = 0.01_f32;
     let ray = Ray::new(ray_origin, ray_direction, ray_t);
-    let result = scene.intersect(&ray).unwrap();
+    let result = scene.intersect(&ray);
 
-    assert!(result.t <= ray_t);
+    assert!(result.is_none());
 }
 
@@ -0,0 +1,48 @@
+use bvhtracer::{
+    BvhBuilder,
+    Mesh,
+    MeshDecoder,
+    ObjMeshDecoder,
+    Ray,
+    TriangleIntersection,
+};
+use cglinalg::{
+    Magnitude,
+    Vector3,
+};
+use std::fs::{
+    File,
+};
+
+
+/// A boundary volume hierarchy built without a triangle intersection test
+/// follows the global default. 
+/// 
+/// The global default is shared by the tests of a test binary, which run in 
+/// parallel, so this test has a binary of its own to change it without racing 
+/// with any other test.
+#[test]
+fn test_bvh_triangle_intersection_global_default() {
+    let file = File::open("assets/cube.obj").unwrap();
+    let mut mesh: Mesh<f32> = ObjMeshDecoder::new(file).read_mesh().unwrap();
+    let bvh = BvhBuilder::new().build_for_mesh(&mut mesh);
+    let origin = Vector3::from_fill(0.5_f32);
+
+    assert_eq!(TriangleIntersection::global_default(), TriangleIntersection::MollerTrumbore);
+    assert_eq!(bvh.triangle_intersection(), TriangleIntersection::MollerTrumbore);
+
+    TriangleIntersection::set_global_default(TriangleIntersection::Watertight);
+
+    assert_eq!(bvh.triangle_intersection(), TriangleIntersection::Watertight);
+    for triangle in mesh.primitives().iter() {
+        for vertex in triangle.vertices.iter() {
+            let ray = Ray::from_origin_dir(origin, (vertex - origin).normalize());
+
+            assert!(bvh.intersect_mesh(&mesh, &ray).is_some());
+        }
+    }
+
+    TriangleIntersection::set_global_default(TriangleIntersection::MollerTrumbore);
+
+    assert_eq!(bvh.triangle_intersection(), TriangleIntersection::MollerTrumbore);
+}
@@ -0,0 +1,350 @@
+use bvhtracer::{
+    Aabb,
+    Accumulator,
+    BoundedPrimitive,
+    BoxSpec,
+    CameraAttitudeSpec,
+    Camera,
+    LambertianMaterial,
+    MeshBuilder,
+    ModelBuilder,
+    Normals,
+    Ray,
+    RigidBody,
+    Scene,
+    SceneBuilder,
+    SceneObjectBuilder,
+    TextureCoordinates,
+    TextureMaterialAccumulator,
+    Transform3,
+    Triangle,
+    VoxelGrid,
+    World,
+};
+use approx::{
+    assert_relative_eq,
+};
+use cglinalg::{
+    Magnitude,
+    Rotation3,
+    Vector3,
+};
+use std::sync::{
+    Arc,
+};
+
+
+/// A grid of unit voxels from the origin with a single solid voxel in the middle.
+fn single_voxel() -> VoxelGrid<f32> {
+    let mut grid = VoxelGrid::new(Vector3::zero(), 1_f32, [3, 3, 3]);
+    grid.set([1, 1, 1], 7);
+
+    grid
+}
+
+/// A sparse, irregular pattern of solid voxels.
+fn pattern() -> VoxelGrid<f32> {
+    let mut grid = VoxelGrid::new(Vector3::new(-2_f32, -1_f32, -3_f32), 0.5_f32, [12, 10, 14]);
+    for x in 0..12 {
+        for y in 0..10 {
+            for z in 0..14 {
+                if (x * 7 + y * 13 + z * 5) % 11 == 0 {
+                    grid.set([x, y, z], ((x + y + z) % 3) as u32);
+                }
+            }
+        }
+    }
+
+    grid
+}
+
+/// The box of every solid voxel in the grid.
+fn solid_voxel_boxes(grid: &VoxelGrid<f32>) -> Vec<([usize; 3], Aabb<f32>)> {
+    let bounds = grid.bounds();
+    let dimensions = grid.dimensions();
+    let mut boxes = vec![];
+    for x in 0..dimensions[0] {
+        for y in 0..dimensions[1] {
+            for z in 0..dimensions[2] {
+                if grid.get([x, y, z]).is_some() {
+                    let bounds_min = bounds.bounds_min + Vector3::new(x as f32, y as f32, z as f32) * grid.voxel_size();
+                    boxes.push(([x, y, z], Aabb::new(bounds_min, bounds_min + Vector3::from_fill(grid.voxel_size()))));
+                }
+            }
+        }
+    }
+
+    boxes
+}
+
+fn rays() -> Vec<Ray<f32>> {
+    let mut rays = vec![];
+    for i in 0..20 {
+        for j in 0..20 {
+            let origin = Vector3::new(-10_f32 + 0.37_f32 * (i as f32), 12_f32, -8_f32 + 0.53_f32 * (j as f32));
+            let target = Vector3::new(1.03_f32 - 0.1_f32 * (j as f32), -0.47_f32, 0.2_f32 * (i as f32) - 0.97_f32);
+            rays.push(Ray::from_origin_dir(origin, (target - origin).normalize()));
+        }
+    }
+
+    rays
+}
+
+
+#[test]
+fn test_voxel_grid_get_set_clear() {
+    let mut grid = single_voxel();
+
+    assert_eq!(grid.len(), 27);
+    assert_eq!(grid.len_solid(), 1);
+    assert_eq!(grid.get([1, 1, 1]), Some(7));
+    assert_eq!(grid.get([0, 1, 1]), None);
+
+    grid.clear([1, 1, 1]);
+
+    assert_eq!(grid.len_solid(), 0);
+    assert_eq!(grid.get([1, 1, 1]), None);
+}
+
+#[test]
+#[should_panic]
+fn test_voxel_grid_set_outside_grid() {
+    let mut grid = single_voxel();
+    grid.set([3, 0, 0], 0);
+}
+
+#[test]
+fn test_voxel_grid_bounds_and_cell_at() {
+    let grid = pattern();
+    let expected = Aabb::new(Vector3::new(-2_f32, -1_f32, -3_f32), Vector3::new(4_f32, 4_f32, 4_f32));
+
+    assert_eq!(grid.bounds(), expected);
+    assert_eq!(grid.cell_at(&Vector3::new(-1.9_f32, -0.9_f32, -2.9_f32)), Some([0, 0, 0]));
+    assert_eq!(grid.cell_at(&Vector3::new(3.9_f32, 3.9_f32, 3.9_f32)), Some([11, 9, 13]));
+    assert_eq!(grid.cell_at(&Vector3::new(-2.1_f32, 0_f32, 0_f32)), None);
+    assert_eq!(grid.cell_at(&Vector3::new(0_f32, 4.1_f32, 0_f32)), None);
+}
+
+/// A ray along each axis in each direction should hit the face of the voxel
+/// that faces it.
+#[test]
+fn test_voxel_grid_face_normals() {
+    let grid = single_voxel();
+    let center = Vector3::from_fill(1.5_f32);
+    let axes = [Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()];
+    for axis in axes.iter() {
+        for sign in [-1_f32, 1_f32] {
+            let direction = axis * sign;
+            let ray = Ray::from_origin_dir(center - direction * 10_f32, direction);
+            let hit = grid.intersect_voxel(&ray).unwrap();
+
+            assert_eq!(hit.cell, [1, 1, 1]);
+            assert_eq!(hit.material_id, 7);
+            assert_eq!(hit.normal, -direction);
+            assert_relative_eq!(hit.t, 9.5_f32, epsilon = 1e-5);
+        }
+    }
+}
+
+#[test]
+fn test_voxel_grid_ray_from_inside_grid() {
+    let grid = single_voxel();
+    let ray = Ray::from_origin_dir(Vector3::new(0.5_f32, 1.25_f32, 1.5_f32), Vector3::unit_x());
+    let hit = grid.intersect_voxel(&ray).unwrap();
+
+    assert_eq!(hit.cell, [1, 1, 1]);
+    assert_relative_eq!(hit.t, 0.5_f32, epsilon = 1e-6);
+    assert_eq!(hit.normal, -Vector3::unit_x());
+}
+
+/// A ray leaving the surface of a voxel does not hit the voxel it started in.
+#[test]
+fn test_voxel_grid_ray_from_inside_solid_voxel() {
+    let grid = single_voxel();
+    let ray = Ray::from_origin_dir(Vector3::from_fill(1.5_f32), Vector3::unit_x());
+
+    assert!(grid.intersect_voxel(&ray).is_none());
+}
+
+#[test]
+fn test_voxel_grid_ray_distance() {
+    let grid = single_voxel();
+    let ray = Ray::new(Vector3::new(-5_f32, 1.5_f32, 1.5_f32), Vector3::unit_x(), 5.9_f32);
+
+    assert!(grid.intersect_voxel(&ray).is_none());
+    assert!(!grid.occluded(&ray));
+
+    let ray = Ray::new(Vector3::new(-5_f32, 1.5_f32, 1.5_f32), Vector3::unit_x(), 6.1_f32);
+
+    assert!(grid.occluded(&ray));
+}
+
+#[test]
+fn test_voxel_grid_miss() {
+    let grid = single_voxel();
+    let ray = Ray::from_origin_dir(Vector3::new(-5_f32, 0.5_f32, 1.5_f32), Vector3::unit_x());
+
+    assert!(grid.intersect_voxel(&ray).is_none());
+
+    let ray = Ray::from_origin_dir(Vector3::new(-5_f32, 1.5_f32, 1.5_f32), -Vector3::unit_x());
+
+    assert!(grid.intersect_voxel(&ray).is_none());
+}
+
+#[test]
+fn test_voxel_grid_face_coordinates() {
+    let grid = single_voxel();
+    let ray = Ray::from_origin_dir(Vector3::new(1.25_f32, 1.75_f32, 10_f32), -Vector3::unit_z());
+    let interaction = BoundedPrimitive::intersect(&grid, &ray).unwrap();
+
+    // The face of a voxel facing the z-axis is spanned by the x-axis and the y-axis.
+    assert_relative_eq!(interaction.t, 8_f32);
+    assert_relative_eq!(interaction.u, 0.25_f32, epsilon = 1e-6);
+    assert_relative_eq!(interaction.v, 0.75_f32, epsilon = 1e-6);
+}
+
+/// Walking the grid should find the same first hit as testing every solid
+/// voxel as a box.
+#[test]
+fn test_voxel_grid_matches_brute_force() {
+    let grid = pattern();
+    let boxes = solid_voxel_boxes(&grid);
+    let mut hit_count = 0;
+    for ray in rays().iter() {
+        let expected = boxes.iter()
+            .filter_map(|(cell, aabb)| BoundedPrimitive::intersect(aabb, ray).map(|interaction| (interaction.t, *cell)))
+            .min_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        let result = grid.intersect_voxel(ray);
+        match (result, expected) {
+            (Some(hit), Some((t, _))) => {
+                assert_relative_eq!(hit.t, t, epsilon = 1e-4);
+                let aabb = boxes.iter().find(|(cell, _)| *cell == hit.cell).unwrap().1;
+                let entry_t = BoundedPrimitive::intersect(&aabb, ray).unwrap().t;
+                assert_relative_eq!(entry_t, t, epsilon = 1e-4);
+                assert!(hit.normal.dot(&ray.direction) < 0_f32);
+                hit_count += 1;
+            }
+            (None, None) => {}
+            (result, expected) => panic!("expected {:?} but got {:?}", expected, result),
+        }
+    }
+
+    assert!(hit_count > 100);
+}
+
+fn floor() -> bvhtracer::ModelInstance {
+    let normals = Normals::from([Vector3::unit_y(); 3]);
+    let mesh = MeshBuilder::new()
+        .with_primitive(
+            Triangle::new(
+                Vector3::new(-10_f32, 0_f32,  10_f32),
+                Vector3::new( 10_f32, 0_f32,  10_f32),
+                Vector3::new( 10_f32, 0_f32, -10_f32),
+            ),
+            TextureCoordinates::default(),
+            normals
+        )
+        .with_primitive(
+            Triangle::new(
+                Vector3::new(-10_f32, 0_f32,  10_f32),
+                Vector3::new( 10_f32, 0_f32, -10_f32),
+                Vector3::new(-10_f32, 0_f32, -10_f32),
+            ),
+            TextureCoordinates::default(),
+            normals
+        )
+        .build();
+
+    ModelBuilder::new()
+        .with_mesh(mesh)
+        .with_material(Arc::new(LambertianMaterial::new(Vector3::from_fill(0.5_f32))))
+        .build()
+}
+
+/// A scene with a triangle floor and a staircase of voxels with one material
+/// per step, moved into place by its instance transform.
+fn scene() -> Scene {
+    let projection_spec = BoxSpec::new(-1_f32, 1_f32, -1_f32, 1_f32, 1_f32, 100_f32);
+    let attitude_spec = CameraAttitudeSpec::new(
+         Vector3::new(0_f32, 5_f32, 20_f32),
+        -Vector3::unit_z(),
+         Vector3::unit_x(),
+         Vector3::unit_y(),
+        -Vector3::unit_z()
+    );
+    let camera = Camera::new(&projection_spec, &attitude_spec);
+    let mut physics = World::new();
+    let floor = SceneObjectBuilder::new(floor(), physics.register_body(RigidBody::default()))
+        .build();
+    let mut grid = VoxelGrid::new(Vector3::zero(), 1_f32, [3, 3, 1]);
+    for step in 0..3 {
+        for height in 0..=step {
+            grid.set([step, height, 0], step as u32);
+        }
+    }
+    let stairs = ModelBuilder::new()
+        .with_material_id(0, Arc::new(LambertianMaterial::new(Vector3::new(1_f32, 0_f32, 0_f32))))
+        .with_material_id(1, Arc::new(LambertianMaterial::new(Vector3::new(0_f32, 1_f32, 0_f32))))
+        .with_material_id(2, Arc::new(LambertianMaterial::new(Vector3::new(0_f32, 0_f32, 1_f32))))
+        .with_voxels(grid)
+        .build();
+    let transform = Transform3::new(
+        &Vector3::from_fill(2_f32),
+        &Vector3::new(1_f32, 0_f32, 0_f32),
+        Rotation3::identity()
+    );
+    let stairs = SceneObjectBuilder::new(stairs, physics.register_body(RigidBody::default()))
+        .with_transform(&transform)
+        .build();
+
+    SceneBuilder::new(camera)
+        .with_physics(physics)
+        .with_objects(vec![floor, stairs])
+        .build()
+}
+
+#[test]
+fn test_scene_with_voxels_and_triangles() {
+    let scene = scene();
+    for step in 0..3 {
+        let x = 1_f32 + 2_f32 * (step as f32) + 1_f32;
+        let ray = Ray::from_origin_dir(Vector3::new(x, 20_f32, 1_f32), -Vector3::unit_y());
+        let intersection = scene.intersect(&ray).unwrap();
+
+        assert_eq!(intersection.instance_primitive.instance_index(), 1);
+        assert_relative_eq!(intersection.interaction.t, 20_f32 - 2_f32 * ((step + 1) as f32), epsilon = 1e-4);
+    }
+
+    let ray = Ray::from_origin_dir(Vector3::new(-1_f32, 20_f32, 1_f32), -Vector3::unit_y());
+    let intersection = scene.intersect(&ray).unwrap();
+
+    assert_eq!(intersection.instance_primitive.instance_index(), 0);
+    assert_relative_eq!(intersection.interaction.t, 20_f32, epsilon = 1e-4);
+}
+
+#[test]
+fn test_scene_voxel_materials() {
+    let scene = scene();
+    let mut accumulator = TextureMaterialAccumulator::new();
+    let colors = [Vector3::unit_x(), Vector3::unit_y(), Vector3::unit_z()];
+    for (step, color) in colors.iter().enumerate() {
+        let x = 1_f32 + 2_f32 * (step as f32) + 1_f32;
+        let ray = Ray::from_origin_dir(Vector3::new(x, 20_f32, 1_f32), -Vector3::unit_y());
+
+        assert_eq!(accumulator.evaluate(&scene, &ray), *color);
+    }
+}
+
+/// A voxel grid is a single primitive of its model, however many voxels it has.
+#[test]
+fn test_voxel_model_len_primitives() {
+    let mut grid = VoxelGrid::new(Vector3::zero(), 1_f32, [2, 2, 2]);
+    grid.set([0, 0, 0], 0);
+    grid.set([1, 1, 1], 0);
+    let model = ModelBuilder::new()
+        .with_voxels(grid)
+        .build();
+
+    assert_eq!(model.len_primitives(), 1);
+    assert_eq!(model.len(), 1);
+}
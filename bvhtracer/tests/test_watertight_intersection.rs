@@ -0,0 +1,209 @@
+use bvhtracer::{
+    Bvh,
+    BvhBuilder,
+    Mesh,
+    MeshDecoder,
+    ObjMeshDecoder,
+    Ray,
+    Triangle,
+    TriangleIntersection,
+};
+use approx::{
+    assert_relative_eq,
+};
+use cglinalg::{
+    Magnitude,
+    Vector3,
+};
+use std::fs::{
+    File,
+};
+
+
+fn cube_mesh() -> Mesh<f32> {
+    let file = File::open("assets/cube.obj").unwrap();
+
+    ObjMeshDecoder::new(file).read_mesh().unwrap()
+}
+
+fn cube_bvh(mesh: &mut Mesh<f32>, intersection: TriangleIntersection) -> Bvh {
+    BvhBuilder::new()
+        .with_triangle_intersection(intersection)
+        .build_for_mesh(mesh)
+}
+
+/// Points along every edge of every triangle of the mesh, including the
+/// vertices and the diagonals shared by the two triangles of each face.
+fn edge_points(mesh: &Mesh<f32>) -> Vec<Vector3<f32>> {
+    let mut points = vec![];
+    for triangle in mesh.primitives().iter() {
+        for i in 0..3 {
+            let start = triangle.vertices[i];
+            let end = triangle.vertices[(i + 1) % 3];
+            for j in 0..=16 {
+                let s = (j as f32) / 16_f32;
+                points.push(start + (end - start) * s);
+            }
+        }
+    }
+
+    points
+}
+
+/// Determine whether a point lies on the surface of the unit cube with one
+/// corner at the origin.
+fn on_unit_cube(point: &Vector3<f32>) -> bool {
+    let center = Vector3::from_fill(0.5_f32);
+    let offset = point - center;
+    let largest = f32::max(f32::abs(offset.x), f32::max(f32::abs(offset.y), f32::abs(offset.z)));
+
+    f32::abs(largest - 0.5_f32) < 1e-4
+}
+
+fn triangle() -> Triangle<f32> {
+    Triangle::new(
+        Vector3::new(0_f32, 1_f32 / 2_f32, 0_f32),
+        Vector3::new(-1_f32 / f32::sqrt(3_f32), -1_f32 / 2_f32, 0_f32),
+        Vector3::new(1_f32 / f32::sqrt(3_f32), -1_f32 / 2_f32, 0_f32),
+    )
+}
+
+
+/// Rays from inside the cube toward its shared edges and vertices must not
+/// leak out of the cube.
+#[test]
+fn test_watertight_cube_no_leaks_from_inside() {
+    let mut mesh = cube_mesh();
+    let bvh = cube_bvh(&mut mesh, TriangleIntersection::Watertight);
+    let origins = [
+        Vector3::from_fill(0.5_f32),
+        Vector3::new(0.25_f32, 0.5_f32, 0.75_f32),
+        Vector3::new(0.9_f32, 0.1_f32, 0.3_f32),
+    ];
+    for origin in origins.iter() {
+        for point in edge_points(&mesh).iter() {
+            let ray = Ray::from_origin_dir(*origin, (point - origin).normalize());
+            let intersection = bvh.intersect_mesh(&mesh, &ray);
+
+            assert!(intersection.is_some(), "ray from {:?} toward {:?} leaked", origin, point);
+            let intersection = intersection.unwrap();
+            assert!(on_unit_cube(&ray.interpolate(intersection.interaction.t)));
+            assert!(bvh.occluded_mesh(&mesh, &ray));
+        }
+    }
+}
+
+/// Rays from outside the cube that enter it through its shared edges and
+/// vertices must hit it.
+#[test]
+fn test_watertight_cube_no_leaks_from_outside() {
+    let mut mesh = cube_mesh();
+    let bvh = cube_bvh(&mut mesh, TriangleIntersection::Watertight);
+    let targets = [
+        Vector3::from_fill(0.5_f32),
+        Vector3::new(0.25_f32, 0.5_f32, 0.75_f32),
+        Vector3::new(0.9_f32, 0.1_f32, 0.3_f32),
+    ];
+    for target in targets.iter() {
+        for point in edge_points(&mesh).iter() {
+            let origin = point + (point - target) * 3_f32;
+            let ray = Ray::from_origin_dir(origin, (target - origin).normalize());
+            let intersection = bvh.intersect_mesh(&mesh, &ray);
+
+            assert!(intersection.is_some(), "ray from {:?} through {:?} leaked", origin, point);
+            let intersection = intersection.unwrap();
+            assert!(on_unit_cube(&ray.interpolate(intersection.interaction.t)));
+            assert_relative_eq!(intersection.interaction.t, (point - origin).magnitude(), epsilon = 1e-4);
+        }
+    }
+}
+
+/// Both triangle intersection tests agree away from the edges of a triangle.
+#[test]
+fn test_watertight_matches_moller_trumbore_inside_triangle() {
+    let triangle = triangle();
+    for i in 0..10 {
+        for j in 0..10 {
+            let target = Vector3::new(-0.3_f32 + 0.06_f32 * (i as f32), -0.4_f32 + 0.06_f32 * (j as f32), 0_f32);
+            let origin = Vector3::new(0.5_f32, -0.25_f32, 5_f32);
+            let ray = Ray::from_origin_dir(origin, (target - origin).normalize());
+            let expected = triangle.intersect_with(&ray, TriangleIntersection::MollerTrumbore);
+            let result = triangle.intersect_with(&ray, TriangleIntersection::Watertight);
+
+            assert_eq!(result.is_some(), expected.is_some());
+            if let (Some(result), Some(expected)) = (result, expected) {
+                assert_relative_eq!(result.t, expected.t, epsilon = 1e-5);
+                assert_relative_eq!(result.u, expected.u, epsilon = 1e-5);
+                assert_relative_eq!(result.v, expected.v, epsilon = 1e-5);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_watertight_triangle_misses() {
+    let triangle = triangle();
+    let origin = Vector3::new(0_f32, 0_f32, 5_f32);
+    let outside = Vector3::new(0_f32, 0.51_f32, 0_f32);
+    let ray = Ray::from_origin_dir(origin, (outside - origin).normalize());
+
+    assert!(triangle.intersect_with(&ray, TriangleIntersection::Watertight).is_none());
+
+    // The triangle is behind the ray.
+    let ray = Ray::from_origin_dir(origin, Vector3::unit_z());
+
+    assert!(triangle.intersect_with(&ray, TriangleIntersection::Watertight).is_none());
+}
+
+/// Triangles much smaller than the old hidden epsilon are hit by both tests.
+#[test]
+fn test_small_triangle_hits() {
+    let scale = 1e-4_f32;
+    let triangle = Triangle::new(
+        Vector3::new(0_f32, 0.5_f32, 0_f32) * scale,
+        Vector3::new(-0.5_f32, -0.5_f32, 0_f32) * scale,
+        Vector3::new(0.5_f32, -0.5_f32, 0_f32) * scale,
+    );
+    let ray = Ray::from_origin_dir(Vector3::new(0_f32, 0_f32, 1_f32), -Vector3::unit_z());
+
+    for intersection in [TriangleIntersection::MollerTrumbore, TriangleIntersection::Watertight] {
+        let result = triangle.intersect_with(&ray, intersection).unwrap();
+
+        assert_relative_eq!(result.t, 1_f32, epsilon = 1e-6);
+    }
+}
+
+/// Hits closer than the minimum distance of a ray are ignored.
+#[test]
+fn test_ray_t_min() {
+    let triangle = triangle();
+    let ray = Ray::from_origin_dir(Vector3::new(0_f32, 0_f32, 5_f32), -Vector3::unit_z());
+
+    assert_eq!(ray.t_min, Ray::<f32>::default_t_min());
+
+    for intersection in [TriangleIntersection::MollerTrumbore, TriangleIntersection::Watertight] {
+        assert!(triangle.intersect_with(&ray, intersection).is_some());
+        assert!(triangle.intersect_with(&ray.with_t_min(5.5_f32), intersection).is_none());
+        assert!(!triangle.occluded_with(&ray.with_t_min(5.5_f32), intersection));
+
+        // A ray leaving the triangle does not hit it again at its origin.
+        let leaving_ray = Ray::from_origin_dir(Vector3::zero(), Vector3::unit_z());
+        let grazing_ray = Ray::from_origin_dir(Vector3::zero(), -Vector3::unit_z());
+
+        assert!(triangle.intersect_with(&leaving_ray, intersection).is_none());
+        assert!(triangle.intersect_with(&grazing_ray, intersection).is_none());
+        assert!(triangle.intersect_with(&grazing_ray.with_t_min(-1_f32), intersection).is_some());
+    }
+}
+
+#[test]
+fn test_bvh_triangle_intersection_selection() {
+    let mut mesh = cube_mesh();
+    let bvh = cube_bvh(&mut mesh, TriangleIntersection::Watertight);
+
+    assert_eq!(bvh.triangle_intersection(), TriangleIntersection::Watertight);
+
+    let bvh = cube_bvh(&mut mesh, TriangleIntersection::MollerTrumbore);
+
+    assert_eq!(bvh.triangle_intersection(), TriangleIntersection::MollerTrumbore);
+}
@@ -67,6 +67,13 @@ pub trait AppState {
     fn active_scene(&self) -> &Scene;
 
     fn active_scene_mut(&mut self) -> &mut Scene;
+
+    /// Determine whether the last update changed the scene or the camera, in which
+    /// case the samples accumulated from previous frames are discarded. Static 
+    /// scenes should return `false` so that the image converges over many frames.
+    fn scene_changed(&self) -> bool {
+        true
+    }
 }
 
 pub struct App {
@@ -104,6 +111,10 @@ impl App {
         self.renderer.render(&mut self.renderer_state, self.state.active_scene())
     }
 
+    pub fn reset_accumulation(&mut self) {
+        self.renderer_state.reset_accumulation();
+    }
+
     pub fn run(&mut self) {
         println!("Rendering scene.");
         let now = SystemTime::now();
@@ -216,6 +227,9 @@ impl App {
     
             println!("Updating scene");
             self.update(time_elapsed);
+            if self.state.scene_changed() {
+                self.reset_accumulation();
+            }
             println!("Rendering scene");
             let now = SystemTime::now();
             self.render();
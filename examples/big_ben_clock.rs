@@ -128,7 +128,7 @@ fn main() -> io::Result<()> {
         Rgba::new(255, 255, 255, 255), 
         Rgba::new(0, 0, 0, 255),
     ));
-    let renderer = Renderer::new(Box::new(PathTracer::new()));
+    let renderer = Renderer::new(Box::new(PrimaryRayTracer::new()));
     let context = init_gl("OpenGL Window", SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32).unwrap();
     let mut app = App::new(context, pixel_shader, accumulator, state, renderer, SCREEN_WIDTH, SCREEN_HEIGHT);
     app.run();
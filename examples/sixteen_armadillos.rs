@@ -181,7 +181,7 @@ pub fn main() -> io::Result<()> {
     println!("Scene building time = {:?}", elapsed);
     let accumulator = Box::new(DepthAccumulator::new());
     let pixel_shader = Box::new(DepthMappingShader::new(80_f32, 3_f32));
-    let renderer = Renderer::new(Box::new(PathTracer::new()));
+    let renderer = Renderer::new(Box::new(PrimaryRayTracer::new()));
     let context = init_gl("OpenGL Window", SCREEN_WIDTH as u32, SCREEN_HEIGHT as u32).unwrap();
     let mut app = App::new(context, pixel_shader, accumulator, state, renderer, SCREEN_WIDTH, SCREEN_HEIGHT);
     app.run();